@@ -14,7 +14,7 @@ pub fn worktable(input: TokenStream) -> TokenStream {
         .into()
 }
 
-#[proc_macro_derive(PersistIndex)]
+#[proc_macro_derive(PersistIndex, attributes(persist_index))]
 pub fn persist_index(input: TokenStream) -> TokenStream {
     persist_index::expand(input.into())
         .unwrap_or_else(|e| e.to_compile_error())
@@ -36,6 +36,13 @@ impl WorktableNameGenerator {
         )
     }
 
+    pub fn get_work_table_builder_ident(&self) -> Ident {
+        Ident::new(
+            format!("{}WorkTableBuilder", self.name).as_str(),
+            Span::mixed_site(),
+        )
+    }
+
     pub fn get_primary_key_type_ident(&self) -> Ident {
         Ident::new(
             format!("{}PrimaryKey", self.name).as_str(),
@@ -47,6 +54,20 @@ impl WorktableNameGenerator {
         Ident::new(format!("{}Wrapper", self.name).as_str(), Span::mixed_site())
     }
 
+    pub fn get_scalars_mut_type_ident(&self) -> Ident {
+        Ident::new(
+            format!("{}ScalarsMut", self.name).as_str(),
+            Span::mixed_site(),
+        )
+    }
+
+    pub fn get_read_only_table_ident(&self) -> Ident {
+        Ident::new(
+            format!("{}ReadOnlyTable", self.name).as_str(),
+            Span::mixed_site(),
+        )
+    }
+
     pub fn get_index_type_ident(&self) -> Ident {
         Ident::new(format!("{}Index", self.name).as_str(), Span::mixed_site())
     }
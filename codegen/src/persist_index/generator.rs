@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use proc_macro2::{Ident, Literal, TokenStream};
 use quote::__private::Span;
 use quote::{quote, ToTokens};
-use syn::ItemStruct;
+use syn::{Field, ItemStruct};
 
 use crate::name_generator::WorktableNameGenerator;
 
@@ -39,6 +39,33 @@ impl Generator {
         }
     }
 
+    /// Returns `true` if index field is not marked with `#[persist_index(skip)]` attribute.
+    fn is_persisted(field: &Field) -> bool {
+        !field.attrs.iter().any(|a| {
+            a.path().is_ident("persist_index")
+                && a.parse_args::<Ident>()
+                    .map(|i| i == "skip")
+                    .unwrap_or(false)
+        })
+    }
+
+    /// Returns index fields which are written to the file. Fields marked with `#[persist_index(skip)]` are not
+    /// persisted and are rebuilt from the rows when table is loaded.
+    fn persisted_fields(&self) -> impl Iterator<Item = &Field> {
+        self.struct_def
+            .fields
+            .iter()
+            .filter(|f| Self::is_persisted(f))
+    }
+
+    /// Returns `true` if index type is hash-style (`LockedHashMap`, `LockFreeMap`), so its entries are not iterated
+    /// in key's order.
+    fn is_hash_index_type(ty: &str) -> bool {
+        ["LockedHashMap", "LockFreeMap"]
+            .iter()
+            .any(|name| ty.trim_start().starts_with(name))
+    }
+
     /// Generates persisted index type. This type has same name as index, but with `Persisted` postfix. Field names of
     /// this type are same to index type, and values are `Vec<GeneralPage<IndexData<T>>>`, where `T` is index key type.
     pub fn gen_persist_type(&mut self) -> syn::Result<TokenStream> {
@@ -48,7 +75,7 @@ impl Generator {
         let mut fields = vec![];
         let mut types = vec![];
 
-        for field in &self.struct_def.fields {
+        for field in self.persisted_fields() {
             fields.push(
                 field
                     .ident
@@ -117,9 +144,7 @@ impl Generator {
     /// header of last page.
     fn gen_get_last_header_mut_fn(&self) -> TokenStream {
         let get_last_header: Vec<_> = self
-            .struct_def
-            .fields
-            .iter()
+            .persisted_fields()
             .map(|f| f.ident.as_ref().expect("index fields should always be named fields"))
             .map(|i| {
                 quote! {
@@ -159,9 +184,7 @@ impl Generator {
     /// Generates `persist` function for persisted index. It calls `persist_page` function for every page in index.
     fn gen_persist_fn(&self) -> TokenStream {
         let persist_logic = self
-            .struct_def
-            .fields
-            .iter()
+            .persisted_fields()
             .map(|f| {
                 f.ident
                     .as_ref()
@@ -188,9 +211,7 @@ impl Generator {
     /// interval. Currently only one sequential `Interval` is returned for each index.
     fn gen_get_intervals_fn(&self) -> TokenStream {
         let interval_map_creation: Vec<_> = self
-            .struct_def
-            .fields
-            .iter()
+            .persisted_fields()
             .map(|f| {
                 (
                     Literal::string(
@@ -240,9 +261,7 @@ impl Generator {
         let page_const_name = name_generator.get_page_size_const_ident();
 
         let field_names_literals: Vec<_> = self
-            .struct_def
-            .fields
-            .iter()
+            .persisted_fields()
             .map(|f| (
                 Literal::string(
                     f.ident
@@ -257,7 +276,8 @@ impl Generator {
             ))
             .map(|(l, i)| quote! {
                 let mut #i = vec![];
-                let intervals = map.get(#l).expect("index name should exist");
+                // Index is missing if file was written while it was not persisted, so it's rebuilt from the rows.
+                let intervals = map.get(#l).map(Vec::as_slice).unwrap_or_default();
                 for interval in intervals {
                     for page_id in interval.0..interval.1 {
                         let index = parse_page::<IndexData<_>, { #page_const_name as u32 }>(file, page_id as u32)?;
@@ -270,9 +290,7 @@ impl Generator {
             .collect();
 
         let idents = self
-            .struct_def
-            .fields
-            .iter()
+            .persisted_fields()
             .map(|f| {
                 f.ident
                     .as_ref()
@@ -291,6 +309,50 @@ impl Generator {
         }
     }
 
+    /// Generates `rebuilt_index_names` function of the index. It returns names of the indexes that are empty after
+    /// `from_persisted`, so they must be rebuilt from the rows. Index is empty if it's not persisted, or if file was
+    /// written while it was not persisted.
+    pub fn gen_index_impl(&self) -> TokenStream {
+        let ident = &self.struct_def.ident;
+        let name_generator = WorktableNameGenerator::from_index_ident(&self.struct_def.ident);
+        let name_ident = name_generator.get_persisted_index_ident();
+
+        let checks = self
+            .struct_def
+            .fields
+            .iter()
+            .map(|f| {
+                let i = f
+                    .ident
+                    .as_ref()
+                    .expect("index fields should always be named fields");
+                let l = Literal::string(i.to_string().as_str());
+                if Self::is_persisted(f) {
+                    quote! {
+                        if persisted.#i.is_empty() {
+                            names.push(#l);
+                        }
+                    }
+                } else {
+                    quote! {
+                        names.push(#l);
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        quote! {
+            impl #ident {
+                #[allow(unused_variables)]
+                pub fn rebuilt_index_names(persisted: &#name_ident) -> Vec<&'static str> {
+                    let mut names = vec![];
+                    #(#checks)*
+                    names
+                }
+            }
+        }
+    }
+
     /// Generates `PersistableIndex` trait implementation for persisted index.
     pub fn gen_persistable_impl(&self) -> syn::Result<TokenStream> {
         let ident = &self.struct_def.ident;
@@ -316,9 +378,7 @@ impl Generator {
     /// all indexes as strings.
     fn gen_get_index_names_fn(&self) -> TokenStream {
         let field_names_lits: Vec<_> = self
-            .struct_def
-            .fields
-            .iter()
+            .persisted_fields()
             .map(|f| {
                 Literal::string(
                     f.ident
@@ -344,9 +404,7 @@ impl Generator {
         let name_generator = WorktableNameGenerator::from_index_ident(&self.struct_def.ident);
         let const_name = name_generator.get_page_size_const_ident();
         let idents = self
-            .struct_def
-            .fields
-            .iter()
+            .persisted_fields()
             .map(|f| {
                 f.ident
                     .as_ref()
@@ -354,41 +412,48 @@ impl Generator {
             })
             .collect::<Vec<_>>();
         let field_names_init: Vec<_> = self
-            .struct_def
-            .fields
-            .iter()
+            .persisted_fields()
             .map(|f| {
+                let ty = f.ty.to_token_stream().to_string();
                 (
                     f.ident.as_ref().expect("index fields should always be named fields"),
-                    !f.ty
-                        .to_token_stream()
-                        .to_string()
-                        .to_lowercase()
-                        .contains("lockfree"),
+                    !ty.to_lowercase().contains("lockfree"),
+                    Self::is_hash_index_type(&ty),
                 )
             })
-            .map(|(i, is_unique)| {
+            .map(|(i, is_unique, is_hash)| {
                 let ty = self.field_types.get(i).expect("should be available as constructed from same values");
-                if is_unique {
+                // Hash-style index is iterated in random order, so its entries are sorted to write same pages on
+                // every run. Links of the non-unique index's value are kept in hash set, so every link is written as
+                // separate entry and entries are sorted by key and link for every index type.
+                let pages = if is_unique && is_hash {
                     quote! {
-                        let mut #i = map_index_pages_to_general(
-                            map_unique_tree_index::<#ty, #const_name>(TableIndex::iter(&self.#i)),
-                            previous_header
-                        );
-                        previous_header = &mut #i.last_mut()
-                            .expect("at least one page should be presented, even if index contains no values")
-                            .header;
+                        {
+                            let mut entries = TableIndex::iter(&self.#i).collect::<Vec<_>>();
+                            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                            map_unique_tree_index::<#ty, #const_name>(entries.into_iter())
+                        }
+                    }
+                } else if is_unique {
+                    quote! {
+                        map_unique_tree_index::<#ty, #const_name>(TableIndex::iter(&self.#i))
                     }
                 } else {
                     quote! {
-                        let mut #i =  map_index_pages_to_general(
-                            map_tree_index::<#ty, #const_name>(TableIndex::iter(&self.#i)),
-                            previous_header
-                        );
-                        previous_header = &mut #i.last_mut()
-                            .expect("at least one page should be presented, even if index contains no values")
-                            .header;
+                        {
+                            let mut entries = TableIndex::iter(&self.#i)
+                                .flat_map(|(key, links)| links.iter().map(move |l| (key, *l.as_ref())))
+                                .collect::<Vec<_>>();
+                            entries.sort();
+                            map_unique_tree_index::<#ty, #const_name>(entries.iter().map(|(key, link)| (*key, link)))
+                        }
                     }
+                };
+                quote! {
+                    let mut #i = map_index_pages_to_general(#pages, previous_header);
+                    previous_header = &mut #i.last_mut()
+                        .expect("at least one page should be presented, even if index contains no values")
+                        .header;
                 }
             })
             .collect();
@@ -405,7 +470,7 @@ impl Generator {
     }
 
     /// Generates `from_persisted` function of `PersistableIndex` trait for persisted index. It maps every page in
-    /// persisted page back to `TreeIndex`
+    /// persisted page back to `TreeIndex`. Indexes which are not persisted are created empty.
     fn gen_from_persisted_fn(&self) -> syn::Result<TokenStream> {
         let idents = self
             .struct_def
@@ -442,7 +507,17 @@ impl Generator {
                     .to_string()
                     .to_lowercase()
                     .contains("lockfree");
-                if is_unique {
+                if !Self::is_persisted(f) {
+                    if is_unique {
+                        quote! {
+                            let #i: #t<_, Link> = #t::new();
+                        }
+                    } else {
+                        quote! {
+                            let #i: #t<_, std::sync::Arc<lockfree::set::Set<Link>>> = #t::new();
+                        }
+                    }
+                } else if is_unique {
                     quote! {
                         let #i: #t<_, Link> = #t::new();
                         for page in persisted.#i {
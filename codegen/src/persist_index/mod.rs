@@ -14,11 +14,13 @@ pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
     let type_def = gen.gen_persist_type()?;
     let persistable_def = gen.gen_persistable_impl()?;
     let impl_def = gen.gen_persist_impl()?;
+    let index_impl_def = gen.gen_index_impl();
 
     Ok(quote! {
         #type_def
         #impl_def
         #persistable_def
+        #index_impl_def
     })
 }
 
@@ -57,4 +59,25 @@ mod tests {
         let res = expand(input).unwrap();
         println!("{:?}", res.to_string())
     }
+
+    #[test]
+    fn test_skip() {
+        let input = quote! {
+            #[derive(Debug, Default, Clone)]
+            pub struct TestIndex {
+                test_idx: TreeIndex<i64, Link>,
+                #[persist_index(skip)]
+                exchnage_idx: TreeIndex<String, std::sync::Arc<LockFreeSet<Link>>>
+            }
+        };
+
+        let res = expand(input).unwrap().to_string();
+        let persisted = res
+            .split("impl TestIndexPersisted")
+            .next()
+            .expect("persisted type is generated first");
+        assert!(persisted.contains("test_idx"));
+        assert!(!persisted.contains("exchnage_idx"));
+        assert!(res.contains("names . push (\"exchnage_idx\")"));
+    }
 }
@@ -8,12 +8,35 @@ mod size_measurable;
 mod space_deserialize;
 mod space_serialize;
 
+/// Names which are reserved on Windows, even with extension. It's a copy of `worktable`'s `RESERVED_FILE_NAMES`,
+/// because proc macro crate can't depend on it, so they are compared by test.
+const RESERVED_FILENAMES: &[&str] = &[
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
 pub struct Generator {
     pub struct_def: ItemStruct,
     pub pk_ident: Ident,
     pub index_type_ident: Ident
 }
 
+impl Generator {
+    /// Checks that file name derived from table's name can be used on all platforms.
+    pub fn check_filename(&self) -> syn::Result<()> {
+        let name_generator = WorktableNameGenerator::from_struct_ident(&self.struct_def.ident);
+        let filename = name_generator.get_filename();
+        if RESERVED_FILENAMES.contains(&filename.as_str()) {
+            return Err(syn::Error::new(
+                self.struct_def.ident.span(),
+                format!("Table's file name `{filename}` is reserved on some platforms"),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
 impl WorktableNameGenerator {
     pub fn get_space_ident(&self) -> Ident {
         Ident::new(format!("{}Space", self.name).as_str(), Span::mixed_site())
@@ -23,3 +46,13 @@ impl WorktableNameGenerator {
         self.name.from_case(Case::Pascal).to_case(Case::Snake)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::RESERVED_FILENAMES;
+
+    #[test]
+    fn reserved_filenames_match_worktable() {
+        assert_eq!(RESERVED_FILENAMES, worktable::prelude::RESERVED_FILE_NAMES);
+    }
+}
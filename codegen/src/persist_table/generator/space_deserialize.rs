@@ -10,12 +10,71 @@ impl Generator {
         let space_ident = name_generator.get_space_ident();
 
         let space_into_table = self.gen_space_into_table()?;
+        let space_into_read_only = self.gen_space_into_read_only()?;
         let parse_space = self.gen_parse_space()?;
+        let spaces_diff = self.gen_spaces_diff()?;
 
         Ok(quote! {
             impl #space_ident {
                 #space_into_table
+                #space_into_read_only
                 #parse_space
+                #spaces_diff
+            }
+        })
+    }
+
+    /// Generates `spaces_diff` and `spaces_equal` functions, which compare tables of two files logically, ignoring
+    /// their physical layout.
+    fn gen_spaces_diff(&self) -> syn::Result<TokenStream> {
+        let name_generator = WorktableNameGenerator::from_struct_ident(&self.struct_def.ident);
+        let pk_type = name_generator.get_primary_key_type_ident();
+        let row_type = name_generator.get_row_type_ident();
+        let rows = |table: TokenStream| {
+            quote! {
+                TableIndex::iter(&#table.0.pk_map)
+                    .map(|(pk, link)| {
+                        #table
+                            .0
+                            .data
+                            .select(*link)
+                            .map(|row| (pk.clone(), row))
+                            .map_err(|e| eyre::eyre!("row of {:?} can't be read: {}", pk, e))
+                    })
+                    .collect::<eyre::Result<Vec<_>>>()?
+            }
+        };
+        let first_rows = rows(quote! { first });
+        let second_rows = rows(quote! { second });
+
+        Ok(quote! {
+            /// Parses both files and compares their rows by primary key. Indexes of every file are checked against its
+            /// rows, so files with same rows and consistent indexes have same index contents too. Physical layout of
+            /// the files (page order, empty links) is ignored.
+            pub fn spaces_diff(
+                first: &mut std::fs::File,
+                second: &mut std::fs::File,
+            ) -> eyre::Result<SpaceDiff<#pk_type, #row_type>> {
+                // Tables are not persisted, so manager's paths are never used.
+                let manager = std::sync::Arc::new(DatabaseManager::new(String::new(), String::new()));
+                let first = Self::parse_file(first)?.into_worktable(manager.clone())?;
+                let second = Self::parse_file(second)?.into_worktable(manager)?;
+                let first_rows = #first_rows;
+                let second_rows = #second_rows;
+
+                let mut diff = SpaceDiff::from_rows(first_rows, second_rows);
+                diff.first_integrity = first.verify_integrity().err().unwrap_or_default();
+                diff.second_integrity = second.verify_integrity().err().unwrap_or_default();
+
+                Ok(diff)
+            }
+
+            /// Returns `true` if both files contain same table, see `spaces_diff`.
+            pub fn spaces_equal(
+                first: &mut std::fs::File,
+                second: &mut std::fs::File,
+            ) -> eyre::Result<bool> {
+                Ok(Self::spaces_diff(first, second)?.is_empty())
             }
         })
     }
@@ -25,9 +84,12 @@ impl Generator {
         let name_generator = WorktableNameGenerator::from_struct_ident(&self.struct_def.ident);
         let index_ident = name_generator.get_index_type_ident();
         let index_type_ident = &self.index_type_ident;
+        let table_name = name_generator.get_work_table_literal_name();
 
         Ok(quote! {
-            pub fn into_worktable(self, db_manager: std::sync::Arc<DatabaseManager>) -> #wt_ident {
+            /// Builds table from parsed file. Error is returned if primary index of the file has duplicate keys, its rows
+            /// can't be read or they violate unique indexes, so corrupted file doesn't panic.
+            pub fn into_worktable(self, db_manager: std::sync::Arc<DatabaseManager>) -> eyre::Result<#wt_ident> {
                 let mut page_id = 0;
                 let data = self.data.into_iter().map(|p| {
                     let mut data = Data::from_data_page(p);
@@ -37,32 +99,98 @@ impl Generator {
                     std::sync::Arc::new(data)
                 })
                     .collect();
-                let data = DataPages::from_data(data)
+                let mut data = DataPages::from_data(data)
                     .with_empty_links(self.info.inner.empty_links_list);
+                data.set_table_name(#table_name);
+                let rebuilt_indexes = #index_ident::rebuilt_index_names(&self.indexes);
                 let indexes = #index_ident::from_persisted(self.indexes);
 
                 let pk_map = #index_type_ident::new();
                 for page in self.primary_index {
                     for val in page.inner.index_values {
                         TableIndex::insert(&pk_map, val.key, val.link)
-                            .expect("index is unique");
+                            .map_err(|_| eyre::eyre!("primary index of the file has duplicate keys"))?;
                     }
                 }
+                // Free list of the file can be out of sync with its rows, so empty links which would overwrite live
+                // rows are dropped.
+                data.drop_invalid_empty_links(TableIndex::iter(&pk_map).map(|(_, l)| *l));
 
                 let table = WorkTable {
                     data,
                     pk_map,
                     indexes,
+                    // Generator continues from persisted state, so new keys don't collide with loaded ones.
                     pk_gen: PrimaryKeyGeneratorState::from_state(self.info.inner.pk_gen_state),
                     lock_map: LockMap::new(),
-                    table_name: "",
+                    transaction_lock: tokio::sync::Mutex::default(),
+                    capture_lock: std::sync::RwLock::default(),
+                    table_name: #table_name,
                     pk_phantom: std::marker::PhantomData
                 };
+                // Indexes which are not persisted are rebuilt from the rows.
+                if !rebuilt_indexes.is_empty() {
+                    for (_, link) in TableIndex::iter(&table.pk_map) {
+                        let row = table.data.select(*link)
+                            .map_err(|e| eyre::eyre!("row of the primary index can't be read: {}", e))?;
+                        table.indexes
+                            .save_row_to(&row, *link, &rebuilt_indexes)
+                            .map_err(|e| eyre::eyre!("rows of the file violate unique indexes: {}", e))?;
+                    }
+                }
 
-                #wt_ident(
+                Ok(#wt_ident(
                     table,
-                    db_manager
-                )
+                    db_manager,
+                    None
+                ))
+            }
+        })
+    }
+
+    /// Generates `into_readonly` function, which builds read-only table from parsed file. Only pages, primary index
+    /// and secondary indexes are built, free list, locks and primary key generator of the table are skipped.
+    fn gen_space_into_read_only(&self) -> syn::Result<TokenStream> {
+        let name_generator = WorktableNameGenerator::from_struct_ident(&self.struct_def.ident);
+        let index_ident = name_generator.get_index_type_ident();
+        let read_only_ident = name_generator.get_read_only_table_ident();
+        let table_name = name_generator.get_work_table_literal_name();
+
+        Ok(quote! {
+            /// Builds read-only table from parsed file. Error is returned if primary index of the file has duplicate
+            /// keys, its rows can't be read or they violate unique indexes.
+            pub fn into_readonly(self) -> eyre::Result<#read_only_ident> {
+                let data = self.data.into_iter().enumerate().map(|(page_id, p)| {
+                    let mut data = Data::from_data_page(p);
+                    data.set_page_id((page_id as u32).into());
+                    std::sync::Arc::new(data)
+                })
+                    .collect();
+                let mut data = DataPages::from_data(data);
+                data.set_table_name(#table_name);
+                let rebuilt_indexes = #index_ident::rebuilt_index_names(&self.indexes);
+                let indexes = #index_ident::from_persisted(self.indexes);
+
+                let mut pk_map = std::collections::BTreeMap::new();
+                for page in self.primary_index {
+                    for val in page.inner.index_values {
+                        if pk_map.insert(val.key, val.link).is_some() {
+                            eyre::bail!("primary index of the file has duplicate keys");
+                        }
+                    }
+                }
+                // Indexes which are not persisted are rebuilt from the rows.
+                if !rebuilt_indexes.is_empty() {
+                    for link in pk_map.values() {
+                        let row = data.select(*link)
+                            .map_err(|e| eyre::eyre!("row of the primary index can't be read: {}", e))?;
+                        indexes
+                            .save_row_to(&row, *link, &rebuilt_indexes)
+                            .map_err(|e| eyre::eyre!("rows of the file violate unique indexes: {}", e))?;
+                    }
+                }
+
+                Ok(#read_only_ident(ReadOnlyTable::new(data, pk_map), indexes))
             }
         })
     }
@@ -75,8 +203,31 @@ impl Generator {
         let persisted_index_name = name_generator.get_persisted_index_ident();
 
         Ok(quote! {
+            /// Parses file after data version check. If file's version is not current, registered migrations are
+            /// applied to its copy, which is parsed instead. Migrated copy's version is checked again, so migration
+            /// that didn't update it returns error instead of parsing pages of the old layout.
             pub fn parse_file(file: &mut std::fs::File) -> eyre::Result<Self> {
                 let info = parse_page::<SpaceInfoData<<<#pk_type as TablePrimaryKey>::Generator as PrimaryKeyGeneratorState>::State>, { #page_const_name as u32 }>(file, 0)?;
+                let found = info.header.data_version as u32;
+                if found != DATA_VERSION as u32 {
+                    let mut migrated = migrate_file(file, found)?;
+                    let info = parse_page::<SpaceInfoData<<<#pk_type as TablePrimaryKey>::Generator as PrimaryKeyGeneratorState>::State>, { #page_const_name as u32 }>(migrated.file(), 0)?;
+                    let migrated_version = info.header.data_version as u32;
+                    if migrated_version != DATA_VERSION as u32 {
+                        return Err(VersionError::MigratedVersionMismatch {
+                            found: migrated_version,
+                            expected: DATA_VERSION as u32,
+                        }
+                        .into());
+                    }
+                    return Self::parse_file_unchecked(migrated.file());
+                }
+
+                Self::parse_file_unchecked(file)
+            }
+
+            fn parse_file_unchecked(file: &mut std::fs::File) -> eyre::Result<Self> {
+                let info = parse_page::<SpaceInfoData<<<#pk_type as TablePrimaryKey>::Generator as PrimaryKeyGeneratorState>::State>, { #page_const_name as u32 }>(file, 0)?;
 
                 let mut primary_index = vec![];
                 for interval in &info.inner.primary_key_intervals {
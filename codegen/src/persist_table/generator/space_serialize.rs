@@ -13,7 +13,6 @@ impl Generator {
         let space_ident = name_generator.get_space_ident();
 
         Ok(quote! {
-            #[derive(Debug)]
             pub struct #space_ident {
                 pub path: String,
 
@@ -22,6 +21,41 @@ impl Generator {
                 pub indexes: #index_persisted_ident,
                 pub data: Vec<GeneralPage<DataPage<#inner_const_name>>>,
             }
+
+            /// Prints layout of the space: count of pages in each section, their intervals and primary key generator's
+            /// state. Pages' bytes are not printed.
+            impl std::fmt::Debug for #space_ident {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    let info = &self.info.inner;
+                    let secondary_index_intervals = info
+                        .secondary_index_intervals
+                        .iter()
+                        .collect::<std::collections::BTreeMap<_, _>>();
+                    let secondary_index_pages = secondary_index_intervals
+                        .iter()
+                        .map(|(name, intervals)| (*name, intervals.iter().map(|i| i.1 - i.0 + 1).sum::<usize>()))
+                        .collect::<std::collections::BTreeMap<_, _>>();
+                    let page_count = 1
+                        + self.primary_index.len()
+                        + secondary_index_pages.values().sum::<usize>()
+                        + self.data.len();
+
+                    f.debug_struct(stringify!(#space_ident))
+                        .field("path", &self.path)
+                        .field("name", &info.name)
+                        .field("data_version", &self.info.header.data_version)
+                        .field("primary_index_pages", &self.primary_index.len())
+                        .field("secondary_index_pages", &secondary_index_pages)
+                        .field("data_pages", &self.data.len())
+                        .field("primary_key_intervals", &info.primary_key_intervals)
+                        .field("secondary_index_intervals", &secondary_index_intervals)
+                        .field("data_intervals", &info.data_intervals)
+                        .field("empty_links", &info.empty_links_list.len())
+                        .field("pk_gen_state", &info.pk_gen_state)
+                        .field("total_bytes", &(page_count * PAGE_SIZE))
+                        .finish()
+                }
+            }
         })
     }
 
@@ -32,7 +66,14 @@ impl Generator {
         let into_space = self.gen_into_space()?;
 
         let persist_fn = self.gen_persist_fn()?;
+        let persist_append_fn = self.gen_persist_append_fn()?;
+        let persist_streaming_fn = self.gen_persist_streaming_fn()?;
         let from_file_fn = self.gen_from_file_fn()?;
+        let refresh_from_file_fn = self.gen_refresh_from_file_fn()?;
+        let parse_file_lenient_fn = self.gen_parse_file_lenient_fn()?;
+
+        let register_fn = self.gen_register_fn()?;
+        let wal_fns = self.gen_wal_fns()?;
 
         let space_persist = self.gen_space_persist_fn()?;
 
@@ -43,18 +84,450 @@ impl Generator {
                 #into_space
 
                 #persist_fn
+                #persist_append_fn
+                #persist_streaming_fn
                 #from_file_fn
+                #refresh_from_file_fn
+                #parse_file_lenient_fn
+                #register_fn
+                #wal_fns
+            }
+
+            impl PersistableTable for #ident {
+                fn name(&self) -> &'static str {
+                    self.0.table_name
+                }
+
+                fn persist(&self) -> eyre::Result<()> {
+                    #ident::persist(self)
+                }
             }
 
             #space_persist
         })
     }
 
+    /// Generates `register` function, which moves table into `Arc` and registers it in its [`DatabaseManager`], so
+    /// it's persisted by `persist_all`.
+    fn gen_register_fn(&self) -> syn::Result<TokenStream> {
+        Ok(quote! {
+            /// Moves table into `Arc` and registers it in table's [`DatabaseManager`], so it's persisted by
+            /// [`DatabaseManager::persist_all`]. Works same for created and loaded tables.
+            pub fn register(self) -> std::sync::Arc<Self> {
+                let table = std::sync::Arc::new(self);
+                table.1.register(&table);
+                table
+            }
+        })
+    }
+
+    /// Generates functions of the table's write-ahead log: `load_from_file_with_wal`, which enables it, and private
+    /// functions used by generated operations to append records.
+    fn gen_wal_fns(&self) -> syn::Result<TokenStream> {
+        let name_generator = WorktableNameGenerator::from_struct_ident(&self.struct_def.ident);
+        let row_type = name_generator.get_row_type_ident();
+        let pk_type = name_generator.get_primary_key_type_ident();
+        let name_underscore = name_generator.get_filename();
+
+        Ok(quote! {
+            /// Loads table like [`Self::load_from_file`] and replays its write-ahead log on top of it. After load
+            /// every insert, update and delete is appended to the log before it returns, so operations done after
+            /// last `persist` are not lost on crash.
+            pub fn load_from_file_with_wal(manager: std::sync::Arc<DatabaseManager>) -> eyre::Result<Self> {
+                let mut table = Self::load_from_file(manager.clone())?;
+                table.enable_wal(&manager, DurabilityMode::default())?;
+                Ok(table)
+            }
+
+            /// Returns [`WritePolicy`] of the table's writes, which is defined by its write-ahead log.
+            pub fn write_policy(&self) -> WritePolicy {
+                WritePolicy::from_wal_durability(self.2.as_ref().map(Wal::durability))
+            }
+
+            /// Replays table's write-ahead log and opens it, so next operations are appended to it.
+            fn enable_wal(&mut self, manager: &DatabaseManager, durability: DurabilityMode) -> eyre::Result<()> {
+                let path = wal_path(table_file_path(&manager.database_files_dir, #name_underscore)?);
+                self.0.replay_wal(Wal::read(&path)?)?;
+                std::fs::create_dir_all(&manager.database_files_dir)?;
+                self.2 = Some(Wal::open(&path, durability)?);
+                Ok(())
+            }
+
+            /// Locks table's write-ahead log. Operation is applied and logged while returned writer is held, so
+            /// operations are logged in the same order as they are applied. It's `None` if log is not enabled.
+            fn wal_writer(&self) -> Option<WalWriter<'_>> {
+                self.2.as_ref().map(Wal::writer)
+            }
+
+            fn log_wal_row(&self, wal: &mut Option<WalWriter<'_>>, op: WalOp, row: &#row_type) -> core::result::Result<(), WorkTableError> {
+                self.log_wal_rows(wal, op, std::slice::from_ref(row))
+            }
+
+            /// Appends all `rows` as `op` by single write, so they are logged together or not logged.
+            fn log_wal_rows(&self, wal: &mut Option<WalWriter<'_>>, op: WalOp, rows: &[#row_type]) -> core::result::Result<(), WorkTableError> {
+                if let Some(wal) = wal {
+                    for row in rows {
+                        wal.push(op, row)?;
+                    }
+                    if op == WalOp::Insert {
+                        wal.push(WalOp::PrimaryKeyState, &self.0.pk_gen.get_state())?;
+                    }
+                    wal.flush()?;
+                }
+                Ok(())
+            }
+
+            /// Appends deletes of all `pks` by single write, so they are logged together or not logged.
+            fn log_wal_delete(&self, wal: &mut Option<WalWriter<'_>>, pks: &[#pk_type]) -> core::result::Result<(), WorkTableError> {
+                if let Some(wal) = wal {
+                    for pk in pks {
+                        wal.push(WalOp::Delete, pk)?;
+                    }
+                    wal.flush()?;
+                }
+                Ok(())
+            }
+
+            fn log_wal_truncate(&self, wal: &mut Option<WalWriter<'_>>) -> core::result::Result<(), WorkTableError> {
+                if let Some(wal) = wal {
+                    wal.append(WalOp::Truncate, &0u8)?;
+                }
+                Ok(())
+            }
+
+            /// Appends primary key generator's state. It's logged with every insert too, so if it's not logged
+            /// after generator's reset, keys after replay just continue from the previous state.
+            fn log_wal_pk_state(&self, wal: &mut Option<WalWriter<'_>>) {
+                if let Some(wal) = wal {
+                    let _ = wal.append(WalOp::PrimaryKeyState, &self.0.pk_gen.get_state());
+                }
+            }
+        })
+    }
+
     fn gen_persist_fn(&self) -> syn::Result<TokenStream> {
+        let name_generator = WorktableNameGenerator::from_struct_ident(&self.struct_def.ident);
+        let name_underscore = name_generator.get_filename();
+        let pk_type = name_generator.get_primary_key_type_ident();
+        let page_const_name = name_generator.get_page_size_const_ident();
+
         Ok(quote! {
             pub fn persist(&self) -> eyre::Result<()> {
-                let mut space = self.into_space();
-                space.persist()?;
+                self.persist_with::<Crc32>(DurabilityMode::default())
+            }
+
+            /// Persists table and writes checksums of all its pages using `C` algorithm.
+            pub fn persist_with_checksum<C: Checksum>(&self) -> eyre::Result<()> {
+                self.persist_with::<C>(DurabilityMode::default())
+            }
+
+            /// Persists table with provided [`DurabilityMode`]. With [`DurabilityMode::NoSync`] data can be lost on
+            /// crash even if `Ok` is returned.
+            pub fn persist_with_durability(&self, durability: DurabilityMode) -> eyre::Result<()> {
+                self.persist_with::<Crc32>(durability)
+            }
+
+            pub fn persist_with<C: Checksum>(&self, durability: DurabilityMode) -> eyre::Result<()> {
+                self.persist_with_stats_using::<C>(durability).map(|_| ())
+            }
+
+            /// Persists table like [`Self::persist`] and returns [`PersistStats`] of the written file.
+            pub fn persist_with_stats(&self) -> eyre::Result<PersistStats> {
+                self.persist_with_stats_using::<Crc32>(DurabilityMode::default())
+            }
+
+            /// Returns [`VersionError::FileFromNewerVersion`] if table's file was written by newer version of the
+            /// crate, so full persist doesn't overwrite it. File which can't be opened or parsed is not checked.
+            fn check_file_version(&self) -> eyre::Result<()> {
+                let path = table_file_path(&self.1.config_path, #name_underscore)?;
+                let Ok(mut file) = std::fs::File::open(&path) else {
+                    return Ok(());
+                };
+                let Ok(info) = parse_page::<SpaceInfoData<<<#pk_type as TablePrimaryKey>::Generator as PrimaryKeyGeneratorState>::State>, { #page_const_name as u32 }>(&mut file, 0) else {
+                    return Ok(());
+                };
+                if info.header.data_version as u32 > DATA_VERSION as u32 {
+                    return Err(VersionError::FileFromNewerVersion {
+                        found: info.header.data_version as u32,
+                        supported: DATA_VERSION as u32,
+                    }.into());
+                }
+                Ok(())
+            }
+
+            /// Persists table like [`Self::persist_with`] and returns [`PersistStats`] of the written file. Its
+            /// duration includes table's snapshot and checksums. File written by newer version of the crate is not
+            /// overwritten and [`VersionError::FileFromNewerVersion`] is returned.
+            pub fn persist_with_stats_using<C: Checksum>(&self, durability: DurabilityMode) -> eyre::Result<PersistStats> {
+                let started = std::time::Instant::now();
+                self.check_file_version()?;
+                // Log's end is taken before snapshot, so operations done while table is persisted stay in the log.
+                let mark = self.2.as_ref().map(Wal::mark).transpose()?;
+                // Sequence is taken before pages are copied, and pages are marked as persisted only after they are
+                // written, so changes made while table is persisted, or pages which failed to be written, are
+                // appended by next `persist_append`.
+                let seq = self.0.data.modification_seq();
+                let mut space = self.into_space()?;
+                validate_space_intervals(&space.info.inner)?;
+                let mut stats = space.persist_with_stats(durability)?;
+                self.0.record_persisted(stats.bytes_written);
+                write_checksums::<C, _>(table_file_path(&space.path, #name_underscore)?, durability)?;
+                self.0.data.mark_persisted(seq);
+                if let (Some(wal), Some(mark)) = (&self.2, mark) {
+                    wal.checkpoint(mark)?;
+                }
+                stats.duration = started.elapsed();
+                Ok(stats)
+            }
+
+            /// Starts checkpoint of the table like [`Self::checkpoint_with`] with default checksum and durability.
+            pub async fn checkpoint(self: &std::sync::Arc<Self>) -> eyre::Result<CheckpointHandle> {
+                self.checkpoint_with::<Crc32>(DurabilityMode::default()).await
+            }
+
+            /// Persists table without blocking writers while file is written. Table's image (data pages' bytes, free
+            /// list, primary key generator's state and index pages) is captured first, and then it's written to the
+            /// file by background thread, so writers continue as soon as image is captured. Captured bytes are
+            /// copies, so changes done after capture are not written.
+            ///
+            /// Checkpoint reflects state of the table at capture time, not at the time it's completed. Capture waits
+            /// for running transaction and holds table's `capture_lock`, which writes hold while they are applied, so
+            /// image contains each write fully or doesn't contain it. Pages are marked as persisted only after image
+            /// is written. Other persist of the table must not be started until returned [`CheckpointHandle`] is
+            /// finished, because both of them write the same file. File written by newer version of the crate is not
+            /// overwritten and [`VersionError::FileFromNewerVersion`] is returned.
+            pub async fn checkpoint_with<C: Checksum + 'static>(
+                self: &std::sync::Arc<Self>,
+                durability: DurabilityMode,
+            ) -> eyre::Result<CheckpointHandle> {
+                let started = std::time::Instant::now();
+                self.check_file_version()?;
+                let (mut space, mark, seq) = {
+                    let _guard = self.0.transaction_lock.lock().await;
+                    let _capture = self.0.capture_lock.write().unwrap_or_else(std::sync::PoisonError::into_inner);
+                    let mark = self.2.as_ref().map(Wal::mark).transpose()?;
+                    let seq = self.0.data.modification_seq();
+                    (self.into_space()?, mark, seq)
+                };
+                validate_space_intervals(&space.info.inner)?;
+
+                let table = self.clone();
+                Ok(CheckpointHandle::spawn(move || {
+                    let mut stats = space.persist_with_stats(durability)?;
+                    table.0.record_persisted(stats.bytes_written);
+                    write_checksums::<C, _>(table_file_path(&space.path, #name_underscore)?, durability)?;
+                    table.0.data.mark_persisted(seq);
+                    if let (Some(wal), Some(mark)) = (&table.2, mark) {
+                        wal.checkpoint(mark)?;
+                    }
+                    stats.duration = started.elapsed();
+                    Ok(stats)
+                }))
+            }
+        })
+    }
+
+    /// Generates `persist_streaming` functions, which write data pages to the file one by one.
+    fn gen_persist_streaming_fn(&self) -> syn::Result<TokenStream> {
+        let name_generator = WorktableNameGenerator::from_struct_ident(&self.struct_def.ident);
+        let name_underscore = name_generator.get_filename();
+
+        Ok(quote! {
+            pub fn persist_streaming(&self) -> eyre::Result<()> {
+                self.persist_streaming_with::<Crc32>(DurabilityMode::default())
+            }
+
+            /// Persists table like [`Self::persist_with`], but data pages are copied and written one by one, so only
+            /// one data page is copied at once instead of all of them. Index pages are still built in memory before
+            /// they are written. Data pages which are added while table is persisted are written by next persist.
+            pub fn persist_streaming_with<C: Checksum>(&self, durability: DurabilityMode) -> eyre::Result<()> {
+                let mark = self.2.as_ref().map(Wal::mark).transpose()?;
+                // Pages are marked as persisted only after they are written, so failed persist doesn't lose them.
+                let seq = self.0.data.modification_seq();
+                let mut space = self.into_space_without_data();
+
+                let last_index_page_id: usize = match space.indexes.get_last_header_mut() {
+                    Some(header) => header.page_id.into(),
+                    None => space.primary_index
+                        .last()
+                        .expect("Primary index page always exists, even if empty")
+                        .header
+                        .page_id
+                        .into(),
+                };
+                let first_page_id = last_index_page_id + 1;
+                let page_count = self.0.data.get_page_count();
+                space.info.inner.data_intervals = vec![Interval(first_page_id, first_page_id + page_count - 1)];
+
+                validate_space_intervals(&space.info.inner)?;
+                let path = table_file_path(&space.path, #name_underscore)?;
+                std::fs::create_dir_all(&space.path)?;
+                let mut file = std::fs::File::create(&path)?;
+                persist_page(&mut space.info, &mut file)?;
+                for mut primary_index_page in &mut space.primary_index {
+                    persist_page(&mut primary_index_page, &mut file)?;
+                }
+                space.indexes.persist(&mut file)?;
+                for i in 0..page_count {
+                    let (bytes, length) = self.0.data.get_page_bytes(i)?.ok_or_else(|| {
+                        eyre::eyre!("data page {} was removed while table was persisted", i)
+                    })?;
+                    let page_id = first_page_id + i;
+                    let mut page = GeneralPage {
+                        header: GeneralHeader {
+                            data_version: DATA_VERSION,
+                            page_id: (page_id as u32).into(),
+                            previous_id: (if i == 0 { 0 } else { page_id as u32 - 1 }).into(),
+                            next_id: (if i + 1 == page_count { 0 } else { page_id as u32 + 1 }).into(),
+                            page_type: PageType::Data,
+                            space_id: 0.into(),
+                            data_length: 0,
+                        },
+                        inner: DataPage {
+                            data: bytes,
+                            length,
+                        },
+                    };
+                    persist_pages(std::slice::from_mut(&mut page), &mut file)?;
+                }
+                if durability == DurabilityMode::Fsync {
+                    file.sync_all()?;
+                }
+                self.0.record_persisted(file.metadata()?.len());
+                drop(file);
+
+                write_checksums::<C, _>(path, durability)?;
+                self.0.data.mark_persisted(seq);
+                if let (Some(wal), Some(mark)) = (&self.2, mark) {
+                    wal.checkpoint(mark)?;
+                }
+                Ok(())
+            }
+        })
+    }
+
+    /// Generates `persist_append` functions, which write only changed and new data pages to the existing file.
+    fn gen_persist_append_fn(&self) -> syn::Result<TokenStream> {
+        let name_generator = WorktableNameGenerator::from_struct_ident(&self.struct_def.ident);
+        let name_underscore = name_generator.get_filename();
+        let pk_type = name_generator.get_primary_key_type_ident();
+        let page_const_name = name_generator.get_page_size_const_ident();
+
+        Ok(quote! {
+            pub fn persist_append(&self) -> eyre::Result<()> {
+                self.persist_append_with::<Crc32>(DurabilityMode::default())
+            }
+
+            /// Persists table to the existing file without rewriting it. Data pages which were changed since they
+            /// were persisted are rewritten in place, new data pages and all index pages are appended to the end of
+            /// the file and space info page is rewritten last with new intervals. Index pages of the previous persist
+            /// are left unused in the file until next full [`Self::persist`]. If there is no file yet, or table has
+            /// less data pages than file, full persist is done instead. File written by newer version of the crate is
+            /// not overwritten and [`VersionError::FileFromNewerVersion`] is returned.
+            pub fn persist_append_with<C: Checksum>(&self, durability: DurabilityMode) -> eyre::Result<()> {
+                let path = table_file_path(&self.1.config_path, #name_underscore)?;
+                let Ok(mut file) = std::fs::OpenOptions::new().read(true).write(true).open(&path) else {
+                    return self.persist_with::<C>(durability);
+                };
+                let mut info = parse_page::<SpaceInfoData<<<#pk_type as TablePrimaryKey>::Generator as PrimaryKeyGeneratorState>::State>, { #page_const_name as u32 }>(&mut file, 0)?;
+                let mut data_page_ids = info.inner.data_intervals
+                    .iter()
+                    .flat_map(|i| i.0..=i.1)
+                    .collect::<Vec<_>>();
+                if info.header.data_version as u32 > DATA_VERSION as u32 {
+                    return Err(VersionError::FileFromNewerVersion {
+                        found: info.header.data_version as u32,
+                        supported: DATA_VERSION as u32,
+                    }.into());
+                }
+                if info.header.data_version as u32 != DATA_VERSION as u32
+                    || data_page_ids.is_empty()
+                    || data_page_ids.len() > self.0.data.get_page_count()
+                {
+                    drop(file);
+                    return self.persist_with::<C>(durability);
+                }
+
+                let mark = self.2.as_ref().map(Wal::mark).transpose()?;
+                let seq = self.0.data.modification_seq();
+                let pages = self.0.data.get_bytes_for_append(data_page_ids.len())?;
+                // Last page is always returned, so pages count is taken from it, not from pages which can be added
+                // concurrently.
+                let page_count = pages.last().map(|(i, _, _)| i + 1).unwrap_or_default();
+                let mut next_page_id = (file.metadata()?.len() as usize).div_ceil(PAGE_SIZE);
+                for _ in data_page_ids.len()..page_count {
+                    data_page_ids.push(next_page_id);
+                    next_page_id += 1;
+                }
+                let mut data = pages
+                    .into_iter()
+                    .map(|(i, bytes, length)| GeneralPage {
+                        header: GeneralHeader {
+                            data_version: DATA_VERSION,
+                            page_id: (data_page_ids[i] as u32).into(),
+                            previous_id: (i.checked_sub(1).map(|i| data_page_ids[i]).unwrap_or(0) as u32).into(),
+                            next_id: (data_page_ids.get(i + 1).copied().unwrap_or(0) as u32).into(),
+                            page_type: PageType::Data,
+                            space_id: 0.into(),
+                            data_length: 0,
+                        },
+                        inner: DataPage {
+                            data: bytes,
+                            length,
+                        },
+                    })
+                    .collect::<Vec<_>>();
+
+                // Index pages are placed after the last page of the file, and their ids are counted from its header,
+                // which is not written.
+                let mut previous_header = GeneralHeader {
+                    data_version: DATA_VERSION,
+                    page_id: (next_page_id as u32 - 1).into(),
+                    previous_id: 0.into(),
+                    next_id: 0.into(),
+                    page_type: PageType::Data,
+                    space_id: 0.into(),
+                    data_length: 0,
+                };
+                let mut primary_index = map_index_pages_to_general(
+                    self.get_peristed_primary_key(),
+                    &mut previous_header
+                );
+                let mut indexes = self.0.indexes.get_persisted_index(
+                    &mut primary_index
+                        .last_mut()
+                        .expect("Primary index page always exists, even if empty")
+                        .header
+                );
+
+                info.inner.pk_gen_state = self.0.pk_gen.get_state();
+                info.inner.empty_links_list = self.0.data.empty_links_snapshot();
+                info.inner.primary_key_intervals = page_intervals(
+                    &primary_index.iter().map(|p| p.header.page_id.into()).collect::<Vec<_>>()
+                );
+                info.inner.secondary_index_intervals = indexes.get_intervals();
+                info.inner.data_intervals = page_intervals(&data_page_ids);
+                validate_disjoint_intervals(&info.inner)?;
+
+                let mut written = data.iter().map(|p| p.header.page_id.into()).collect::<Vec<usize>>();
+                written.push(0);
+                persist_pages(&mut data, &mut file)?;
+                for mut primary_index_page in &mut primary_index {
+                    persist_page(&mut primary_index_page, &mut file)?;
+                }
+                indexes.persist(&mut file)?;
+                persist_page(&mut info, &mut file)?;
+                if durability == DurabilityMode::Fsync {
+                    file.sync_all()?;
+                }
+                drop(file);
+
+                update_checksums::<C, _>(&path, &written, durability)?;
+                // Pages are marked as persisted only after they are written, so failed append doesn't lose them.
+                self.0.data.mark_persisted(seq);
+                if let (Some(wal), Some(mark)) = (&self.2, mark) {
+                    wal.checkpoint(mark)?;
+                }
                 Ok(())
             }
         })
@@ -64,19 +537,211 @@ impl Generator {
         let name_generator = WorktableNameGenerator::from_struct_ident(&self.struct_def.ident);
         let space_ident = name_generator.get_space_ident();
         let wt_ident = name_generator.get_work_table_ident();
+        let read_only_ident = name_generator.get_read_only_table_ident();
         let name_underscore = name_generator.get_filename();
+        let table_name = name_generator.get_work_table_literal_name();
 
         Ok(quote! {
+            /// Loads table from its file, or returns empty table if file can't be opened. If file was written by newer
+            /// version of the crate, [`VersionError::FileFromNewerVersion`] is returned instead of empty table, so
+            /// file is not overwritten by next persist.
             pub fn load_from_file(manager: std::sync::Arc<DatabaseManager>) -> eyre::Result<Self> {
-                let filename = format!("{}/{}.wt", manager.database_files_dir.as_str(), #name_underscore);
-                let filename = std::path::Path::new(filename.as_str());
-                let Ok(mut file) = std::fs::File::open(filename) else {
+                let filename = table_file_path(&manager.database_files_dir, #name_underscore)?;
+                let Ok(mut file) = std::fs::File::open(&filename) else {
                     return Ok(#wt_ident::new(manager));
                 };
+                verify_checksums(&filename)?;
                 let space = #space_ident::parse_file(&mut file)?;
-                let table = space.into_worktable(manager);
+                let table = space.into_worktable(manager)?;
                 Ok(table)
             }
+
+            /// Loads table from its file as read-only table, which has no write methods. Pages and indexes of the file
+            /// are loaded directly into it, so free list, locks and write-ahead log of the table are not created. Empty
+            /// read-only table is returned if file can't be opened.
+            pub fn load_readonly_from_file(
+                manager: std::sync::Arc<DatabaseManager>,
+            ) -> eyre::Result<#read_only_ident> {
+                let filename = table_file_path(&manager.database_files_dir, #name_underscore)?;
+                let Ok(mut file) = std::fs::File::open(&filename) else {
+                    let mut data = DataPages::new();
+                    data.set_table_name(#table_name);
+                    return Ok(#read_only_ident(ReadOnlyTable::new(data, Default::default()), Default::default()));
+                };
+                verify_checksums(&filename)?;
+                #space_ident::parse_file(&mut file)?.into_readonly()
+            }
+        })
+    }
+
+    /// Generates `refresh_from_file` function, which reloads only data pages whose checksums differ from the
+    /// on-disk ones and fixes primary and secondary index entries of the rows stored on these pages.
+    fn gen_refresh_from_file_fn(&self) -> syn::Result<TokenStream> {
+        let name_generator = WorktableNameGenerator::from_struct_ident(&self.struct_def.ident);
+        let space_ident = name_generator.get_space_ident();
+
+        Ok(quote! {
+            /// Reloads pages that were changed on disk and returns count of the refreshed pages.
+            pub fn refresh_from_file<P: AsRef<std::path::Path>>(&self, path: P) -> eyre::Result<usize> {
+                verify_checksums(&path)?;
+                let mut file = std::fs::File::open(path)?;
+                let space = #space_ident::parse_file(&mut file)?;
+
+                let mut changed = std::collections::HashSet::new();
+                let mut pages = vec![];
+                for (page_id, page) in space.data.into_iter().enumerate() {
+                    let mut data = Data::from_data_page(page);
+                    data.set_page_id((page_id as u32).into());
+                    if self.0.data.page_checksum(data.id())? != Some(data.checksum()?) {
+                        changed.insert(page_id);
+                        pages.push(data);
+                    }
+                }
+                if changed.is_empty() {
+                    return Ok(0);
+                }
+
+                let outdated = TableIndex::iter(&self.0.pk_map)
+                    .filter(|(_, link)| {
+                        let page_id: usize = link.page_id.into();
+                        changed.contains(&page_id)
+                    })
+                    .map(|(pk, link)| (pk.clone(), *link))
+                    .collect::<Vec<_>>();
+                for (pk, link) in outdated {
+                    let row = self.0.data.select(link).map_err(WorkTableError::PagesError)?;
+                    self.0.indexes.delete_row(row, link)?;
+                    TableIndex::remove(&self.0.pk_map, &pk);
+                }
+
+                for data in pages {
+                    self.0.data.replace_page(data).map_err(WorkTableError::PagesError)?;
+                }
+
+                for page in space.primary_index {
+                    for val in page.inner.index_values {
+                        let page_id: usize = val.link.page_id.into();
+                        if !changed.contains(&page_id) {
+                            continue;
+                        }
+                        let row = self.0.data.select(val.link).map_err(WorkTableError::PagesError)?;
+                        TableIndex::remove(&self.0.pk_map, &val.key);
+                        TableIndex::insert(&self.0.pk_map, val.key, val.link)
+                            .map_err(|_| eyre::eyre!("primary index of the file has duplicate keys"))?;
+                        self.0.indexes.save_row(row, val.link)?;
+                    }
+                }
+
+                Ok(changed.len())
+            }
+        })
+    }
+
+    /// Generates `parse_file_lenient` function, which recovers as much rows as possible from partially corrupted
+    /// file. Intervals of the info page are validated first, and pages are read until the first unreadable one or the
+    /// end of the file. Primary keys and secondary indexes are rebuilt from the rows which links point to survived
+    /// data pages.
+    fn gen_parse_file_lenient_fn(&self) -> syn::Result<TokenStream> {
+        let name_generator = WorktableNameGenerator::from_struct_ident(&self.struct_def.ident);
+        let pk_type = name_generator.get_primary_key_type_ident();
+        let page_const_name = name_generator.get_page_size_const_ident();
+        let inner_const_name = name_generator.get_page_inner_size_const_ident();
+
+        Ok(quote! {
+            /// Parses table from file, skipping everything after the first unreadable page. Returns partial table
+            /// and report about skipped pages and rows.
+            pub fn parse_file_lenient(
+                file: &mut std::fs::File,
+                manager: std::sync::Arc<DatabaseManager>,
+            ) -> eyre::Result<(Self, LenientParseReport)> {
+                let info = parse_page::<SpaceInfoData<<<#pk_type as TablePrimaryKey>::Generator as PrimaryKeyGeneratorState>::State>, { #page_const_name as u32 }>(file, 0)?;
+                // Intervals of the corrupted info page can overlap, so index pages could be read from data section.
+                // Pages after the end of the file are not read at all.
+                validate_disjoint_intervals(&info.inner)?;
+                let file_pages = file.metadata()?.len().div_ceil(#page_const_name as u64) as usize;
+                let mut report = LenientParseReport::default();
+
+                let mut links = vec![];
+                let index_pages_count: usize = info.inner.primary_key_intervals.iter().map(|i| i.1 - i.0 + 1).sum();
+                let mut index_pages_read = 0;
+                'index: for interval in &info.inner.primary_key_intervals {
+                    for page_id in interval.0..=interval.1 {
+                        if page_id >= file_pages {
+                            break 'index;
+                        }
+                        let Ok(page) = parse_page::<IndexData<#pk_type>, { #page_const_name as u32 }>(file, page_id as u32) else {
+                            break 'index;
+                        };
+                        links.extend(page.inner.index_values.into_iter().map(|v| v.link));
+                        index_pages_read += 1;
+                    }
+                }
+
+                let data_pages_count: usize = info.inner.data_intervals.iter().map(|i| i.1 - i.0 + 1).sum();
+                let mut data = vec![];
+                'data: for interval in &info.inner.data_intervals {
+                    for page_id in interval.0..=interval.1 {
+                        if page_id >= file_pages {
+                            break 'data;
+                        }
+                        let Ok(page) = parse_data_page::<{ #page_const_name }, { #inner_const_name }>(file, page_id as u32) else {
+                            break 'data;
+                        };
+                        let mut page = Data::from_data_page(page);
+                        page.set_page_id((data.len() as u32).into());
+                        data.push(std::sync::Arc::new(page));
+                    }
+                }
+                report.pages_skipped = index_pages_count - index_pages_read + data_pages_count - data.len();
+                let data_pages_read = data.len();
+                if data.is_empty() {
+                    data.push(std::sync::Arc::new(Data::new(0.into())));
+                }
+
+                let mut table = Self::new(manager);
+                table.0.data = DataPages::from_data(data).with_empty_links(
+                    info.inner
+                        .empty_links_list
+                        .into_iter()
+                        .filter(|l| {
+                            let page_id: usize = l.page_id.into();
+                            page_id < data_pages_read
+                        })
+                        .collect(),
+                );
+                table.0.data.set_table_name(table.0.table_name);
+                table.0.pk_gen = PrimaryKeyGeneratorState::from_state(info.inner.pk_gen_state);
+
+                for link in links {
+                    let page_id: usize = link.page_id.into();
+                    if page_id >= data_pages_read {
+                        report.rows_skipped += 1;
+                        continue;
+                    }
+                    let Ok(row) = table.0.data.select(link) else {
+                        report.rows_skipped += 1;
+                        continue;
+                    };
+                    let pk = row.get_primary_key();
+                    if TableIndex::insert(&table.0.pk_map, pk.clone(), link).is_err() {
+                        report.rows_skipped += 1;
+                        continue;
+                    }
+                    // Row of the damaged file can violate unique index, so it's skipped instead of failing parse.
+                    if table.0.indexes.save_row(row, link).is_err() {
+                        TableIndex::remove(&table.0.pk_map, &pk);
+                        report.rows_skipped += 1;
+                        continue;
+                    }
+                    report.rows_recovered += 1;
+                }
+                table
+                    .0
+                    .data
+                    .drop_invalid_empty_links(TableIndex::iter(&table.0.pk_map).map(|(_, l)| *l));
+
+                Ok((table, report))
+            }
         })
     }
 
@@ -122,7 +787,11 @@ impl Generator {
 
         Ok(quote! {
             pub fn get_peristed_primary_key(&self) -> Vec<IndexData<#pk_type>> {
-                map_unique_tree_index::<_, #const_name>(TableIndex::iter(&self.0.pk_map))
+                // Hash-style primary index is iterated in random order, so entries are sorted to write same pages on
+                // every run. Entries of the ordered index are sorted too, which takes single pass for sorted input.
+                let mut entries = TableIndex::iter(&self.0.pk_map).collect::<Vec<_>>();
+                entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+                map_unique_tree_index::<_, #const_name>(entries.into_iter())
             }
         })
     }
@@ -133,12 +802,48 @@ impl Generator {
         let space_ident = name_generator.get_space_ident();
 
         Ok(quote! {
-            pub fn into_space(&self) -> #space_ident {
+            pub fn into_space(&self) -> eyre::Result<#space_ident> {
+                let mut space = self.into_space_without_data();
+
+                let previous_header = match space.indexes.get_last_header_mut() {
+                    Some(previous_header) => previous_header,
+                    None => &mut space.primary_index
+                        .last_mut()
+                        .expect("Primary index page always exists, even if empty")
+                        .header,
+                };
+                let data = map_data_pages_to_general(self.0.data.get_bytes()?.into_iter().map(|(b, offset)| DataPage {
+                    data: b,
+                    length: offset,
+                }).collect::<Vec<_>>(), previous_header);
+                let interval = Interval(
+                    data
+                        .first()
+                        .expect("Data page always exists, even if empty")
+                        .header
+                        .page_id
+                        .into(),
+                    data
+                        .last()
+                        .expect("Data page always exists, even if empty")
+                        .header
+                        .page_id
+                        .into()
+                );
+                space.info.inner.data_intervals = vec![interval];
+                space.data = data;
+
+                Ok(space)
+            }
+
+            /// Returns space of the table with info and index pages, but without data pages. Data pages are placed
+            /// after the last index page and their interval is not set.
+            fn into_space_without_data(&self) -> #space_ident {
                 let path = self.1.config_path.clone();
 
                 let mut info = #ident::space_info_default();
                 info.inner.pk_gen_state = self.0.pk_gen.get_state();
-                info.inner.empty_links_list = self.0.data.get_empty_links();
+                info.inner.empty_links_list = self.0.data.empty_links_snapshot();
                 info.inner.page_count = 1;
                 let mut header = &mut info.header;
 
@@ -169,36 +874,12 @@ impl Generator {
                 let secondary_intevals = indexes.get_intervals();
                 info.inner.secondary_index_intervals = secondary_intevals;
 
-                let previous_header = match indexes.get_last_header_mut() {
-                    Some(previous_header) => previous_header,
-                    None => previous_header,
-                };
-                let data = map_data_pages_to_general(self.0.data.get_bytes().into_iter().map(|(b, offset)| DataPage {
-                    data: b,
-                    length: offset,
-                }).collect::<Vec<_>>(), previous_header);
-                let interval = Interval(
-                    data
-                        .first()
-                        .expect("Data page always exists, even if empty")
-                        .header
-                        .page_id
-                        .into(),
-                    data
-                        .last()
-                        .expect("Data page always exists, even if empty")
-                        .header
-                        .page_id
-                        .into()
-                );
-                info.inner.data_intervals = vec![interval];
-
                 #space_ident {
                     path,
                     info,
                     primary_index,
                     indexes,
-                    data,
+                    data: vec![],
                 }
             }
         })
@@ -212,23 +893,50 @@ impl Generator {
         Ok(quote! {
             impl #space_ident {
                 pub fn persist(&mut self) -> eyre::Result<()> {
-                    let file_name = #file_name;
-                    let path = std::path::Path::new(format!("{}/{}.wt", &self.path , file_name).as_str());
-                    let prefix = &self.path;
-                    std::fs::create_dir_all(prefix).unwrap();
+                    self.persist_with(DurabilityMode::default())
+                }
+
+                /// Writes all pages to the file. File is synced to disk only with [`DurabilityMode::Fsync`], so
+                /// without it successful return doesn't mean that data will survive a crash. If pages' intervals
+                /// overlap, [`IntervalError`] is returned and file is not written.
+                pub fn persist_with(&mut self, durability: DurabilityMode) -> eyre::Result<()> {
+                    self.persist_with_stats(durability).map(|_| ())
+                }
+
+                /// Same as [`Self::persist_with`], but returns [`PersistStats`] of the written file.
+                pub fn persist_with_stats(&mut self, durability: DurabilityMode) -> eyre::Result<PersistStats> {
+                    let started = std::time::Instant::now();
+                    // Broken layout is returned as error before file is truncated. Parsed space can have gaps left by
+                    // append, so only overlaps are checked here.
+                    validate_disjoint_intervals(&self.info.inner)?;
+                    let path = table_file_path(&self.path, #file_name)?;
+                    std::fs::create_dir_all(&self.path)?;
 
-                    let mut file = std::fs::File::create(format!("{}/{}.wt", &self.path , file_name))?;
+                    let mut file = std::fs::File::create(path)?;
                     persist_page(&mut self.info, &mut file)?;
 
                     for mut primary_index_page in &mut self.primary_index {
                         persist_page(&mut primary_index_page, &mut file)?;
                     }
                     self.indexes.persist(&mut file)?;
-                    for mut data_page in &mut self.data {
-                        persist_page(&mut data_page, &mut file)?;
+                    persist_pages(&mut self.data, &mut file)?;
+                    if durability == DurabilityMode::Fsync {
+                        file.sync_all()?;
                     }
 
-                    Ok(())
+                    let secondary_index_pages = self
+                        .indexes
+                        .get_intervals()
+                        .values()
+                        .flatten()
+                        .map(|i| i.1 - i.0 + 1)
+                        .sum::<usize>();
+                    Ok(PersistStats {
+                        bytes_written: file.metadata()?.len(),
+                        data_pages: self.data.len(),
+                        index_pages: self.primary_index.len() + secondary_index_pages,
+                        duration: started.elapsed(),
+                    })
                 }
             }
         })
@@ -17,6 +17,7 @@ pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
         pk_ident,
         index_type_ident
     };
+    gen.check_filename()?;
 
     let space_type = gen.gen_space_type()?;
     let space_impl = gen.gen_space_impls()?;
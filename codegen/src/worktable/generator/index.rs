@@ -1,5 +1,6 @@
 use crate::name_generator::WorktableNameGenerator;
 use crate::worktable::generator::Generator;
+use crate::worktable::model::{ComputedIndex, Index};
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
 
@@ -18,7 +19,8 @@ impl Generator {
 
     /// Generates table's secondary index struct definition. It has fields with index names and types varying on index
     /// uniqueness. For unique index it's `TreeIndex<T, Link`, for non-unique `TreeIndex<T, Arc<LockFreeSet<Link>>>`.
-    /// Index also derives `PersistIndex` macro.
+    /// For `optional` column `T` is inner type, because `None` values are not indexed. Index also derives
+    /// `PersistIndex` macro, and indexes with `persist: false` and computed indexes are marked to be skipped by it.
     fn gen_type_def(&mut self) -> TokenStream {
         let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
         let ident = name_generator.get_index_type_ident();
@@ -28,15 +30,31 @@ impl Generator {
             .iter()
             .map(|(i, idx)| {
                 let index_type = &idx.index_type;
-                let t = self.columns.columns_map.get(&i);
+                let t = self.columns.index_key_type(i);
                 let i = &idx.name;
+                let skip = if idx.persist {
+                    quote! {}
+                } else {
+                    quote! { #[persist_index(skip)] }
+                };
 
                 if idx.is_unique {
-                    quote! {#i: #index_type<#t, Link>}
+                    quote! {#skip #i: #index_type<#t, Link>}
                 } else {
-                    quote! {#i: #index_type<#t, std::sync::Arc<LockFreeSet<Link>>>}
+                    quote! {#skip #i: #index_type<#t, std::sync::Arc<LockFreeSet<Link>>>}
                 }
             })
+            .chain(self.columns.computed_indexes.iter().map(|idx| {
+                let index_type = &idx.index_type;
+                let t = &idx.key_type;
+                let i = &idx.name;
+
+                if idx.is_unique {
+                    quote! {#[persist_index(skip)] #i: #index_type<#t, Link>}
+                } else {
+                    quote! {#[persist_index(skip)] #i: #index_type<#t, std::sync::Arc<LockFreeSet<Link>>>}
+                }
+            }))
             .collect::<Vec<_>>();
 
         quote! {
@@ -55,50 +73,189 @@ impl Generator {
 
         let save_row_fn = self.gen_save_row_index_fn();
         let delete_row_fn = self.gen_delete_row_index_fn();
+        let validate_rows_fn = self.gen_validate_rows_index_fn();
+        let clear_fn = self.gen_clear_index_fn();
+        let save_row_to_fn = self.gen_save_row_to_index_fn();
+        let verify_fn = self.gen_verify_index_fn();
+        let update_computed_fn = self.gen_update_computed_index_fn();
+        let key_fns = self.gen_computed_key_fns();
 
         quote! {
             impl TableSecondaryIndex<#row_type_ident> for #index_type_ident {
                 #save_row_fn
                 #delete_row_fn
+                #validate_rows_fn
+                #clear_fn
+            }
+
+            impl #index_type_ident {
+                #save_row_to_fn
+                #verify_fn
+                #update_computed_fn
+                #(#key_fns)*
+            }
+        }
+    }
+
+    /// Generates code which saves `Link` to the index of the `column`. For unique index we can just insert `Link` in
+    /// index, but for non-unique we need to get set from index first and then insert `Link` in set. `None` values of
+    /// `optional` columns are skipped. If key is taken, `on_error` code is run and error is returned.
+    fn gen_save_row_to_index(
+        &self,
+        column: &Ident,
+        idx: &Index,
+        on_error: TokenStream,
+    ) -> TokenStream {
+        let save = Self::gen_save_value_to_index(&idx.name, idx.is_unique, on_error);
+        if self.columns.is_optional(column) {
+            quote! {
+                if let Some(value) = row.#column.clone() {
+                    #save
+                }
+            }
+        } else {
+            quote! {
+                {
+                    let value = row.#column.clone();
+                    #save
+                }
+            }
+        }
+    }
+
+    /// Generates code which saves `Link` to the computed index. Key is computed from the `row`. If key is taken,
+    /// `on_error` code is run and error is returned.
+    fn gen_save_row_to_computed_index(idx: &ComputedIndex, on_error: TokenStream) -> TokenStream {
+        let key_fn = idx.key_fn_ident();
+        let save = Self::gen_save_value_to_index(&idx.name, idx.is_unique, on_error);
+        quote! {
+            {
+                let value = Self::#key_fn(&row);
+                #save
             }
         }
     }
 
-    /// Generates `save_row` function of `TableIndex` trait for index. It saves `Link` to all secondary indexes. Logic
-    /// varies on index uniqueness. For unique index we can just insert `Link` in index, but for non-unique we need to
-    /// get set from index first and then insert `Link` in set.
+    /// Generates code which saves `link` by `value` key to the index with `index_field_name`. If key is taken,
+    /// `on_error` code is run and [`WorkTableError::AlreadyExists`] is returned.
+    fn gen_save_value_to_index(
+        index_field_name: &Ident,
+        is_unique: bool,
+        on_error: TokenStream,
+    ) -> TokenStream {
+        if is_unique {
+            quote! {
+                if TableIndex::insert(&self.#index_field_name, value, link).is_err() {
+                    #on_error
+                    return core::result::Result::Err(WorkTableError::AlreadyExists);
+                }
+            }
+        } else {
+            quote! {
+                if let Some(set) = TableIndex::peek(&self.#index_field_name, &value) {
+                    set.insert(link).expect("is ok");
+                } else {
+                    let set = LockFreeSet::new();
+                    set.insert(link).expect("`Link` should not be already in set");
+                    if TableIndex::insert(&self.#index_field_name, value, std::sync::Arc::new(set)).is_err() {
+                        #on_error
+                        return core::result::Result::Err(WorkTableError::AlreadyExists);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Generates code which removes `link` stored by `value` key from the index with `index_field_name`.
+    fn gen_delete_value_from_index(index_field_name: &Ident, is_unique: bool) -> TokenStream {
+        if is_unique {
+            quote! {
+                TableIndex::remove(&self.#index_field_name, value);
+            }
+        } else {
+            quote! {
+                if let Some(set) = TableIndex::peek(&self.#index_field_name, value) {
+                    set.remove(&link);
+                }
+            }
+        }
+    }
+
+    /// Generates `save_row` function of `TableIndex` trait for index. It saves `Link` to all secondary indexes. If
+    /// key of unique index is taken, `Link` is removed from indexes it was already saved to, so indexes are left
+    /// unchanged.
     fn gen_save_row_index_fn(&self) -> TokenStream {
         let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
         let row_type_ident = name_generator.get_row_type_ident();
 
+        let deletes = self.gen_delete_rows();
+        let columns_count = self.columns.indexes.len();
+        let save_rows = self
+            .columns
+            .indexes
+            .iter()
+            .enumerate()
+            .map(|(pos, (i, idx))| {
+                let rollback = &deletes[..pos];
+                self.gen_save_row_to_index(i, idx, quote! { #(#rollback)* })
+            })
+            .chain(
+                self.columns
+                    .computed_indexes
+                    .iter()
+                    .enumerate()
+                    .map(|(pos, idx)| {
+                        let rollback = &deletes[..columns_count + pos];
+                        Self::gen_save_row_to_computed_index(idx, quote! { #(#rollback)* })
+                    }),
+            )
+            .collect::<Vec<_>>();
+
+        quote! {
+            fn save_row(&self, row: #row_type_ident, link: Link) -> core::result::Result<(), WorkTableError> {
+                #(#save_rows)*
+                core::result::Result::Ok(())
+            }
+        }
+    }
+
+    /// Generates `save_row_to` function of index. It saves `Link` only to secondary indexes with provided names, so
+    /// indexes which were not loaded from the file are rebuilt from the rows without touching loaded ones.
+    fn gen_save_row_to_index_fn(&self) -> TokenStream {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type_ident = name_generator.get_row_type_ident();
+
         let save_rows = self
             .columns
             .indexes
             .iter()
             .map(|(i, idx)| {
-                let index_field_name = &idx.name;
-                if idx.is_unique {
-                    quote! {
-                        TableIndex::insert(&self.#index_field_name, row.#i, link)
-                            .map_err(|_| WorkTableError::AlreadyExists)?;
-                    }
-                } else {
-                    quote! {
-                        if let Some(set) = TableIndex::peek(&self.#index_field_name, &row.#i) {
-                            set.insert(link).expect("is ok");
-                        } else {
-                            let set = LockFreeSet::new();
-                            set.insert(link).expect("`Link` should not be already in set");
-                            TableIndex::insert(&self.#index_field_name, row.#i, std::sync::Arc::new(set))
-                                .map_err(|_| WorkTableError::AlreadyExists)?;
-                        }
+                let index_name = idx.name.to_string();
+                let save = self.gen_save_row_to_index(i, idx, quote! {});
+                quote! {
+                    if names.contains(&#index_name) {
+                        #save
                     }
                 }
             })
+            .chain(self.columns.computed_indexes.iter().map(|idx| {
+                let index_name = idx.name.to_string();
+                let save = Self::gen_save_row_to_computed_index(idx, quote! {});
+                quote! {
+                    if names.contains(&#index_name) {
+                        #save
+                    }
+                }
+            }))
             .collect::<Vec<_>>();
 
         quote! {
-            fn save_row(&self, row: #row_type_ident, link: Link) -> core::result::Result<(), WorkTableError> {
+            pub fn save_row_to(
+                &self,
+                row: &#row_type_ident,
+                link: Link,
+                names: &[&str],
+            ) -> core::result::Result<(), WorkTableError> {
                 #(#save_rows)*
                 core::result::Result::Ok(())
             }
@@ -112,31 +269,401 @@ impl Generator {
         let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
         let row_type_ident = name_generator.get_row_type_ident();
 
-        let delete_rows = self
+        let delete_rows = self.gen_delete_rows();
+
+        quote! {
+            fn delete_row(&self, row: #row_type_ident, link: Link) -> core::result::Result<(), WorkTableError> {
+                #(#delete_rows)*
+                core::result::Result::Ok(())
+            }
+        }
+    }
+
+    /// Generates code which removes `row`'s `Link` from each secondary index, in the same order as indexes are saved
+    /// by `save_row`. Column indexes go first and computed indexes go after them.
+    fn gen_delete_rows(&self) -> Vec<TokenStream> {
+        self.columns
+            .indexes
+            .iter()
+            .map(|(i, idx)| {
+                let delete = Self::gen_delete_value_from_index(&idx.name, idx.is_unique);
+                if self.columns.is_optional(i) {
+                    quote! {
+                        if let Some(value) = &row.#i {
+                            #delete
+                        }
+                    }
+                } else {
+                    quote! {
+                        {
+                            let value = &row.#i;
+                            #delete
+                        }
+                    }
+                }
+            })
+            .chain(self.columns.computed_indexes.iter().map(|idx| {
+                let key_fn = idx.key_fn_ident();
+                let delete = Self::gen_delete_value_from_index(&idx.name, idx.is_unique);
+                quote! {
+                    {
+                        let value = &Self::#key_fn(&row);
+                        #delete
+                    }
+                }
+            }))
+            .collect()
+    }
+
+    /// Generates `validate_rows` function of `TableIndex` trait for index. Only unique indexes are checked: row's value
+    /// must not be presented in index already and must not be duplicated by another row. `None` values of `optional`
+    /// columns are not indexed, so they are not checked.
+    fn gen_validate_rows_index_fn(&self) -> TokenStream {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type_ident = name_generator.get_row_type_ident();
+
+        let validate_rows = self
             .columns
             .indexes
             .iter()
+            .filter(|(_, idx)| idx.is_unique)
             .map(|(i, idx)| {
                 let index_field_name = &idx.name;
-                if idx.is_unique {
+                let index_name = idx.name.to_string();
+                let value = if self.columns.is_optional(i) {
                     quote! {
-                        TableIndex::remove(&self.#index_field_name, &row.#i);
+                        let Some(value) = &row.#i else {
+                            continue;
+                        };
                     }
                 } else {
                     quote! {
-                        if let Some(set) = TableIndex::peek(&self.#index_field_name, &row.#i) {
-                            set.remove(&link);
+                        let value = &row.#i;
+                    }
+                };
+                quote! {
+                    {
+                        let mut values = std::collections::BTreeSet::new();
+                        for (pos, row) in rows.iter().enumerate() {
+                            #value
+                            if TableIndex::peek(&self.#index_field_name, value).is_some()
+                                || !values.insert(value)
+                            {
+                                errors.push(BatchError::UniqueIndexViolation {
+                                    row: pos,
+                                    index: #index_name,
+                                });
+                            }
                         }
                     }
                 }
             })
+            .chain(
+                self.columns
+                    .computed_indexes
+                    .iter()
+                    .filter(|idx| idx.is_unique)
+                    .map(|idx| {
+                        let index_field_name = &idx.name;
+                        let index_name = idx.name.to_string();
+                        let key_fn = idx.key_fn_ident();
+                        quote! {
+                            {
+                                let mut values = std::collections::BTreeSet::new();
+                                for (pos, row) in rows.iter().enumerate() {
+                                    let value = Self::#key_fn(row);
+                                    if TableIndex::peek(&self.#index_field_name, &value).is_some()
+                                        || !values.insert(value)
+                                    {
+                                        errors.push(BatchError::UniqueIndexViolation {
+                                            row: pos,
+                                            index: #index_name,
+                                        });
+                                    }
+                                }
+                            }
+                        }
+                    }),
+            )
             .collect::<Vec<_>>();
 
         quote! {
-            fn delete_row(&self, row: #row_type_ident, link: Link) -> core::result::Result<(), WorkTableError> {
-                #(#delete_rows)*
+            fn validate_rows(&self, rows: &[#row_type_ident]) -> Vec<BatchError> {
+                let mut errors = vec![];
+                #(#validate_rows)*
+                errors
+            }
+        }
+    }
+
+    /// Generates `verify` function of index. It checks every secondary index against live `rows` of the table: every
+    /// entry must point to live row with the same column's value, and every live row must have an entry. `None` values
+    /// of `optional` columns are not indexed, so rows with them must have no entry. Keys of computed indexes are
+    /// computed from the rows.
+    fn gen_verify_index_fn(&self) -> TokenStream {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type_ident = name_generator.get_row_type_ident();
+
+        let verify_rows = self
+            .columns
+            .indexes
+            .iter()
+            .map(|(i, idx)| {
+                let matches = if self.columns.is_optional(i) {
+                    quote! { row.#i.as_ref() == Some(key) }
+                } else {
+                    quote! { &row.#i == key }
+                };
+                let value = if self.columns.is_optional(i) {
+                    quote! {
+                        let Some(value) = &row.#i else {
+                            continue;
+                        };
+                    }
+                } else {
+                    quote! {
+                        let value = &row.#i;
+                    }
+                };
+                Self::gen_verify_index(&idx.name, idx.is_unique, matches, value)
+            })
+            .chain(self.columns.computed_indexes.iter().map(|idx| {
+                let key_fn = idx.key_fn_ident();
+                let matches = quote! { &Self::#key_fn(row) == key };
+                let value = quote! {
+                    let value = &Self::#key_fn(row);
+                };
+                Self::gen_verify_index(&idx.name, idx.is_unique, matches, value)
+            }))
+            .collect::<Vec<_>>();
+        let rows_arg = if verify_rows.is_empty() {
+            quote! { _rows }
+        } else {
+            quote! { rows }
+        };
+
+        quote! {
+            pub fn verify(
+                &self,
+                #rows_arg: &std::collections::HashMap<Link, #row_type_ident>,
+            ) -> Vec<IntegrityError> {
+                let mut errors = vec![];
+                #(#verify_rows)*
+                errors
+            }
+        }
+    }
+
+    /// Generates functions that compute keys of the computed indexes. Expression from the declaration is coerced to
+    /// `fn` pointer, so closure's argument type is inferred and capturing closures are rejected.
+    fn gen_computed_key_fns(&self) -> Vec<TokenStream> {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type_ident = name_generator.get_row_type_ident();
+
+        self.columns
+            .computed_indexes
+            .iter()
+            .map(|idx| {
+                let key_fn = idx.key_fn_ident();
+                let key_type = &idx.key_type;
+                let expr = &idx.expr;
+                quote! {
+                    fn #key_fn(row: &#row_type_ident) -> #key_type {
+                        let key: fn(&#row_type_ident) -> #key_type = #expr;
+                        key(row)
+                    }
+                }
+            })
+            .collect()
+    }
+
+    /// Generates `update_computed` function of index. It's called before row is updated in place, while row is
+    /// locked, and moves row's `Link` from `old` row's keys to `new` row's keys in computed indexes, which keys are
+    /// changed. New keys are saved first, and old keys are removed only after all new keys are saved, so if one of
+    /// them fails, saved ones are removed and indexes are left unchanged. Other indexes are not touched. Generated
+    /// only if table has computed indexes.
+    fn gen_update_computed_index_fn(&self) -> TokenStream {
+        if self.columns.computed_indexes.is_empty() {
+            return quote! {};
+        }
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type_ident = name_generator.get_row_type_ident();
+
+        let keys = self
+            .columns
+            .computed_indexes
+            .iter()
+            .map(|idx| {
+                let key_fn = idx.key_fn_ident();
+                let (old_key, new_key, changed) = Self::computed_key_idents(idx);
+                quote! {
+                    let #old_key = Self::#key_fn(old);
+                    let #new_key = Self::#key_fn(new);
+                    let #changed = #old_key != #new_key;
+                }
+            })
+            .collect::<Vec<_>>();
+        let remove = |idx: &ComputedIndex, key: &Ident| {
+            let (_, _, changed) = Self::computed_key_idents(idx);
+            let delete = Self::gen_delete_value_from_index(&idx.name, idx.is_unique);
+            quote! {
+                if #changed {
+                    let value = &#key;
+                    #delete
+                }
+            }
+        };
+        let saves = self
+            .columns
+            .computed_indexes
+            .iter()
+            .enumerate()
+            .map(|(pos, idx)| {
+                let (_, new_key, changed) = Self::computed_key_idents(idx);
+                let save_fn = Self::computed_save_fn_ident(idx);
+                let rollback = self.columns.computed_indexes[..pos]
+                    .iter()
+                    .map(|idx| remove(idx, &Self::computed_key_idents(idx).1))
+                    .collect::<Vec<_>>();
+                quote! {
+                    if #changed {
+                        if let core::result::Result::Err(e) = self.#save_fn(#new_key.clone(), link) {
+                            #(#rollback)*
+                            return core::result::Result::Err(e);
+                        }
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+        let removes = self
+            .columns
+            .computed_indexes
+            .iter()
+            .map(|idx| remove(idx, &Self::computed_key_idents(idx).0))
+            .collect::<Vec<_>>();
+        let save_fns = self
+            .columns
+            .computed_indexes
+            .iter()
+            .map(|idx| {
+                let save_fn = Self::computed_save_fn_ident(idx);
+                let key_type = &idx.key_type;
+                let save = Self::gen_save_value_to_index(&idx.name, idx.is_unique, quote! {});
+                quote! {
+                    fn #save_fn(&self, value: #key_type, link: Link) -> core::result::Result<(), WorkTableError> {
+                        #save
+                        core::result::Result::Ok(())
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        quote! {
+            /// Moves `link` of the row from `old` row's keys to `new` row's keys in computed indexes. It's called
+            /// before row is changed. If new key of unique computed index is taken by another row, `AlreadyExists` is
+            /// returned and indexes are not changed.
+            pub fn update_computed(
+                &self,
+                old: &#row_type_ident,
+                new: &#row_type_ident,
+                link: Link,
+            ) -> core::result::Result<(), WorkTableError> {
+                #(#keys)*
+                #(#saves)*
+                #(#removes)*
                 core::result::Result::Ok(())
             }
+
+            #(#save_fns)*
+        }
+    }
+
+    /// Returns name of the function that saves `Link` by key to computed index.
+    fn computed_save_fn_ident(idx: &ComputedIndex) -> Ident {
+        Ident::new(format!("save_{}", idx.name).as_str(), Span::mixed_site())
+    }
+
+    /// Returns names of the variables with old key, new key and flag of key's change of computed index, which are
+    /// used by `update_computed` function.
+    fn computed_key_idents(idx: &ComputedIndex) -> (Ident, Ident, Ident) {
+        let name = &idx.name;
+        (
+            Ident::new(format!("{name}_old").as_str(), Span::mixed_site()),
+            Ident::new(format!("{name}_new").as_str(), Span::mixed_site()),
+            Ident::new(format!("{name}_changed").as_str(), Span::mixed_site()),
+        )
+    }
+
+    /// Generates check of one index for `verify` function. `matches` checks that `row` has index's `key`, and `value`
+    /// binds `value` to the row's key or skips the row if it's not indexed.
+    fn gen_verify_index(
+        index_field_name: &Ident,
+        is_unique: bool,
+        matches: TokenStream,
+        value: TokenStream,
+    ) -> TokenStream {
+        let index_name = index_field_name.to_string();
+        let links = if is_unique {
+            quote! { vec![*links] }
+        } else {
+            quote! { links.iter().map(|l| *l.as_ref()).collect::<Vec<_>>() }
+        };
+        let indexed = if is_unique {
+            quote! { TableIndex::peek(&self.#index_field_name, value) == Some(*link) }
+        } else {
+            quote! {
+                TableIndex::peek(&self.#index_field_name, value)
+                    .map(|set| set.iter().any(|l| l.as_ref() == link))
+                    .unwrap_or_default()
+            }
+        };
+        quote! {
+            for (key, links) in TableIndex::iter(&self.#index_field_name) {
+                for link in #links {
+                    match rows.get(&link) {
+                        Some(row) if #matches => {}
+                        Some(_) => errors.push(IntegrityError::KeyMismatch {
+                            index: #index_name,
+                            link,
+                        }),
+                        None => errors.push(IntegrityError::OrphanedLink {
+                            index: #index_name,
+                            link,
+                        }),
+                    }
+                }
+            }
+            for (link, row) in rows {
+                #value
+                if !(#indexed) {
+                    errors.push(IntegrityError::MissingEntry {
+                        index: #index_name,
+                        link: *link,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Generates `clear` function of `TableSecondaryIndex` trait for index. It removes all entries from every index.
+    fn gen_clear_index_fn(&self) -> TokenStream {
+        let clear_rows = self
+            .columns
+            .indexes
+            .values()
+            .map(|idx| &idx.name)
+            .chain(self.columns.computed_indexes.iter().map(|idx| &idx.name))
+            .map(|index_field_name| {
+                quote! {
+                    TableIndex::clear(&self.#index_field_name);
+                }
+            })
+            .collect::<Vec<_>>();
+
+        quote! {
+            fn clear(&self) {
+                #(#clear_rows)*
+            }
         }
     }
 }
@@ -6,7 +6,8 @@ mod table;
 //mod table_old;
 mod wrapper;
 
-use proc_macro2::Ident;
+use proc_macro2::{Ident, TokenStream};
+use quote::quote;
 
 use crate::worktable::model::{Columns, Config, PrimaryKey, Queries};
 
@@ -31,4 +32,168 @@ impl Generator {
             columns,
         }
     }
+
+    /// Returns `true` if `serde` support is enabled in table's config.
+    pub fn is_serde(&self) -> bool {
+        self.config.as_ref().map(|c| c.serde).unwrap_or_default()
+    }
+
+    /// Returns code that locks table's write-ahead log as `wal`. Operation is applied and logged while it's locked,
+    /// so log's order is the same as operations' order. Table's `capture_lock` is held for read too, so checkpoint
+    /// doesn't capture half of the operation. Only persisted tables have log, so for others it's empty.
+    pub fn gen_wal_lock(&self) -> TokenStream {
+        if self.is_persist {
+            quote! {
+                let _capture = self.0.capture_lock.read().unwrap_or_else(std::sync::PoisonError::into_inner);
+                let mut wal = self.wal_writer();
+            }
+        } else {
+            quote! {}
+        }
+    }
+
+    /// Returns code that appends `row` to the log locked by [`Generator::gen_wal_lock`] as `op`. If append fails,
+    /// `revert` code, which undoes the operation, is run and error is returned, so operation is not applied if it's
+    /// not logged. Only persisted tables have log, so for others it's empty.
+    pub fn gen_wal_log_row(
+        &self,
+        op: TokenStream,
+        row: TokenStream,
+        revert: TokenStream,
+    ) -> TokenStream {
+        if self.is_persist {
+            quote! {
+                if let core::result::Result::Err(e) = self.log_wal_row(&mut wal, WalOp::#op, &#row) {
+                    #revert
+                    return core::result::Result::Err(e);
+                }
+            }
+        } else {
+            quote! {}
+        }
+    }
+
+    /// Returns code that reads value of the row stored by `link` as `previous` before row is changed. It's restored by
+    /// [`Generator::gen_wal_log_link`] if change can't be logged. If row can't be read, `on_error` code is run and error
+    /// is returned. It's `None` if log is not enabled, and code is empty if table has no log.
+    pub fn gen_wal_previous(&self, link: TokenStream, on_error: TokenStream) -> TokenStream {
+        if self.is_persist {
+            quote! {
+                let previous = if wal.is_some() {
+                    match self.0.data.select(#link) {
+                        core::result::Result::Ok(row) => Some(row),
+                        core::result::Result::Err(e) => {
+                            #on_error
+                            return core::result::Result::Err(WorkTableError::PagesError(e));
+                        }
+                    }
+                } else {
+                    None
+                };
+            }
+        } else {
+            quote! {}
+        }
+    }
+
+    /// Returns code that appends current value of the row stored by `link` to the log locked by
+    /// [`Generator::gen_wal_lock`] as update. If append fails, row's value read by [`Generator::gen_wal_previous`] is
+    /// restored, `on_error` code is run and error is returned.
+    pub fn gen_wal_log_link(&self, link: TokenStream, on_error: TokenStream) -> TokenStream {
+        if self.is_persist {
+            quote! {
+                if let Some(previous) = previous {
+                    let logged = self.0.data.select(#link)
+                        .map_err(WorkTableError::PagesError)
+                        .and_then(|row| self.log_wal_row(&mut wal, WalOp::Update, &row));
+                    if let core::result::Result::Err(e) = logged {
+                        let _ = self.0.restore(previous, #link);
+                        #on_error
+                        return core::result::Result::Err(e);
+                    }
+                }
+            }
+        } else {
+            quote! {}
+        }
+    }
+
+    /// Returns code that waits for the current lock of the row stored by `link` and locks it by `row_lock` guard. Lock
+    /// is released when `row_lock` is dropped, so it's released on every exit path of the generated function. Row can
+    /// be moved by the lock's holder, so `link` is resolved again by row's primary key after wait, and
+    /// [`WorkTableError::NotFound`] is returned if row was deleted.
+    pub fn gen_row_lock(&self, link: TokenStream) -> TokenStream {
+        quote! {
+            let row_lock = RowLockGuard::new(&self.0.lock_map);
+            let id = self.0.data.with_ref(#link, |archived| {
+                archived.is_locked()
+            }).map_err(WorkTableError::PagesError)?;
+            let #link = if let Some(id) = id {
+                let pk = self.0.data.select(#link).map_err(WorkTableError::PagesError)?.get_primary_key();
+                if let Some(lock) = self.0.lock_map.get(&(id.into())) {
+                    lock.as_ref().await
+                }
+                TableIndex::peek(&self.0.pk_map, &pk).ok_or(WorkTableError::NotFound)?
+            } else {
+                #link
+            };
+            unsafe { self.0.data.with_mut_ref(#link, |archived| {
+                archived.lock = row_lock.id().into();
+            }).map_err(WorkTableError::PagesError)? };
+        }
+    }
+
+    /// Returns code that resets lock of the row stored by `link` locked by [`Generator::gen_row_lock`]. It's used on
+    /// error paths, where row is not moved, so id of the released lock is not left in the row. Error of the reset is
+    /// ignored, because error of the failed operation is returned anyway.
+    pub fn gen_row_lock_reset(&self, link: TokenStream) -> TokenStream {
+        quote! {
+            let _ = unsafe { self.0.data.with_mut_ref(#link, |archived| {
+                archived.lock = 0u16.into();
+            }) };
+        }
+    }
+
+    /// Returns code that moves `link` in computed indexes to the keys of row's new value before row stored by `link`
+    /// is changed, so indexes are not changed if new key of unique computed index is taken. Row's current value is
+    /// read to `computed_before`, and `changes` code sets changed fields of its copy `computed_after`. If move fails,
+    /// `on_error` code is run and error is returned. It's empty if table has no computed indexes.
+    pub fn gen_computed_index_update(
+        &self,
+        link: TokenStream,
+        changes: TokenStream,
+        on_error: TokenStream,
+    ) -> TokenStream {
+        if self.columns.computed_indexes.is_empty() {
+            return quote! {};
+        }
+        quote! {
+            let computed = self.0.data.select(#link).map_err(WorkTableError::PagesError).and_then(|computed_before| {
+                let mut computed_after = computed_before.clone();
+                #changes
+                self.0.indexes.update_computed(&computed_before, &computed_after, #link)?;
+                core::result::Result::Ok((computed_before, computed_after))
+            });
+            let (computed_before, computed_after) = match computed {
+                core::result::Result::Ok(computed) => computed,
+                core::result::Result::Err(e) => {
+                    #on_error
+                    return core::result::Result::Err(e);
+                }
+            };
+        }
+    }
+
+    /// Returns code that moves `link` in computed indexes back to the keys of `computed_before` if row's write after
+    /// `gen_computed_index_update`, which result is `written`, failed. It's empty if table has no computed indexes.
+    pub fn gen_computed_index_revert(&self, link: TokenStream) -> TokenStream {
+        if self.columns.computed_indexes.is_empty() {
+            return quote! {};
+        }
+        quote! {
+            if written.is_err() {
+                let _ = self.0.indexes.update_computed(&computed_after, &computed_before, #link);
+            }
+        }
+    }
 }
@@ -4,8 +4,8 @@ use crate::name_generator::WorktableNameGenerator;
 use crate::worktable::generator::Generator;
 use crate::worktable::model::{GeneratorType, PrimaryKey};
 
-use proc_macro2::{Ident, TokenStream};
-use quote::quote;
+use proc_macro2::{Ident, TokenStream, TokenTree};
+use quote::{quote, quote_spanned};
 
 impl Generator {
     /// Generates primary key type and it's impls.
@@ -29,17 +29,60 @@ impl Generator {
             })
             .collect::<HashMap<_, _>>();
 
+        let ord_check = self.gen_primary_key_ord_check()?;
         let def = self.gen_primary_key_type();
         let impl_ = self.gen_table_primary_key_impl()?;
 
         self.pk = Some(PrimaryKey { ident, values });
 
         Ok(quote! {
+            #ord_check
             #def
             #impl_
         })
     }
 
+    /// Checks that primary key columns' types implement `Ord`, because primary index and range selects rely on it.
+    /// Float types are rejected right away. Other types are checked by generated assertion, which is spanned to the
+    /// column's name, so error points to the declaration instead of generated code.
+    fn gen_primary_key_ord_check(&self) -> syn::Result<TokenStream> {
+        let mut asserts = vec![];
+        for i in &self.columns.primary_keys.0 {
+            let type_ = self
+                .columns
+                .columns_map
+                .get(i)
+                .expect("primary key column name always exists if in primary keys list");
+            if Self::contains_float(type_.clone()) {
+                return Err(syn::Error::new(
+                    i.span(),
+                    format!(
+                        "primary key column `{i}` has type `{type_}` which doesn't implement `Ord`, but primary \
+                         index is ordered by it"
+                    ),
+                ));
+            }
+            asserts.push(quote_spanned! { i.span() =>
+                assert_primary_key_ord::<#type_>();
+            });
+        }
+
+        Ok(quote! {
+            const _: () = {
+                const fn assert_primary_key_ord<T: Ord>() {}
+                #(#asserts)*
+            };
+        })
+    }
+
+    fn contains_float(type_: TokenStream) -> bool {
+        type_.into_iter().any(|t| match t {
+            TokenTree::Ident(i) => i == "f32" || i == "f64",
+            TokenTree::Group(g) => Self::contains_float(g.stream()),
+            _ => false,
+        })
+    }
+
     /// Generates table's primary key struct definition. It's newtype for type that was chosen as primary key column in
     /// definition.
     fn gen_primary_key_type(&self) -> TokenStream {
@@ -80,6 +123,10 @@ impl Generator {
                 quote! {
                     impl TablePrimaryKey for #ident {
                         type Generator = ();
+
+                        fn active_page_key(&self) -> u64 {
+                            serialized_key_hash(self)
+                        }
                     }
                 }
             }
@@ -100,6 +147,10 @@ impl Generator {
                 quote! {
                     impl TablePrimaryKey for #ident {
                         type Generator = #gen;
+
+                        fn active_page_key(&self) -> u64 {
+                            serialized_key_hash(self)
+                        }
                     }
                 }
             }
@@ -22,10 +22,12 @@ impl Generator {
             quote! {}
         };
         let full_row_delete = self.gen_full_row_delete();
+        let delete_many_by_pk = self.gen_delete_many_by_pk();
 
         Ok(quote! {
             impl #table_ident {
                 #full_row_delete
+                #delete_many_by_pk
                 #custom_deletes
             }
         })
@@ -33,6 +35,16 @@ impl Generator {
 
     fn gen_full_row_delete(&mut self) -> TokenStream {
         let pk_ident = &self.pk.as_ref().unwrap().ident;
+        let wal_lock = self.gen_wal_lock();
+        let log = if self.is_persist {
+            quote! {
+                if let core::result::Result::Err(e) = self.log_wal_delete(&mut wal, std::slice::from_ref(&pk)) {
+                    return core::result::Result::Err(e.with_rollback(self.0.relink_many(&[(row, link)])));
+                }
+            }
+        } else {
+            quote! {}
+        };
 
         quote! {
             pub async fn delete(&self, pk: #pk_ident) -> core::result::Result<(), WorkTableError> {
@@ -40,14 +52,21 @@ impl Generator {
                 let id = self.0.data.with_ref(link, |archived| {
                     archived.is_locked()
                 }).map_err(WorkTableError::PagesError)?;
-                if let Some(id) = id {
+                let link = if let Some(id) = id {
                     if let Some(lock) = self.0.lock_map.get(&(id.into())) {
                         lock.as_ref().await
                     }
-                }
-                let row = self.select(pk.clone()).unwrap();
-                self.0.indexes.delete_row(row, link)?;
+                    // Row can be moved or deleted by the lock's holder, so its link is resolved again.
+                    TableIndex::peek(&self.0.pk_map, &pk).ok_or(WorkTableError::NotFound)?
+                } else {
+                    link
+                };
+                #wal_lock
+                let row = self.0.data.select(link).map_err(WorkTableError::PagesError)?;
+                self.0.indexes.delete_row(row.clone(), link)?;
                 self.0.pk_map.remove(&pk);
+                // Row's slot is freed only after delete is logged, so row can be linked back if log fails.
+                #log
                 self.0.data.delete(link).map_err(WorkTableError::PagesError)?;
 
                 core::result::Result::Ok(())
@@ -55,6 +74,74 @@ impl Generator {
         }
     }
 
+    fn gen_delete_many_by_pk(&mut self) -> TokenStream {
+        let pk_ident = &self.pk.as_ref().unwrap().ident;
+        let wal_lock = self.gen_wal_lock();
+        let delete = if self.is_persist {
+            quote! {
+                // Rows are only unlinked before they are logged, so they can be linked back if log fails. Deletes
+                // are logged after that, so rows stay deleted even if their slots can't be freed.
+                let entries = self.0.unlink_many_by_pk(keys)?;
+                let pks = entries.iter().map(|(row, _)| row.get_primary_key()).collect::<Vec<_>>();
+                if let core::result::Result::Err(e) = self.log_wal_delete(&mut wal, &pks) {
+                    return core::result::Result::Err(e.with_rollback(self.0.relink_many(&entries)));
+                }
+                let links = entries.iter().map(|(_, link)| *link).collect::<Vec<_>>();
+                self.0.data.delete_many(&links).map_err(WorkTableError::PagesError)?;
+                let deleted = entries.len();
+            }
+        } else {
+            quote! {
+                let deleted = self.0.delete_many_by_pk(keys)?.len();
+            }
+        };
+
+        quote! {
+            /// Deletes rows with provided primary keys and returns count of the deleted rows. Keys which are not
+            /// presented are skipped. Locked rows are waited for, and rows are deleted together after none of them is
+            /// locked.
+            pub async fn delete_many_by_pk(&self, keys: &[#pk_ident]) -> core::result::Result<usize, WorkTableError> {
+                loop {
+                    for pk in keys {
+                        let Some(link) = TableIndex::peek(&self.0.pk_map, pk) else {
+                            continue;
+                        };
+                        let id = self.0.data.with_ref(link, |archived| {
+                            archived.is_locked()
+                        }).map_err(WorkTableError::PagesError)?;
+                        if let Some(id) = id {
+                            if let Some(lock) = self.0.lock_map.get(&(id.into())) {
+                                lock.as_ref().await
+                            }
+                        }
+                    }
+                    #wal_lock
+                    // Rows can be locked again while they were waited for, so locks are checked once more right
+                    // before delete, and all rows are waited for again if any of them is locked.
+                    let mut locked = false;
+                    for pk in keys {
+                        let Some(link) = TableIndex::peek(&self.0.pk_map, pk) else {
+                            continue;
+                        };
+                        let id = self.0.data.with_ref(link, |archived| {
+                            archived.is_locked()
+                        }).map_err(WorkTableError::PagesError)?;
+                        if id.is_some_and(|id| self.0.lock_map.get(&(id.into())).is_some()) {
+                            locked = true;
+                            break;
+                        }
+                    }
+                    if locked {
+                        continue;
+                    }
+                    #delete
+
+                    return core::result::Result::Ok(deleted);
+                }
+            }
+        }
+    }
+
     fn gen_custom_deletes(&mut self, deleted: HashMap<Ident, Operation>) -> TokenStream {
         let defs = deleted
             .iter()
@@ -75,6 +162,7 @@ impl Generator {
                 let type_ = self.columns.columns_map.get(&op.by).unwrap();
                 if let Some(index) = index {
                     let index_name = &index.name;
+                    let type_ = self.columns.index_key_type(&op.by).unwrap();
 
                     if index.is_unique {
                         Self::gen_unique_delete(&type_, &method_ident, index_name)
@@ -0,0 +1,111 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+
+use crate::name_generator::WorktableNameGenerator;
+use crate::worktable::generator::Generator;
+
+impl Generator {
+    pub fn gen_query_in_place_impl(&mut self) -> syn::Result<TokenStream> {
+        let Some(q) = &self.queries else {
+            return Ok(quote! {});
+        };
+        if q.in_place.is_empty() {
+            return Ok(quote! {});
+        }
+
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let table_ident = name_generator.get_work_table_ident();
+
+        let fns = q
+            .in_place
+            .iter()
+            .map(|column| self.gen_update_field_fn(column))
+            .collect::<syn::Result<Vec<_>>>()?;
+
+        Ok(quote! {
+            impl #table_ident {
+                #(#fns)*
+            }
+        })
+    }
+
+    /// Generates `update_field_<column>` function, which overwrites one column of the archived row in place using
+    /// `with_mut_ref`. Only fixed-size, not indexed columns are allowed, because in place update can't change row's
+    /// length and doesn't touch column indexes. Computed indexes are updated, because their keys can depend on any
+    /// column.
+    fn gen_update_field_fn(&self, column: &Ident) -> syn::Result<TokenStream> {
+        let type_ = self
+            .columns
+            .columns_map
+            .get(column)
+            .ok_or(syn::Error::new(column.span(), "Unexpected column name"))?;
+        if self.columns.primary_keys.0.contains(column) {
+            return Err(syn::Error::new(
+                column.span(),
+                format!("Primary key column `{column}` can't be updated in place"),
+            ));
+        }
+        if self.columns.indexes.contains_key(column) {
+            return Err(syn::Error::new(
+                column.span(),
+                format!("Indexed column `{column}` can't be updated in place"),
+            ));
+        }
+        if !self.columns.is_fixed_size(column) {
+            return Err(syn::Error::new(
+                column.span(),
+                format!(
+                    "Column `{column}` has type `{type_}` which is not fixed-size, so it can't be updated in place"
+                ),
+            ));
+        }
+
+        let fn_name = Ident::new(
+            format!("update_field_{column}").as_str(),
+            Span::mixed_site(),
+        );
+        let row_lock = self.gen_row_lock(quote! { link });
+        // Row is not moved by in place update, so its lock is reset on every error path.
+        let reset_lock = self.gen_row_lock_reset(quote! { link });
+        let wal_lock = self.gen_wal_lock();
+        let previous = self.gen_wal_previous(quote! { link }, reset_lock.clone());
+        let log = self.gen_wal_log_link(quote! { link }, reset_lock.clone());
+        let computed_update = self.gen_computed_index_update(
+            quote! { link },
+            quote! { computed_after.#column = value.clone(); },
+            reset_lock.clone(),
+        );
+        let computed_revert = self.gen_computed_index_revert(quote! { link });
+
+        Ok(quote! {
+            /// Overwrites this column of the row stored by `link` in place, without reserializing the whole row. Row is
+            /// locked while it's written, so other writers of this row wait for it.
+            pub async fn #fn_name(&self, link: Link, value: #type_) -> core::result::Result<(), WorkTableError> {
+                let mut bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&value).map_err(|_| WorkTableError::SerializeError)?;
+                #row_lock
+                #wal_lock
+                #previous
+                // Computed indexes are moved before row is changed, so row is not changed if new key is taken.
+                #computed_update
+                let value = unsafe { rkyv::access_unchecked_mut::<<#type_ as rkyv::Archive>::Archived>(&mut bytes[..]).unseal_unchecked() };
+                // SAFETY: column is fixed-size (checked at macro expansion), so swapping archived values keeps row's
+                // length and layout the same.
+                let written = unsafe { self.0.data.with_mut_ref(link, |archived| {
+                    std::mem::swap(&mut archived.inner.#column, &mut *value);
+                }).map_err(WorkTableError::PagesError) };
+                #computed_revert
+                if let core::result::Result::Err(e) = written {
+                    #reset_lock
+                    return core::result::Result::Err(e);
+                }
+                #log
+                unsafe { self.0.data.with_mut_ref(link, |archived| {
+                    archived.lock = 0u16.into();
+                }).map_err(WorkTableError::PagesError)? };
+                drop(row_lock);
+
+                core::result::Result::Ok(())
+            }
+        })
+    }
+}
@@ -1,4 +1,5 @@
 mod delete;
+mod in_place;
 mod locks;
 mod select;
 pub mod r#type;
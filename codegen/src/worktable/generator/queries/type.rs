@@ -45,11 +45,13 @@ impl Generator {
                 .values()
                 .map(|op| {
                     let ident = Ident::new(format!("{}By", &op.name).as_str(), Span::mixed_site());
-                    let field_type = self
-                        .columns
-                        .columns_map
-                        .get(&op.by)
-                        .ok_or(syn::Error::new(op.by.span(), "Unexpected column name"))?;
+                    // Indexed columns are found by value of the index, which is inner type for `optional` ones.
+                    let field_type = if self.columns.indexes.contains_key(&op.by) {
+                        self.columns.index_key_type(&op.by)
+                    } else {
+                        self.columns.columns_map.get(&op.by)
+                    }
+                    .ok_or(syn::Error::new(op.by.span(), "Unexpected column name"))?;
 
                     Ok::<_, syn::Error>(quote! {
                         pub type #ident = #field_type;
@@ -44,39 +44,83 @@ impl Generator {
                 }
             })
             .collect::<Vec<_>>();
+        let wal_lock = self.gen_wal_lock();
+        let log_relocated = self.gen_wal_log_row(
+            quote! { Update },
+            quote! { new_row },
+            quote! { let _ = self.0.restore(previous, new_link); },
+        );
+        let log = self.gen_wal_log_row(
+            quote! { Update },
+            quote! { new_row },
+            quote! { let _ = self.0.restore(previous, link); },
+        );
+        let relocate = if self.is_persist {
+            quote! { let new_link = self.0.relocate(previous.clone(), new_row.clone(), link)?; }
+        } else {
+            quote! { self.0.relocate(previous.clone(), new_row, link)?; }
+        };
+        let row_lock = self.gen_row_lock(quote! { link });
+        let (computed_update, computed_revert) = if self.columns.computed_indexes.is_empty() {
+            (quote! {}, quote! {})
+        } else {
+            (
+                quote! {
+                    self.0.indexes.update_computed(&previous, &new_row, link)?;
+                },
+                quote! {
+                    if written.is_err() {
+                        let _ = self.0.indexes.update_computed(&new_row, &previous, link);
+                    }
+                },
+            )
+        };
 
         quote! {
             pub async fn update(&self, row: #row_ident) -> core::result::Result<(), WorkTableError> {
-                let pk = row.get_primary_key();
-                let op_id = self.0.lock_map.next_id();
-                let lock = std::sync::Arc::new(Lock::new());
-                self.0.lock_map.insert(op_id.into(), lock.clone());
+                self.update_returning(row).await?.ok_or(WorkTableError::NotFound)?;
+                core::result::Result::Ok(())
+            }
 
+            /// Updates row and returns its previous value, or `None` if there is no row with same primary key. Previous
+            /// value is read while row is locked, so other writers can't change it between read and update. If updated
+            /// row's size is changed, row is moved to the new slot and indexes are pointed to it.
+            pub async fn update_returning(&self, row: #row_ident) -> core::result::Result<Option<#row_ident>, WorkTableError> {
+                let pk = row.get_primary_key();
+                let Some(link) = TableIndex::peek(&self.0.pk_map, &pk) else {
+                    return core::result::Result::Ok(None);
+                };
+                let length = self.0.data.stored_length(row.clone()).map_err(WorkTableError::PagesError)?;
+                let new_row = row.clone();
                 let mut bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&row).map_err(|_| WorkTableError::SerializeError)?;
                 let mut row = unsafe { rkyv::access_unchecked_mut::<<#row_ident as rkyv::Archive>::Archived>(&mut bytes[..]).unseal_unchecked() };
-                let link = TableIndex::peek(&self.0.pk_map, &pk).ok_or(WorkTableError::NotFound)?;
-                let id = self.0.data.with_ref(link, |archived| {
-                    archived.is_locked()
-                }).map_err(WorkTableError::PagesError)?;
-                if let Some(id) = id {
-                    if let Some(lock) = self.0.lock_map.get(&(id.into())) {
-                        lock.as_ref().await
-                    }
+                // Lock is released by `row_lock`'s drop, so it's released on errors too.
+                #row_lock
+                #wal_lock
+                let previous = self.0.data.select(link).map_err(WorkTableError::PagesError)?;
+                if length != link.length {
+                    // Row's size is changed, so it can't be updated in place and is moved to the new slot.
+                    #relocate
+                    // Row is logged before lock is released, so its writes are logged in the same order as applied.
+                    #log_relocated
+                    drop(row_lock);
+                    return core::result::Result::Ok(Some(previous));
                 }
-                unsafe { self.0.data.with_mut_ref(link, |archived| {
-                    archived.lock = op_id.into();
-                }).map_err(WorkTableError::PagesError)? };
-                unsafe { self.0.data.with_mut_ref(link, move |archived| {
+                // Computed indexes are moved before row is changed, so row is not changed if new key is taken.
+                #computed_update
+                let written = unsafe { self.0.data.with_mut_ref(link, move |archived| {
                     #(#row_updates)*
-                }).map_err(WorkTableError::PagesError)? };
+                }).map_err(WorkTableError::PagesError) };
+                #computed_revert
+                written?;
+                #log
                 unsafe { self.0.data.with_mut_ref(link, |archived| {
                     unsafe {
                         archived.lock = 0u16.into();
                     }
                 }).map_err(WorkTableError::PagesError)? };
-                lock.unlock();
-                self.0.lock_map.remove(&op_id.into());
-                core::result::Result::Ok(())
+                drop(row_lock);
+                core::result::Result::Ok(Some(previous))
             }
         }
     }
@@ -125,6 +169,30 @@ impl Generator {
         }
     }
 
+    /// Returns code that clones update query's `row` before it's archived, code that moves `link` in computed indexes
+    /// to the keys of row changed by query before row is written and code that moves it back if write failed. If
+    /// computed index's key is taken, `on_error` code is run. All parts are empty if table has no computed indexes.
+    fn gen_query_computed_update(
+        &self,
+        idents: &[Ident],
+        link: TokenStream,
+        on_error: TokenStream,
+    ) -> (TokenStream, TokenStream, TokenStream) {
+        if self.columns.computed_indexes.is_empty() {
+            return (quote! {}, quote! {}, quote! {});
+        }
+        let query_clone = quote! {
+            let query = row.clone();
+        };
+        let changes = quote! {
+            #(computed_after.#idents = query.#idents.clone();)*
+        };
+        let update = self.gen_computed_index_update(link.clone(), changes, on_error);
+        let revert = self.gen_computed_index_revert(link);
+
+        (query_clone, update, revert)
+    }
+
     fn gen_pk_update(
         &self,
         snake_case_name: String,
@@ -163,6 +231,20 @@ impl Generator {
                 }
             })
             .collect::<Vec<_>>();
+        let unlock = quote! {
+            let _ = unsafe { self.0.data.with_mut_ref(link, |archived| {
+                unsafe {
+                    archived.#unlock_ident()
+                }
+            }) };
+            lock.unlock();
+            self.0.lock_map.remove(&op_id.into());
+        };
+        let wal_lock = self.gen_wal_lock();
+        let previous = self.gen_wal_previous(quote! { link }, unlock.clone());
+        let log = self.gen_wal_log_link(quote! { link }, unlock.clone());
+        let (query_clone, computed_update, computed_revert) =
+            self.gen_query_computed_update(idents, quote! { link }, unlock);
 
         quote! {
             pub async fn #method_ident(&self, row: #query_ident, by: #pk_ident) -> core::result::Result<(), WorkTableError> {
@@ -171,6 +253,7 @@ impl Generator {
 
                 self.0.lock_map.insert(op_id.into(), lock.clone());
 
+                #query_clone
                 let mut bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&row).map_err(|_| WorkTableError::SerializeError)?;
                 let mut row = unsafe { rkyv::access_unchecked_mut::<<#query_ident as rkyv::Archive>::Archived>(&mut bytes[..]).unseal_unchecked() };
                 let link = {
@@ -180,11 +263,15 @@ impl Generator {
                 let id = self.0.data.with_ref(link, |archived| {
                     archived.#check_ident()
                 }).map_err(WorkTableError::PagesError)?;
-                if let Some(id) = id {
+                let link = if let Some(id) = id {
                     if let Some(lock) = self.0.lock_map.get(&(id.into())) {
                         lock.as_ref().await
                     }
-                }
+                    // Row can be moved or deleted by the lock's holder, so its link is resolved again.
+                    TableIndex::peek(&self.0.pk_map, &by).ok_or(WorkTableError::NotFound)?
+                } else {
+                    link
+                };
                 unsafe { self.0.data.with_mut_ref(link, |archived| {
                     while !archived.#verify_ident(op_id) {
                         unsafe {
@@ -193,9 +280,16 @@ impl Generator {
                     }
                 }).map_err(WorkTableError::PagesError)? };
 
-                unsafe { self.0.data.with_mut_ref(link, |archived| {
+                #wal_lock
+                #previous
+                #computed_update
+                let written = unsafe { self.0.data.with_mut_ref(link, |archived| {
                     #(#row_updates)*
-                }).map_err(WorkTableError::PagesError)? };
+                }).map_err(WorkTableError::PagesError) };
+                #computed_revert
+                written?;
+                // Row is logged before lock is released, so its writes are logged in the same order as applied.
+                #log
 
                 unsafe { self.0.data.with_mut_ref(link, |archived| {
                     unsafe {
@@ -249,6 +343,89 @@ impl Generator {
                 }
             })
             .collect::<Vec<_>>();
+        let unlock_rows = quote! {
+            for link in rows_to_update.iter() {
+                let _ = unsafe { self.0.data.with_mut_ref(*link.as_ref(), |archived| {
+                    unsafe {
+                        archived.#unlock_ident()
+                    }
+                }) };
+            }
+            lock.unlock();
+            self.0.lock_map.remove(&op_id.into());
+        };
+        // If computed index's key of one of the rows is taken, computed indexes of the rows before it are moved
+        // back, so no row is changed.
+        let unlock = quote! {
+            for (link, (computed_before, computed_after)) in computed.iter() {
+                let _ = self.0.indexes.update_computed(computed_after, computed_before, *link);
+            }
+            #unlock_rows
+        };
+        let (query_clone, computed_update, computed_revert) =
+            self.gen_query_computed_update(idents, quote! { *link.as_ref() }, unlock);
+        let computed_prepare = if self.columns.computed_indexes.is_empty() {
+            quote! {}
+        } else {
+            quote! {
+                let mut computed = std::collections::HashMap::new();
+                for link in rows_to_update.iter() {
+                    #computed_update
+                    computed.insert(*link.as_ref(), (computed_before, computed_after));
+                }
+            }
+        };
+        let computed_take = if self.columns.computed_indexes.is_empty() {
+            quote! {}
+        } else {
+            quote! {
+                let Some((computed_before, computed_after)) = computed.remove(link.as_ref()) else {
+                    continue;
+                };
+            }
+        };
+        let wal_lock = self.gen_wal_lock();
+        let (previous, log) = if self.is_persist {
+            (
+                quote! {
+                    let previous = if wal.is_some() {
+                        let previous = rows_to_update
+                            .iter()
+                            .map(|link| self.0.data.select(*link.as_ref()).map(|row| (*link.as_ref(), row)))
+                            .collect::<core::result::Result<Vec<_>, _>>();
+                        match previous {
+                            core::result::Result::Ok(previous) => Some(previous),
+                            core::result::Result::Err(e) => {
+                                #unlock_rows
+                                return core::result::Result::Err(WorkTableError::PagesError(e));
+                            }
+                        }
+                    } else {
+                        None
+                    };
+                },
+                // All rows are logged by single write, so they are logged together or all restored.
+                quote! {
+                    if let Some(previous) = previous {
+                        let logged = previous
+                            .iter()
+                            .map(|(link, _)| self.0.data.select(*link))
+                            .collect::<core::result::Result<Vec<_>, _>>()
+                            .map_err(WorkTableError::PagesError)
+                            .and_then(|rows| self.log_wal_rows(&mut wal, WalOp::Update, &rows));
+                        if let core::result::Result::Err(e) = logged {
+                            for (link, previous) in previous {
+                                let _ = self.0.restore(previous, link);
+                            }
+                            #unlock_rows
+                            return core::result::Result::Err(e);
+                        }
+                    }
+                },
+            )
+        } else {
+            (quote! {}, quote! {})
+        };
 
         quote! {
             pub async fn #method_ident(&self, row: #query_ident, by: #by_ident) -> core::result::Result<(), WorkTableError> {
@@ -257,6 +434,7 @@ impl Generator {
 
                 self.0.lock_map.insert(op_id.into(), lock.clone());
 
+                #query_clone
                 let rows_to_update = TableIndex::peek(&self.0.indexes.#index, &by).ok_or(WorkTableError::NotFound)?;
                 for link in rows_to_update.iter() {
                     let id = self.0.data.with_ref(*link.as_ref(), |archived| {
@@ -267,6 +445,10 @@ impl Generator {
                             lock.as_ref().await
                         }
                     }
+                }
+                // Rows can be moved or deleted by the locks' holders, so their links are resolved again.
+                let rows_to_update = TableIndex::peek(&self.0.indexes.#index, &by).ok_or(WorkTableError::NotFound)?;
+                for link in rows_to_update.iter() {
                     unsafe { self.0.data.with_mut_ref(*link.as_ref(), |archived| {
                         while !archived.#verify_ident(op_id) {
                             unsafe {
@@ -276,13 +458,23 @@ impl Generator {
                     }).map_err(WorkTableError::PagesError)? };
                 }
 
+                #wal_lock
+                #previous
+                // Computed indexes of all rows are moved before rows are changed, so rows are not changed if new key
+                // is taken.
+                #computed_prepare
                 for link in rows_to_update.iter() {
+                    #computed_take
                     let mut bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&row).map_err(|_| WorkTableError::SerializeError)?;
                     let mut row = unsafe { rkyv::access_unchecked_mut::<<#query_ident as rkyv::Archive>::Archived>(&mut bytes[..]).unseal_unchecked() };
-                    unsafe { self.0.data.with_mut_ref(*link.as_ref(), |archived| {
+                    let written = unsafe { self.0.data.with_mut_ref(*link.as_ref(), |archived| {
                         #(#row_updates)*
-                    }).map_err(WorkTableError::PagesError)? };
+                    }).map_err(WorkTableError::PagesError) };
+                    #computed_revert
+                    written?;
                 }
+                // Rows are logged before locks are released, so their writes are logged in the same order as applied.
+                #log
 
                 for link in rows_to_update.iter() {
                     unsafe { self.0.data.with_mut_ref(*link.as_ref(), |archived| {
@@ -338,6 +530,20 @@ impl Generator {
                 }
             })
             .collect::<Vec<_>>();
+        let unlock = quote! {
+            let _ = unsafe { self.0.data.with_mut_ref(link, |archived| {
+                unsafe {
+                    archived.#unlock_ident()
+                }
+            }) };
+            lock.unlock();
+            self.0.lock_map.remove(&op_id.into());
+        };
+        let wal_lock = self.gen_wal_lock();
+        let previous = self.gen_wal_previous(quote! { link }, unlock.clone());
+        let log = self.gen_wal_log_link(quote! { link }, unlock.clone());
+        let (query_clone, computed_update, computed_revert) =
+            self.gen_query_computed_update(idents, quote! { link }, unlock);
 
         quote! {
             pub async fn #method_ident(&self, row: #query_ident, by: #by_ident) -> core::result::Result<(), WorkTableError> {
@@ -346,17 +552,22 @@ impl Generator {
 
                 self.0.lock_map.insert(op_id.into(), lock.clone());
 
+                #query_clone
                 let mut bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&row).map_err(|_| WorkTableError::SerializeError)?;
                 let mut row = unsafe { rkyv::access_unchecked_mut::<<#query_ident as rkyv::Archive>::Archived>(&mut bytes[..]).unseal_unchecked() };
                 let link = TableIndex::peek(&self.0.indexes.#index, &by).ok_or(WorkTableError::NotFound)?;
                 let id = self.0.data.with_ref(link, |archived| {
                     archived.#check_ident()
                 }).map_err(WorkTableError::PagesError)?;
-                if let Some(id) = id {
+                let link = if let Some(id) = id {
                     if let Some(lock) = self.0.lock_map.get(&(id.into())) {
                         lock.as_ref().await
                     }
-                }
+                    // Row can be moved or deleted by the lock's holder, so its link is resolved again.
+                    TableIndex::peek(&self.0.indexes.#index, &by).ok_or(WorkTableError::NotFound)?
+                } else {
+                    link
+                };
                 unsafe { self.0.data.with_mut_ref(link, |archived| {
                     while !archived.#verify_ident(op_id) {
                         unsafe {
@@ -365,9 +576,16 @@ impl Generator {
                     }
                 }).map_err(WorkTableError::PagesError)? };
 
-                unsafe { self.0.data.with_mut_ref(link, |archived| {
+                #wal_lock
+                #previous
+                #computed_update
+                let written = unsafe { self.0.data.with_mut_ref(link, |archived| {
                     #(#row_updates)*
-                }).map_err(WorkTableError::PagesError)? };
+                }).map_err(WorkTableError::PagesError) };
+                #computed_revert
+                written?;
+                // Row is logged before lock is released, so its writes are logged in the same order as applied.
+                #log
 
                 unsafe { self.0.data.with_mut_ref(link, |archived| {
                     unsafe {
@@ -8,10 +8,40 @@ impl Generator {
     pub fn gen_row_def(&mut self) -> TokenStream {
         let def = self.gen_row_type();
         let table_row_impl = self.gen_row_table_row_impl();
+        let scalars_mut = self.gen_row_scalars_mut_type();
 
         quote! {
             #def
             #table_row_impl
+            #scalars_mut
+        }
+    }
+
+    /// Generates struct with mutable references to the scalar columns of the archived row, which is passed to the
+    /// table's `with_row_scalars_mut`. It's not generated if row has no scalar columns.
+    fn gen_row_scalars_mut_type(&self) -> TokenStream {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let ident = name_generator.get_scalars_mut_type_ident();
+
+        let columns = self.columns.scalar_columns();
+        if columns.is_empty() {
+            return quote! {};
+        }
+        let fields = columns.iter().map(|name| {
+            let type_ = self
+                .columns
+                .columns_map
+                .get(*name)
+                .expect("scalar columns are taken from columns map");
+            quote! {pub #name: &'a mut rkyv::Archived<#type_>,}
+        });
+
+        quote! {
+            /// Mutable references to the fixed-size columns of the archived row, which are not part of primary key and
+            /// not indexed. Any value can be written to them without breaking row's layout or indexes.
+            pub struct #ident<'a> {
+                #(#fields)*
+            }
         }
     }
 
@@ -73,8 +103,18 @@ impl Generator {
             })
             .collect();
 
+        let serde_derive = if self.is_serde() {
+            quote! {
+                #[derive(worktable::serde::Serialize, worktable::serde::Deserialize)]
+                #[serde(crate = "worktable::serde")]
+            }
+        } else {
+            quote! {}
+        };
+
         quote! {
             #[derive(rkyv::Archive, Debug, rkyv::Deserialize, Clone, rkyv::Serialize, PartialEq)]
+            #serde_derive
             #[rkyv(derive(Debug))]
             #[repr(C)]
             pub struct #ident {
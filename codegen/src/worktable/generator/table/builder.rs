@@ -0,0 +1,145 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+
+use crate::name_generator::WorktableNameGenerator;
+use crate::worktable::generator::Generator;
+
+impl Generator {
+    /// Generates builder of the table, which sets table's runtime options, and `builder` function of the table which
+    /// returns it. Persisted table's builder loads table from its file, because it's how persisted tables are created.
+    pub fn gen_table_builder_def(&self) -> TokenStream {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let ident = name_generator.get_work_table_ident();
+        let builder_ident = name_generator.get_work_table_builder_ident();
+
+        let (persist_fields, persist_arg, persist_init, persist_fns) = if self.is_persist {
+            (
+                quote! {
+                    manager: std::sync::Arc<DatabaseManager>,
+                    wal: Option<DurabilityMode>,
+                },
+                quote! { manager: std::sync::Arc<DatabaseManager> },
+                quote! {
+                    manager,
+                    wal: None,
+                },
+                quote! {
+                    /// Enables write-ahead log with provided [`DurabilityMode`] of its appends. Log is replayed when
+                    /// table is built, like by `load_from_file_with_wal`.
+                    pub fn wal(mut self, durability: DurabilityMode) -> Self {
+                        self.wal = Some(durability);
+                        self
+                    }
+
+                    /// Sets [`WritePolicy`] of the table's writes. It's another way to set write-ahead log: with
+                    /// [`WritePolicy::MemoryOnly`] log is not used, other policies enable it with matching
+                    /// [`DurabilityMode`].
+                    pub fn write_policy(mut self, policy: WritePolicy) -> Self {
+                        self.wal = policy.wal_durability();
+                        self
+                    }
+
+                    /// Loads table from its file like `load_from_file` (table is empty if there is no file yet) and
+                    /// applies builder's options.
+                    pub fn build(self) -> eyre::Result<#ident> {
+                        let mut table = #ident::load_from_file(self.manager.clone())?;
+                        self.configure(&mut table);
+                        // Pages loaded from file are evicted right away.
+                        table.0.data.evict_cold_pages()?;
+                        if let Some(durability) = self.wal {
+                            table.enable_wal(&self.manager, durability)?;
+                        }
+                        Ok(table)
+                    }
+                },
+            )
+        } else {
+            (
+                quote! {},
+                quote! {},
+                quote! {},
+                quote! {
+                    pub fn build(self) -> #ident {
+                        let mut table = #ident::default();
+                        self.configure(&mut table);
+                        table
+                    }
+                },
+            )
+        };
+
+        quote! {
+            /// Builder of the table's runtime options. Options which are not set are same as for table created
+            /// without builder. Page size is type's parameter, so it's set by `page_size` in declaration's `config`.
+            #[derive(Debug)]
+            pub struct #builder_ident {
+                empty_link_policy: EmptyLinkPolicy,
+                active_pages: usize,
+                reserved_pages: u32,
+                max_bytes: Option<usize>,
+                spill: Option<SpillConfig>,
+                #persist_fields
+            }
+
+            impl #builder_ident {
+                /// Sets [`EmptyLinkPolicy`] that defines which deleted row's slot is reused by insert first.
+                pub fn empty_link_policy(mut self, policy: EmptyLinkPolicy) -> Self {
+                    self.empty_link_policy = policy;
+                    self
+                }
+
+                /// Sets count of the active pages, which concurrent inserts are spread over.
+                pub fn active_pages(mut self, count: usize) -> Self {
+                    self.active_pages = count;
+                    self
+                }
+
+                /// Sets count of the empty pages which are allocated when table is built.
+                pub fn reserved_pages(mut self, count: u32) -> Self {
+                    self.reserved_pages = count;
+                    self
+                }
+
+                /// Sets limit of the bytes allocated by table's data pages. Inserts which need new page over this
+                /// limit fail with `PagesExecutionError::CapacityExceeded`.
+                pub fn max_bytes(mut self, bytes: usize) -> Self {
+                    self.max_bytes = Some(bytes);
+                    self
+                }
+
+                /// Enables eviction of the cold data pages to the spill file, so only
+                /// `SpillConfig::max_resident_pages` pages are kept in memory. Evicted pages are loaded back on access.
+                pub fn spill(mut self, config: SpillConfig) -> Self {
+                    self.spill = Some(config);
+                    self
+                }
+
+                #persist_fns
+
+                fn configure(&self, table: &mut #ident) {
+                    table.0.data.set_empty_link_policy(self.empty_link_policy);
+                    table.0.data.set_active_pages(self.active_pages);
+                    table.0.data.set_max_bytes(self.max_bytes);
+                    table.0.data.set_spill(self.spill.clone());
+                    if self.reserved_pages > 0 {
+                        table.0.data.reserve_pages(self.reserved_pages);
+                    }
+                }
+            }
+
+            impl #ident {
+                /// Returns builder of the table with default options.
+                pub fn builder(#persist_arg) -> #builder_ident {
+                    #builder_ident {
+                        empty_link_policy: EmptyLinkPolicy::default(),
+                        active_pages: 1,
+                        reserved_pages: 0,
+                        max_bytes: None,
+                        spill: None,
+                        #persist_init
+                    }
+                }
+            }
+        }
+    }
+}
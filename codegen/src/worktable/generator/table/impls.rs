@@ -1,4 +1,4 @@
-use proc_macro2::TokenStream;
+use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
 
 use crate::name_generator::WorktableNameGenerator;
@@ -11,24 +11,56 @@ impl Generator {
         let ident = name_generator.get_work_table_ident();
 
         let new_fn = self.gen_table_new_fn();
+        let empty_link_policy_fn = self.gen_table_empty_link_policy_fn();
         let name_fn = self.gen_table_name_fn();
         let select_fn = self.gen_table_select_fn();
         let insert_fn = self.gen_table_insert_fn();
+        let ttl_fns = self.gen_table_ttl_fns();
+        let batch_fns = self.gen_table_batch_fns();
         let upsert_fn = self.gen_table_upsert_fn();
+        let truncate_fn = self.gen_table_truncate_fn();
+        let transaction_fn = self.gen_table_transaction_fn();
         let get_next_fn = self.gen_table_get_next_fn();
         let iter_with_fn = self.gen_table_iter_with_fn();
         let iter_with_async_fn = self.gen_table_iter_with_async_fn();
+        let iter_with_links_fn = self.gen_table_iter_with_links_fn();
+        let index_entries_fn = self.gen_table_index_entries_fn();
+        let get_all_fn = self.gen_table_get_all_fn();
+        let iter_ordered_fn = self.gen_table_iter_ordered_fn();
+        let scan_where_fn = self.gen_table_scan_where_fn();
+        let select_col_fns = self.gen_table_select_col_fns();
+        let with_row_ref_fns = self.gen_table_with_row_ref_fns();
+        let export_jsonl_fn = self.gen_table_export_jsonl_fn();
+        let import_jsonl_fn = self.gen_table_import_jsonl_fn();
+        let verify_integrity_fn = self.gen_table_verify_integrity_fn();
+        let async_fns = self.gen_table_async_fns();
 
         quote! {
             impl #ident {
                 #new_fn
+                #empty_link_policy_fn
                 #name_fn
                 #select_fn
                 #insert_fn
+                #ttl_fns
+                #batch_fns
                 #upsert_fn
+                #truncate_fn
+                #transaction_fn
                 #get_next_fn
                 #iter_with_fn
                 #iter_with_async_fn
+                #iter_with_links_fn
+                #index_entries_fn
+                #get_all_fn
+                #iter_ordered_fn
+                #scan_where_fn
+                #select_col_fns
+                #with_row_ref_fns
+                #export_jsonl_fn
+                #import_jsonl_fn
+                #verify_integrity_fn
+                #async_fns
             }
         }
     }
@@ -41,8 +73,8 @@ impl Generator {
             quote! {
                 pub fn new(manager:  std::sync::Arc<DatabaseManager>) -> Self {
                     let mut inner = WorkTable::default();
-                    inner.table_name = #table_name;
-                    Self(inner, manager)
+                    inner.set_table_name(#table_name);
+                    Self(inner, manager, None)
                 }
             }
         } else {
@@ -50,6 +82,23 @@ impl Generator {
         }
     }
 
+    fn gen_table_empty_link_policy_fn(&self) -> TokenStream {
+        quote! {
+            /// Sets [`EmptyLinkPolicy`] that defines which deleted row's slot is reused by insert first.
+            pub fn with_empty_link_policy(mut self, policy: EmptyLinkPolicy) -> Self {
+                self.0.data.set_empty_link_policy(policy);
+                self
+            }
+
+            /// Sets count of the active pages. Inserts are spread over active pages by primary key's hash, so
+            /// concurrent inserts mostly fill different pages instead of contending on single one.
+            pub fn with_active_pages(mut self, count: usize) -> Self {
+                self.0.data.set_active_pages(count);
+                self
+            }
+        }
+    }
+
     fn gen_table_name_fn(&self) -> TokenStream {
         quote! {
             pub fn name(&self) -> &'static str {
@@ -67,6 +116,80 @@ impl Generator {
             pub fn select(&self, pk: #primary_key_type) -> Option<#row_type> {
                 self.0.select(pk)
             }
+
+            /// Same as `select`, but returns `Ok(None)` only if row is not presented, and error if it can't be read.
+            pub fn get(&self, pk: &#primary_key_type) -> core::result::Result<Option<#row_type>, PagesExecutionError> {
+                self.0.get(pk)
+            }
+
+            pub fn select_many_by_pk(&self, keys: &[#primary_key_type]) -> Vec<Option<#row_type>> {
+                self.0.select_many_by_pk(keys)
+            }
+
+            pub fn exists(&self, pk: &#primary_key_type) -> bool {
+                self.0.exists(pk)
+            }
+        }
+    }
+
+    /// Generates `transaction` function, which applies operations buffered by closure and reverts applied ones if one
+    /// of them fails.
+    fn gen_table_transaction_fn(&self) -> TokenStream {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type = name_generator.get_row_type_ident();
+        let primary_key_type = name_generator.get_primary_key_type_ident();
+
+        quote! {
+            /// Calls `f`, which buffers operations in [`Transaction`], and applies them one by one. If `f` returns
+            /// error, nothing is applied. Each operation is applied as a unit, so failed one leaves table unchanged,
+            /// and operations which are already applied are reverted in reverse order and error is returned. Only one
+            /// transaction is applied at a time, but writes that are not done in transaction are not blocked by it.
+            pub async fn transaction<F, T>(&self, f: F) -> core::result::Result<T, WorkTableError>
+            where
+                F: FnOnce(&mut Transaction<#row_type, #primary_key_type>) -> core::result::Result<T, WorkTableError>,
+            {
+                let mut tx = Transaction::default();
+                let res = f(&mut tx)?;
+
+                let _guard = self.0.transaction_lock.lock().await;
+                let mut undo = vec![];
+                for op in tx.into_ops() {
+                    match self.apply_transaction_op(op).await {
+                        core::result::Result::Ok(revert) => undo.push(revert),
+                        core::result::Result::Err(e) => {
+                            // Revert is best effort, error of the failed operation is returned anyway.
+                            for op in undo.into_iter().rev() {
+                                let _ = self.apply_transaction_op(op).await;
+                            }
+                            return core::result::Result::Err(e);
+                        }
+                    }
+                }
+
+                core::result::Result::Ok(res)
+            }
+
+            /// Applies operation and returns operation that reverts it.
+            async fn apply_transaction_op(
+                &self,
+                op: TransactionOp<#row_type, #primary_key_type>,
+            ) -> core::result::Result<TransactionOp<#row_type, #primary_key_type>, WorkTableError> {
+                match op {
+                    TransactionOp::Insert(row) => {
+                        let pk = self.insert(row)?;
+                        core::result::Result::Ok(TransactionOp::Delete(pk))
+                    }
+                    TransactionOp::Update(row) => {
+                        let previous = self.update_returning(row).await?.ok_or(WorkTableError::NotFound)?;
+                        core::result::Result::Ok(TransactionOp::Update(previous))
+                    }
+                    TransactionOp::Delete(pk) => {
+                        let row = self.select(pk.clone()).ok_or(WorkTableError::NotFound)?;
+                        self.delete(pk).await?;
+                        core::result::Result::Ok(TransactionOp::Insert(row))
+                    }
+                }
+            }
         }
     }
 
@@ -75,12 +198,228 @@ impl Generator {
     let row_type = name_generator.get_row_type_ident();
     let primary_key_type = name_generator.get_primary_key_type_ident();
 
-    quote! {
-        pub fn insert(&self, row: #row_type) -> core::result::Result<#primary_key_type, WorkTableError> {
-            self.0.insert(row)
+    if self.is_persist {
+        let wal_lock = self.gen_wal_lock();
+        let log = self.gen_wal_log_row(
+            quote! { Insert },
+            quote! { row },
+            quote! { let _ = self.0.remove(&pk); },
+        );
+        quote! {
+            /// Inserts row. Depending on table's [`WritePolicy`], returns right after row is saved in memory or after
+            /// it's also appended to the write-ahead log. If row can't be logged, it's removed and error is returned.
+            pub fn insert(&self, row: #row_type) -> core::result::Result<#primary_key_type, WorkTableError> {
+                #wal_lock
+                let pk = self.0.insert(row.clone())?;
+                #log
+                core::result::Result::Ok(pk)
+            }
+
+            /// Same as [`Self::insert`], but row is saved only if it fits in the existing pages, so table never grows.
+            /// Otherwise `ExecutionError::NoSpace` is returned.
+            pub fn try_insert(&self, row: #row_type) -> core::result::Result<#primary_key_type, WorkTableError> {
+                #wal_lock
+                let pk = self.0.try_insert(row.clone())?;
+                #log
+                core::result::Result::Ok(pk)
+            }
+        }
+    } else {
+        quote! {
+            pub fn insert(&self, row: #row_type) -> core::result::Result<#primary_key_type, WorkTableError> {
+                self.0.insert(row)
+            }
+
+            /// Same as [`Self::insert`], but row is saved only if it fits in the existing pages, so table never grows.
+            /// Otherwise `ExecutionError::NoSpace` is returned.
+            pub fn try_insert(&self, row: #row_type) -> core::result::Result<#primary_key_type, WorkTableError> {
+                self.0.try_insert(row)
+            }
         }
     }
 }
+
+    /// Generates functions of the rows with TTL. Row is inserted by table's `insert`, so persisted table logs it to
+    /// write-ahead log like any other insert, and deletes of the purged rows are logged too.
+    fn gen_table_ttl_fns(&self) -> TokenStream {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type = name_generator.get_row_type_ident();
+        let primary_key_type = name_generator.get_primary_key_type_ident();
+
+        let wal_lock = self.gen_wal_lock();
+        let log = if self.is_persist {
+            quote! {
+                if let core::result::Result::Err(e) = self.log_wal_delete(&mut wal, &pks) {
+                    // Purged rows are expired, so they are inserted back expired.
+                    for row in purged {
+                        if let core::result::Result::Ok(pk) = self.0.insert(row) {
+                            self.0.expire_at(&pk, now);
+                        }
+                    }
+                    return core::result::Result::Err(e);
+                }
+            }
+        } else {
+            quote! {}
+        };
+
+        quote! {
+            /// Inserts row which expires after `ttl`. Expired row is not returned by reads and is deleted by
+            /// `purge_expired`. Expiration is kept in memory only.
+            pub fn insert_with_ttl(&self, row: #row_type, ttl: std::time::Duration) -> core::result::Result<#primary_key_type, WorkTableError> {
+                let pk = self.insert(row)?;
+                self.0.expire_at(&pk, std::time::Instant::now() + ttl);
+                core::result::Result::Ok(pk)
+            }
+
+            /// Deletes all rows which are expired at `now` and returns their count. Each row is locked while its
+            /// expiration is checked again and it's deleted, and rows which are locked by running writes are skipped,
+            /// so they are deleted by next call.
+            pub fn purge_expired(&self, now: std::time::Instant) -> core::result::Result<usize, WorkTableError> {
+                #wal_lock
+                let mut purged = vec![];
+                let mut failed = None;
+                for link in self.0.expired_links(now) {
+                    let row_lock = RowLockGuard::new(&self.0.lock_map);
+                    let locked = unsafe { self.0.data.with_mut_ref(link, |archived| {
+                        if archived.is_locked().is_some() {
+                            return false;
+                        }
+                        archived.lock = row_lock.id().into();
+                        true
+                    }) };
+                    if !matches!(locked, core::result::Result::Ok(true)) {
+                        continue;
+                    }
+                    let row = self.0.purge_expired_row(link, now);
+                    if !matches!(row, core::result::Result::Ok(Some(_))) {
+                        // Row is not deleted, so its lock is reset.
+                        let _ = unsafe { self.0.data.with_mut_ref(link, |archived| {
+                            archived.lock = 0u16.into();
+                        }) };
+                    }
+                    drop(row_lock);
+                    match row {
+                        core::result::Result::Ok(Some(row)) => purged.push(row),
+                        core::result::Result::Ok(None) => {}
+                        core::result::Result::Err(e) => {
+                            // Rows which are already purged are logged before error is returned.
+                            failed = Some(e);
+                            break;
+                        }
+                    }
+                }
+                let pks = purged.iter().map(|row| row.get_primary_key()).collect::<Vec<_>>();
+                #log
+                if let Some(e) = failed {
+                    return core::result::Result::Err(e);
+                }
+                core::result::Result::Ok(pks.len())
+            }
+        }
+    }
+
+    /// Generates `_async` variants of the table's operations with `async` feature. They are called on table in
+    /// `Arc`, which is cloned into the closure run by `run_blocking`, so operation doesn't block async executor's
+    /// thread. Operations which are already async are run by `block_on` of the blocking thread.
+    fn gen_table_async_fns(&self) -> TokenStream {
+        if !cfg!(feature = "async") {
+            return quote! {};
+        }
+
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type = name_generator.get_row_type_ident();
+        let primary_key_type = name_generator.get_primary_key_type_ident();
+
+        quote! {
+            pub async fn insert_async(self: &std::sync::Arc<Self>, row: #row_type) -> core::result::Result<#primary_key_type, WorkTableError> {
+                let table = self.clone();
+                run_blocking(move || table.insert(row)).await
+            }
+
+            pub async fn select_async(self: &std::sync::Arc<Self>, pk: #primary_key_type) -> Option<#row_type> {
+                let table = self.clone();
+                run_blocking(move || table.select(pk)).await
+            }
+
+            pub async fn get_async(self: &std::sync::Arc<Self>, pk: #primary_key_type) -> core::result::Result<Option<#row_type>, PagesExecutionError> {
+                let table = self.clone();
+                run_blocking(move || table.get(&pk)).await
+            }
+
+            pub async fn get_all_async(self: &std::sync::Arc<Self>) -> core::result::Result<Vec<#row_type>, WorkTableError> {
+                let table = self.clone();
+                run_blocking(move || table.get_all()).await
+            }
+
+            pub async fn update_async(self: &std::sync::Arc<Self>, row: #row_type) -> core::result::Result<(), WorkTableError> {
+                let table = self.clone();
+                run_blocking(move || tokio::runtime::Handle::current().block_on(table.update(row))).await
+            }
+
+            pub async fn upsert_async(self: &std::sync::Arc<Self>, row: #row_type) -> core::result::Result<(), WorkTableError> {
+                let table = self.clone();
+                run_blocking(move || tokio::runtime::Handle::current().block_on(table.upsert(row))).await
+            }
+
+            pub async fn delete_async(self: &std::sync::Arc<Self>, pk: #primary_key_type) -> core::result::Result<(), WorkTableError> {
+                let table = self.clone();
+                run_blocking(move || tokio::runtime::Handle::current().block_on(table.delete(pk))).await
+            }
+        }
+    }
+
+    fn gen_table_batch_fns(&self) -> TokenStream {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type = name_generator.get_row_type_ident();
+        let primary_key_type = name_generator.get_primary_key_type_ident();
+
+        let (insert_batch, bulk_load) = if self.is_persist {
+            let wal_lock = self.gen_wal_lock();
+            let log = quote! {
+                if let core::result::Result::Err(e) = self.log_wal_rows(&mut wal, WalOp::Insert, &rows) {
+                    for pk in pks.iter() {
+                        let _ = self.0.remove(pk);
+                    }
+                    return core::result::Result::Err(e);
+                }
+            };
+            (
+                quote! {
+                    #wal_lock
+                    let pks = self.0.insert_batch(rows.clone())?;
+                    #log
+                    core::result::Result::Ok(pks)
+                },
+                quote! {
+                    #wal_lock
+                    let pks = self.0.bulk_load(rows.clone())?;
+                    #log
+                    core::result::Result::Ok(pks)
+                },
+            )
+        } else {
+            (
+                quote! { self.0.insert_batch(rows) },
+                quote! { self.0.bulk_load(rows) },
+            )
+        };
+
+        quote! {
+            pub fn validate_batch(&self, rows: &[#row_type]) -> core::result::Result<(), Vec<BatchError>> {
+                self.0.validate_batch(rows)
+            }
+
+            pub fn insert_batch(&self, rows: Vec<#row_type>) -> core::result::Result<Vec<#primary_key_type>, WorkTableError> {
+                #insert_batch
+            }
+
+            pub fn bulk_load(&self, rows: Vec<#row_type>) -> core::result::Result<Vec<#primary_key_type>, WorkTableError> {
+                #bulk_load
+            }
+        }
+    }
+
     fn gen_table_upsert_fn(&self) -> TokenStream {
         let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
         let row_type = name_generator.get_row_type_ident();
@@ -106,6 +445,53 @@ impl Generator {
         }
     }
 
+    fn gen_table_truncate_fn(&self) -> TokenStream {
+        // Custom generators don't have to implement `PrimaryKeyGeneratorState`, so they are not reset.
+        let (reset_pk_gen, doc) = match self.columns.generator_type {
+            GeneratorType::Custom => (
+                quote! {},
+                quote! {
+                    /// Removes all rows from table. Custom primary key generator is not reset.
+                },
+            ),
+            GeneratorType::None | GeneratorType::Autoincrement => (
+                quote! {
+                    PrimaryKeyGeneratorState::reset(&self.0.pk_gen);
+                },
+                quote! {
+                    /// Removes all rows from table and resets primary key generator.
+                },
+            ),
+        };
+
+        let wal_lock = self.gen_wal_lock();
+        let (log, log_pk_state) = if self.is_persist {
+            (
+                quote! {
+                    // Truncate can't be reverted, so it's logged before rows are removed.
+                    self.log_wal_truncate(&mut wal)?;
+                },
+                quote! {
+                    self.log_wal_pk_state(&mut wal);
+                },
+            )
+        } else {
+            (quote! {}, quote! {})
+        };
+
+        quote! {
+            #doc
+            pub fn truncate(&self) -> core::result::Result<(), WorkTableError> {
+                #wal_lock
+                #log
+                self.0.truncate();
+                #reset_pk_gen
+                #log_pk_state
+                core::result::Result::Ok(())
+            }
+        }
+    }
+
     fn gen_table_get_next_fn(&self) -> TokenStream {
         let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
         let primary_key_type = name_generator.get_primary_key_type_ident();
@@ -157,6 +543,554 @@ impl Generator {
         }
     }
 
+    /// Generates `iter_with_links` function, which returns rows with their links in primary key's order.
+    fn gen_table_iter_with_links_fn(&self) -> TokenStream {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type = name_generator.get_row_type_ident();
+
+        quote! {
+            /// Returns all not expired rows with their [`Link`]s in primary key's order, so matched rows can be updated
+            /// by link without second index lookup.
+            pub fn iter_with_links(&self) -> impl Iterator<Item = core::result::Result<(Link, #row_type), WorkTableError>> + '_ {
+                self.0.iter_with_links().map(|res| res.map_err(WorkTableError::PagesError))
+            }
+        }
+    }
+
+    /// Generates `index_entries` function, which returns primary index's entries without reading rows.
+    fn gen_table_index_entries_fn(&self) -> TokenStream {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let primary_key_type = name_generator.get_primary_key_type_ident();
+
+        quote! {
+            /// Returns primary keys with [`Link`]s of their not expired rows. Data pages are not touched, so it's cheap
+            /// way to get table's key space. Entries are returned in primary index's order, so they are unordered for
+            /// hash-style indexes.
+            pub fn index_entries(&self) -> impl Iterator<Item = (#primary_key_type, Link)> + '_ {
+                TableIndex::iter(&self.0.pk_map)
+                    .filter(|(_, link)| !self.0.is_expired(**link))
+                    .map(|(pk, link)| (pk.clone(), *link))
+            }
+        }
+    }
+
+    /// Generates `get_all` function, which returns all rows in primary key's order.
+    fn gen_table_get_all_fn(&self) -> TokenStream {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type = name_generator.get_row_type_ident();
+
+        quote! {
+            /// Returns all rows in primary key's order. Rows are read under single pages lock.
+            pub fn get_all(&self) -> core::result::Result<Vec<#row_type>, WorkTableError> {
+                self.0.get_all()
+            }
+        }
+    }
+
+    /// Generates `iter_ordered` function, which returns rows in primary key's order regardless of their placement.
+    fn gen_table_iter_ordered_fn(&self) -> TokenStream {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type = name_generator.get_row_type_ident();
+
+        quote! {
+            /// Returns iterator over all rows in primary key's order. Order doesn't depend on rows' placement in
+            /// pages, but every row is read separately, so it's slower than reading pages sequentially.
+            pub fn iter_ordered(&self) -> impl Iterator<Item = #row_type> + '_ {
+                self.0.iter_ordered()
+            }
+        }
+    }
+
+    /// Generates `verify_integrity` function, which checks primary and secondary indexes against rows in pages.
+    fn gen_table_verify_integrity_fn(&self) -> TokenStream {
+        quote! {
+            /// Checks that primary and secondary indexes are consistent with rows stored in pages: every index entry
+            /// points to the live row with the same key, and every live row is presented in every index. Entries
+            /// which point into the free lists and rows which share bytes of the page are reported too. All found
+            /// inconsistencies are returned. Concurrent writes can be reported as inconsistencies, so it's meant to be
+            /// called on idle table, for example in tests or after loading table from file.
+            pub fn verify_integrity(&self) -> core::result::Result<(), Vec<IntegrityError>> {
+                let (rows, mut errors) = self.0.verify_primary_index();
+                errors.extend(self.0.mark_freed_links(self.0.indexes.verify(&rows)));
+                if errors.is_empty() {
+                    core::result::Result::Ok(())
+                } else {
+                    core::result::Result::Err(errors)
+                }
+            }
+        }
+    }
+
+    /// Generates `scan_where` function, which checks predicate on archived rows and deserializes only matching ones.
+    fn gen_table_scan_where_fn(&self) -> TokenStream {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type = name_generator.get_row_type_ident();
+
+        quote! {
+            /// Returns all not expired rows for which `pred` returns `true`. `pred` is called with archived row, so
+            /// rows are deserialized only if they match. Archived row's fields are archived types too, so they are
+            /// compared slightly differently than owned ones, for example `row.another == 1` is
+            /// `row.another.to_native() == 1`. Returns error if matched row can't be read or deserialized.
+            pub fn scan_where<F>(&self, pred: F) -> core::result::Result<Vec<#row_type>, WorkTableError>
+            where
+                F: Fn(&<#row_type as rkyv::Archive>::Archived) -> bool,
+            {
+                let mut rows = vec![];
+                for (_, link) in TableIndex::iter(&self.0.pk_map).filter(|(_, link)| !self.0.is_expired(**link)) {
+                    let row = self
+                        .0
+                        .data
+                        .with_ref(*link, |archived| {
+                            pred(&archived.inner)
+                                .then(|| rkyv::deserialize::<#row_type, rkyv::rancor::Error>(&archived.inner))
+                                .transpose()
+                                .map_err(|_| WorkTableError::SerializeError)
+                        })
+                        .map_err(WorkTableError::PagesError)??;
+                    rows.extend(row);
+                }
+
+                core::result::Result::Ok(rows)
+            }
+        }
+    }
+
+    /// Generates `select_col_<column>_by_pk` function for every column, which deserializes only this column of the
+    /// archived row.
+    fn gen_table_select_col_fns(&self) -> TokenStream {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let primary_key_type = name_generator.get_primary_key_type_ident();
+
+        let mut columns = self.columns.columns_map.iter().collect::<Vec<_>>();
+        columns.sort_by_key(|(name, _)| name.to_string());
+        let fns = columns.into_iter().map(|(name, type_)| {
+            let fn_name = Ident::new(
+                format!("select_col_{name}_by_pk").as_str(),
+                Span::mixed_site(),
+            );
+            quote! {
+                /// Returns this column of the row with provided primary key. Only the column is deserialized from the
+                /// archived row, so it doesn't allocate for `Copy` columns.
+                pub fn #fn_name(&self, pk: #primary_key_type) -> Option<#type_> {
+                    let link = TableIndex::peek(&self.0.pk_map, &pk).filter(|l| !self.0.is_expired(*l))?;
+                    self.0
+                        .data
+                        .with_ref(link, |archived| {
+                            rkyv::deserialize::<#type_, rkyv::rancor::Error>(&archived.inner.#name).ok()
+                        })
+                        .ok()
+                        .flatten()
+                }
+            }
+        });
+
+        quote! {
+            #(#fns)*
+        }
+    }
+
+    /// Generates `with_row_ref` and `with_row_mut_ref` functions, which call provided closure with archived row of
+    /// the primary key, so row is read or changed without deserializing it.
+    fn gen_table_with_row_ref_fns(&self) -> TokenStream {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type = name_generator.get_row_type_ident();
+        let primary_key_type = name_generator.get_primary_key_type_ident();
+        let scalars_mut_fn = self.gen_table_with_row_scalars_mut_fn();
+        let locked_fns = self.gen_table_with_row_locked_fns();
+
+        quote! {
+            /// Calls `op` with archived row of provided primary key. Returns `Ok(None)` if there is no row with this
+            /// primary key or it's expired.
+            pub fn with_row_ref<Op, R>(
+                &self,
+                pk: #primary_key_type,
+                op: Op,
+            ) -> core::result::Result<Option<R>, PagesExecutionError>
+            where
+                Op: Fn(&<#row_type as rkyv::Archive>::Archived) -> R,
+            {
+                let Some(link) = TableIndex::peek(&self.0.pk_map, &pk).filter(|l| !self.0.is_expired(*l)) else {
+                    return core::result::Result::Ok(None);
+                };
+                self.0
+                    .data
+                    .with_ref(link, |archived| op(&archived.inner))
+                    .map(Some)
+            }
+
+            /// Same as `with_row_ref`, but `op` can change archived row in place. Changes are not written to the
+            /// table's write-ahead log.
+            ///
+            /// # Safety
+            ///
+            /// Same as for `DataPages::with_mut_ref`: `op` must not change row's length or layout, and caller must
+            /// guarantee that no other thread accesses this row while `op` is running. Indexed columns and columns
+            /// used by computed indexes must not be changed, because indexes are not updated.
+            pub unsafe fn with_row_mut_ref<Op, R>(
+                &self,
+                pk: #primary_key_type,
+                mut op: Op,
+            ) -> core::result::Result<Option<R>, PagesExecutionError>
+            where
+                Op: FnMut(&mut <#row_type as rkyv::Archive>::Archived) -> R,
+            {
+                let Some(link) = TableIndex::peek(&self.0.pk_map, &pk).filter(|l| !self.0.is_expired(*l)) else {
+                    return core::result::Result::Ok(None);
+                };
+                self.0
+                    .data
+                    .with_mut_ref(link, |archived| op(&mut archived.inner))
+                    .map(Some)
+            }
+
+            #scalars_mut_fn
+            #locked_fns
+        }
+    }
+
+    /// Generates `with_row_locked` and `with_row_locked_mut` functions, which hold row's lock while provided closure
+    /// runs, so row can't be changed by other writers between read and decision made on it.
+    fn gen_table_with_row_locked_fns(&self) -> TokenStream {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type = name_generator.get_row_type_ident();
+        let primary_key_type = name_generator.get_primary_key_type_ident();
+
+        let row_lock = self.gen_row_lock(quote! { link });
+        // Lock is released by `row_lock`'s drop, so it's released if `op` panics or row's read or write fails too.
+        let lock = quote! {
+            let Some(link) = TableIndex::peek(&self.0.pk_map, &pk).filter(|l| !self.0.is_expired(*l)) else {
+                return core::result::Result::Ok(None);
+            };
+            #row_lock
+        };
+        let unlock = quote! {
+            drop(row_lock);
+        };
+        let reset_lock = quote! {
+            unsafe { self.0.data.with_mut_ref(link, |archived| {
+                archived.lock = 0u16.into();
+            }).map_err(WorkTableError::PagesError)? };
+        };
+        let pk_columns = &self.columns.primary_keys.0;
+        let row_updates = self
+            .columns
+            .columns_map
+            .keys()
+            .map(|i| {
+                quote! {
+                    std::mem::swap(&mut archived.inner.#i, &mut new.#i);
+                }
+            })
+            .collect::<Vec<_>>();
+        let wal_lock = self.gen_wal_lock();
+        let log = if self.is_persist {
+            let log = self.gen_wal_log_row(
+                quote! { Update },
+                quote! { row },
+                quote! { let _ = self.0.restore(previous, link); },
+            );
+            quote! {
+                let (res, written) = res?;
+                if let Some((row, previous, link)) = written {
+                    #log
+                }
+            }
+        } else {
+            quote! {
+                let (res, _) = res?;
+            }
+        };
+
+        quote! {
+            /// Calls `op` with row of provided primary key while row's lock is held, so writers of this row
+            /// (`update`, update queries, `delete`, in place updates and other `with_row_locked` calls) wait until
+            /// `op` returns. Readers are not blocked. Returns `Ok(None)` if there is no row with this primary key or
+            /// it's expired.
+            ///
+            /// # Deadlocks
+            ///
+            /// `op` must not write this row through the table, because such write waits for the lock that is released
+            /// only after `op` returns. Writes of other rows can deadlock with concurrent `with_row_locked` calls
+            /// that lock rows in different order.
+            pub async fn with_row_locked<Op, R>(
+                &self,
+                pk: #primary_key_type,
+                op: Op,
+            ) -> core::result::Result<Option<R>, WorkTableError>
+            where
+                Op: FnOnce(&#row_type) -> R,
+            {
+                #lock
+                let res = self.0.data.select(link).map(|row| op(&row));
+                #reset_lock
+                #unlock
+                let res = res.map_err(WorkTableError::PagesError)?;
+                core::result::Result::Ok(Some(res))
+            }
+
+            /// Same as `with_row_locked`, but `op` can change the row, which is written back before lock is released.
+            /// Changes of primary key's columns are ignored. If row's size is changed, it's moved to the new slot,
+            /// and secondary indexes are updated for changed values.
+            ///
+            /// # Deadlocks
+            ///
+            /// Same as for `with_row_locked`.
+            pub async fn with_row_locked_mut<Op, R>(
+                &self,
+                pk: #primary_key_type,
+                op: Op,
+            ) -> core::result::Result<Option<R>, WorkTableError>
+            where
+                Op: FnOnce(&mut #row_type) -> R,
+            {
+                #lock
+                #wal_lock
+                let res = self.locked_row_update(link, op);
+                if res.is_err() {
+                    // Row is not moved if update failed, so its lock is reset to not leave id of released lock in it.
+                    let _ = unsafe { self.0.data.with_mut_ref(link, |archived| {
+                        archived.lock = 0u16.into();
+                    }) };
+                }
+                // Row is logged before lock is released, so its writes are logged in the same order as applied.
+                #log
+                #unlock
+                core::result::Result::Ok(Some(res))
+            }
+
+            /// Reads row stored by locked `link`, calls `op` with it and writes changed row back. Returns `op`'s
+            /// result and written row with its previous value and link, which is `None` if row is not changed. Row's
+            /// lock is reset if row is not moved.
+            fn locked_row_update<Op, R>(
+                &self,
+                link: Link,
+                op: Op,
+            ) -> core::result::Result<(R, Option<(#row_type, #row_type, Link)>), WorkTableError>
+            where
+                Op: FnOnce(&mut #row_type) -> R,
+            {
+                let previous = self.0.data.select(link).map_err(WorkTableError::PagesError)?;
+                let mut row = previous.clone();
+                let res = op(&mut row);
+                #(row.#pk_columns = previous.#pk_columns.clone();)*
+                if row == previous {
+                    #reset_lock
+                    return core::result::Result::Ok((res, None));
+                }
+
+                let length = self.0.data.stored_length(row.clone()).map_err(WorkTableError::PagesError)?;
+                if length != link.length {
+                    // Row is moved to the new slot, so old slot's lock is not reset.
+                    let new_link = self.0.relocate(previous.clone(), row.clone(), link)?;
+                    return core::result::Result::Ok((res, Some((row, previous, new_link))));
+                }
+                TableSecondaryIndex::delete_row(&self.0.indexes, previous.clone(), link)?;
+                if !TableSecondaryIndex::validate_rows(&self.0.indexes, std::slice::from_ref(&row)).is_empty() {
+                    TableSecondaryIndex::save_row(&self.0.indexes, previous, link)?;
+                    #reset_lock
+                    return core::result::Result::Err(WorkTableError::AlreadyExists);
+                }
+                // Row stays in pages if it's not written, so its index entries are saved back on error.
+                let written = rkyv::to_bytes::<rkyv::rancor::Error>(&row)
+                    .map_err(|_| WorkTableError::SerializeError)
+                    .and_then(|mut bytes| {
+                        let mut new = unsafe { rkyv::access_unchecked_mut::<<#row_type as rkyv::Archive>::Archived>(&mut bytes[..]).unseal_unchecked() };
+                        unsafe { self.0.data.with_mut_ref(link, move |archived| {
+                            #(#row_updates)*
+                            archived.lock = 0u16.into();
+                        }).map_err(WorkTableError::PagesError) }
+                    });
+                if let Err(e) = written {
+                    let _ = TableSecondaryIndex::save_row(&self.0.indexes, previous, link);
+                    return core::result::Result::Err(e);
+                }
+                if let Err(e) = TableSecondaryIndex::save_row(&self.0.indexes, row.clone(), link) {
+                    // SAFETY: previous row has the same length, and row is locked.
+                    if unsafe { self.0.data.update(previous.clone(), link) }.is_ok() {
+                        let _ = TableSecondaryIndex::save_row(&self.0.indexes, previous, link);
+                    }
+                    return core::result::Result::Err(e);
+                }
+
+                core::result::Result::Ok((res, Some((row, previous, link))))
+            }
+        }
+    }
+
+    /// Generates `with_row_scalars_mut` function, which is safe version of `with_row_mut_ref` that gives access only
+    /// to the scalar columns of the row. It's not generated if row has no scalar columns.
+    fn gen_table_with_row_scalars_mut_fn(&self) -> TokenStream {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type = name_generator.get_row_type_ident();
+        let primary_key_type = name_generator.get_primary_key_type_ident();
+        let scalars_type = name_generator.get_scalars_mut_type_ident();
+
+        let columns = self.columns.scalar_columns();
+        if columns.is_empty() {
+            return quote! {};
+        }
+        let row_lock = self.gen_row_lock(quote! { link });
+        // Row is not moved by in place write, so its lock is reset on every error path.
+        let reset_lock = self.gen_row_lock_reset(quote! { link });
+        let wal_lock = self.gen_wal_lock();
+        let previous = self.gen_wal_previous(quote! { link }, reset_lock.clone());
+        let log = self.gen_wal_log_link(quote! { link }, reset_lock.clone());
+        let write = if self.columns.computed_indexes.is_empty() {
+            quote! {
+                // SAFETY: only fixed-size columns are given to `op` (checked at macro expansion), so row's length and
+                // layout are kept, and row is locked above.
+                let res = unsafe {
+                    self.0.data.with_mut_ref(link, |archived| {
+                        let row = &mut archived.inner;
+                        op(#scalars_type {
+                            #(#columns: &mut row.#columns,)*
+                        })
+                    })
+                };
+                let res = match res {
+                    core::result::Result::Ok(res) => res,
+                    core::result::Result::Err(e) => {
+                        #reset_lock
+                        return core::result::Result::Err(WorkTableError::PagesError(e));
+                    }
+                };
+            }
+        } else {
+            let computed_revert = self.gen_computed_index_revert(quote! { link });
+            quote! {
+                // `op` changes row's copy, so computed indexes are moved to its keys before stored row is changed,
+                // and row is not changed if new key is taken.
+                let prepared = self.0.data.select(link).map_err(WorkTableError::PagesError).and_then(|computed_before| {
+                    let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&computed_before).map_err(|_| WorkTableError::SerializeError)?;
+                    core::result::Result::Ok((computed_before, bytes))
+                });
+                let (computed_before, mut bytes) = match prepared {
+                    core::result::Result::Ok(prepared) => prepared,
+                    core::result::Result::Err(e) => {
+                        #reset_lock
+                        return core::result::Result::Err(e);
+                    }
+                };
+                let copy = unsafe { rkyv::access_unchecked_mut::<<#row_type as rkyv::Archive>::Archived>(&mut bytes[..]).unseal_unchecked() };
+                let res = op(#scalars_type {
+                    #(#columns: &mut copy.#columns,)*
+                });
+                let computed = rkyv::deserialize::<#row_type, rkyv::rancor::Error>(&*copy).map_err(|_| WorkTableError::SerializeError).and_then(|computed_after| {
+                    self.0.indexes.update_computed(&computed_before, &computed_after, link)?;
+                    core::result::Result::Ok(computed_after)
+                });
+                let computed_after = match computed {
+                    core::result::Result::Ok(computed_after) => computed_after,
+                    core::result::Result::Err(e) => {
+                        #reset_lock
+                        return core::result::Result::Err(e);
+                    }
+                };
+                // SAFETY: only fixed-size columns are swapped (checked at macro expansion), so row's length and layout
+                // are kept, and row is locked above.
+                let written = unsafe {
+                    self.0.data.with_mut_ref(link, |archived| {
+                        #(std::mem::swap(&mut archived.inner.#columns, &mut copy.#columns);)*
+                    })
+                }
+                .map_err(WorkTableError::PagesError);
+                #computed_revert
+                if let core::result::Result::Err(e) = written {
+                    #reset_lock
+                    return core::result::Result::Err(e);
+                }
+            }
+        };
+
+        quote! {
+            /// Calls `op` with mutable references to the scalar columns of the archived row of provided primary key,
+            /// so they are changed in place without reserializing the row. Only fixed-size columns that are not part
+            /// of primary key and not indexed are available, so row's layout and indexes can't be broken. If table
+            /// has computed indexes, `op` changes row's copy, which is written to the row after computed indexes are
+            /// updated. Row is locked while `op` runs, so other writers of this row wait for it. Returns `Ok(None)` if
+            /// there is no row with this primary key or it's expired.
+            pub async fn with_row_scalars_mut<Op, R>(
+                &self,
+                pk: #primary_key_type,
+                op: Op,
+            ) -> core::result::Result<Option<R>, WorkTableError>
+            where
+                Op: FnOnce(#scalars_type<'_>) -> R,
+            {
+                let Some(link) = TableIndex::peek(&self.0.pk_map, &pk).filter(|l| !self.0.is_expired(*l)) else {
+                    return core::result::Result::Ok(None);
+                };
+                #row_lock
+                #wal_lock
+                #previous
+                #write
+                #log
+                unsafe { self.0.data.with_mut_ref(link, |archived| {
+                    archived.lock = 0u16.into();
+                }).map_err(WorkTableError::PagesError)? };
+                drop(row_lock);
+
+                core::result::Result::Ok(Some(res))
+            }
+        }
+    }
+
+    /// Generates `export_jsonl` function, which writes all table's rows as JSON lines. Generated only if `serde` is
+    /// enabled in table's config.
+    fn gen_table_export_jsonl_fn(&self) -> TokenStream {
+        if !self.is_serde() {
+            return quote! {};
+        }
+        let inner = self.gen_table_iter_inner(quote! {
+            worktable::serde_json::to_writer(&mut *w, &data)?;
+            std::io::Write::write_all(w, b"\n")?;
+        });
+
+        quote! {
+            pub fn export_jsonl<W: std::io::Write>(&self, w: &mut W) -> eyre::Result<()> {
+                #inner
+            }
+        }
+    }
+
+    /// Generates `import_jsonl` function, which parses every not empty line as a row and inserts all rows using
+    /// `insert_batch`. Generated only if `serde` is enabled in table's config.
+    fn gen_table_import_jsonl_fn(&self) -> TokenStream {
+        if !self.is_serde() {
+            return quote! {};
+        }
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type = name_generator.get_row_type_ident();
+
+        quote! {
+            pub fn import_jsonl<R: std::io::Read>(&self, r: &mut R) -> core::result::Result<usize, ImportError> {
+                let reader = std::io::BufReader::new(r);
+                let mut rows = vec![];
+                let mut lines = vec![];
+                for (i, line) in std::io::BufRead::lines(reader).enumerate() {
+                    let line = line.map_err(ImportError::Io)?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let row = worktable::serde_json::from_str::<#row_type>(&line).map_err(|e| ImportError::MalformedLine {
+                        line: i + 1,
+                        message: e.to_string(),
+                    })?;
+                    rows.push(row);
+                    lines.push(i + 1);
+                }
+
+                match self.insert_batch(rows) {
+                    Ok(pks) => core::result::Result::Ok(pks.len()),
+                    Err(WorkTableError::BatchValidationError(errors)) => Err(ImportError::InvalidRows(
+                        errors.into_iter().map(|e| (lines[e.row()], e)).collect()
+                    )),
+                    Err(e) => Err(ImportError::Insert(e)),
+                }
+            }
+        }
+    }
+
     fn gen_table_iter_inner(&self, func: TokenStream) -> TokenStream {
         quote! {
             let first = TableIndex::iter(&self.0.pk_map).next().map(|(k, v)| (k.clone(), *v));
@@ -164,8 +1098,10 @@ impl Generator {
                 return Ok(())
             };
 
-            let data = self.0.data.select(link).map_err(WorkTableError::PagesError)?;
-            #func
+            if !self.0.is_expired(link) {
+                let data = self.0.data.select(link).map_err(WorkTableError::PagesError)?;
+                #func
+            }
 
             let mut ind = false;
             while !ind {
@@ -180,8 +1116,10 @@ impl Generator {
                     }
                 };
                 if let Some((key, link)) = next {
-                    let data = self.0.data.select(link).map_err(WorkTableError::PagesError)?;
-                   #func
+                    if !self.0.is_expired(link) {
+                        let data = self.0.data.select(link).map_err(WorkTableError::PagesError)?;
+                        #func
+                    }
                     k = key
                 } else {
                     ind = true;
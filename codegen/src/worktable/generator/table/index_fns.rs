@@ -1,11 +1,9 @@
-use std::collections::HashMap;
-
 use proc_macro2::{Ident, Span, TokenStream};
 use quote::quote;
 
 use crate::name_generator::WorktableNameGenerator;
 use crate::worktable::generator::Generator;
-use crate::worktable::model::Index;
+use crate::worktable::model::{Columns, ComputedIndex, Index};
 
 impl Generator {
     pub fn gen_table_index_fns(&self) -> syn::Result<TokenStream> {
@@ -18,39 +16,101 @@ impl Generator {
             .indexes
             .iter()
             .map(|(i, idx)| {
-                if idx.is_unique {
-                    Self::gen_unique_index_fn(
-                        i,
-                        idx,
-                        &self.columns.columns_map,
-                        row_ident.clone(),
-                    )
+                let select_fn = if idx.is_unique {
+                    Self::gen_unique_index_fn(i, idx, &self.columns, row_ident.clone())?
                 } else {
-                    Self::gen_non_unique_index_fn(
+                    let select_fn =
+                        Self::gen_non_unique_index_fn(i, idx, &self.columns, row_ident.clone())?;
+                    let iter_fn = Self::gen_non_unique_index_iter_fn(
                         i,
                         idx,
-                        &self.columns.columns_map,
+                        &self.columns,
                         row_ident.clone(),
-                    )
-                }
+                    )?;
+                    quote! {
+                        #select_fn
+                        #iter_fn
+                    }
+                };
+                let range_fn = if idx.is_ordered() {
+                    let range_fn =
+                        Self::gen_range_index_fn(i, idx, &self.columns, row_ident.clone())?;
+                    let range_iter_fn =
+                        Self::gen_range_index_iter_fn(i, idx, &self.columns, row_ident.clone())?;
+                    quote! {
+                        #range_fn
+                        #range_iter_fn
+                    }
+                } else {
+                    quote! {}
+                };
+
+                Ok(quote! {
+                    #select_fn
+                    #range_fn
+                })
             })
             .collect::<Result<Vec<_>, syn::Error>>()?;
+        let computed_fn_defs = self
+            .columns
+            .computed_indexes
+            .iter()
+            .map(|idx| Self::gen_computed_index_fn(idx, row_ident.clone()))
+            .collect::<Vec<_>>();
 
         Ok(quote! {
             impl #ident {
                 #(#fn_defs)*
+                #(#computed_fn_defs)*
             }
         })
     }
 
+    /// Generates `select_by_{index_name}` function of the computed index. Like for column indexes, unique index
+    /// returns one row, and non-unique returns all rows with the key.
+    fn gen_computed_index_fn(idx: &ComputedIndex, row_ident: Ident) -> TokenStream {
+        let type_ = &idx.key_type;
+        let field_ident = &idx.name;
+        let fn_name = Ident::new(
+            format!("select_by_{field_ident}").as_str(),
+            Span::mixed_site(),
+        );
+
+        if idx.is_unique {
+            quote! {
+                pub fn #fn_name(&self, by: #type_) -> Option<#row_ident> {
+                    let link = TableIndex::peek(&self.0.indexes.#field_ident, &by).filter(|l| !self.0.is_expired(*l))?;
+                    self.0.data.select(link).ok()
+                }
+            }
+        } else {
+            quote! {
+                pub fn #fn_name(&self, by: #type_) -> core::result::Result<SelectResult<#row_ident, Self>, WorkTableError> {
+                    let rows = {
+                        TableIndex::peek(&self.0.indexes.#field_ident, &by)
+                            .ok_or(WorkTableError::NotFound)?
+                            .iter()
+                            .map(|l| *l.as_ref())
+                            .filter(|l| !self.0.is_expired(*l))
+                            .collect::<Vec<_>>()
+                    }.iter().map(|link| {
+                        self.0.data.select(*link).map_err(WorkTableError::PagesError)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                    core::result::Result::Ok(SelectResult::<#row_ident, Self>::new(rows))
+                }
+            }
+        }
+    }
+
     fn gen_unique_index_fn(
         i: &Ident,
         idx: &Index,
-        columns_map: &HashMap<Ident, TokenStream>,
+        columns: &Columns,
         row_ident: Ident,
     ) -> syn::Result<TokenStream> {
-        let type_ = columns_map
-            .get(&i)
+        let type_ = columns
+            .index_key_type(i)
             .ok_or(syn::Error::new(i.span(), "Row not found"))?;
         let fn_name = Ident::new(format!("select_by_{i}").as_str(), Span::mixed_site());
         let field_ident = &idx.name;
@@ -58,7 +118,7 @@ impl Generator {
         Ok(quote! {
             pub fn #fn_name(&self, by: #type_) -> Option<#row_ident> {
                 let guard = Guard::new();
-                let link = TableIndex::peek(&self.0.indexes.#field_ident, &by)?;
+                let link = TableIndex::peek(&self.0.indexes.#field_ident, &by).filter(|l| !self.0.is_expired(*l))?;
                 self.0.data.select(link).ok()
             }
         })
@@ -67,11 +127,11 @@ impl Generator {
     fn gen_non_unique_index_fn(
         i: &Ident,
         idx: &Index,
-        columns_map: &HashMap<Ident, TokenStream>,
+        columns: &Columns,
         row_ident: Ident,
     ) -> syn::Result<TokenStream> {
-        let type_ = columns_map
-            .get(&i)
+        let type_ = columns
+            .index_key_type(i)
             .ok_or(syn::Error::new(i.span(), "Row not found"))?;
         let fn_name = Ident::new(format!("select_by_{i}").as_str(), Span::mixed_site());
         let field_ident = &idx.name;
@@ -83,6 +143,7 @@ impl Generator {
                         .ok_or(WorkTableError::NotFound)?
                         .iter()
                         .map(|l| *l.as_ref())
+                        .filter(|l| !self.0.is_expired(*l))
                         .collect::<Vec<_>>()
                 }.iter().map(|link| {
                     self.0.data.select(*link).map_err(WorkTableError::PagesError)
@@ -92,4 +153,146 @@ impl Generator {
             }
         })
     }
-}
\ No newline at end of file
+
+    /// Generates `select_by_{column}_iter` function, which returns lazy iterator of rows with index's value `by`.
+    /// Only `Link`s are collected from the index, and every row is read from the pages when iterator is advanced,
+    /// so callers that need only first rows don't deserialize all of them.
+    fn gen_non_unique_index_iter_fn(
+        i: &Ident,
+        idx: &Index,
+        columns: &Columns,
+        row_ident: Ident,
+    ) -> syn::Result<TokenStream> {
+        let type_ = columns
+            .index_key_type(i)
+            .ok_or(syn::Error::new(i.span(), "Row not found"))?;
+        let fn_name = Ident::new(format!("select_by_{i}_iter").as_str(), Span::mixed_site());
+        let field_ident = &idx.name;
+        let select = Self::gen_index_iter_select(i, columns);
+
+        Ok(quote! {
+            /// Returns iterator of the rows with provided value, which reads every row only when it's advanced. Row
+            /// deleted, expired or changed to other value before iterator reached it is skipped, and failed read of
+            /// the pages is returned as `Err`.
+            pub fn #fn_name(&self, by: #type_) -> impl Iterator<Item = core::result::Result<#row_ident, WorkTableError>> + '_ {
+                let links = TableIndex::peek(&self.0.indexes.#field_ident, &by)
+                    .map(|set| set.iter().map(|l| (by.clone(), *l.as_ref())).collect::<Vec<_>>())
+                    .unwrap_or_default();
+                links
+                    .into_iter()
+                    .filter(move |(_, link)| !self.0.is_expired(*link))
+                    .filter_map(move |(key, link)| #select)
+            }
+        })
+    }
+
+    /// Generates code which reads row stored by `link` for lazy iterators of the index on `column`. Slot of the row
+    /// can be reused by other row after `link` is read from index, so row is returned only if its value of the column
+    /// is still `key`. Deleted row is skipped, and other errors are returned.
+    fn gen_index_iter_select(column: &Ident, columns: &Columns) -> TokenStream {
+        let matches = if columns.is_optional(column) {
+            quote! { row.#column.as_ref() == Some(&key) }
+        } else {
+            quote! { row.#column == key }
+        };
+        quote! {
+            match self.0.data.select(link) {
+                core::result::Result::Ok(row) => (#matches).then_some(core::result::Result::Ok(row)),
+                core::result::Result::Err(PagesExecutionError::Deleted) => None,
+                core::result::Result::Err(e) => Some(core::result::Result::Err(WorkTableError::PagesError(e))),
+            }
+        }
+    }
+
+    /// Generates `select_by_{column}_range` function, which returns rows with index's value in `range` in index's
+    /// order. It's generated only for ordered indexes, so calling it for column with hash-style index is a compile
+    /// error.
+    fn gen_range_index_fn(
+        i: &Ident,
+        idx: &Index,
+        columns: &Columns,
+        row_ident: Ident,
+    ) -> syn::Result<TokenStream> {
+        let type_ = columns
+            .index_key_type(i)
+            .ok_or(syn::Error::new(i.span(), "Row not found"))?;
+        let fn_name = Ident::new(format!("select_by_{i}_range").as_str(), Span::mixed_site());
+        let field_ident = &idx.name;
+        let links = if idx.is_unique {
+            quote! {
+                TableIndex::range(&self.0.indexes.#field_ident, range)
+                    .map(|(_, l)| *l)
+                    .collect::<Vec<_>>()
+            }
+        } else {
+            quote! {
+                TableIndex::range(&self.0.indexes.#field_ident, range)
+                    .flat_map(|(_, set)| set.iter().map(|l| *l.as_ref()).collect::<Vec<_>>())
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        Ok(quote! {
+            pub fn #fn_name<R>(&self, range: R) -> core::result::Result<SelectResult<#row_ident, Self>, WorkTableError>
+            where
+                R: std::ops::RangeBounds<#type_>,
+            {
+                let rows = { #links }
+                    .iter()
+                    .filter(|link| !self.0.is_expired(**link))
+                    .map(|link| {
+                        self.0.data.select(*link).map_err(WorkTableError::PagesError)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                core::result::Result::Ok(SelectResult::<#row_ident, Self>::new(rows))
+            }
+        })
+    }
+
+    /// Generates `select_by_{column}_range_iter` function, which is lazy version of `select_by_{column}_range`.
+    fn gen_range_index_iter_fn(
+        i: &Ident,
+        idx: &Index,
+        columns: &Columns,
+        row_ident: Ident,
+    ) -> syn::Result<TokenStream> {
+        let type_ = columns
+            .index_key_type(i)
+            .ok_or(syn::Error::new(i.span(), "Row not found"))?;
+        let fn_name = Ident::new(
+            format!("select_by_{i}_range_iter").as_str(),
+            Span::mixed_site(),
+        );
+        let field_ident = &idx.name;
+        let links = if idx.is_unique {
+            quote! {
+                TableIndex::range(&self.0.indexes.#field_ident, range)
+                    .map(|(k, l)| (k.clone(), *l))
+                    .collect::<Vec<_>>()
+            }
+        } else {
+            quote! {
+                TableIndex::range(&self.0.indexes.#field_ident, range)
+                    .flat_map(|(k, set)| set.iter().map(|l| (k.clone(), *l.as_ref())).collect::<Vec<_>>())
+                    .collect::<Vec<_>>()
+            }
+        };
+        let select = Self::gen_index_iter_select(i, columns);
+
+        Ok(quote! {
+            /// Returns iterator of the rows with index's value in `range` in index's order, which reads every row
+            /// only when it's advanced. Row deleted, expired or changed to other value before iterator reached it is
+            /// skipped, and failed read of the pages is returned as `Err`.
+            pub fn #fn_name<R>(&self, range: R) -> impl Iterator<Item = core::result::Result<#row_ident, WorkTableError>> + '_
+            where
+                R: std::ops::RangeBounds<#type_>,
+            {
+                let links = { #links };
+                links
+                    .into_iter()
+                    .filter(move |(_, link)| !self.0.is_expired(*link))
+                    .filter_map(move |(key, link)| #select)
+            }
+        })
+    }
+}
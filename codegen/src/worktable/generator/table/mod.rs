@@ -4,9 +4,11 @@ use quote::quote;
 use crate::name_generator::WorktableNameGenerator;
 use crate::worktable::generator::Generator;
 
+mod builder;
 mod impls;
 mod select_executor;
 mod index_fns;
+mod read_only;
 
 impl Generator {
     pub fn gen_table_def(&mut self) -> syn::Result<TokenStream> {
@@ -17,6 +19,8 @@ impl Generator {
         let index_fns = self.gen_table_index_fns()?;
         let select_executor_impl = self.gen_table_select_executor_impl();
         let select_result_executor_impl = self.gen_table_select_result_executor_impl();
+        let read_only_def = self.gen_table_read_only_def()?;
+        let builder_def = self.gen_table_builder_def();
 
         Ok(quote! {
             #page_size_consts
@@ -26,6 +30,8 @@ impl Generator {
             #index_fns
             #select_executor_impl
             #select_result_executor_impl
+            #read_only_def
+            #builder_def
         })
     }
 
@@ -41,7 +47,7 @@ impl Generator {
                  impl Default for #ident {
                     fn default() -> Self {
                         let mut inner = WorkTable::default();
-                        inner.table_name = #table_name;
+                        inner.set_table_name(#table_name);
                         Self(inner)
                     }
                 }
@@ -69,7 +75,7 @@ impl Generator {
         };
         let persist_type_part = if self.is_persist {
             quote! {
-                , std::sync::Arc<DatabaseManager>
+                , std::sync::Arc<DatabaseManager>, Option<Wal>
             }
         } else {
             quote! {}
@@ -106,16 +112,43 @@ impl Generator {
         }
     }
 
+    /// Generates page size consts of the table. If page size is set in declaration's `config`, it's also checked at
+    /// compile time: page must be bigger than its header, inner part of the page must fit fixed part of the archived
+    /// row (row's variable-length data, like `String`s, is checked only on insert) and persisted table's page must be
+    /// the same size as file's pages, because files are always written with `PAGE_SIZE` pages.
     fn gen_page_size_consts(&self) -> TokenStream {
         let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
         let page_const_name = name_generator.get_page_size_const_ident();
         let inner_const_name = name_generator.get_page_inner_size_const_ident();
+        let wrapper_type = name_generator.get_wrapper_type_ident();
 
         if let Some(page_size) = &self.config.as_ref().map(|c| c.page_size).flatten() {
             let page_size = Literal::usize_unsuffixed(*page_size as usize);
+            let persist_assert = if self.is_persist {
+                quote! {
+                    assert!(
+                        #page_const_name == PAGE_SIZE,
+                        "`page_size` of persisted table must be equal to `PAGE_SIZE`"
+                    );
+                }
+            } else {
+                quote! {}
+            };
             quote! {
                 const #page_const_name: usize = #page_size;
-                const #inner_const_name: usize = #page_size - GENERAL_HEADER_SIZE;
+                const #inner_const_name: usize = #page_const_name.saturating_sub(GENERAL_HEADER_SIZE);
+
+                const _: () = {
+                    assert!(
+                        #page_const_name > GENERAL_HEADER_SIZE,
+                        "`page_size` must be bigger than `GENERAL_HEADER_SIZE`"
+                    );
+                    assert!(
+                        core::mem::size_of::<<#wrapper_type as rkyv::Archive>::Archived>() <= #inner_const_name,
+                        "`page_size` is too small to fit a row"
+                    );
+                    #persist_assert
+                };
             }
         } else {
             quote! {
@@ -0,0 +1,154 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+
+use crate::name_generator::WorktableNameGenerator;
+use crate::worktable::generator::Generator;
+
+impl Generator {
+    /// Generates read-only snapshot type of the table, which wraps `ReadOnlyTable` and secondary indexes of its rows,
+    /// and `snapshot_readonly` function of the table which returns it. Persisted tables can be loaded as this type by
+    /// `load_readonly_from_file`.
+    pub fn gen_table_read_only_def(&self) -> syn::Result<TokenStream> {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let ident = name_generator.get_work_table_ident();
+        let read_only_ident = name_generator.get_read_only_table_ident();
+        let row_type = name_generator.get_row_type_ident();
+        let primary_key_type = name_generator.get_primary_key_type_ident();
+        let inner_const_name = name_generator.get_page_inner_size_const_ident();
+        let index_type = name_generator.get_index_type_ident();
+        let index_fns = self.gen_read_only_index_fns()?;
+
+        let type_ = if self.config.as_ref().and_then(|c| c.page_size).is_some() {
+            quote! {
+                ReadOnlyTable<#row_type, #primary_key_type, #inner_const_name>
+            }
+        } else {
+            quote! {
+                ReadOnlyTable<#row_type, #primary_key_type>
+            }
+        };
+
+        Ok(quote! {
+            /// Read-only snapshot of the table, which is returned by `snapshot_readonly`. It has no write methods, so
+            /// it can't be modified.
+            #[derive(Debug)]
+            pub struct #read_only_ident(#type_, #index_type);
+
+            impl #read_only_ident {
+                pub fn count(&self) -> usize {
+                    self.0.count()
+                }
+
+                pub fn select(&self, pk: #primary_key_type) -> Option<#row_type> {
+                    self.0.select(pk)
+                }
+
+                pub fn get(&self, pk: &#primary_key_type) -> core::result::Result<Option<#row_type>, PagesExecutionError> {
+                    self.0.get(pk)
+                }
+
+                /// Returns all rows of the snapshot in primary key's order.
+                pub fn iter(&self) -> impl Iterator<Item = #row_type> + '_ {
+                    self.0.iter()
+                }
+
+                /// Same as table's `scan_where`, but for rows of the snapshot.
+                pub fn scan_where<F>(&self, pred: F) -> Vec<#row_type>
+                where
+                    F: Fn(&<#row_type as rkyv::Archive>::Archived) -> bool,
+                {
+                    self.0.filter_map_refs(|pk, archived| {
+                        pred(&archived.inner)
+                            .then(|| rkyv::deserialize::<#row_type, rkyv::rancor::Error>(&archived.inner).ok())
+                            .flatten()
+                            .filter(|row| &row.get_primary_key() == pk)
+                    })
+                }
+
+                #(#index_fns)*
+            }
+
+            impl #ident {
+                /// Takes read-only snapshot of the table. Writes to the table after it are not visible in snapshot.
+                /// Secondary indexes of the snapshot are built from its rows, see `WorkTable::snapshot_readonly`.
+                pub fn snapshot_readonly(&self) -> core::result::Result<#read_only_ident, WorkTableError> {
+                    let table = self.0.snapshot_readonly()?;
+                    let indexes = #index_type::default();
+                    // Row deleted after primary index is copied can keep its bytes in copied pages, so its key can
+                    // be taken by other row too. Such row is not indexed instead of failing whole snapshot.
+                    for (link, row) in table.iter_with_links() {
+                        let _ = indexes.save_row(row, link);
+                    }
+                    core::result::Result::Ok(#read_only_ident(table, indexes))
+                }
+            }
+        })
+    }
+
+    /// Generates `select_by_{index}` functions of the read-only type for column and computed indexes. Unique index
+    /// returns one row, and non-unique returns all rows with the key.
+    fn gen_read_only_index_fns(&self) -> syn::Result<Vec<TokenStream>> {
+        let name_generator = WorktableNameGenerator::from_table_name(self.name.to_string());
+        let row_type = name_generator.get_row_type_ident();
+
+        let mut fns = self
+            .columns
+            .indexes
+            .iter()
+            .map(|(i, idx)| {
+                let type_ = self
+                    .columns
+                    .index_key_type(i)
+                    .ok_or(syn::Error::new(i.span(), "Row not found"))?;
+                Ok(Self::gen_read_only_index_fn(
+                    i,
+                    &idx.name,
+                    quote! { #type_ },
+                    idx.is_unique,
+                    &row_type,
+                ))
+            })
+            .collect::<syn::Result<Vec<_>>>()?;
+        fns.extend(self.columns.computed_indexes.iter().map(|idx| {
+            let type_ = &idx.key_type;
+            Self::gen_read_only_index_fn(
+                &idx.name,
+                &idx.name,
+                quote! { #type_ },
+                idx.is_unique,
+                &row_type,
+            )
+        }));
+        Ok(fns)
+    }
+
+    fn gen_read_only_index_fn(
+        name: &Ident,
+        field_ident: &Ident,
+        type_: TokenStream,
+        is_unique: bool,
+        row_type: &Ident,
+    ) -> TokenStream {
+        let fn_name = Ident::new(format!("select_by_{name}").as_str(), Span::mixed_site());
+        if is_unique {
+            quote! {
+                pub fn #fn_name(&self, by: #type_) -> Option<#row_type> {
+                    let link = TableIndex::peek(&self.1.#field_ident, &by)?;
+                    self.0.select_by_link(link)
+                }
+            }
+        } else {
+            quote! {
+                pub fn #fn_name(&self, by: #type_) -> Vec<#row_type> {
+                    let Some(links) = TableIndex::peek(&self.1.#field_ident, &by) else {
+                        return vec![];
+                    };
+                    links
+                        .iter()
+                        .filter_map(|l| self.0.select_by_link(*l.as_ref()))
+                        .collect()
+                }
+            }
+        }
+    }
+}
@@ -12,7 +12,25 @@ impl Generator {
 
         let columns = self.columns.columns_map.iter().map(|(name, _)| {
             let lit = Literal::string(name.to_string().as_str());
-            if let Some(index) = self.columns.indexes.get(&name) {
+            if self.columns.indexes.contains_key(name) && self.columns.is_optional(name) {
+                // Rows with `None` value are not in index, so all rows are read and then sorted.
+                quote! {
+                    #lit => {
+                        let mut iter = TableIndex::iter(&self.0.pk_map);
+                        let mut rows = vec![];
+
+                        while let Some((_, l)) = iter.next() {
+                            if self.0.is_expired(*l) {
+                                continue;
+                            }
+                            let next = self.0.data.select(*l).map_err(WorkTableError::PagesError)?;
+                            rows.push(next);
+                        }
+
+                        rows
+                    },
+                }
+            } else if let Some(index) = self.columns.indexes.get(&name) {
                 let idx_name = &index.name;
                 if index.is_unique {
                     quote! {
@@ -23,6 +41,9 @@ impl Generator {
                             let mut rows = vec![];
 
                             while let Some((_, l)) = iter.next() {
+                                if self.0.is_expired(*l) {
+                                    continue;
+                                }
                                 if q.params.orders.len() < 2 {
                                     if offset != 0 {
                                         offset -= 1;
@@ -52,6 +73,9 @@ impl Generator {
 
                             while let Some((_, links)) = iter.next() {
                                 for l in links.iter() {
+                                    if self.0.is_expired(*l.as_ref()) {
+                                        continue;
+                                    }
                                     if q.params.orders.len() < 2 {
                                         if offset != 0 {
                                             offset -= 1;
@@ -95,6 +119,9 @@ impl Generator {
                         let mut rows = vec![];
 
                         while let Some((_, l)) = iter.next() {
+                            if self.0.is_expired(*l) {
+                                continue;
+                            }
                             if offset != 0 {
                                 offset -= 1;
                                 continue;
@@ -111,6 +111,14 @@ impl Generator {
                     #(#checks)*
                     None
                 }
+
+                fn is_deleted(&self) -> bool {
+                    self.is_deleted
+                }
+
+                fn set_deleted(&mut self, deleted: bool) {
+                    self.is_deleted = deleted
+                }
             }
         }
     }
@@ -40,8 +40,9 @@ pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
     }
 
     let mut columns = columns.expect("defined");
-    if let Some(i) = indexes {
-        columns.indexes = i
+    if let Some((i, computed)) = indexes {
+        columns.indexes = i;
+        columns.computed_indexes = computed;
     }
     let mut generator = Generator::new(name, is_persist, columns);
     generator.queries = queries;
@@ -57,6 +58,7 @@ pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
     let select_impls = generator.gen_query_select_impl()?;
     let update_impls = generator.gen_query_update_impl()?;
     let delete_impls = generator.gen_query_delete_impl()?;
+    let in_place_impls = generator.gen_query_in_place_impl()?;
 
     Ok(TokenStream::from(quote! {
         #pk_def
@@ -69,6 +71,7 @@ pub fn expand(input: TokenStream) -> syn::Result<TokenStream> {
         #select_impls
         #update_impls
         #delete_impls
+        #in_place_impls
     }))
 }
 
@@ -94,4 +97,21 @@ mod test {
 
         let _ = expand(tokens).unwrap();
     }
+
+    #[test]
+    fn test_float_primary_key() {
+        let tokens = quote! {
+            name: Test,
+        columns: {
+            id: f64 primary_key,
+            test: i64,
+        }
+        };
+
+        let err = expand(tokens).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "primary key column `id` has type `f64` which doesn't implement `Ord`, but primary index is ordered by it"
+        );
+    }
 }
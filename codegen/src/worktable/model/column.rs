@@ -1,15 +1,25 @@
 use std::collections::HashMap;
 
-use crate::worktable::model::index::Index;
+use crate::worktable::model::index::{ComputedIndex, Index};
 use crate::worktable::model::GeneratorType;
 use proc_macro2::{Ident, TokenStream};
 use quote::quote;
 use syn::spanned::Spanned;
 
+/// Column types which archived representation always has the same size.
+const FIXED_SIZE_TYPES: [&str; 16] = [
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize", "f32",
+    "f64", "bool", "char",
+];
+
 #[derive(Debug, Clone)]
 pub struct Columns {
     pub columns_map: HashMap<Ident, TokenStream>,
+    /// Inner types of the `optional` columns.
+    pub optional_columns: HashMap<Ident, TokenStream>,
     pub indexes: HashMap<Ident, Index>,
+    /// Indexes over keys computed from the whole row.
+    pub computed_indexes: Vec<ComputedIndex>,
     pub primary_keys: (Vec<Ident>, Ident),
     pub generator_type: GeneratorType,
 }
@@ -25,8 +35,53 @@ pub struct Row {
 }
 
 impl Columns {
+    /// Checks if column's archived representation has fixed size, so it can be overwritten in place without changing
+    /// the row's length. Only primitive types (and `Option`s of them) are treated as fixed-size, because layout of the
+    /// custom types is unknown at macro expansion time.
+    pub fn is_fixed_size(&self, column: &Ident) -> bool {
+        let Some(type_) = self.columns_map.get(column) else {
+            return false;
+        };
+        let type_ = type_.to_string();
+        let type_ = type_
+            .strip_prefix("core :: option :: Option <")
+            .and_then(|t| t.strip_suffix('>'))
+            .unwrap_or(type_.as_str())
+            .trim();
+
+        FIXED_SIZE_TYPES.contains(&type_)
+    }
+
+    /// Returns columns which can be changed in place by safe code: fixed-size columns that are not part of primary
+    /// key and not indexed, so changing them keeps row's layout and doesn't need index updates. Columns are sorted by
+    /// name.
+    pub fn scalar_columns(&self) -> Vec<&Ident> {
+        let mut columns = self
+            .columns_map
+            .keys()
+            .filter(|c| self.is_fixed_size(c))
+            .filter(|c| !self.primary_keys.0.contains(c) && !self.indexes.contains_key(*c))
+            .collect::<Vec<_>>();
+        columns.sort();
+        columns
+    }
+
+    /// Returns `true` if column is declared as `optional`.
+    pub fn is_optional(&self, column: &Ident) -> bool {
+        self.optional_columns.contains_key(column)
+    }
+
+    /// Returns type of the column's values in secondary index. `None` values are not indexed, so for `optional` column
+    /// it's inner type.
+    pub fn index_key_type(&self, column: &Ident) -> Option<&TokenStream> {
+        self.optional_columns
+            .get(column)
+            .or_else(|| self.columns_map.get(column))
+    }
+
     pub fn try_from_rows(rows: Vec<Row>, input: &TokenStream) -> syn::Result<Self> {
         let mut columns_map = HashMap::new();
+        let mut optional_columns = HashMap::new();
         let mut pk = vec![];
         let mut gen_type = None;
         let mut index_type = None;
@@ -34,6 +89,7 @@ impl Columns {
         for row in rows {
             let type_ = &row.type_;
             let type_ = if row.optional {
+                optional_columns.insert(row.name.clone(), quote! { #type_ });
                 quote! { core::option::Option<#type_> }
             } else {
                 quote! { #type_ }
@@ -41,6 +97,12 @@ impl Columns {
             columns_map.insert(row.name.clone(), type_);
 
             if row.is_primary_key {
+                if row.optional {
+                    return Err(syn::Error::new(
+                        row.name.span(),
+                        "Primary key can't be optional",
+                    ));
+                }
                 if let Some(t) = gen_type {
                     if t != row.gen_type {
                         return Err(syn::Error::new(input.span(), "Generator type must be same"));
@@ -65,7 +127,9 @@ impl Columns {
 
         Ok(Self {
             columns_map,
+            optional_columns,
             indexes: Default::default(),
+            computed_indexes: vec![],
             primary_keys: (pk, index_type.unwrap()),
             generator_type: gen_type.expect("set"),
         })
@@ -1,4 +1,6 @@
 #[derive(Debug, Default)]
 pub struct Config {
     pub page_size: Option<u32>,
+    /// If set, row derives `serde` traits and table has JSONL export/import functions.
+    pub serde: bool,
 }
@@ -1,4 +1,4 @@
-use proc_macro2::Ident;
+use proc_macro2::{Ident, TokenStream};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Index {
@@ -6,4 +6,36 @@ pub struct Index {
     pub field: Ident,
     pub is_unique: bool,
     pub index_type: Ident,
+    /// If `false`, index is not written to the table's file and is rebuilt from the rows when table is loaded.
+    pub persist: bool,
+}
+
+impl Index {
+    /// Returns `true` if index keeps keys ordered, so it can be walked by range. Hash-style indexes (`LockedHashMap`,
+    /// `LockFreeMap`) are not ordered.
+    pub fn is_ordered(&self) -> bool {
+        matches!(
+            self.index_type.to_string().as_str(),
+            "TreeIndex" | "IndexSet" | "AdaptiveIndex"
+        )
+    }
+}
+
+/// Secondary index over the key computed from the whole row. It's declared as `name: computed(KeyType, expr)`, where
+/// `expr` is non-capturing closure or path of the function that takes `&Row` and returns key. Computed index is never
+/// persisted and is rebuilt from the rows when table is loaded.
+#[derive(Debug, Clone)]
+pub struct ComputedIndex {
+    pub name: Ident,
+    pub key_type: TokenStream,
+    pub expr: TokenStream,
+    pub is_unique: bool,
+    pub index_type: Ident,
+}
+
+impl ComputedIndex {
+    /// Returns name of the index's function that computes key of the row.
+    pub fn key_fn_ident(&self) -> Ident {
+        Ident::new(format!("{}_key", self.name).as_str(), self.name.span())
+    }
 }
@@ -7,7 +7,7 @@ mod queries;
 
 pub use column::{Columns, Row};
 pub use config::Config;
-pub use index::Index;
+pub use index::{ComputedIndex, Index};
 pub use operation::Operation;
 pub use primary_key::{GeneratorType, PrimaryKey};
 pub use queries::Queries;
@@ -121,6 +121,9 @@ impl Parser {
         } else {
             if cfg!(feature = "tree_index") {
                 Ident::new("TreeIndex", Span::mixed_site())
+            } else if cfg!(feature = "index_set") && is_primary_key {
+                // Most of the tables are small, so primary key's index starts as sorted `Vec`.
+                Ident::new("AdaptiveIndex", Span::mixed_site())
             } else if cfg!(feature = "index_set") {
                 Ident::new("IndexSet", Span::mixed_site())
             } else {
@@ -221,6 +224,18 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_columns_parse_optional_primary_key() {
+        let tokens = TokenStream::from(quote! {columns: {
+            id: i64 primary_key optional,
+            test: u64,
+        }});
+        let mut parser = Parser::new(tokens);
+        let columns = parser.parse_columns();
+
+        assert!(columns.is_err());
+    }
+
     #[test]
     fn test_columns_parse_three() {
         let tokens = TokenStream::from(quote! {columns: {
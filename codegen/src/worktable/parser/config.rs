@@ -51,7 +51,7 @@ impl Parser {
 
         let mut parser = Parser::new(tt);
         let mut config = Config::default();
-        parser.parse_config(&mut config)?;
+        while parser.parse_config(&mut config)?.is_some() {}
 
         Ok(config)
     }
@@ -80,13 +80,26 @@ impl Parser {
                 } else {
                     return Err(syn::Error::new(value.span(), "Expected identifier."));
                 };
-                let value = value.to_string();
-                let value = value.replace("_", "");
+                let page_size = value.to_string().replace("_", "");
+                let page_size = u32::from_str(page_size.as_str())
+                    .map_err(|_| syn::Error::new(value.span(), "Expected page size in bytes."))?;
 
-                config.page_size = Some(u32::from_str(value.as_str()).unwrap())
+                config.page_size = Some(page_size)
+            }
+            "serde" => {
+                let value = self.input_iter.next().ok_or(syn::Error::new(
+                    self.input.span(),
+                    "Expected `serde` value in declaration",
+                ))?;
+                config.serde = match value {
+                    TokenTree::Ident(value) if value == "true" => true,
+                    TokenTree::Ident(value) if value == "false" => false,
+                    _ => return Err(syn::Error::new(value.span(), "Expected `true` or `false`.")),
+                };
             }
             _ => return Err(syn::Error::new(name.span(), "Unexpected identifier")),
         }
+        self.try_parse_comma()?;
 
         Ok(Some(()))
     }
@@ -110,4 +123,27 @@ mod tests {
         assert!(configs.is_ok());
         let columns = configs.unwrap();
     }
+
+    #[test]
+    fn test_serde_parse() {
+        let tokens = TokenStream::from(quote! {config: {
+            page_size: 16_000,
+            serde: true,
+        }});
+        let mut parser = Parser::new(tokens);
+        let config = parser.parse_configs().unwrap();
+
+        assert_eq!(config.page_size, Some(16_000));
+        assert!(config.serde);
+    }
+
+    #[test]
+    fn test_page_size_invalid() {
+        let tokens = TokenStream::from(quote! {config: {
+            page_size: "big",
+        }});
+        let mut parser = Parser::new(tokens);
+
+        assert!(parser.parse_configs().is_err());
+    }
 }
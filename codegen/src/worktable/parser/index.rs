@@ -1,11 +1,21 @@
-use crate::worktable::model::Index;
+use crate::worktable::model::{ComputedIndex, Index};
 use crate::worktable::Parser;
 use proc_macro2::{Delimiter, Ident, Span, TokenTree};
+use quote::ToTokens;
 use std::collections::HashMap;
+use syn::parse::{ParseStream, Parser as _};
 use syn::spanned::Spanned;
 
+/// Parsed declaration of one index.
+pub enum IndexDeclaration {
+    /// Index over the column.
+    Column(Ident, Index),
+    /// Index over the key computed from the row.
+    Computed(ComputedIndex),
+}
+
 impl Parser {
-    pub fn parse_indexes(&mut self) -> syn::Result<HashMap<Ident, Index>> {
+    pub fn parse_indexes(&mut self) -> syn::Result<(HashMap<Ident, Index>, Vec<ComputedIndex>)> {
         let ident = self.input_iter.next().ok_or(syn::Error::new(
             self.input.span(),
             "Expected `indexes` field in declaration",
@@ -45,20 +55,25 @@ impl Parser {
         let mut parser = Parser::new(tt);
 
         let mut rows = HashMap::new();
+        let mut computed = vec![];
         let mut ind = true;
 
         while ind {
-            let (name, row) = parser.parse_index()?;
-            rows.insert(name, row);
+            match parser.parse_index()? {
+                IndexDeclaration::Column(name, row) => {
+                    rows.insert(name, row);
+                }
+                IndexDeclaration::Computed(index) => computed.push(index),
+            }
             ind = parser.has_next()
         }
 
         self.try_parse_comma()?;
 
-        Ok(rows)
+        Ok((rows, computed))
     }
 
-    pub fn parse_index(&mut self) -> syn::Result<(Ident, Index)> {
+    pub fn parse_index(&mut self) -> syn::Result<IndexDeclaration> {
         let ident = self.input_iter.next().ok_or(syn::Error::new(
             self.input.span(),
             "Expected index name field in declaration",
@@ -80,6 +95,17 @@ impl Parser {
         } else {
             return Err(syn::Error::new(row_name.span(), "Expected row name"));
         };
+        let computed = match self.input_iter.peek() {
+            Some(TokenTree::Group(group))
+                if row_name.to_string().as_str() == "computed"
+                    && group.delimiter() == Delimiter::Parenthesis =>
+            {
+                let group = group.clone();
+                self.input_iter.next();
+                Some(Self::parse_computed_key(group.stream())?)
+            }
+            _ => None,
+        };
 
         let is_unique = if let Some(TokenTree::Ident(unique)) = self.input_iter.peek() {
             if unique.to_string().as_str() == "unique" {
@@ -92,7 +118,11 @@ impl Parser {
             false
         };
 
-        let index_type = if let Some(TokenTree::Ident(index_type)) = self.input_iter.peek() {
+        let index_type = if let Some(TokenTree::Ident(index_type)) = self
+            .input_iter
+            .peek()
+            .filter(|tt| tt.to_string().as_str() != "persist")
+        {
             let t = index_type.clone();
             self.input_iter.next();
             t
@@ -106,25 +136,83 @@ impl Parser {
             }
         };
 
+        if let Some((key_type, expr)) = computed {
+            if let Some(TokenTree::Ident(persist)) = self.input_iter.peek() {
+                if persist.to_string().as_str() == "persist" {
+                    return Err(syn::Error::new(
+                        persist.span(),
+                        "Computed index is always rebuilt on load, so it has no `persist` flag",
+                    ));
+                }
+            }
+            self.try_parse_comma()?;
+
+            return Ok(IndexDeclaration::Computed(ComputedIndex {
+                name: ident,
+                key_type,
+                expr,
+                is_unique,
+                index_type,
+            }));
+        }
+
+        let persist = self.parse_index_persist()?;
+
         self.try_parse_comma()?;
 
-        Ok((
+        Ok(IndexDeclaration::Column(
             row_name.clone(),
             Index {
                 name: ident,
                 field: row_name,
                 is_unique,
                 index_type,
+                persist,
             },
         ))
     }
+
+    /// Parses `KeyType, expr` arguments of the computed index.
+    fn parse_computed_key(
+        tokens: proc_macro2::TokenStream,
+    ) -> syn::Result<(proc_macro2::TokenStream, proc_macro2::TokenStream)> {
+        let parser = |input: ParseStream| {
+            let key_type: syn::Type = input.parse()?;
+            input.parse::<syn::Token![,]>()?;
+            let expr: syn::Expr = input.parse()?;
+            Ok((key_type.into_token_stream(), expr.into_token_stream()))
+        };
+        parser.parse2(tokens)
+    }
+
+    /// Parses optional `persist: <bool>` flag of the index. Index is persisted if flag is not set.
+    fn parse_index_persist(&mut self) -> syn::Result<bool> {
+        match self.input_iter.peek() {
+            Some(TokenTree::Ident(ident)) if ident.to_string().as_str() == "persist" => {
+                self.input_iter.next();
+            }
+            _ => return Ok(true),
+        }
+
+        self.parse_colon()?;
+
+        let value = self.input_iter.next().ok_or(syn::Error::new(
+            self.input.span(),
+            "Expected `persist` value in declaration",
+        ))?;
+        match value {
+            TokenTree::Ident(value) if value == "true" => Ok(true),
+            TokenTree::Ident(value) if value == "false" => Ok(false),
+            _ => Err(syn::Error::new(value.span(), "Expected `true` or `false`.")),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::worktable::Parser;
 
-    use proc_macro2::TokenStream;
+    use proc_macro2::{Ident, Span, TokenStream};
     use quote::quote;
 
     #[test]
@@ -139,4 +227,39 @@ mod tests {
         assert!(columns.is_ok());
         let columns = columns.unwrap();
     }
+
+    #[test]
+    fn test_index_persist_parse() {
+        let tokens = TokenStream::from(quote! {indexes: {
+            id_idx: id unique TreeIndex persist: false,
+            test_idx: test TreeIndex persist: true,
+            another_idx: another TreeIndex,
+        }});
+        let mut parser = Parser::new(tokens);
+        let (indexes, _) = parser.parse_indexes().unwrap();
+
+        assert!(!indexes[&Ident::new("id", Span::call_site())].persist);
+        assert!(indexes[&Ident::new("test", Span::call_site())].persist);
+        assert!(indexes[&Ident::new("another", Span::call_site())].persist);
+    }
+
+    #[test]
+    fn test_computed_index_parse() {
+        let tokens = TokenStream::from(quote! {indexes: {
+            day_idx: computed(u64, |row| row.timestamp / 86_400) TreeIndex,
+            name_idx: computed(String, |row| row.name.to_lowercase()) unique,
+            computed_idx: computed,
+        }});
+        let mut parser = Parser::new(tokens);
+        let (indexes, computed) = parser.parse_indexes().unwrap();
+
+        assert_eq!(indexes.len(), 1);
+        assert!(indexes.contains_key(&Ident::new("computed", Span::call_site())));
+        assert_eq!(computed.len(), 2);
+        assert_eq!(computed[0].name.to_string(), "day_idx");
+        assert_eq!(computed[0].key_type.to_string(), "u64");
+        assert!(!computed[0].is_unique);
+        assert_eq!(computed[1].name.to_string(), "name_idx");
+        assert!(computed[1].is_unique);
+    }
 }
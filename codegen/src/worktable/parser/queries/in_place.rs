@@ -0,0 +1,73 @@
+use proc_macro2::{Ident, TokenTree};
+use syn::spanned::Spanned;
+
+use crate::worktable::Parser;
+
+impl Parser {
+    pub fn parse_in_place(&mut self) -> syn::Result<Vec<Ident>> {
+        let ident = self.input_iter.next().ok_or(syn::Error::new(
+            self.input.span(),
+            "Expected `in_place` field in declaration",
+        ))?;
+        if let TokenTree::Ident(ident) = ident {
+            if ident.to_string().as_str() != "in_place" {
+                return Err(syn::Error::new(ident.span(), "Expected `in_place` field"));
+            }
+        } else {
+            return Err(syn::Error::new(
+                ident.span(),
+                "Expected field name identifier.",
+            ));
+        };
+
+        self.parse_colon()?;
+
+        let columns = self.input_iter.next().ok_or(syn::Error::new(
+            self.input.span(),
+            "Expected column identifiers in declaration",
+        ))?;
+        let columns = if let TokenTree::Group(columns) = columns {
+            let mut parser = Parser::new(columns.stream());
+            let mut columns = Vec::new();
+            while parser.has_next() {
+                let column = parser.parse_column_ident()?;
+                columns.push(column);
+                parser.try_parse_comma()?;
+            }
+            columns
+        } else {
+            return Err(syn::Error::new(
+                columns.span(),
+                "Expected column identifiers in declaration",
+            ));
+        };
+
+        self.try_parse_comma()?;
+
+        Ok(columns)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quote::quote;
+
+    use crate::worktable::Parser;
+
+    #[test]
+    fn test_in_place() {
+        let tokens = quote! {
+            in_place: {
+                test,
+                another
+            },
+        };
+        let mut parser = Parser::new(tokens);
+        let columns = parser.parse_in_place().unwrap();
+
+        assert_eq!(columns.len(), 2);
+        assert_eq!(columns[0], "test");
+        assert_eq!(columns[1], "another");
+        assert!(!parser.has_next());
+    }
+}
@@ -1,3 +1,4 @@
+use performance_measurement::PerformanceProfiler;
 use performance_measurement_codegen::performance_measurement;
 
 #[performance_measurement(prefix_name = "MeasureMe")]
@@ -30,4 +31,9 @@ fn test() {
     t.test(2);
     t.test(3);
     t.test(4);
+
+    let report = PerformanceProfiler::report("MeasureMe::");
+    assert_eq!(report.get("MeasureMe::count").unwrap().count, 3);
+    assert_eq!(report.get("MeasureMe::test").unwrap().count, 3);
+    assert!(report.get("MeasureMe::measure_me").is_none());
 }
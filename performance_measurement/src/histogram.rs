@@ -0,0 +1,129 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use derive_more::Display;
+
+/// Count of the histogram's buckets. Bucket `i` contains durations in `[2^i, 2^(i + 1))` nanoseconds.
+const BUCKETS: usize = 64;
+
+/// Latency histogram with power of two buckets. Buckets are updated with atomics, so recording doesn't take locks.
+#[derive(Debug)]
+pub struct Histogram {
+    buckets: [AtomicU64; BUCKETS],
+    count: AtomicU64,
+    max: AtomicU64,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            count: AtomicU64::new(0),
+            max: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Histogram {
+    pub fn record(&self, duration: Duration) {
+        let nanos = duration.as_nanos().min(u64::MAX as u128) as u64;
+        let bucket = (u64::BITS - nanos.max(1).leading_zeros() - 1) as usize;
+
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.max.fetch_max(nanos, Ordering::Relaxed);
+    }
+
+    /// Returns upper bound of the bucket which contains `q` quantile.
+    fn quantile(&self, buckets: &[u64; BUCKETS], count: u64, q: f64) -> Duration {
+        if count == 0 {
+            return Duration::ZERO;
+        }
+
+        let rank = ((count as f64 * q).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (i, c) in buckets.iter().enumerate() {
+            seen += c;
+            if seen >= rank {
+                let upper = 1u64.checked_shl(i as u32 + 1).unwrap_or(u64::MAX);
+                return Duration::from_nanos(upper);
+            }
+        }
+
+        Duration::from_nanos(self.max.load(Ordering::Relaxed))
+    }
+
+    pub fn snapshot(&self, name: &'static str) -> HistogramSnapshot {
+        let buckets = std::array::from_fn(|i| self.buckets[i].load(Ordering::Relaxed));
+        let count = buckets.iter().sum();
+        let max = Duration::from_nanos(self.max.load(Ordering::Relaxed));
+
+        HistogramSnapshot {
+            name,
+            count,
+            // Bucket's upper bound can be bigger than real max value.
+            p50: self.quantile(&buckets, count, 0.5).min(max),
+            p99: self.quantile(&buckets, count, 0.99).min(max),
+            max,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Display)]
+#[display("{}, count={} p50={:?} p99={:?} max={:?}", name, count, p50, p99, max)]
+pub struct HistogramSnapshot {
+    pub name: &'static str,
+
+    pub count: u64,
+
+    /// Approximate median duration. It's precise up to the power of two.
+    pub p50: Duration,
+
+    /// Approximate 99th percentile duration. It's precise up to the power of two.
+    pub p99: Duration,
+
+    pub max: Duration,
+}
+
+/// Latency histograms of the measured functions.
+#[derive(Clone, Debug, Default)]
+pub struct PerfReport {
+    pub histograms: Vec<HistogramSnapshot>,
+}
+
+impl PerfReport {
+    pub fn get(&self, name: &str) -> Option<&HistogramSnapshot> {
+        self.histograms.iter().find(|h| h.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Histogram;
+
+    #[test]
+    fn quantiles() {
+        let histogram = Histogram::default();
+        for _ in 0..98 {
+            histogram.record(Duration::from_nanos(100));
+        }
+        histogram.record(Duration::from_micros(10));
+        histogram.record(Duration::from_millis(1));
+
+        let snapshot = histogram.snapshot("test");
+        assert_eq!(snapshot.count, 100);
+        assert_eq!(snapshot.p50, Duration::from_nanos(128));
+        assert_eq!(snapshot.p99, Duration::from_nanos(16_384));
+        assert_eq!(snapshot.max, Duration::from_millis(1));
+    }
+
+    #[test]
+    fn empty() {
+        let snapshot = Histogram::default().snapshot("test");
+        assert_eq!(snapshot.count, 0);
+        assert_eq!(snapshot.p50, Duration::ZERO);
+        assert_eq!(snapshot.max, Duration::ZERO);
+    }
+}
@@ -1,3 +1,5 @@
+mod histogram;
 mod profiler;
 
+pub use histogram::{HistogramSnapshot, PerfReport};
 pub use profiler::PerformanceProfiler;
@@ -5,9 +5,12 @@ use derive_more::Display;
 use lazy_static::lazy_static;
 use lockfree::map::Map;
 
+use crate::histogram::{Histogram, PerfReport};
+
 lazy_static! {
     static ref GLOBAL_PERFORMANCE_MEASUREMENTS: Map<&'static str, PerformanceMeasurement> =
         Map::new();
+    static ref GLOBAL_HISTOGRAMS: Map<&'static str, Histogram> = Map::new();
 }
 
 #[derive(Copy, Clone, Debug, Display)]
@@ -36,6 +39,8 @@ pub struct PerformanceProfiler;
 
 impl PerformanceProfiler {
     pub fn store_measurement(function_name: &'static str, duration: Duration) {
+        Self::record_histogram(function_name, duration);
+
         let mut global_performance_measurements = &GLOBAL_PERFORMANCE_MEASUREMENTS;
 
         let duration_ms = duration.as_nanos() as f64 / 1_000_000.0;
@@ -64,6 +69,31 @@ impl PerformanceProfiler {
         };
     }
 
+    fn record_histogram(function_name: &'static str, duration: Duration) {
+        let histograms = &GLOBAL_HISTOGRAMS;
+        if let Some(guard) = histograms.get(function_name) {
+            guard.val().record(duration);
+            return;
+        }
+
+        // First measurements of the function can race here, so few of them can be lost.
+        let histogram = Histogram::default();
+        histogram.record(duration);
+        histograms.insert(function_name, histogram);
+    }
+
+    /// Returns histograms of all functions which names start with `prefix`.
+    pub fn report(prefix: &str) -> PerfReport {
+        let mut histograms = GLOBAL_HISTOGRAMS
+            .iter()
+            .filter(|guard| guard.key().starts_with(prefix))
+            .map(|guard| guard.val().snapshot(*guard.key()))
+            .collect::<Vec<_>>();
+        histograms.sort_by_key(|h| h.name);
+
+        PerfReport { histograms }
+    }
+
     pub fn get_state<'a>() -> &'a Map<&'static str, PerformanceMeasurement> {
         &GLOBAL_PERFORMANCE_MEASUREMENTS
     }
@@ -0,0 +1,35 @@
+/// Runs `f` on `tokio`'s blocking thread pool, so async executor's threads are not blocked by it. Panic of `f` is
+/// resumed in the calling task.
+pub async fn run_blocking<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    match tokio::task::spawn_blocking(f).await {
+        Ok(res) => res,
+        Err(e) => match e.try_into_panic() {
+            Ok(panic) => std::panic::resume_unwind(panic),
+            Err(e) => panic!("blocking task failed: {e}"),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::run_blocking;
+
+    #[tokio::test]
+    async fn run_blocking_returns_result() {
+        let caller = std::thread::current().id();
+        let (res, thread) = run_blocking(|| (2 + 2, std::thread::current().id())).await;
+
+        assert_eq!(res, 4);
+        assert_ne!(thread, caller);
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "failed")]
+    async fn run_blocking_resumes_panic() {
+        run_blocking(|| panic!("failed")).await
+    }
+}
@@ -0,0 +1,262 @@
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use data_bucket::PAGE_SIZE;
+use derive_more::{Display, Error};
+
+use crate::database::DurabilityMode;
+
+/// Checksum algorithm used to verify persisted pages. Checksums of all pages are written next to the table's file,
+/// together with [`ChecksumAlgorithm`] identifier, so matching verifier is used on load.
+pub trait Checksum {
+    /// Identifier of the algorithm which is recorded with checksums.
+    const ALGORITHM: ChecksumAlgorithm;
+
+    fn checksum(bytes: &[u8]) -> u64;
+}
+
+/// Fast default [`Checksum`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Crc32;
+
+impl Checksum for Crc32 {
+    const ALGORITHM: ChecksumAlgorithm = ChecksumAlgorithm::Crc32;
+
+    fn checksum(bytes: &[u8]) -> u64 {
+        crc32fast::hash(bytes) as u64
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct XxHash64;
+
+impl Checksum for XxHash64 {
+    const ALGORITHM: ChecksumAlgorithm = ChecksumAlgorithm::XxHash64;
+
+    fn checksum(bytes: &[u8]) -> u64 {
+        xxhash_rust::xxh64::xxh64(bytes, 0)
+    }
+}
+
+/// Cryptographic [`Checksum`]. Only first 8 bytes of the hash are stored.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Blake3;
+
+impl Checksum for Blake3 {
+    const ALGORITHM: ChecksumAlgorithm = ChecksumAlgorithm::Blake3;
+
+    fn checksum(bytes: &[u8]) -> u64 {
+        let hash = blake3::hash(bytes);
+        let mut head = [0; 8];
+        head.copy_from_slice(&hash.as_bytes()[..8]);
+        u64::from_le_bytes(head)
+    }
+}
+
+/// Identifier of the [`Checksum`] implementation.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[repr(u8)]
+pub enum ChecksumAlgorithm {
+    #[default]
+    Crc32 = 0,
+    XxHash64 = 1,
+    Blake3 = 2,
+}
+
+impl ChecksumAlgorithm {
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::Crc32),
+            1 => Some(Self::XxHash64),
+            2 => Some(Self::Blake3),
+            _ => None,
+        }
+    }
+
+    pub fn checksum(self, bytes: &[u8]) -> u64 {
+        match self {
+            Self::Crc32 => Crc32::checksum(bytes),
+            Self::XxHash64 => XxHash64::checksum(bytes),
+            Self::Blake3 => Blake3::checksum(bytes),
+        }
+    }
+}
+
+/// Returns path of the checksums file for the table's file, which is `<file>.checksum`.
+pub fn checksum_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut path = OsString::from(path.as_ref());
+    path.push(".checksum");
+    path.into()
+}
+
+/// Writes checksums of all pages of the file using `C` algorithm. Checksums file contains algorithm identifier byte
+/// followed by little-endian `u64` checksum of every page.
+pub fn write_checksums<C: Checksum, P: AsRef<Path>>(
+    path: P,
+    durability: DurabilityMode,
+) -> Result<(), ChecksumError> {
+    let bytes = fs::read(&path).map_err(ChecksumError::Io)?;
+    let mut checksums = Vec::with_capacity(1 + bytes.len().div_ceil(PAGE_SIZE) * 8);
+    checksums.push(C::ALGORITHM as u8);
+    for page in bytes.chunks(PAGE_SIZE) {
+        checksums.extend_from_slice(&C::checksum(page).to_le_bytes());
+    }
+
+    let mut file = File::create(checksum_path(path)).map_err(ChecksumError::Io)?;
+    file.write_all(&checksums).map_err(ChecksumError::Io)?;
+    if durability == DurabilityMode::Fsync {
+        file.sync_all().map_err(ChecksumError::Io)?;
+    }
+
+    Ok(())
+}
+
+/// Updates checksums of the pages with provided ids and of the pages added since checksums were written, without
+/// reading other pages of the file. If there is no checksums file or it was written by other algorithm, checksums of
+/// all pages are written like by [`write_checksums`].
+pub fn update_checksums<C: Checksum, P: AsRef<Path>>(
+    path: P,
+    page_ids: &[usize],
+    durability: DurabilityMode,
+) -> Result<(), ChecksumError> {
+    let mut checksums = match fs::read(checksum_path(&path)) {
+        Ok(checksums) if checksums.first() == Some(&(C::ALGORITHM as u8)) => checksums,
+        _ => return write_checksums::<C, _>(path, durability),
+    };
+    let old_count = (checksums.len() - 1) / 8;
+
+    let mut file = File::open(&path).map_err(ChecksumError::Io)?;
+    let length = file.metadata().map_err(ChecksumError::Io)?.len() as usize;
+    let count = length.div_ceil(PAGE_SIZE);
+    checksums.resize(1 + count * 8, 0);
+
+    // Last page of the file is not padded, so old last page's checksum changes if file has grown.
+    let added = old_count.saturating_sub(1)..count;
+    let mut page = vec![0; PAGE_SIZE];
+    for page_id in page_ids.iter().copied().chain(added) {
+        if page_id >= count {
+            continue;
+        }
+        let page = &mut page[..PAGE_SIZE.min(length - page_id * PAGE_SIZE)];
+        file.seek(SeekFrom::Start((page_id * PAGE_SIZE) as u64))
+            .map_err(ChecksumError::Io)?;
+        file.read_exact(page).map_err(ChecksumError::Io)?;
+        checksums[1 + page_id * 8..][..8].copy_from_slice(&C::checksum(page).to_le_bytes());
+    }
+
+    let mut file = File::create(checksum_path(path)).map_err(ChecksumError::Io)?;
+    file.write_all(&checksums).map_err(ChecksumError::Io)?;
+    if durability == DurabilityMode::Fsync {
+        file.sync_all().map_err(ChecksumError::Io)?;
+    }
+
+    Ok(())
+}
+
+/// Verifies all pages of the file using algorithm recorded in its checksums file and returns this algorithm. Every
+/// persist writes checksums file, so file without it fails with [`ChecksumError::Missing`] instead of being loaded
+/// unverified. Space info page is verified before others, and its mismatch is reported as
+/// [`ChecksumError::SpaceInfoCorrupt`].
+pub fn verify_checksums<P: AsRef<Path>>(path: P) -> Result<ChecksumAlgorithm, ChecksumError> {
+    let checksums = match fs::read(checksum_path(&path)) {
+        Ok(checksums) => checksums,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Err(ChecksumError::Missing),
+        Err(e) => return Err(ChecksumError::Io(e)),
+    };
+    let Some((id, checksums)) = checksums.split_first() else {
+        return Err(ChecksumError::Empty);
+    };
+    let algorithm = ChecksumAlgorithm::from_id(*id).ok_or(ChecksumError::UnknownAlgorithm(*id))?;
+
+    let bytes = fs::read(path).map_err(ChecksumError::Io)?;
+    // Intervals of all other pages are read from space info page, so it's verified first.
+    let info = &bytes[..bytes.len().min(PAGE_SIZE)];
+    if !info.is_empty() && checksums.get(..8) != Some(&algorithm.checksum(info).to_le_bytes()[..]) {
+        return Err(ChecksumError::SpaceInfoCorrupt);
+    }
+
+    let pages = bytes.chunks(PAGE_SIZE);
+    if pages.len() * 8 != checksums.len() {
+        return Err(ChecksumError::CountMismatch {
+            checksums: checksums.len() / 8,
+            pages: pages.len(),
+        });
+    }
+    for (page_id, (page, expected)) in pages.zip(checksums.chunks(8)).enumerate().skip(1) {
+        if algorithm.checksum(page).to_le_bytes() != expected {
+            return Err(ChecksumError::Mismatch(page_id));
+        }
+    }
+
+    Ok(algorithm)
+}
+
+#[derive(Debug, Display, Error)]
+pub enum ChecksumError {
+    Io(io::Error),
+
+    /// File has no checksums file. It's removed or file was written by older version of the crate, which didn't
+    /// write checksums. Checksums of the trusted file can be written by [`write_checksums`].
+    #[display("checksums file is missing")]
+    Missing,
+
+    /// Checksums file has no algorithm identifier.
+    #[display("checksums file is empty")]
+    Empty,
+
+    /// Checksums file has unknown algorithm identifier.
+    #[display("unknown checksum algorithm {}", _0)]
+    UnknownAlgorithm(#[error(not(source))] u8),
+
+    #[display("file has {} pages, but {} checksums", pages, checksums)]
+    CountMismatch {
+        checksums: usize,
+        pages: usize,
+    },
+
+    /// Checksum of the page with provided index is not same as recorded.
+    #[display("checksum mismatch on page {}", _0)]
+    Mismatch(#[error(not(source))] usize),
+
+    /// Checksum of the space info page is not same as recorded, so its intervals can't be trusted.
+    #[display("space info page is corrupted")]
+    SpaceInfoCorrupt,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Blake3, Checksum, ChecksumAlgorithm, Crc32, XxHash64};
+
+    #[test]
+    fn algorithm_dispatch() {
+        let bytes = b"worktable";
+
+        assert_eq!(
+            ChecksumAlgorithm::Crc32.checksum(bytes),
+            Crc32::checksum(bytes)
+        );
+        assert_eq!(
+            ChecksumAlgorithm::XxHash64.checksum(bytes),
+            XxHash64::checksum(bytes)
+        );
+        assert_eq!(
+            ChecksumAlgorithm::Blake3.checksum(bytes),
+            Blake3::checksum(bytes)
+        );
+        assert_ne!(Crc32::checksum(bytes), XxHash64::checksum(bytes));
+    }
+
+    #[test]
+    fn algorithm_id() {
+        for algorithm in [
+            ChecksumAlgorithm::Crc32,
+            ChecksumAlgorithm::XxHash64,
+            ChecksumAlgorithm::Blake3,
+        ] {
+            assert_eq!(ChecksumAlgorithm::from_id(algorithm as u8), Some(algorithm));
+        }
+        assert_eq!(ChecksumAlgorithm::from_id(3), None);
+    }
+}
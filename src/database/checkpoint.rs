@@ -0,0 +1,36 @@
+use std::thread::JoinHandle;
+
+use crate::database::PersistStats;
+
+/// Handle of the checkpoint started by generated `checkpoint` function of the persisted table. Table's image is
+/// captured before handle is returned, and it's written to the file by background thread.
+#[derive(Debug)]
+pub struct CheckpointHandle {
+    handle: JoinHandle<eyre::Result<PersistStats>>,
+}
+
+impl CheckpointHandle {
+    /// Runs `write` on the new thread, which writes captured image of the table.
+    pub fn spawn<F>(write: F) -> Self
+    where
+        F: FnOnce() -> eyre::Result<PersistStats> + Send + 'static,
+    {
+        Self {
+            handle: std::thread::spawn(write),
+        }
+    }
+
+    /// Returns `true` if captured image is written or writing failed.
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Blocks until captured image is written and returns [`PersistStats`] of the written file. Panic of the
+    /// background thread is resumed in caller's thread.
+    pub fn wait(self) -> eyre::Result<PersistStats> {
+        match self.handle.join() {
+            Ok(res) => res,
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+
+use crate::IntegrityError;
+
+/// Logical difference of two persisted spaces of the same table, which is returned by generated `spaces_diff`. Rows
+/// are matched by primary key, so physical layout of the files (page order, empty links) doesn't matter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpaceDiff<PrimaryKey, Row> {
+    /// Rows which are presented only in the first space.
+    pub only_in_first: Vec<Row>,
+
+    /// Rows which are presented only in the second space.
+    pub only_in_second: Vec<Row>,
+
+    /// Rows which have same primary key, but different values. Row of the first space goes first.
+    pub changed: Vec<(PrimaryKey, Row, Row)>,
+
+    /// Inconsistencies between indexes and rows of the first space.
+    pub first_integrity: Vec<IntegrityError>,
+
+    /// Inconsistencies between indexes and rows of the second space.
+    pub second_integrity: Vec<IntegrityError>,
+}
+
+impl<PrimaryKey, Row> Default for SpaceDiff<PrimaryKey, Row> {
+    fn default() -> Self {
+        Self {
+            only_in_first: vec![],
+            only_in_second: vec![],
+            changed: vec![],
+            first_integrity: vec![],
+            second_integrity: vec![],
+        }
+    }
+}
+
+impl<PrimaryKey, Row> SpaceDiff<PrimaryKey, Row>
+where
+    PrimaryKey: Ord,
+    Row: PartialEq,
+{
+    /// Compares rows of two spaces by primary key. Rows in the result are ordered by primary key.
+    pub fn from_rows<First, Second>(first: First, second: Second) -> Self
+    where
+        First: IntoIterator<Item = (PrimaryKey, Row)>,
+        Second: IntoIterator<Item = (PrimaryKey, Row)>,
+    {
+        let mut second = second.into_iter().collect::<BTreeMap<_, _>>();
+        let mut diff = Self::default();
+        for (pk, row) in first.into_iter().collect::<BTreeMap<_, _>>() {
+            match second.remove(&pk) {
+                None => diff.only_in_first.push(row),
+                Some(other) if other != row => diff.changed.push((pk, row, other)),
+                Some(_) => {}
+            }
+        }
+        diff.only_in_second = second.into_values().collect();
+
+        diff
+    }
+}
+
+impl<PrimaryKey, Row> SpaceDiff<PrimaryKey, Row> {
+    /// Returns `true` if spaces have same rows and indexes of both are consistent with their rows. Index contents are
+    /// defined by rows, so such spaces have same index contents too.
+    pub fn is_empty(&self) -> bool {
+        self.only_in_first.is_empty()
+            && self.only_in_second.is_empty()
+            && self.changed.is_empty()
+            && self.first_integrity.is_empty()
+            && self.second_integrity.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::SpaceDiff;
+
+    #[test]
+    fn from_rows() {
+        let first = vec![(3, "c"), (1, "a"), (2, "b")];
+        let second = vec![(2, "x"), (4, "d"), (1, "a")];
+
+        let diff = SpaceDiff::from_rows(first, second);
+        assert_eq!(diff.only_in_first, vec!["c"]);
+        assert_eq!(diff.only_in_second, vec!["d"]);
+        assert_eq!(diff.changed, vec![(2, "b", "x")]);
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn from_rows_in_different_order() {
+        let first = vec![(1, "a"), (2, "b")];
+        let second = vec![(2, "b"), (1, "a")];
+
+        assert!(SpaceDiff::from_rows(first, second).is_empty());
+    }
+}
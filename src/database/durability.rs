@@ -0,0 +1,76 @@
+/// Defines if persisted files are synced to disk.
+///
+/// Without [`DurabilityMode::Fsync`] successful `persist` only means that data was passed to OS. It can still be in
+/// OS page cache and can be lost on crash or power failure.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum DurabilityMode {
+    /// Files are synced with `sync_all` before `persist` returns.
+    #[default]
+    Fsync,
+
+    /// Files are not synced. Useful for frequent checkpoints when sync cost is too high.
+    NoSync,
+}
+
+/// Defines when table's write returns relative to durability of its change, so caller chooses between write's latency
+/// and what is lost on crash.
+///
+/// Policy is implemented by table's write-ahead log: writes are appended to it only if policy is not
+/// [`WritePolicy::MemoryOnly`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum WritePolicy {
+    /// Write returns as soon as it's done in memory. Changes after last `persist` are lost on crash.
+    #[default]
+    MemoryOnly,
+
+    /// Write returns after it's appended to the write-ahead log. Log is not synced, so change survives process crash,
+    /// but can be lost on power failure.
+    WalDurable,
+
+    /// Write returns after it's appended to the write-ahead log and log is synced to disk.
+    Synced,
+}
+
+impl WritePolicy {
+    /// Returns [`DurabilityMode`] of the write-ahead log appends, or `None` if log is not used.
+    pub fn wal_durability(self) -> Option<DurabilityMode> {
+        match self {
+            Self::MemoryOnly => None,
+            Self::WalDurable => Some(DurabilityMode::NoSync),
+            Self::Synced => Some(DurabilityMode::Fsync),
+        }
+    }
+
+    /// Returns policy of the table which write-ahead log has provided durability, or
+    /// [`WritePolicy::MemoryOnly`] if table has no log.
+    pub fn from_wal_durability(durability: Option<DurabilityMode>) -> Self {
+        match durability {
+            None => Self::MemoryOnly,
+            Some(DurabilityMode::NoSync) => Self::WalDurable,
+            Some(DurabilityMode::Fsync) => Self::Synced,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::database::{DurabilityMode, WritePolicy};
+
+    #[test]
+    fn wal_durability() {
+        for policy in [
+            WritePolicy::MemoryOnly,
+            WritePolicy::WalDurable,
+            WritePolicy::Synced,
+        ] {
+            assert_eq!(
+                WritePolicy::from_wal_durability(policy.wal_durability()),
+                policy
+            );
+        }
+        assert_eq!(
+            WritePolicy::Synced.wal_durability(),
+            Some(DurabilityMode::Fsync)
+        );
+    }
+}
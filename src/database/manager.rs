@@ -1,8 +1,20 @@
+use std::sync::{Arc, PoisonError, RwLock, Weak};
+
+/// Table that can be registered in [`DatabaseManager`] to be persisted by [`DatabaseManager::persist_all`].
+pub trait PersistableTable: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn persist(&self) -> eyre::Result<()>;
+}
+
 // This manager is used to share common table information.
 #[derive(Debug, Clone)]
 pub struct DatabaseManager {
     pub config_path: String,
     pub database_files_dir: String,
+
+    /// Registered tables. Only weak references are stored, so dropped tables are just skipped.
+    tables: Arc<RwLock<Vec<Weak<dyn PersistableTable>>>>,
 }
 
 impl DatabaseManager {
@@ -10,6 +22,30 @@ impl DatabaseManager {
         Self {
             config_path,
             database_files_dir,
+            tables: Arc::default(),
         }
     }
+
+    /// Registers table, so it will be persisted by [`DatabaseManager::persist_all`] while it's alive.
+    pub fn register<T: PersistableTable + 'static>(&self, table: &Arc<T>) {
+        let table = Arc::downgrade(table) as Weak<dyn PersistableTable>;
+        let mut tables = self.tables.write().unwrap_or_else(PoisonError::into_inner);
+        tables.retain(|t| t.strong_count() != 0);
+        tables.push(table);
+    }
+
+    /// Persists all registered tables which are still alive. Failure of one table doesn't stop others from being
+    /// persisted, so result of every table is returned with its name.
+    pub fn persist_all(&self) -> eyre::Result<Vec<(String, eyre::Result<()>)>> {
+        let tables = {
+            let mut tables = self.tables.write().unwrap_or_else(PoisonError::into_inner);
+            tables.retain(|t| t.strong_count() != 0);
+            tables.iter().filter_map(Weak::upgrade).collect::<Vec<_>>()
+        };
+
+        Ok(tables
+            .into_iter()
+            .map(|t| (t.name().to_string(), t.persist()))
+            .collect())
+    }
 }
@@ -1,4 +1,21 @@
+mod checkpoint;
 mod config;
+mod diff;
+mod durability;
 mod manager;
+mod parse;
+mod path;
+mod persist;
+mod stats;
 
-pub use manager::DatabaseManager;
+pub use checkpoint::CheckpointHandle;
+pub use diff::SpaceDiff;
+pub use durability::{DurabilityMode, WritePolicy};
+pub use manager::{DatabaseManager, PersistableTable};
+pub use parse::{parse_pages, parse_space_info, PageParseError};
+pub use path::{table_file_path, PathError, RESERVED_FILE_NAMES};
+pub use persist::{
+    page_bytes, page_intervals, persist_pages, validate_disjoint_intervals,
+    validate_space_intervals, IntervalError,
+};
+pub use stats::PersistStats;
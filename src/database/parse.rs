@@ -0,0 +1,209 @@
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+
+use data_bucket::{GeneralHeader, GeneralPage, SpaceInfoData, GENERAL_HEADER_SIZE, PAGE_SIZE};
+use derive_more::{Display, Error};
+use rkyv::api::high::{HighDeserializer, HighValidator};
+use rkyv::bytecheck::CheckBytes;
+use rkyv::util::AlignedVec;
+use rkyv::{Archive, Archived, Deserialize};
+
+/// Returns iterator over all pages of the file with their data as raw bytes. Iteration stops after the last page or
+/// on the first error.
+///
+/// File is a sequence of pages. Page with id `n` starts at offset `n * PAGE_SIZE` and consists of:
+///
+/// | Offset                | Length                | Content                                   |
+/// |-----------------------|-----------------------|-------------------------------------------|
+/// | `0`                   | `GENERAL_HEADER_SIZE` | `rkyv` archived [`GeneralHeader`]         |
+/// | `GENERAL_HEADER_SIZE` | `data_length`         | page's data, which depends on `page_type` |
+///
+/// Header contains `data_version`, `space_id`, `page_id`, `previous_id`, `next_id`, `page_type` and `data_length`
+/// fields in this order. All of them are fixed size little-endian values, so header's length doesn't depend on its
+/// values. Space between page's data and next page's start is not used, last page is not padded.
+///
+/// Page `0` is always `SpaceInfo` page with `rkyv` archived [`SpaceInfoData`]. Its intervals describe which pages
+/// contain primary index, every secondary index and rows. Index pages contain archived `IndexData` and data pages
+/// contain archived rows placed one after another, as they are stored in memory.
+pub fn parse_pages<R: Read + Seek>(
+    mut reader: R,
+) -> impl Iterator<Item = Result<GeneralPage<Vec<u8>>, PageParseError>> {
+    let mut page_id = 0;
+    let mut failed = false;
+    std::iter::from_fn(move || {
+        if failed {
+            return None;
+        }
+        match parse_raw_page(&mut reader, page_id) {
+            Ok(page) => {
+                page_id += 1;
+                page.map(Ok)
+            }
+            Err(e) => {
+                failed = true;
+                Some(Err(e))
+            }
+        }
+    })
+}
+
+/// Parses `SpaceInfo` page of the file. `State` is table's primary key generator state, which is `()` for tables
+/// without generator state.
+pub fn parse_space_info<R, State>(
+    mut reader: R,
+) -> Result<GeneralPage<SpaceInfoData<State>>, PageParseError>
+where
+    R: Read + Seek,
+    SpaceInfoData<State>: Archive,
+    Archived<SpaceInfoData<State>>: for<'a> CheckBytes<HighValidator<'a, rkyv::rancor::Error>>
+        + Deserialize<SpaceInfoData<State>, HighDeserializer<rkyv::rancor::Error>>,
+{
+    let page = parse_raw_page(&mut reader, 0)?.ok_or(PageParseError::Empty)?;
+    let inner = decode(&page.inner).map_err(|_| PageParseError::InvalidData { page_id: 0 })?;
+
+    Ok(GeneralPage {
+        header: page.header,
+        inner,
+    })
+}
+
+/// Reads page with `page_id`. Returns `None` if file ends before the page.
+fn parse_raw_page<R: Read + Seek>(
+    reader: &mut R,
+    page_id: u32,
+) -> Result<Option<GeneralPage<Vec<u8>>>, PageParseError> {
+    reader
+        .seek(SeekFrom::Start(page_id as u64 * PAGE_SIZE as u64))
+        .map_err(PageParseError::Io)?;
+    let mut bytes = vec![0; PAGE_SIZE];
+    let mut read = 0;
+    while read < PAGE_SIZE {
+        match reader.read(&mut bytes[read..]) {
+            Ok(0) => break,
+            Ok(n) => read += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(PageParseError::Io(e)),
+        }
+    }
+    if read == 0 {
+        return Ok(None);
+    }
+    if read < GENERAL_HEADER_SIZE {
+        return Err(PageParseError::TruncatedHeader { page_id });
+    }
+
+    let header: GeneralHeader = decode(&bytes[..GENERAL_HEADER_SIZE])
+        .map_err(|_| PageParseError::InvalidHeader { page_id })?;
+    let data_length = header.data_length as usize;
+    if GENERAL_HEADER_SIZE + data_length > read {
+        return Err(PageParseError::DataOutOfBounds {
+            page_id,
+            data_length: header.data_length,
+        });
+    }
+    bytes.truncate(GENERAL_HEADER_SIZE + data_length);
+    bytes.drain(..GENERAL_HEADER_SIZE);
+
+    Ok(Some(GeneralPage {
+        header,
+        inner: bytes,
+    }))
+}
+
+fn decode<T>(bytes: &[u8]) -> Result<T, rkyv::rancor::Error>
+where
+    T: Archive,
+    T::Archived: for<'a> CheckBytes<HighValidator<'a, rkyv::rancor::Error>>
+        + Deserialize<T, HighDeserializer<rkyv::rancor::Error>>,
+{
+    // Archived values must be aligned, but page's data can start at any offset.
+    let mut aligned = AlignedVec::<16>::with_capacity(bytes.len());
+    aligned.extend_from_slice(bytes);
+    rkyv::from_bytes::<T, rkyv::rancor::Error>(&aligned)
+}
+
+#[derive(Debug, Display, Error)]
+pub enum PageParseError {
+    Io(std::io::Error),
+
+    #[display("file is empty")]
+    Empty,
+
+    #[display("page {} is shorter than header", page_id)]
+    TruncatedHeader {
+        page_id: u32,
+    },
+
+    #[display("page {} has invalid header", page_id)]
+    InvalidHeader {
+        page_id: u32,
+    },
+
+    #[display("page {} data length {} is out of bounds", page_id, data_length)]
+    DataOutOfBounds {
+        page_id: u32,
+        data_length: u32,
+    },
+
+    #[display("data of page {} can't be deserialized", page_id)]
+    InvalidData {
+        page_id: u32,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use data_bucket::{GeneralHeader, PageType, Persistable, DATA_VERSION};
+    use data_bucket::{GENERAL_HEADER_SIZE, PAGE_SIZE};
+
+    use super::{parse_pages, PageParseError};
+
+    fn page(page_id: u32, data: Vec<u8>) -> Vec<u8> {
+        let header = GeneralHeader {
+            data_version: DATA_VERSION,
+            page_id: page_id.into(),
+            previous_id: page_id.saturating_sub(1).into(),
+            next_id: (page_id + 1).into(),
+            page_type: PageType::Data,
+            space_id: 0.into(),
+            data_length: data.len() as u32,
+        };
+        let mut bytes = header.as_bytes().as_ref().to_vec();
+        bytes.resize(GENERAL_HEADER_SIZE, 0);
+        bytes.extend_from_slice(&data);
+        bytes
+    }
+
+    #[test]
+    fn parse_raw_pages() {
+        let mut file = page(0, vec![1; 10]);
+        file.resize(PAGE_SIZE, 0);
+        file.extend_from_slice(&page(1, vec![2; 20]));
+
+        let pages = parse_pages(Cursor::new(file))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(pages[0].header.page_id, 0.into());
+        assert_eq!(pages[0].inner, vec![1; 10]);
+        assert_eq!(pages[1].header.page_id, 1.into());
+        assert_eq!(pages[1].inner, vec![2; 20]);
+    }
+
+    #[test]
+    fn parse_truncated_page() {
+        let mut file = page(0, vec![1; 10]);
+        file.truncate(file.len() - 1);
+
+        let mut pages = parse_pages(Cursor::new(file));
+        assert!(matches!(
+            pages.next(),
+            Some(Err(PageParseError::DataOutOfBounds {
+                page_id: 0,
+                data_length: 10
+            }))
+        ));
+        assert!(pages.next().is_none());
+    }
+}
@@ -0,0 +1,92 @@
+use std::path::{Path, PathBuf};
+
+use derive_more::{Display, Error};
+
+/// Names which are reserved on Windows, even with extension. `worktable!` checks table's file name against the same
+/// list at compile time.
+pub const RESERVED_FILE_NAMES: &[&str] = &[
+    "con", "prn", "aux", "nul", "com1", "com2", "com3", "com4", "com5", "com6", "com7", "com8",
+    "com9", "lpt1", "lpt2", "lpt3", "lpt4", "lpt5", "lpt6", "lpt7", "lpt8", "lpt9",
+];
+
+/// Returns path of the table's `.wt` file in `dir`. Table's file name must be single file name that is valid on all
+/// platforms, otherwise [`PathError::InvalidFileName`] is returned.
+pub fn table_file_path<P: AsRef<Path>>(dir: P, name: &str) -> Result<PathBuf, PathError> {
+    validate_file_name(name)?;
+    Ok(dir.as_ref().join(format!("{}.wt", name)))
+}
+
+fn validate_file_name(name: &str) -> Result<(), PathError> {
+    let invalid = |reason| {
+        Err(PathError::InvalidFileName {
+            name: name.to_string(),
+            reason,
+        })
+    };
+
+    if name.is_empty() {
+        return invalid("name is empty");
+    }
+    if name == "." || name == ".." {
+        return invalid("name is relative path");
+    }
+    if name.chars().any(|c| {
+        matches!(c, '/' | '\\' | '<' | '>' | ':' | '"' | '|' | '?' | '*') || c.is_control()
+    }) {
+        return invalid(
+            "name contains path separator or character that is not allowed in file names",
+        );
+    }
+    if name.ends_with('.') || name.ends_with(' ') {
+        return invalid("name ends with dot or space");
+    }
+    if RESERVED_FILE_NAMES.contains(&name.to_lowercase().as_str()) {
+        return invalid("name is reserved");
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, Display, Error)]
+pub enum PathError {
+    #[display("invalid table file name `{}`: {}", name, reason)]
+    InvalidFileName { name: String, reason: &'static str },
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{table_file_path, PathError};
+
+    #[test]
+    fn table_file_path_join() {
+        assert_eq!(
+            table_file_path("tests/data", "test_persist").unwrap(),
+            Path::new("tests/data").join("test_persist.wt")
+        );
+    }
+
+    #[test]
+    fn table_file_path_invalid() {
+        for name in [
+            "",
+            "..",
+            "../test",
+            "dir/test",
+            "dir\\test",
+            "a:b",
+            "con",
+            "Nul",
+            "test.",
+        ] {
+            assert!(
+                matches!(
+                    table_file_path("tests/data", name),
+                    Err(PathError::InvalidFileName { .. })
+                ),
+                "{name}"
+            );
+        }
+    }
+}
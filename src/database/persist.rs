@@ -0,0 +1,335 @@
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+
+use data_bucket::{
+    GeneralPage, Interval, Persistable, SpaceInfoData, GENERAL_HEADER_SIZE, PAGE_SIZE,
+};
+use derive_more::{Display, Error};
+
+/// Name of the primary index's part in [`IntervalError`].
+const PRIMARY_PART: &str = "primary index";
+
+/// Name of the data pages' part in [`IntervalError`].
+const DATA_PART: &str = "data";
+
+/// Serializes page the same way as `persist_page` writes it: header followed by inner data. Header's `data_length`
+/// is set to the length of the inner data.
+pub fn page_bytes<T: Persistable>(page: &mut GeneralPage<T>) -> Vec<u8> {
+    let inner = page.inner.as_bytes();
+    let inner = inner.as_ref();
+    page.header.data_length = inner.len() as u32;
+
+    let mut bytes = Vec::with_capacity(GENERAL_HEADER_SIZE + inner.len());
+    bytes.extend_from_slice(page.header.as_bytes().as_ref());
+    bytes.resize(GENERAL_HEADER_SIZE, 0);
+    bytes.extend_from_slice(inner);
+
+    bytes
+}
+
+/// Writes pages at their ids' offsets. With `parallel` feature pages are serialized on `rayon`'s thread pool, but they
+/// are still written one by one in id's order, so file is the same as with sequential persist.
+#[cfg(feature = "parallel")]
+pub fn persist_pages<T>(pages: &mut [GeneralPage<T>], file: &mut File) -> eyre::Result<()>
+where
+    T: Persistable + Send,
+{
+    use rayon::prelude::*;
+
+    let buffers = pages.par_iter_mut().map(page_bytes).collect::<Vec<_>>();
+    for (page, bytes) in pages.iter().zip(buffers) {
+        write_page(page, &bytes, file)?;
+    }
+
+    Ok(())
+}
+
+/// Writes pages at their ids' offsets. Every page is written right after it's serialized, so only one page's bytes
+/// are kept in memory.
+#[cfg(not(feature = "parallel"))]
+pub fn persist_pages<T>(pages: &mut [GeneralPage<T>], file: &mut File) -> eyre::Result<()>
+where
+    T: Persistable,
+{
+    for page in pages.iter_mut() {
+        let bytes = page_bytes(page);
+        write_page(page, &bytes, file)?;
+    }
+
+    Ok(())
+}
+
+/// Groups page ids into [`Interval`]s of consecutive ids. Order of the ids is kept, so pages that are read by the
+/// returned intervals one by one are in the same order as `page_ids`.
+pub fn page_intervals(page_ids: &[usize]) -> Vec<Interval> {
+    let mut intervals: Vec<Interval> = vec![];
+    for &page_id in page_ids {
+        match intervals.last_mut() {
+            Some(last) if last.1 + 1 == page_id => last.1 = page_id,
+            _ => intervals.push(Interval(page_id, page_id)),
+        }
+    }
+
+    intervals
+}
+
+/// Error of the space's page intervals, which is returned instead of writing file where pages of different parts
+/// overwrite each other.
+#[derive(Clone, Debug, Display, Error, Eq, PartialEq)]
+pub enum IntervalError {
+    #[display("interval {}..={} of {} is reversed", start, end, part)]
+    Reversed {
+        part: String,
+        start: usize,
+        end: usize,
+    },
+    #[display("page {} is used by both {} and {}", page_id, first, second)]
+    Overlap {
+        first: String,
+        second: String,
+        page_id: usize,
+    },
+    #[display("pages {}..{} before {} are not used by any part", start, end, part)]
+    Gap {
+        part: String,
+        start: usize,
+        end: usize,
+    },
+}
+
+/// Checks that intervals of the primary index, secondary indexes and data pages of the space are disjoint and cover
+/// contiguous page ids which start right after the space info page. It's the layout written by full persist, so
+/// overlapping intervals mean that pages' headers were threaded wrong.
+pub fn validate_space_intervals<T>(info: &SpaceInfoData<T>) -> Result<(), IntervalError> {
+    // Space info page is always the first one.
+    let mut next = 1;
+    for (part, interval) in sorted_intervals(info)? {
+        if interval.0 > next {
+            return Err(IntervalError::Gap {
+                part: part.to_string(),
+                start: next,
+                end: interval.0,
+            });
+        }
+        next = interval.1 + 1;
+    }
+
+    Ok(())
+}
+
+/// Checks that intervals of the space are disjoint and don't use space info page. Unlike
+/// [`validate_space_intervals`], gaps are allowed, because append leaves unused pages in the file.
+pub fn validate_disjoint_intervals<T>(info: &SpaceInfoData<T>) -> Result<(), IntervalError> {
+    sorted_intervals(info).map(|_| ())
+}
+
+/// Returns all intervals of the space with names of their parts sorted by start, or error if any two of them
+/// overlap.
+fn sorted_intervals<T>(info: &SpaceInfoData<T>) -> Result<Vec<(&str, &Interval)>, IntervalError> {
+    let mut intervals = info
+        .primary_key_intervals
+        .iter()
+        .map(|i| (PRIMARY_PART, i))
+        .chain(
+            info.secondary_index_intervals
+                .iter()
+                .flat_map(|(name, intervals)| intervals.iter().map(|i| (name.as_str(), i))),
+        )
+        .chain(info.data_intervals.iter().map(|i| (DATA_PART, i)))
+        .collect::<Vec<_>>();
+    intervals.sort_by_key(|(_, i)| i.0);
+
+    let mut previous = ("space info", &Interval(0, 0));
+    for (part, interval) in &intervals {
+        if interval.0 > interval.1 {
+            return Err(IntervalError::Reversed {
+                part: part.to_string(),
+                start: interval.0,
+                end: interval.1,
+            });
+        }
+        if interval.0 <= previous.1 .1 {
+            return Err(IntervalError::Overlap {
+                first: previous.0.to_string(),
+                second: part.to_string(),
+                page_id: interval.0,
+            });
+        }
+        previous = (part, interval);
+    }
+
+    Ok(intervals)
+}
+
+fn write_page<T>(page: &GeneralPage<T>, bytes: &[u8], file: &mut File) -> eyre::Result<()> {
+    let page_id: usize = page.header.page_id.into();
+    file.seek(SeekFrom::Start((page_id * PAGE_SIZE) as u64))?;
+    file.write_all(bytes)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use data_bucket::{
+        DataPage, GeneralHeader, GeneralPage, Interval, PageType, SpaceInfoData, DATA_VERSION,
+    };
+    use data_bucket::{GENERAL_HEADER_SIZE, INNER_PAGE_SIZE};
+
+    use super::{
+        page_bytes, page_intervals, validate_disjoint_intervals, validate_space_intervals,
+        IntervalError,
+    };
+
+    fn data_page(page_id: u32, length: u32) -> GeneralPage<DataPage<INNER_PAGE_SIZE>> {
+        GeneralPage {
+            header: GeneralHeader {
+                data_version: DATA_VERSION,
+                page_id: page_id.into(),
+                previous_id: page_id.saturating_sub(1).into(),
+                next_id: (page_id + 1).into(),
+                page_type: PageType::Data,
+                space_id: 0.into(),
+                data_length: 0,
+            },
+            inner: DataPage {
+                data: [page_id as u8; INNER_PAGE_SIZE],
+                length,
+            },
+        }
+    }
+
+    #[test]
+    fn page_bytes_length() {
+        let mut page = data_page(3, 100);
+        let bytes = page_bytes(&mut page);
+
+        assert_eq!(bytes.len(), GENERAL_HEADER_SIZE + 100);
+        assert_eq!(page.header.data_length, 100);
+    }
+
+    #[test]
+    fn page_intervals_keep_order() {
+        let intervals = page_intervals(&[3, 4, 5, 9, 10, 1]);
+
+        assert_eq!(
+            intervals
+                .into_iter()
+                .map(|i| (i.0, i.1))
+                .collect::<Vec<_>>(),
+            vec![(3, 5), (9, 10), (1, 1)]
+        );
+    }
+
+    fn space_info(
+        primary: Vec<Interval>,
+        secondary: Vec<(&str, Vec<Interval>)>,
+        data: Vec<Interval>,
+    ) -> SpaceInfoData<()> {
+        SpaceInfoData {
+            id: 0.into(),
+            page_count: 0,
+            name: "test".to_string(),
+            primary_key_intervals: primary,
+            secondary_index_intervals: secondary
+                .into_iter()
+                .map(|(name, i)| (name.to_string(), i))
+                .collect(),
+            data_intervals: data,
+            pk_gen_state: (),
+            empty_links_list: vec![],
+            secondary_index_map: HashMap::default(),
+        }
+    }
+
+    #[test]
+    fn space_intervals_valid() {
+        let info = space_info(
+            vec![Interval(1, 2)],
+            vec![("a", vec![Interval(3, 3)]), ("b", vec![Interval(4, 5)])],
+            vec![Interval(6, 9)],
+        );
+
+        assert_eq!(validate_space_intervals(&info), Ok(()));
+        assert_eq!(validate_disjoint_intervals(&info), Ok(()));
+    }
+
+    #[test]
+    fn space_intervals_overlap() {
+        let info = space_info(
+            vec![Interval(1, 2)],
+            vec![("a", vec![Interval(2, 3)])],
+            vec![Interval(4, 4)],
+        );
+
+        let err = IntervalError::Overlap {
+            first: "primary index".to_string(),
+            second: "a".to_string(),
+            page_id: 2,
+        };
+        assert_eq!(validate_space_intervals(&info), Err(err.clone()));
+        assert_eq!(validate_disjoint_intervals(&info), Err(err));
+
+        let info = space_info(vec![Interval(0, 1)], vec![], vec![Interval(2, 2)]);
+        assert!(matches!(
+            validate_disjoint_intervals(&info),
+            Err(IntervalError::Overlap { page_id: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn space_intervals_gap() {
+        let info = space_info(vec![Interval(1, 1)], vec![], vec![Interval(3, 4)]);
+
+        assert_eq!(
+            validate_space_intervals(&info),
+            Err(IntervalError::Gap {
+                part: "data".to_string(),
+                start: 2,
+                end: 3,
+            })
+        );
+        assert_eq!(validate_disjoint_intervals(&info), Ok(()));
+    }
+
+    #[test]
+    fn space_intervals_reversed() {
+        let info = space_info(vec![Interval(1, 1)], vec![], vec![Interval(3, 2)]);
+
+        assert!(matches!(
+            validate_disjoint_intervals(&info),
+            Err(IntervalError::Reversed {
+                start: 3,
+                end: 2,
+                ..
+            })
+        ));
+    }
+
+    //#[test]
+    #[cfg(feature = "parallel")]
+    fn bench_persist_pages() {
+        use std::time::Instant;
+
+        use data_bucket::PAGE_SIZE;
+        use rayon::prelude::*;
+
+        // 1GB of data pages.
+        let count = (1 << 30) / PAGE_SIZE;
+        let mut pages = (0..count as u32)
+            .map(|i| data_page(i, INNER_PAGE_SIZE as u32))
+            .collect::<Vec<_>>();
+
+        let start = Instant::now();
+        let sequential = pages.iter_mut().map(page_bytes).collect::<Vec<_>>();
+        println!("sequential {:?}", start.elapsed());
+
+        let start = Instant::now();
+        let parallel = pages.par_iter_mut().map(page_bytes).collect::<Vec<_>>();
+        println!("parallel {:?}", start.elapsed());
+
+        assert_eq!(sequential, parallel);
+    }
+}
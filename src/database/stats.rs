@@ -0,0 +1,17 @@
+use std::time::Duration;
+
+/// Statistics of the single table's persist.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct PersistStats {
+    /// Count of bytes written to the table's file.
+    pub bytes_written: u64,
+
+    /// Count of the written data pages.
+    pub data_pages: usize,
+
+    /// Count of the written primary and secondary index pages.
+    pub index_pages: usize,
+
+    /// Time spent on persist, including table's snapshot and checksums.
+    pub duration: Duration,
+}
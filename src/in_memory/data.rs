@@ -1,7 +1,9 @@
 use std::cell::UnsafeCell;
+use std::io;
 use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 
 use data_bucket::page::PageId;
 use data_bucket::{DataPage, GeneralPage};
@@ -18,6 +20,9 @@ use rkyv::{
     Archive, Deserialize, Portable, Serialize,
 };
 
+use crate::checksum::{Checksum, Crc32};
+use crate::in_memory::serializer::with_serialized;
+use crate::in_memory::spill::SpillFile;
 use crate::persistence::page::INNER_PAGE_SIZE;
 use crate::prelude::Link;
 
@@ -27,6 +32,19 @@ pub const DATA_HEADER_LENGTH: usize = 4;
 /// Length of the inner [`Data`] page part.
 pub const DATA_INNER_LENGTH: usize = INNER_PAGE_SIZE - DATA_HEADER_LENGTH;
 
+/// Modification sequence of all [`Data`] pages of the process. It's increased on every change of any page.
+static MODIFICATION_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// Returns current modification sequence. Every [`Data`] page changed after this call gets bigger
+/// [`Data::modified_seq`] than returned value.
+pub fn modification_seq() -> u64 {
+    MODIFICATION_SEQ.load(Ordering::SeqCst)
+}
+
+/// Alignment of the raw bytes saved by [`Data::save_raw`]. It's the same as alignment of the page's inner data, so
+/// rows saved after raw bytes stay aligned.
+pub const RAW_ALIGN: usize = 16;
+
 #[derive(Archive, Clone, Copy, Debug, Deserialize, Serialize)]
 #[repr(C, align(16))]
 pub struct AlignedBytes<const N: usize>(pub [u8; N]);
@@ -58,9 +76,33 @@ pub struct Data<Row, const DATA_LENGTH: usize = DATA_INNER_LENGTH> {
     #[rkyv(with = AtomicLoad<Relaxed>)]
     pub free_offset: AtomicU32,
 
-    /// Inner array of bytes where deserialized `Row`s will be stored.
+    /// Inner array of bytes where deserialized `Row`s will be stored. It's `None` while page is evicted to the
+    /// [`SpillFile`].
     #[rkyv(with = Unsafe)]
-    inner_data: UnsafeCell<AlignedBytes<DATA_LENGTH>>,
+    inner_data: UnsafeCell<Option<Box<AlignedBytes<DATA_LENGTH>>>>,
+
+    /// Shows if this [`Data`] page was changed since it was loaded or marked as persisted.
+    #[rkyv(with = Skip)]
+    dirty: AtomicBool,
+
+    /// Modification sequence of the last change of this [`Data`] page, see [`DataPages::modification_seq`].
+    ///
+    /// [`DataPages::modification_seq`]: crate::in_memory::DataPages::modification_seq
+    #[rkyv(with = Skip)]
+    modified_seq: AtomicU64,
+
+    /// Shows if this [`Data`] page is evicted, so its bytes must be loaded from the [`SpillFile`] before access.
+    #[rkyv(with = Skip)]
+    evicted: AtomicBool,
+
+    /// Shows if this [`Data`] page was accessed since the last eviction pass, see [`Data::take_accessed`].
+    #[rkyv(with = Skip)]
+    accessed: AtomicBool,
+
+    /// [`SpillFile`] of the evicted page. Its lock is held while page is evicted or loaded, so page is loaded only
+    /// once by concurrent readers.
+    #[rkyv(with = Skip)]
+    spill: Mutex<Option<Arc<SpillFile>>>,
 
     /// `Row` phantom data.
     _phantom: PhantomData<Row>,
@@ -74,7 +116,14 @@ impl<Row, const DATA_LENGTH: usize> Data<Row, DATA_LENGTH> {
         Self {
             id,
             free_offset: AtomicU32::default(),
-            inner_data: UnsafeCell::new(AlignedBytes::<DATA_LENGTH>([0; DATA_LENGTH])),
+            inner_data: UnsafeCell::new(Some(Box::new(AlignedBytes::<DATA_LENGTH>(
+                [0; DATA_LENGTH],
+            )))),
+            dirty: AtomicBool::new(true),
+            modified_seq: AtomicU64::new(0),
+            evicted: AtomicBool::new(false),
+            accessed: AtomicBool::new(false),
+            spill: Mutex::new(None),
             _phantom: PhantomData,
         }
     }
@@ -83,7 +132,14 @@ impl<Row, const DATA_LENGTH: usize> Data<Row, DATA_LENGTH> {
         Self {
             id: page.header.page_id,
             free_offset: AtomicU32::from(page.header.data_length),
-            inner_data: UnsafeCell::new(AlignedBytes::<DATA_LENGTH>(page.inner.data)),
+            inner_data: UnsafeCell::new(Some(Box::new(AlignedBytes::<DATA_LENGTH>(
+                page.inner.data,
+            )))),
+            dirty: AtomicBool::new(false),
+            modified_seq: AtomicU64::new(0),
+            evicted: AtomicBool::new(false),
+            accessed: AtomicBool::new(false),
+            spill: Mutex::new(None),
             _phantom: PhantomData,
         }
     }
@@ -92,6 +148,195 @@ impl<Row, const DATA_LENGTH: usize> Data<Row, DATA_LENGTH> {
         self.id = id;
     }
 
+    pub fn id(&self) -> PageId {
+        self.id
+    }
+
+    /// Returns `true` if this [`Data`] page was changed since it was loaded or marked as persisted. New pages are
+    /// dirty.
+    pub fn is_dirty(&self) -> bool {
+        self.dirty.load(Ordering::Acquire)
+    }
+
+    /// Marks this [`Data`] page as changed. It's called after bytes are written, so page that is copied after
+    /// [`Data::take_dirty`] is either copied with the change or stays dirty.
+    ///
+    /// Page's modification sequence is set before global sequence is increased, so page changed after
+    /// [`DataPages::modification_seq`] call can't have sequence which is not bigger than returned one. Page is marked
+    /// dirty after its sequence is set, so [`Data::clear_dirty`] which sees old sequence can't lose the change.
+    ///
+    /// [`DataPages::modification_seq`]: crate::in_memory::DataPages::modification_seq
+    pub fn mark_dirty(&self) {
+        let mut current = MODIFICATION_SEQ.load(Ordering::SeqCst);
+        loop {
+            self.modified_seq.fetch_max(current + 1, Ordering::SeqCst);
+            match MODIFICATION_SEQ.compare_exchange_weak(
+                current,
+                current + 1,
+                Ordering::SeqCst,
+                Ordering::SeqCst,
+            ) {
+                Ok(_) => break,
+                Err(actual) => current = actual,
+            }
+        }
+
+        self.dirty.store(true, Ordering::Release);
+    }
+
+    /// Returns modification sequence of the last change of this [`Data`] page. It's zero for page which was not
+    /// changed since it was created or loaded.
+    pub fn modified_seq(&self) -> u64 {
+        self.modified_seq.load(Ordering::SeqCst)
+    }
+
+    /// Marks this [`Data`] page as persisted and returns if it was dirty. Page must be copied for persistence after
+    /// this call, not before it.
+    pub fn take_dirty(&self) -> bool {
+        self.dirty.swap(false, Ordering::AcqRel)
+    }
+
+    /// Marks this [`Data`] page as persisted if it wasn't changed after modification sequence `seq`. It's called
+    /// after page's copy, which was taken after `seq`, is written.
+    pub fn clear_dirty(&self, seq: u64) {
+        self.dirty.store(false, Ordering::Release);
+        if self.modified_seq() > seq {
+            self.dirty.store(true, Ordering::Release);
+        }
+    }
+
+    /// Returns count of the used bytes of this [`Data`] page.
+    pub fn used_length(&self) -> u32 {
+        self.free_offset
+            .load(Ordering::Relaxed)
+            .min(DATA_LENGTH as u32)
+    }
+
+    /// Checks that [`Link`] points to the used part of this [`Data`] page.
+    pub fn is_link_valid(&self, link: Link) -> bool {
+        link.length != 0 && link.offset as u64 + link.length as u64 <= self.used_length() as u64
+    }
+
+    /// Returns `true` if this [`Data`] page is evicted to the [`SpillFile`].
+    pub fn is_evicted(&self) -> bool {
+        self.evicted.load(Ordering::Acquire)
+    }
+
+    /// Returns if this [`Data`] page was accessed since previous call and resets it. It's used as reference bit of
+    /// the clock eviction.
+    pub fn take_accessed(&self) -> bool {
+        self.accessed.swap(false, Ordering::Relaxed)
+    }
+
+    /// Frees bytes of this [`Data`] page, which were written to the `file` by [`SpillFile::write_page`] when page's
+    /// modification sequence was `seq`. Written copy is stale if page was changed after it, so then page is kept in
+    /// memory. Returns `true` if page is evicted. Evicted page is loaded back on the next access.
+    ///
+    /// # Safety
+    ///
+    /// No references to the page's bytes may exist while page is evicted. [`DataPages`] evicts pages only under
+    /// pages write lock, while all references are taken under its read lock.
+    ///
+    /// [`DataPages`]: crate::in_memory::DataPages
+    pub unsafe fn evict(&self, file: &Arc<SpillFile>, seq: u64) -> bool {
+        let mut spill = self.spill.lock().unwrap_or_else(PoisonError::into_inner);
+        if self.is_evicted() || self.modified_seq() != seq {
+            return false;
+        }
+        unsafe { *self.inner_data.get() = None };
+        *spill = Some(file.clone());
+        self.evicted.store(true, Ordering::Release);
+
+        true
+    }
+
+    /// Loads bytes of the evicted page from its [`SpillFile`]. If they can't be read, page stays evicted, so next
+    /// access tries to load it again.
+    fn load(&self) -> Result<(), ExecutionError> {
+        let mut spill = self.spill.lock().unwrap_or_else(PoisonError::into_inner);
+        if !self.is_evicted() {
+            return Ok(());
+        }
+        let file = spill
+            .as_ref()
+            .ok_or(ExecutionError::SpillError(io::ErrorKind::NotFound))?;
+        let bytes = file
+            .read_page::<DATA_LENGTH>(self.id)
+            .map_err(|e| ExecutionError::SpillError(e.kind()))?;
+        file.mark_loaded();
+        unsafe { *self.inner_data.get() = Some(bytes) };
+        *spill = None;
+        self.evicted.store(false, Ordering::Release);
+
+        Ok(())
+    }
+
+    /// Loads evicted page and marks page as accessed. It's called before every access of the page's bytes.
+    fn touch(&self) -> Result<(), ExecutionError> {
+        if self.is_evicted() {
+            self.load()?;
+        }
+        if !self.accessed.load(Ordering::Relaxed) {
+            self.accessed.store(true, Ordering::Relaxed);
+        }
+
+        Ok(())
+    }
+
+    /// Returns inner bytes of this [`Data`] page. Evicted page is loaded first.
+    ///
+    /// # Safety
+    ///
+    /// Bytes that are changed concurrently must not be read.
+    unsafe fn bytes(&self) -> Result<&AlignedBytes<DATA_LENGTH>, ExecutionError> {
+        self.touch()?;
+        unsafe { &*self.inner_data.get() }
+            .as_deref()
+            .ok_or(ExecutionError::SpillError(io::ErrorKind::NotFound))
+    }
+
+    /// Returns mutable inner bytes of this [`Data`] page. Evicted page is loaded first.
+    ///
+    /// # Safety
+    ///
+    /// Same bytes must not be accessed concurrently.
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn bytes_mut(&self) -> Result<&mut AlignedBytes<DATA_LENGTH>, ExecutionError> {
+        self.touch()?;
+        unsafe { &mut *self.inner_data.get() }
+            .as_deref_mut()
+            .ok_or(ExecutionError::SpillError(io::ErrorKind::NotFound))
+    }
+
+    /// Applies `op` to the inner bytes of this [`Data`] page. Bytes of the evicted page are read from its
+    /// [`SpillFile`] for this call only, so page is not loaded and stays evicted.
+    fn with_bytes<Res>(
+        &self,
+        op: impl FnOnce(&AlignedBytes<DATA_LENGTH>) -> Res,
+    ) -> Result<Res, ExecutionError> {
+        let spill = self.spill.lock().unwrap_or_else(PoisonError::into_inner);
+        match spill.as_ref() {
+            Some(file) if self.is_evicted() => {
+                let bytes = file
+                    .read_page::<DATA_LENGTH>(self.id)
+                    .map_err(|e| ExecutionError::SpillError(e.kind()))?;
+                Ok(op(&bytes))
+            }
+            _ => {
+                let bytes = unsafe { &*self.inner_data.get() }
+                    .as_deref()
+                    .ok_or(ExecutionError::SpillError(io::ErrorKind::NotFound))?;
+                Ok(op(bytes))
+            }
+        }
+    }
+
+    /// Returns checksum of the used part of this [`Data`] page. Evicted page is not loaded.
+    pub fn checksum(&self) -> Result<u64, ExecutionError> {
+        let length = self.used_length() as usize;
+        self.with_bytes(|inner_data| Crc32::checksum(&inner_data[..length]))
+    }
+
     #[cfg_attr(
         feature = "perf_measurements",
         performance_measurement(prefix_name = "DataRow")
@@ -103,9 +348,23 @@ impl<Row, const DATA_LENGTH: usize> Data<Row, DATA_LENGTH> {
                 Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
             >,
     {
-        let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(row)
-            .map_err(|_| ExecutionError::SerializeError)?;
+        with_serialized::<DATA_LENGTH, _, _, _>(row, |bytes| self.save_row_bytes(bytes))
+            .map_err(|_| ExecutionError::SerializeError)?
+    }
+
+    /// Saves serialized `Row`'s `bytes` as is. Unlike [`Data::save_raw`], taken space is not rounded up, so `bytes`
+    /// can be accessed as archived `Row` by returned [`Link`].
+    pub(crate) fn save_row_bytes(&self, bytes: &[u8]) -> Result<Link, ExecutionError> {
         let length = bytes.len() as u32;
+        // Row that can't fit even in empty page is rejected before space is taken, so page is not marked as full.
+        if length > DATA_LENGTH as u32 {
+            return Err(ExecutionError::RowTooLarge {
+                size: length,
+                capacity: DATA_LENGTH as u32,
+            });
+        }
+        // Evicted page is loaded before space is taken, so space is not lost if page can't be loaded.
+        let inner_data = unsafe { self.bytes_mut()? };
         let offset = self.free_offset.fetch_add(length, Ordering::SeqCst);
         if offset > DATA_LENGTH as u32 - length {
             return Err(ExecutionError::PageIsFull {
@@ -114,16 +373,14 @@ impl<Row, const DATA_LENGTH: usize> Data<Row, DATA_LENGTH> {
             });
         }
 
-        let inner_data = unsafe { &mut *self.inner_data.get() };
-        inner_data[offset as usize..][..length as usize].copy_from_slice(bytes.as_slice());
+        inner_data[offset as usize..][..length as usize].copy_from_slice(bytes);
+        self.mark_dirty();
 
-        let link = Link {
+        Ok(Link {
             page_id: self.id,
             offset,
             length,
-        };
-
-        Ok(link)
+        })
     }
 
     #[cfg_attr(
@@ -141,19 +398,39 @@ impl<Row, const DATA_LENGTH: usize> Data<Row, DATA_LENGTH> {
                 Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
             >,
     {
-        let bytes = rkyv::to_bytes(row).map_err(|_| ExecutionError::SerializeError)?;
-        let length = bytes.len() as u32;
-        if length != link.length {
+        with_serialized::<DATA_LENGTH, _, _, _>(row, |bytes| unsafe {
+            self.save_row_bytes_by_link(bytes, link)
+        })
+        .map_err(|_| ExecutionError::SerializeError)?
+    }
+
+    /// Same as [`Data::save_row_by_link`], but saves serialized `Row`'s `bytes` as is.
+    ///
+    /// # Safety
+    ///
+    /// Row stored by [`Link`] must not be accessed concurrently.
+    pub(crate) unsafe fn save_row_bytes_by_link(
+        &self,
+        bytes: &[u8],
+        link: Link,
+    ) -> Result<Link, ExecutionError> {
+        if bytes.len() as u32 != link.length || !self.is_link_valid(link) {
             return Err(ExecutionError::InvalidLink);
         }
 
-        let inner_data = unsafe { &mut *self.inner_data.get() };
-        inner_data[link.offset as usize..][..link.length as usize]
-            .copy_from_slice(bytes.as_slice());
+        let inner_data = unsafe { self.bytes_mut()? };
+        inner_data[link.offset as usize..][..link.length as usize].copy_from_slice(bytes);
+        self.mark_dirty();
 
         Ok(link)
     }
 
+    /// Returns mutable archived `Row` stored by [`Link`].
+    ///
+    /// # Safety
+    ///
+    /// Returned value must not be used to change the archived row's length, and it must not be used concurrently with
+    /// other accesses to the same row.
     pub unsafe fn get_mut_row_ref(
         &self,
         link: Link,
@@ -162,11 +439,11 @@ impl<Row, const DATA_LENGTH: usize> Data<Row, DATA_LENGTH> {
         Row: Archive,
         <Row as Archive>::Archived: Portable,
     {
-        if link.offset > self.free_offset.load(Ordering::Relaxed) {
+        if !self.is_link_valid(link) {
             return Err(ExecutionError::DeserializeError);
         }
 
-        let inner_data = unsafe { &mut *self.inner_data.get() };
+        let inner_data = unsafe { self.bytes_mut()? };
         let bytes = &mut inner_data[link.offset as usize..(link.offset + link.length) as usize];
         Ok(unsafe { rkyv::access_unchecked_mut::<<Row as Archive>::Archived>(&mut bytes[..]) })
     }
@@ -179,11 +456,11 @@ impl<Row, const DATA_LENGTH: usize> Data<Row, DATA_LENGTH> {
     where
         Row: Archive,
     {
-        if link.offset > self.free_offset.load(Ordering::Relaxed) {
+        if !self.is_link_valid(link) {
             return Err(ExecutionError::DeserializeError);
         }
 
-        let inner_data = unsafe { &*self.inner_data.get() };
+        let inner_data = unsafe { self.bytes()? };
         let bytes = &inner_data[link.offset as usize..(link.offset + link.length) as usize];
         Ok(unsafe { rkyv::access_unchecked::<<Row as Archive>::Archived>(&bytes[..]) })
     }
@@ -202,9 +479,83 @@ impl<Row, const DATA_LENGTH: usize> Data<Row, DATA_LENGTH> {
             .map_err(|_| ExecutionError::DeserializeError)?);
     }
 
-    pub fn get_bytes(&self) -> [u8; DATA_LENGTH] {
-        let data = unsafe { &*self.inner_data.get() };
-        data.0.clone()
+    /// Saves raw `bytes` on this [`Data`] page. Taken space is rounded up to [`RAW_ALIGN`] bytes and returned
+    /// [`Link`]'s length is the taken space, so it can be reused by rows like any other freed slot.
+    pub fn save_raw(&self, bytes: &[u8]) -> Result<Link, ExecutionError> {
+        let length = bytes.len().next_multiple_of(RAW_ALIGN) as u32;
+        if length > DATA_LENGTH as u32 {
+            return Err(ExecutionError::RowTooLarge {
+                size: length,
+                capacity: DATA_LENGTH as u32,
+            });
+        }
+        // Evicted page is loaded before space is taken, so space is not lost if page can't be loaded.
+        let inner_data = unsafe { self.bytes_mut()? };
+        let offset = self.free_offset.fetch_add(length, Ordering::SeqCst);
+        if offset > DATA_LENGTH as u32 - length {
+            return Err(ExecutionError::PageIsFull {
+                need: length,
+                left: DATA_LENGTH as i64 - offset as i64,
+            });
+        }
+
+        inner_data[offset as usize..][..bytes.len()].copy_from_slice(bytes);
+        self.mark_dirty();
+
+        Ok(Link {
+            page_id: self.id,
+            offset,
+            length,
+        })
+    }
+
+    /// Overwrites start of the raw bytes stored by [`Link`] with `bytes`.
+    ///
+    /// # Safety
+    ///
+    /// Bytes stored by [`Link`] must not be accessed concurrently.
+    pub unsafe fn save_raw_by_link(&self, bytes: &[u8], link: Link) -> Result<(), ExecutionError> {
+        if bytes.len() > link.length as usize || !self.is_link_valid(link) {
+            return Err(ExecutionError::InvalidLink);
+        }
+
+        let inner_data = unsafe { self.bytes_mut()? };
+        inner_data[link.offset as usize..][..bytes.len()].copy_from_slice(bytes);
+        self.mark_dirty();
+
+        Ok(())
+    }
+
+    /// Returns raw bytes stored by [`Link`].
+    pub fn get_raw(&self, link: Link) -> Result<&[u8], ExecutionError> {
+        if !self.is_link_valid(link) {
+            return Err(ExecutionError::DeserializeError);
+        }
+
+        let inner_data = unsafe { self.bytes()? };
+        Ok(&inner_data[link.offset as usize..(link.offset + link.length) as usize])
+    }
+
+    /// Returns copy of the inner bytes of this [`Data`] page. Evicted page is not loaded.
+    pub fn get_bytes(&self) -> Result<[u8; DATA_LENGTH], ExecutionError> {
+        self.with_bytes(|data| data.0)
+    }
+
+    /// Returns copy of this [`Data`] page with same id and used part.
+    pub fn snapshot(&self) -> Result<Self, ExecutionError> {
+        Ok(Self {
+            id: self.id,
+            free_offset: AtomicU32::new(self.free_offset.load(Ordering::Acquire)),
+            inner_data: UnsafeCell::new(Some(Box::new(AlignedBytes::<DATA_LENGTH>(
+                self.get_bytes()?,
+            )))),
+            dirty: AtomicBool::new(self.is_dirty()),
+            modified_seq: AtomicU64::new(self.modified_seq()),
+            evicted: AtomicBool::new(false),
+            accessed: AtomicBool::new(false),
+            spill: Mutex::new(None),
+            _phantom: PhantomData,
+        })
     }
 }
 
@@ -215,6 +566,11 @@ pub enum ExecutionError {
     #[display("need {}, but {} left", need, left)]
     PageIsFull { need: u32, left: i64 },
 
+    /// Error of trying to save row which serialized size is bigger than [`Data`] page's capacity, so it can't be saved
+    /// in any page.
+    #[display("row of {} bytes doesn't fit in page of {} bytes", size, capacity)]
+    RowTooLarge { size: u32, capacity: u32 },
+
     /// Error of saving `Row` in [`Data`] page.
     SerializeError,
 
@@ -223,6 +579,10 @@ pub enum ExecutionError {
 
     /// Link provided for saving `Row` is invalid.
     InvalidLink,
+
+    /// Error of writing or reading evicted [`Data`] page in its [`SpillFile`].
+    #[display("spill file error: {}", _0)]
+    SpillError(#[error(not(source))] io::ErrorKind),
 }
 
 #[cfg(test)]
@@ -233,7 +593,9 @@ mod tests {
 
     use rkyv::{Archive, Deserialize, Serialize};
 
-    use crate::in_memory::data::{Data, INNER_PAGE_SIZE};
+    use crate::checksum::{Checksum, Crc32};
+    use crate::in_memory::data::{AlignedBytes, Data, ExecutionError, INNER_PAGE_SIZE};
+    use crate::in_memory::SpillFile;
 
     #[derive(
         Archive, Copy, Clone, Deserialize, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
@@ -252,6 +614,19 @@ mod tests {
         assert_eq!(bytes.len(), INNER_PAGE_SIZE)
     }
 
+    #[test]
+    fn data_page_save_row_overflows_page() {
+        let page = Data::<Vec<u8>, 16>::new(1.into());
+
+        // Serializer's buffer grows past `DATA_LENGTH`, so oversized row is rejected by page's size check.
+        let res = page.save_row(&vec![1u8; 1024]);
+        assert!(matches!(
+            res,
+            Err(ExecutionError::RowTooLarge { capacity: 16, .. })
+        ));
+        assert_eq!(page.free_offset.load(Ordering::Relaxed), 0);
+    }
+
     #[test]
     fn data_page_save_row() {
         let page = Data::<TestRow>::new(1.into());
@@ -264,7 +639,7 @@ mod tests {
 
         assert_eq!(page.free_offset.load(Ordering::Relaxed), link.length);
 
-        let inner_data = unsafe { &mut *page.inner_data.get() };
+        let inner_data = unsafe { page.bytes().unwrap() };
         let bytes = &inner_data[link.offset as usize..link.length as usize];
         let archived = unsafe { rkyv::access_unchecked::<ArchivedTestRow>(bytes) };
         assert_eq!(archived, &row)
@@ -282,7 +657,7 @@ mod tests {
 
         assert_eq!(res, link);
 
-        let inner_data = unsafe { &mut *page.inner_data.get() };
+        let inner_data = unsafe { page.bytes().unwrap() };
         let bytes = &inner_data[link.offset as usize..link.length as usize];
         let archived = unsafe { rkyv::access_unchecked::<ArchivedTestRow>(bytes) };
         assert_eq!(archived, &new_row)
@@ -300,6 +675,116 @@ mod tests {
         assert!(res.is_err());
     }
 
+    #[test]
+    fn data_page_row_too_large() {
+        let page = Data::<TestRow, 8>::new(1.into());
+        let row = TestRow { a: 10, b: 20 };
+
+        let res = page.save_row(&row);
+
+        assert!(matches!(
+            res,
+            Err(ExecutionError::RowTooLarge {
+                size: 16,
+                capacity: 8
+            })
+        ));
+        assert_eq!(page.free_offset.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn data_page_evict() {
+        let path =
+            std::env::temp_dir().join(format!("worktable-spill-{}.bin", uuid::Uuid::new_v4()));
+        let file = Arc::new(SpillFile::create(&path).unwrap());
+        let page = Data::<TestRow>::new(1.into());
+        let row = TestRow { a: 10, b: 20 };
+        let link = page.save_row(&row).unwrap();
+
+        let seq = page.modified_seq();
+        file.write_page(1.into(), &AlignedBytes(page.get_bytes().unwrap()))
+            .unwrap();
+        assert!(unsafe { page.evict(&file, seq) });
+        assert!(page.is_evicted());
+
+        // Evicted page is read from the file, but not loaded, by copies.
+        assert_eq!(
+            page.checksum().unwrap(),
+            Crc32::checksum(&page.get_bytes().unwrap()[..link.length as usize])
+        );
+        assert!(page.is_evicted());
+
+        assert_eq!(page.get_row(link).unwrap(), row);
+        assert!(!page.is_evicted());
+
+        // Copy written before the change is stale, so page is not evicted.
+        let seq = page.modified_seq();
+        page.save_row(&row).unwrap();
+        assert!(!unsafe { page.evict(&file, seq) });
+        assert!(!page.is_evicted());
+    }
+
+    #[test]
+    fn data_page_load_error() {
+        let path =
+            std::env::temp_dir().join(format!("worktable-spill-{}.bin", uuid::Uuid::new_v4()));
+        let file = Arc::new(SpillFile::create(&path).unwrap());
+        let page = Data::<TestRow>::new(1.into());
+        let link = page.save_row(&TestRow { a: 10, b: 20 }).unwrap();
+
+        // Page was not written to the file, so it can't be read back.
+        assert!(unsafe { page.evict(&file, page.modified_seq()) });
+        assert!(matches!(
+            page.get_row(link),
+            Err(ExecutionError::SpillError(_))
+        ));
+        assert!(page.is_evicted());
+    }
+
+    #[test]
+    fn data_page_save_raw() {
+        let page = Data::<TestRow, 64>::new(1.into());
+
+        let link = page.save_raw(&[1; 20]).unwrap();
+        assert_eq!(link.offset, 0);
+        assert_eq!(link.length, 32);
+        assert_eq!(&page.get_raw(link).unwrap()[..20], &[1; 20]);
+
+        // Row saved after raw bytes is aligned.
+        let row_link = page.save_row(&TestRow { a: 10, b: 20 }).unwrap();
+        assert_eq!(row_link.offset, 32);
+
+        unsafe { page.save_raw_by_link(&[2; 10], link) }.unwrap();
+        assert_eq!(
+            &page.get_raw(link).unwrap()[..12],
+            &[2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 1]
+        );
+        assert!(matches!(
+            unsafe { page.save_raw_by_link(&[2; 40], link) },
+            Err(ExecutionError::InvalidLink)
+        ));
+
+        assert!(matches!(
+            page.save_raw(&[1; 20]),
+            Err(ExecutionError::PageIsFull { .. })
+        ));
+    }
+
+    #[test]
+    fn data_page_checksum() {
+        let page = Data::<TestRow>::new(1.into());
+        let empty_checksum = page.checksum().unwrap();
+
+        let row = TestRow { a: 10, b: 20 };
+        let link = page.save_row(&row).unwrap();
+        let checksum = page.checksum().unwrap();
+        assert_ne!(checksum, empty_checksum);
+
+        let new_row = TestRow { a: 20, b: 20 };
+        unsafe { page.save_row_by_link(&new_row, link) }.unwrap();
+        assert_ne!(page.checksum().unwrap(), checksum);
+    }
+
     #[test]
     fn data_page_full_multithread() {
         let page = Data::<TestRow, 128>::new(1.into());
@@ -356,7 +841,7 @@ mod tests {
             links.push(link)
         }
 
-        let inner_data = unsafe { &mut *page.inner_data.get() };
+        let inner_data = unsafe { page.bytes().unwrap() };
 
         for (i, link) in links.into_iter().enumerate() {
             let link = link.unwrap();
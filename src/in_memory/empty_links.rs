@@ -0,0 +1,282 @@
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{Mutex, PoisonError};
+
+use lockfree::queue::Queue;
+use lockfree::stack::Stack;
+
+use crate::prelude::Link;
+
+/// Order in which [`Link`]s of the deleted rows are reused by inserts.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum EmptyLinkPolicy {
+    /// Last freed slot is reused first.
+    #[default]
+    Lifo,
+
+    /// First freed slot is reused first.
+    Fifo,
+
+    /// Slot with the lowest page id and offset is reused first. It keeps rows clustered at the start of the pages,
+    /// which reduces fragmentation over time.
+    LowestFirst,
+}
+
+/// [`Link`] ordered by page id and offset.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct OrderedLink(Link);
+
+impl OrderedLink {
+    fn key(&self) -> (usize, u32, u32) {
+        (self.0.page_id.into(), self.0.offset, self.0.length)
+    }
+}
+
+impl PartialOrd for OrderedLink {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrderedLink {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Storage of the empty [`Link`]s, which is chosen by [`EmptyLinkPolicy`].
+#[derive(Debug)]
+pub struct EmptyLinks {
+    storage: Storage,
+
+    /// Count of the stored [`Link`]s. It's increased before [`Link`] is pushed and decreased after it's popped, so
+    /// it's never less than actual count.
+    len: AtomicUsize,
+}
+
+#[derive(Debug)]
+enum Storage {
+    Lifo(Stack<Link>),
+    Fifo(Queue<Link>),
+    LowestFirst(Mutex<BinaryHeap<Reverse<OrderedLink>>>),
+}
+
+impl Default for EmptyLinks {
+    fn default() -> Self {
+        Self::new(EmptyLinkPolicy::default())
+    }
+}
+
+impl EmptyLinks {
+    pub fn new(policy: EmptyLinkPolicy) -> Self {
+        let storage = match policy {
+            EmptyLinkPolicy::Lifo => Storage::Lifo(Stack::new()),
+            EmptyLinkPolicy::Fifo => Storage::Fifo(Queue::new()),
+            EmptyLinkPolicy::LowestFirst => Storage::LowestFirst(Mutex::new(BinaryHeap::new())),
+        };
+        Self {
+            storage,
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn policy(&self) -> EmptyLinkPolicy {
+        match self.storage {
+            Storage::Lifo(_) => EmptyLinkPolicy::Lifo,
+            Storage::Fifo(_) => EmptyLinkPolicy::Fifo,
+            Storage::LowestFirst(_) => EmptyLinkPolicy::LowestFirst,
+        }
+    }
+
+    pub fn push(&self, link: Link) {
+        self.len.fetch_add(1, AtomicOrdering::Relaxed);
+        match &self.storage {
+            Storage::Lifo(s) => s.push(link),
+            Storage::Fifo(q) => q.push(link),
+            // Heap can't be left inconsistent by panic in `push`/`pop`, so poisoned lock is just recovered.
+            Storage::LowestFirst(h) => h
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(Reverse(OrderedLink(link))),
+        }
+    }
+
+    /// Pushes all [`Link`]s, taking heap's lock once.
+    pub fn extend(&self, links: impl IntoIterator<Item = Link>) {
+        let links = links.into_iter().inspect(|_| {
+            self.len.fetch_add(1, AtomicOrdering::Relaxed);
+        });
+        match &self.storage {
+            Storage::Lifo(s) => s.extend(links),
+            Storage::Fifo(q) => q.extend(links),
+            Storage::LowestFirst(h) => h
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .extend(links.map(|l| Reverse(OrderedLink(l)))),
+        }
+    }
+
+    pub fn pop(&self) -> Option<Link> {
+        let link = match &self.storage {
+            Storage::Lifo(s) => s.pop(),
+            Storage::Fifo(q) => q.pop(),
+            Storage::LowestFirst(h) => h
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .pop()
+                .map(|Reverse(l)| l.0),
+        };
+        if link.is_some() {
+            self.len.fetch_sub(1, AtomicOrdering::Relaxed);
+        }
+
+        link
+    }
+
+    /// Returns count of the stored [`Link`]s. It's read from counter, so links are not taken out to count them.
+    pub fn len(&self) -> usize {
+        self.len.load(AtomicOrdering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Takes all [`Link`]s out in reuse order.
+    pub fn drain(&self) -> Vec<Link> {
+        let mut res = vec![];
+        while let Some(l) = self.pop() {
+            res.push(l)
+        }
+
+        res
+    }
+
+    /// Returns all [`Link`]s in reuse order, keeping them stored.
+    pub fn snapshot(&self) -> Vec<Link> {
+        let links = self.drain();
+        self.restore(&links);
+
+        links
+    }
+
+    /// Keeps only [`Link`]s for which `f` returns `true`. Reuse order of kept [`Link`]s is not changed.
+    pub fn retain<F>(&self, f: F)
+    where
+        F: FnMut(&Link) -> bool,
+    {
+        let mut links = self.drain();
+        links.retain(f);
+        self.restore(&links);
+    }
+
+    /// Releases memory which is kept by storage but not used by stored [`Link`]s.
+    pub fn shrink_to_fit(&self) {
+        // Nodes of stack and queue are freed on pop, only heap keeps its capacity.
+        if let Storage::LowestFirst(h) = &self.storage {
+            h.lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .shrink_to_fit()
+        }
+    }
+
+    /// Pushes [`Link`]s which are in reuse order back, so they are reused in same order.
+    fn restore(&self, links: &[Link]) {
+        match self.storage {
+            Storage::Lifo(_) => links.iter().rev().for_each(|l| self.push(*l)),
+            Storage::Fifo(_) | Storage::LowestFirst(_) => links.iter().for_each(|l| self.push(*l)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::in_memory::empty_links::{EmptyLinkPolicy, EmptyLinks};
+    use crate::prelude::Link;
+
+    fn links() -> Vec<Link> {
+        vec![
+            Link {
+                page_id: 1.into(),
+                offset: 24,
+                length: 24,
+            },
+            Link {
+                page_id: 0.into(),
+                offset: 48,
+                length: 24,
+            },
+            Link {
+                page_id: 0.into(),
+                offset: 0,
+                length: 24,
+            },
+        ]
+    }
+
+    fn reused(policy: EmptyLinkPolicy) -> Vec<Link> {
+        let empty_links = EmptyLinks::new(policy);
+        for l in links() {
+            empty_links.push(l)
+        }
+        let snapshot = empty_links.snapshot();
+        assert_eq!(snapshot, empty_links.drain());
+
+        snapshot
+    }
+
+    #[test]
+    fn reuse_order() {
+        let links = links();
+
+        let mut lifo = links.clone();
+        lifo.reverse();
+        assert_eq!(reused(EmptyLinkPolicy::Lifo), lifo);
+        assert_eq!(reused(EmptyLinkPolicy::Fifo), links);
+        assert_eq!(
+            reused(EmptyLinkPolicy::LowestFirst),
+            vec![links[2], links[1], links[0]]
+        );
+    }
+
+    #[test]
+    fn extend() {
+        for policy in [
+            EmptyLinkPolicy::Lifo,
+            EmptyLinkPolicy::Fifo,
+            EmptyLinkPolicy::LowestFirst,
+        ] {
+            let empty_links = EmptyLinks::new(policy);
+            empty_links.extend(links());
+
+            assert_eq!(empty_links.len(), 3);
+            assert_eq!(empty_links.drain(), reused(policy));
+            assert!(empty_links.is_empty());
+        }
+    }
+
+    #[test]
+    fn len_is_counted() {
+        for policy in [
+            EmptyLinkPolicy::Lifo,
+            EmptyLinkPolicy::Fifo,
+            EmptyLinkPolicy::LowestFirst,
+        ] {
+            let empty_links = EmptyLinks::new(policy);
+            for l in links() {
+                empty_links.push(l)
+            }
+            assert_eq!(empty_links.len(), 3);
+
+            empty_links.pop().unwrap();
+            assert_eq!(empty_links.len(), 2);
+            empty_links.retain(|l| l.page_id == 0.into());
+            assert_eq!(empty_links.len(), empty_links.snapshot().len());
+
+            empty_links.drain();
+            assert!(empty_links.pop().is_none());
+            assert_eq!(empty_links.len(), 0);
+        }
+    }
+}
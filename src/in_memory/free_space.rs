@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+use std::sync::{Mutex, PoisonError};
+
+use crate::prelude::Link;
+
+/// Free tails of the pages. When row doesn't fit in the rest of its page, insert moves to the next page and rest of
+/// the full page is never used by the page itself. Such tails are stored here as [`Link`]s grouped by their length, so
+/// later inserts can backfill them instead of adding pages.
+#[derive(Debug, Default)]
+pub struct FreeSpace {
+    tails: Mutex<BTreeMap<u32, Vec<Link>>>,
+}
+
+impl FreeSpace {
+    pub fn push(&self, link: Link) {
+        self.tails
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(link.length)
+            .or_default()
+            .push(link);
+    }
+
+    /// Takes the shortest tail that has at least `length` bytes (best fit), so longer tails are kept for longer
+    /// rows.
+    pub fn take(&self, length: u32) -> Option<Link> {
+        let mut tails = self.tails.lock().unwrap_or_else(PoisonError::into_inner);
+        let entry = tails.range_mut(length..).next()?;
+        let link = entry.1.pop().expect("empty length classes are removed");
+        if entry.1.is_empty() {
+            let key = *entry.0;
+            tails.remove(&key);
+        }
+
+        Some(link)
+    }
+
+    /// Returns all tails ordered by length, keeping them stored.
+    pub fn snapshot(&self) -> Vec<Link> {
+        self.tails
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .flatten()
+            .copied()
+            .collect()
+    }
+
+    /// Takes all tails out.
+    pub fn drain(&self) -> Vec<Link> {
+        std::mem::take(&mut *self.tails.lock().unwrap_or_else(PoisonError::into_inner))
+            .into_values()
+            .flatten()
+            .collect()
+    }
+
+    /// Keeps only tails for which `f` returns `true`.
+    pub fn retain<F>(&self, mut f: F)
+    where
+        F: FnMut(&Link) -> bool,
+    {
+        let mut tails = self.tails.lock().unwrap_or_else(PoisonError::into_inner);
+        tails.retain(|_, links| {
+            links.retain(&mut f);
+            !links.is_empty()
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::in_memory::free_space::FreeSpace;
+    use crate::prelude::Link;
+
+    fn tail(page_id: u32, length: u32) -> Link {
+        Link {
+            page_id: page_id.into(),
+            offset: 48 - length,
+            length,
+        }
+    }
+
+    #[test]
+    fn take_best_fit() {
+        let free_space = FreeSpace::default();
+        free_space.push(tail(0, 24));
+        free_space.push(tail(1, 8));
+        free_space.push(tail(2, 16));
+
+        assert_eq!(free_space.take(10), Some(tail(2, 16)));
+        assert_eq!(free_space.take(8), Some(tail(1, 8)));
+        assert_eq!(free_space.take(32), None);
+        assert_eq!(free_space.snapshot(), vec![tail(0, 24)]);
+        assert_eq!(free_space.take(24), Some(tail(0, 24)));
+        assert_eq!(free_space.take(1), None);
+    }
+
+    #[test]
+    fn retain() {
+        let free_space = FreeSpace::default();
+        free_space.push(tail(0, 8));
+        free_space.push(tail(1, 8));
+        free_space.push(tail(2, 16));
+
+        free_space.retain(|l| l.page_id != 1.into());
+        assert_eq!(free_space.drain(), vec![tail(0, 8), tail(2, 16)]);
+        assert!(free_space.snapshot().is_empty());
+    }
+}
@@ -0,0 +1,11 @@
+use data_bucket::Link;
+
+/// [`Link`] with the generation of the slot it points to.
+///
+/// Deleted slots are reused by next inserts, so plain [`Link`] held from before the delete will silently point to
+/// another row. Generation is bumped every time slot is freed, so outdated [`VersionedLink`] can be detected.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct VersionedLink {
+    pub link: Link,
+    pub generation: u32,
+}
@@ -1,7 +1,17 @@
 mod data;
+mod empty_links;
+mod free_space;
+mod link;
+pub(crate) mod overflow;
 mod pages;
 mod row;
+mod serializer;
+mod spill;
 
 pub use data::{Data, ExecutionError as DataExecutionError, DATA_INNER_LENGTH};
-pub use pages::{DataPages, ExecutionError as PagesExecutionError};
+pub use empty_links::EmptyLinkPolicy;
+pub use link::VersionedLink;
+pub use pages::{DataPages, DataPagesStats, ExecutionError as PagesExecutionError, PageStat};
 pub use row::{ArchivedRow, RowWrapper, StorableRow};
+pub use serializer::with_serialized;
+pub use spill::{SpillConfig, SpillFile};
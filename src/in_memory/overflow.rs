@@ -0,0 +1,148 @@
+use data_bucket::Link;
+
+use crate::in_memory::data::RAW_ALIGN;
+
+/// Flag of the [`Link`]'s length which marks [`Link`] to the overflow row's header. Row's length can't be bigger than
+/// page, so this bit is never set in [`Link`] of the usual row, and [`Link`] with it is never valid for the page.
+pub const OVERFLOW_FLAG: u32 = 1 << 31;
+
+/// Length of the header's part before chunks' [`Link`]s: row's length and count of chunks.
+const HEADER_PREFIX_LENGTH: usize = 8;
+
+/// Length of the chunk's [`Link`] in header.
+const LINK_LENGTH: usize = 12;
+
+/// Returns `true` if [`Link`] points to the overflow row's header.
+pub fn is_overflow(link: Link) -> bool {
+    link.length & OVERFLOW_FLAG != 0
+}
+
+/// Returns [`Link`] to the overflow row's header with [`OVERFLOW_FLAG`] set.
+pub fn overflow_link(header: Link) -> Link {
+    Link {
+        length: header.length | OVERFLOW_FLAG,
+        ..header
+    }
+}
+
+/// Returns [`Link`] of the header's bytes on the page.
+pub fn header_link(link: Link) -> Link {
+    Link {
+        length: link.length & !OVERFLOW_FLAG,
+        ..link
+    }
+}
+
+/// Returns length of the row's chunk saved on single page. Chunks are aligned, so rows saved after them on the same
+/// page are aligned too.
+pub const fn chunk_length(data_length: usize) -> usize {
+    data_length / RAW_ALIGN * RAW_ALIGN
+}
+
+/// Returns max length of the row that can be saved to the pages with `data_length` bytes. Overflow row's header must
+/// fit in single page, so count of chunks is limited by it.
+pub const fn max_row_length(data_length: usize) -> usize {
+    let chunk_length = chunk_length(data_length);
+    if chunk_length < HEADER_PREFIX_LENGTH {
+        return data_length;
+    }
+    let chunks = (chunk_length - HEADER_PREFIX_LENGTH) / LINK_LENGTH;
+    if chunks * chunk_length > data_length {
+        chunks * chunk_length
+    } else {
+        data_length
+    }
+}
+
+/// Returns length of the header of the row with `chunks` count of chunks.
+pub const fn header_length(chunks: usize) -> usize {
+    HEADER_PREFIX_LENGTH + chunks * LINK_LENGTH
+}
+
+/// Serializes header of the overflow row with `length` bytes saved in `chunks`.
+pub fn encode_header(length: usize, chunks: &[Link]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(header_length(chunks.len()));
+    bytes.extend_from_slice(&(length as u32).to_le_bytes());
+    bytes.extend_from_slice(&(chunks.len() as u32).to_le_bytes());
+    for link in chunks {
+        let page_id: usize = link.page_id.into();
+        bytes.extend_from_slice(&(page_id as u32).to_le_bytes());
+        bytes.extend_from_slice(&link.offset.to_le_bytes());
+        bytes.extend_from_slice(&link.length.to_le_bytes());
+    }
+
+    bytes
+}
+
+/// Parses header of the overflow row. Returns row's length and [`Link`]s of its chunks, or `None` if header is
+/// malformed.
+pub fn decode_header(bytes: &[u8]) -> Option<(usize, Vec<Link>)> {
+    let read = |pos: usize| -> Option<u32> {
+        let bytes = bytes.get(pos..pos + 4)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    };
+    let length = read(0)? as usize;
+    let count = read(4)? as usize;
+    let chunks = (0..count)
+        .map(|i| {
+            let pos = HEADER_PREFIX_LENGTH + i * LINK_LENGTH;
+            Some(Link {
+                page_id: read(pos)?.into(),
+                offset: read(pos + 4)?,
+                length: read(pos + 8)?,
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some((length, chunks))
+}
+
+#[cfg(test)]
+mod tests {
+    use data_bucket::Link;
+
+    use super::{
+        decode_header, encode_header, header_link, is_overflow, max_row_length, overflow_link,
+    };
+
+    #[test]
+    fn header() {
+        let chunks = vec![
+            Link {
+                page_id: 1.into(),
+                offset: 0,
+                length: 64,
+            },
+            Link {
+                page_id: 2.into(),
+                offset: 0,
+                length: 16,
+            },
+        ];
+        let bytes = encode_header(70, &chunks);
+
+        assert_eq!(decode_header(&bytes), Some((70, chunks)));
+        assert_eq!(decode_header(&bytes[..20]), None);
+    }
+
+    #[test]
+    fn link() {
+        let header = Link {
+            page_id: 1.into(),
+            offset: 16,
+            length: 32,
+        };
+        let link = overflow_link(header);
+
+        assert!(is_overflow(link));
+        assert!(!is_overflow(header));
+        assert_eq!(header_link(link), header);
+    }
+
+    #[test]
+    fn max_length() {
+        assert_eq!(max_row_length(16), 16);
+        assert_eq!(max_row_length(64), 4 * 64);
+        assert_eq!(max_row_length(70), 4 * 64);
+    }
+}
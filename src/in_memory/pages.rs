@@ -1,11 +1,19 @@
 use std::{
+    collections::BTreeMap,
     fmt::Debug,
-    sync::atomic::{AtomicU32, AtomicU64, Ordering},
-    sync::{Arc, RwLock},
+    hint,
+    sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering},
+    sync::{
+        Arc, Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard,
+        TryLockError,
+    },
+    thread,
+    time::Instant,
 };
 
 use data_bucket::page::PageId;
 use derive_more::{Display, Error, From};
+use lockfree::map::Map;
 use lockfree::stack::Stack;
 #[cfg(feature = "perf_measurements")]
 use performance_measurement_codegen::performance_measurement;
@@ -19,29 +27,123 @@ use rkyv::{
 
 use crate::{
     in_memory::{
-        row::{RowWrapper, StorableRow},
-        Data, DataExecutionError, DATA_INNER_LENGTH,
+        data::{self, AlignedBytes, RAW_ALIGN},
+        empty_links::EmptyLinks,
+        free_space::FreeSpace,
+        overflow,
+        row::{ArchivedRow, RowWrapper, StorableRow},
+        serializer::with_serialized,
+        Data, DataExecutionError, EmptyLinkPolicy, SpillConfig, SpillFile, VersionedLink,
+        DATA_INNER_LENGTH,
     },
+    metrics,
     prelude::Link,
 };
 
+/// Max count of insert attempts after [`DataExecutionError::PageIsFull`] error.
+const MAX_INSERT_RETRIES: usize = 16;
+
+/// Default count of the empty pages which are allocated in advance for [`DataPages::add_next_page`].
+const SPARE_PAGES: usize = 2;
+
 #[derive(Debug)]
 pub struct DataPages<Row, const DATA_LENGTH: usize = DATA_INNER_LENGTH>
 where
     Row: StorableRow,
 {
-    /// Pages vector. Currently, not lock free.
+    /// Pages vector. Currently, not lock free. Write lock is held only to push or replace whole pages, or to compare
+    /// and overwrite single row in [`DataPages::update_if`], so vector is consistent even if this lock is poisoned.
     pages: RwLock<Vec<Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>>>>,
 
-    /// Stack with empty [`Link`]s. It stores [`Link`]s of rows that was deleted.
-    empty_links: Stack<Link>,
+    /// Empty [`Link`]s of rows that was deleted. Order of their reuse is defined by [`EmptyLinkPolicy`].
+    empty_links: EmptyLinks,
+
+    /// Free tails of the pages that were left when row didn't fit in the rest of its page. They are backfilled by the
+    /// rows that don't fit in their active page before new page is added. Tails are not persisted.
+    free_space: FreeSpace,
+
+    /// Tombstoned [`Link`]s which are not reclaimed by [`DataPages::vacuum`] yet.
+    tombstones: Stack<Link>,
+
+    /// Generations of the slots that were freed at least once. Missing slot has zero generation. Generations are kept
+    /// by slot's start, because two live rows can't start at the same offset, so map has at most one entry per aligned
+    /// offset of the pages even if freed slots are merged or split by next inserts.
+    generations: Map<Link, u32>,
+
+    /// Slots which are freed and not reused yet, so same slot can't be freed twice. Overflow row is marked by its
+    /// header's [`Link`].
+    freed: Map<Link, ()>,
+
+    /// Expiration times of the rows that were saved with TTL. Expiration is removed when row's slot is freed.
+    expirations: Map<Link, Instant>,
+
+    /// Shows if any expiration was set, so reads of the pages without TTL rows don't look up expirations.
+    has_expirations: AtomicBool,
 
     /// Count of saved rows.
     row_count: AtomicU64,
 
+    /// Count of times inserts moved to the next page, because their page was full.
+    pages_allocated: AtomicU64,
+
+    /// Count of inserts that reused empty [`Link`] of the deleted row.
+    links_reused: AtomicU64,
+
+    /// Count of inserts that got [`DataExecutionError::PageIsFull`] and were retried.
+    retry_inserts: AtomicU64,
+
+    /// Count of inserts that were saved to the free tail of the earlier page.
+    tails_reused: AtomicU64,
+
+    /// Count of loaded empty [`Link`]s that were dropped, because they could not be reused safely.
+    empty_links_dropped: AtomicU64,
+
     last_page_id: AtomicU32,
 
-    current_page_index: AtomicU32,
+    /// Last page id that was given to one of the active pages. Pages after it are reserved by
+    /// [`DataPages::reserve_pages`] and not filled yet.
+    assigned_page_id: AtomicU32,
+
+    /// Index of the page that is filled by inserts for every active page. Inserts choose active page by key, so
+    /// concurrent inserts with different keys mostly fill different pages.
+    current_pages: Vec<AtomicU32>,
+
+    /// Limit of the [`DataPages::memory_usage`]. Inserts which need new page over this limit fail with
+    /// [`ExecutionError::CapacityExceeded`]. `None` means pages are not limited.
+    max_bytes: Option<usize>,
+
+    /// Options of the cold pages eviction. `None` means all pages are kept in memory.
+    spill: Option<SpillConfig>,
+
+    /// [`SpillFile`] of the evicted pages. It's created on the first eviction. Its lock is held by the whole
+    /// eviction pass, so it's taken before all other locks of [`DataPages`] (see [`DataPages::page_assignment`]).
+    spill_file: Mutex<Option<Arc<SpillFile>>>,
+
+    /// Shows if pages were added since the last eviction pass, so resident pages can be over the limit.
+    eviction_pending: AtomicBool,
+
+    /// Index of the page which is checked first by next eviction pass.
+    eviction_hand: AtomicUsize,
+
+    /// Empty pages allocated in advance, so [`DataPages::add_next_page`] doesn't allocate page while pages write lock
+    /// is held. They are not counted by [`DataPages::memory_usage`].
+    spare_pages: Mutex<Vec<Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>>>>,
+
+    /// Count of the spare pages which are kept allocated.
+    spare_page_count: usize,
+
+    /// Serializes changes of the pages count and of the pages assigned to active pages. Only inserts that filled their
+    /// page take it, so it's not held on the common insert path.
+    ///
+    /// Lock order of [`DataPages`] is: page assignment lock, then `pages` lock, then empty links or free space lock
+    /// (they are never held together). Lock that is later in the order is never waited for while earlier one is
+    /// held, so operations that need several locks (like [`DataPages::truncate_empty_tail_pages`] or
+    /// [`DataPages::clear`]) can't deadlock with each other or with row operations like [`DataPages::select_many`],
+    /// which take single pages lock for all their [`Link`]s.
+    page_assignment: Mutex<()>,
+
+    /// Name of the table which is used as label of the emitted [`metrics`].
+    table_name: &'static str,
 }
 
 impl<Row, const DATA_LENGTH: usize> DataPages<Row, DATA_LENGTH>
@@ -52,13 +154,42 @@ where
     pub fn new() -> Self {
         Self {
             pages: RwLock::new(vec![Arc::new(Data::new(0.into()))]),
-            empty_links: Stack::new(),
+            empty_links: EmptyLinks::default(),
+            free_space: FreeSpace::default(),
+            tombstones: Stack::new(),
+            generations: Map::new(),
+            freed: Map::new(),
+            expirations: Map::new(),
+            has_expirations: AtomicBool::new(false),
             row_count: AtomicU64::new(0),
+            pages_allocated: AtomicU64::new(0),
+            links_reused: AtomicU64::new(0),
+            retry_inserts: AtomicU64::new(0),
+            tails_reused: AtomicU64::new(0),
+            empty_links_dropped: AtomicU64::new(0),
             last_page_id: AtomicU32::new(0),
-            current_page_index: AtomicU32::new(0),
+            assigned_page_id: AtomicU32::new(0),
+            current_pages: vec![AtomicU32::new(0)],
+            max_bytes: None,
+            spill: None,
+            spill_file: Mutex::new(None),
+            eviction_pending: AtomicBool::new(false),
+            eviction_hand: AtomicUsize::new(0),
+            spare_pages: Mutex::new(vec![]),
+            spare_page_count: SPARE_PAGES,
+            page_assignment: Mutex::new(()),
+            table_name: "",
         }
     }
 
+    /// Creates [`DataPages`] with `count` active pages. Every active page has its own page that is filled by
+    /// [`DataPages::insert_with_key`], so concurrent inserts with different keys don't contend on the same page.
+    pub fn with_active_pages(count: usize) -> Self {
+        let mut pages = Self::new();
+        pages.set_active_pages(count);
+        pages
+    }
+
     pub fn from_data(
         vec: Vec<Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>>>,
     ) -> Self {
@@ -66,81 +197,293 @@ where
         let last_page_id = vec.len() - 1;
         Self {
             pages: RwLock::new(vec),
-            empty_links: Stack::new(),
+            empty_links: EmptyLinks::default(),
+            free_space: FreeSpace::default(),
+            tombstones: Stack::new(),
+            generations: Map::new(),
+            freed: Map::new(),
+            expirations: Map::new(),
+            has_expirations: AtomicBool::new(false),
             row_count: AtomicU64::new(0),
+            pages_allocated: AtomicU64::new(0),
+            links_reused: AtomicU64::new(0),
+            retry_inserts: AtomicU64::new(0),
+            tails_reused: AtomicU64::new(0),
+            empty_links_dropped: AtomicU64::new(0),
             last_page_id: AtomicU32::new(last_page_id as u32),
-            current_page_index: AtomicU32::new(last_page_id as u32),
+            assigned_page_id: AtomicU32::new(last_page_id as u32),
+            current_pages: vec![AtomicU32::new(last_page_id as u32)],
+            max_bytes: None,
+            spill: None,
+            spill_file: Mutex::new(None),
+            eviction_pending: AtomicBool::new(false),
+            eviction_hand: AtomicUsize::new(0),
+            spare_pages: Mutex::new(vec![]),
+            spare_page_count: SPARE_PAGES,
+            page_assignment: Mutex::new(()),
+            table_name: "",
         }
     }
 
-    #[cfg_attr(
-        feature = "perf_measurements",
-        performance_measurement(prefix_name = "DataPages")
-    )]
-    pub fn insert(&self, row: Row) -> Result<Link, ExecutionError>
-    where
-        Row: Archive
-            + for<'a> Serialize<
-                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
-            >,
-        <Row as StorableRow>::WrappedRow: Archive
-            + for<'a> Serialize<
-                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
-            >,
-    {
-        let general_row = <Row as StorableRow>::WrappedRow::from_inner(row);
+    /// Returns limit of the [`DataPages::memory_usage`], if it's set.
+    pub fn max_bytes(&self) -> Option<usize> {
+        self.max_bytes
+    }
 
-        if let Some(link) = self.empty_links.pop() {
-            let pages = self.pages.read().unwrap();
-            let current_page: usize = link.page_id.into();
-            let page = &pages[current_page];
+    /// Sets limit of the [`DataPages::memory_usage`]. Pages which are already allocated are kept even if they exceed
+    /// it, so only new pages are refused.
+    pub fn set_max_bytes(&mut self, max_bytes: Option<usize>) {
+        self.max_bytes = max_bytes;
+    }
 
-            return if let Err(e) = unsafe { page.save_row_by_link(&general_row, link) } {
-                match e {
-                    DataExecutionError::InvalidLink => {
-                        self.empty_links.push(link);
-                        self.retry_insert(general_row)
-                    }
-                    DataExecutionError::PageIsFull { .. }
-                    | DataExecutionError::SerializeError
-                    | DataExecutionError::DeserializeError => Err(e.into()),
-                }
-            } else {
-                Ok(link)
-            };
+    /// Returns name of the table which is used as label of the emitted [`metrics`].
+    pub fn table_name(&self) -> &'static str {
+        self.table_name
+    }
+
+    /// Sets name of the table which is used as label of the emitted [`metrics`].
+    pub fn set_table_name(&mut self, name: &'static str) {
+        self.table_name = name;
+    }
+
+    /// Returns count of the empty pages which are allocated in advance for inserts that fill their page.
+    pub fn spare_page_count(&self) -> usize {
+        self.spare_page_count
+    }
+
+    /// Sets count of the empty pages which are allocated in advance. With zero count every new page is allocated
+    /// when it's needed. Spare pages are not kept while [`DataPages::max_bytes`] is set, because they are not counted
+    /// by [`DataPages::memory_usage`].
+    pub fn set_spare_page_count(&mut self, count: usize) {
+        self.spare_page_count = count;
+        self.spare_pages
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .truncate(count);
+    }
+
+    /// Returns count of bytes allocated for the rows by resident [`Data`] pages. Every page allocates `DATA_LENGTH`
+    /// bytes when it's created, so it's the same for empty and full pages. Evicted pages don't take memory, so they
+    /// are not counted, see [`DataPages::resident_pages`].
+    pub fn memory_usage(&self) -> usize {
+        self.resident_pages() * DATA_LENGTH
+    }
+
+    /// Returns options of the cold pages eviction, if it's enabled.
+    pub fn spill(&self) -> Option<&SpillConfig> {
+        self.spill.as_ref()
+    }
+
+    /// Enables eviction of the cold pages with provided options, or disables it with `None`. Evicted pages are kept
+    /// in the file of the previous options until they are loaded. Spill file's path must not be used by other
+    /// [`DataPages`], because it's truncated on the first eviction.
+    pub fn set_spill(&mut self, spill: Option<SpillConfig>) {
+        let spill_file = self
+            .spill_file
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        if spill_file
+            .as_ref()
+            .is_some_and(|file| spill.as_ref().map(|s| s.path.as_path()) != Some(file.path()))
+        {
+            *spill_file = None;
+        }
+        self.eviction_pending
+            .store(spill.is_some(), Ordering::Release);
+        self.spill = spill;
+    }
+
+    /// Returns count of the [`Data`] pages which are kept in memory.
+    pub fn resident_pages(&self) -> usize {
+        let pages = self.pages.read().unwrap_or_else(PoisonError::into_inner);
+        pages.iter().filter(|p| !p.is_evicted()).count()
+    }
+
+    /// Evicts least recently used [`Data`] pages to the spill file until count of the resident pages is not bigger
+    /// than [`SpillConfig::max_resident_pages`]. Returns count of the evicted pages. Pages over the limit, which were
+    /// added or loaded back by reads, are also evicted before next operation reads pages, so it's needed only to
+    /// bound them right away.
+    ///
+    /// Pages are chosen by clock algorithm: page which was accessed since previous pass gets second chance. Active
+    /// pages, which are filled by inserts, are never evicted.
+    pub fn evict_cold_pages(&self) -> Result<usize, ExecutionError> {
+        let mut spill_file = self
+            .spill_file
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        self.eviction_pending.store(false, Ordering::Release);
+        if let Some(file) = spill_file.as_ref() {
+            file.take_loaded();
         }
+        let evicted = self
+            .evict_over_limit(&mut spill_file, true)
+            .inspect_err(|_| self.eviction_pending.store(true, Ordering::Release))?;
 
-        let (link, tried_page) = {
-            let pages = self.pages.read().unwrap();
-            let current_page = self.current_page_index.load(Ordering::Relaxed);
-            let page = &pages[current_page as usize];
+        Ok(evicted.unwrap_or_default())
+    }
 
-            (page.save_row(&general_row), current_page)
+    /// Evicts pages over [`SpillConfig::max_resident_pages`] if pages were added or loaded back by reads since
+    /// previous pass. It's called before pages are read, so pages loaded by one operation are evicted by the next one.
+    /// Caller can already hold [`DataPages`] locks, so they are only tried, and pass is left to the next operation if
+    /// they are taken.
+    fn evict_pending(&self) -> Result<(), ExecutionError> {
+        if self.spill.is_none() {
+            return Ok(());
+        }
+        let mut spill_file = match self.spill_file.try_lock() {
+            Ok(spill_file) => spill_file,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => return Ok(()),
         };
-        let res = match link {
-            Ok(link) => {
-                self.row_count.fetch_add(1, Ordering::Relaxed);
-                link
+        let loaded = spill_file.as_ref().is_some_and(|file| file.take_loaded());
+        if !self.eviction_pending.swap(false, Ordering::AcqRel) && !loaded {
+            return Ok(());
+        }
+        match self.evict_over_limit(&mut spill_file, false) {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => {
+                self.eviction_pending.store(true, Ordering::Release);
+                Ok(())
             }
             Err(e) => {
-                return if let DataExecutionError::PageIsFull { .. } = e {
-                    if tried_page == self.current_page_index.load(Ordering::Relaxed) {
-                        self.add_next_page(tried_page);
-                    }
-                    self.retry_insert(general_row)
-                } else {
-                    Err(e.into())
-                }
+                self.eviction_pending.store(true, Ordering::Release);
+                Err(e)
+            }
+        }
+    }
+
+    /// Evicts pages over [`SpillConfig::max_resident_pages`]. Caller must hold `spill_file` lock, so passes don't
+    /// overlap. Chosen pages are written to the spill file without pages lock, and their memory is freed under pages
+    /// write lock, so no references to their bytes exist. Page which was changed after it was written is kept. If
+    /// `wait` is `false`, pages locks are only tried, and `None` is returned if they are taken.
+    fn evict_over_limit(
+        &self,
+        spill_file: &mut Option<Arc<SpillFile>>,
+        wait: bool,
+    ) -> Result<Option<usize>, ExecutionError> {
+        let Some(spill) = &self.spill else {
+            return Ok(Some(0));
+        };
+        let pages = if wait {
+            self.pages
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone()
+        } else {
+            match self.pages.try_read() {
+                Ok(pages) => pages.clone(),
+                Err(TryLockError::Poisoned(e)) => e.into_inner().clone(),
+                Err(TryLockError::WouldBlock) => return Ok(None),
+            }
+        };
+        let resident = pages.iter().filter(|p| !p.is_evicted()).count();
+        let mut excess = resident.saturating_sub(spill.max_resident_pages);
+        if excess == 0 {
+            return Ok(Some(0));
+        }
+
+        let file = match spill_file.as_ref() {
+            Some(file) => file.clone(),
+            None => {
+                let file = SpillFile::create(&spill.path)
+                    .map_err(|e| DataExecutionError::SpillError(e.kind()))?;
+                spill_file.insert(Arc::new(file)).clone()
             }
         };
+        let active = self
+            .current_pages
+            .iter()
+            .map(|p| p.load(Ordering::Acquire) as usize)
+            .collect::<Vec<_>>();
+        let mut hand = self.eviction_hand.load(Ordering::Relaxed);
+        let mut written = vec![];
+        // Every page's reference bit is reset by first lap, so two laps are enough to find all pages to evict.
+        for _ in 0..pages.len() * 2 {
+            if excess == 0 {
+                break;
+            }
+            let index = hand % pages.len();
+            hand = index + 1;
+            let page = &pages[index];
+            if page.is_evicted() || active.contains(&index) || page.take_accessed() {
+                continue;
+            }
+            // Sequence is taken before copy, so page changed after it is not evicted with stale copy.
+            let seq = page.modified_seq();
+            let bytes = AlignedBytes(page.get_bytes()?);
+            file.write_page(page.id(), &bytes)
+                .map_err(|e| DataExecutionError::SpillError(e.kind()))?;
+            written.push((index, seq));
+            excess -= 1;
+        }
+        self.eviction_hand.store(hand, Ordering::Relaxed);
 
-        Ok(res)
+        let guards = if wait {
+            Some(self.lock_for_resize())
+        } else {
+            self.try_lock_for_resize()
+        };
+        let Some((_assignment, locked)) = guards else {
+            return Ok(None);
+        };
+        let evicted = written
+            .into_iter()
+            .filter(|(index, seq)| {
+                let page = &pages[*index];
+                // Page can be replaced or removed after it was written, and then it's not evicted.
+                if !locked.get(*index).is_some_and(|p| Arc::ptr_eq(p, page)) {
+                    return false;
+                }
+                // SAFETY: pages write lock is held, so nobody holds references to the page's bytes.
+                unsafe { page.evict(&file, *seq) }
+            })
+            .count();
+
+        Ok(Some(evicted))
     }
 
-    fn retry_insert(
+    /// Returns `true` if `added` pages fit in [`DataPages::max_bytes`] together with resident `pages`.
+    fn fits_max_bytes(
         &self,
-        general_row: <Row as StorableRow>::WrappedRow,
-    ) -> Result<Link, ExecutionError>
+        pages: &[Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>>],
+        added: usize,
+    ) -> bool {
+        self.max_bytes
+            .map(|max| {
+                let resident = pages.iter().filter(|p| !p.is_evicted()).count();
+                (resident + added).saturating_mul(DATA_LENGTH) <= max
+            })
+            .unwrap_or(true)
+    }
+
+    /// Returns count of the active pages.
+    pub fn active_pages(&self) -> usize {
+        self.current_pages.len()
+    }
+
+    /// Sets count of the active pages. First active page keeps its current page, and every added one gets next
+    /// reserved or new page. Rows which are already saved are not moved.
+    pub fn set_active_pages(&mut self, count: usize) {
+        assert!(count > 0, "at least one active page is needed");
+
+        let pages = self.pages.get_mut().unwrap_or_else(PoisonError::into_inner);
+        let last_page_id = self.last_page_id.get_mut();
+        let assigned_page_id = self.assigned_page_id.get_mut();
+        self.current_pages.truncate(count);
+        while self.current_pages.len() < count {
+            *assigned_page_id += 1;
+            if *assigned_page_id > *last_page_id {
+                pages.push(Arc::new(Data::new((*assigned_page_id).into())));
+                *last_page_id = *assigned_page_id;
+            }
+            self.current_pages.push(AtomicU32::new(*assigned_page_id));
+        }
+    }
+
+    /// Saves `Row` and returns its [`Link`]. If insert returned [`Link`], [`DataPages::select`] of it succeeds in any
+    /// thread: row is saved only on the page that is already in `pages`, and new pages are pushed under write lock
+    /// before `current_pages` are moved to them, so every reader that takes read lock later sees the page.
+    pub fn insert(&self, row: Row) -> Result<Link, ExecutionError>
     where
         Row: Archive
             + for<'a> Serialize<
@@ -151,262 +494,2901 @@ where
                 Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
             >,
     {
-        let pages = self.pages.read().unwrap();
-        let current_page = self.current_page_index.load(Ordering::Relaxed);
-        let page = &pages[current_page as usize];
-
-        let res = page
-            .save_row(&general_row)
-            .map_err(ExecutionError::DataPageError);
-        if let Ok(link) = res {
-            self.row_count.fetch_add(1, Ordering::Relaxed);
-            Ok(link)
-        } else {
-            res
-        }
-    }
-
-    fn add_next_page(&self, tried_page: u32) {
-        let mut pages = self.pages.write().expect("lock should be not poisoned");
-        if tried_page == self.current_page_index.load(Ordering::Relaxed) {
-            let index = self.last_page_id.fetch_add(1, Ordering::Relaxed) + 1;
-
-            pages.push(Arc::new(Data::new(index.into())));
-            self.current_page_index.fetch_add(1, Ordering::Relaxed);
-        }
+        self.insert_with_key(row, 0)
     }
 
+    /// Same as [`DataPages::insert`], but saves `Row` to the active page chosen by `key`, which is usually hash of the
+    /// row's primary key. With single active page it's same as [`DataPages::insert`].
     #[cfg_attr(
         feature = "perf_measurements",
         performance_measurement(prefix_name = "DataPages")
     )]
-    pub fn select(&self, link: Link) -> Result<Row, ExecutionError>
+    pub fn insert_with_key(&self, row: Row, key: u64) -> Result<Link, ExecutionError>
     where
         Row: Archive
             + for<'a> Serialize<
                 Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
             >,
-        <<Row as StorableRow>::WrappedRow as Archive>::Archived: Portable
-            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
     {
-        let pages = self.pages.read().unwrap();
-        let page = pages
-            .get::<usize>(link.page_id.into())
-            .ok_or(ExecutionError::PageNotFound(link.page_id))?;
-        let gen_row = page.get_row(link).map_err(ExecutionError::DataPageError)?;
-        Ok(gen_row.get_inner())
+        let general_row = <Row as StorableRow>::WrappedRow::from_inner(row);
+        self.insert_using(
+            key,
+            true,
+            |page, link| unsafe { page.save_row_by_link(&general_row, link) },
+            |page| page.save_row(&general_row),
+            || self.insert_overflow(&general_row),
+        )
+        .inspect(|_| metrics::increment(metrics::ROWS_INSERTED, self.table_name, 1))
     }
 
-    #[cfg_attr(
-        feature = "perf_measurements",
-        performance_measurement(prefix_name = "DataPages")
-    )]
-    pub fn with_ref<Op, Res>(&self, link: Link, op: Op) -> Result<Res, ExecutionError>
+    /// Saves pre-serialized row and returns its [`Link`]. `bytes` are stored as is, without `from_inner` and
+    /// serialization, so row received from other table (like replication leader) is not serialized twice. It reuses
+    /// empty links and adds pages like [`DataPages::insert`], and stored row can be checked by
+    /// [`DataPages::with_ref`].
+    ///
+    /// # Safety
+    ///
+    /// `bytes` must be archived `WrappedRow` serialized by `rkyv`, like bytes of the row saved by
+    /// [`DataPages::insert`]. They are not validated, and every read of the row accesses them unchecked.
+    pub unsafe fn insert_raw(&self, bytes: &[u8]) -> Result<Link, ExecutionError> {
+        self.insert_using(
+            0,
+            true,
+            |page, link| unsafe { page.save_row_bytes_by_link(bytes, link) },
+            |page| page.save_row_bytes(bytes),
+            || self.insert_overflow_bytes(bytes),
+        )
+        .inspect(|_| metrics::increment(metrics::ROWS_INSERTED, self.table_name, 1))
+    }
+
+    /// Same as [`DataPages::try_insert_with_key`] for the first active page.
+    pub fn try_insert(&self, row: Row) -> Result<Link, ExecutionError>
     where
         Row: Archive
             + for<'a> Serialize<
                 Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
             >,
-        Op: Fn(&<<Row as StorableRow>::WrappedRow as Archive>::Archived) -> Res,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
     {
-        let pages = self.pages.read().unwrap();
-        let page = pages
-            .get::<usize>(link.page_id.into())
-            .ok_or(ExecutionError::PageNotFound(link.page_id))?;
-        let gen_row = page
-            .get_row_ref(link)
-            .map_err(ExecutionError::DataPageError)?;
-        let res = op(gen_row);
-        Ok(res)
+        self.try_insert_with_key(row, 0)
     }
 
-    #[cfg_attr(
-        feature = "perf_measurements",
-        performance_measurement(prefix_name = "DataPages")
-    )]
-    pub unsafe fn with_mut_ref<Op, Res>(
-        &self,
-        link: Link,
-        mut op: Op,
-    ) -> Result<Res, ExecutionError>
+    /// Same as [`DataPages::insert_with_key`], but never adds pages: row is saved only to the empty [`Link`], to the
+    /// active page, to the free tail or to the page reserved by [`DataPages::reserve_pages`]. If it fits in none of
+    /// them, [`ExecutionError::NoSpace`] is returned and page count is not changed, so caller can decide itself when
+    /// table grows. Rows that need overflow pages are never saved.
+    pub fn try_insert_with_key(&self, row: Row, key: u64) -> Result<Link, ExecutionError>
     where
         Row: Archive
             + for<'a> Serialize<
                 Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
             >,
-        <<Row as StorableRow>::WrappedRow as Archive>::Archived: Portable,
-        Op: FnMut(&mut <<Row as StorableRow>::WrappedRow as Archive>::Archived) -> Res,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
     {
-        let pages = self.pages.read().unwrap();
-        let page = pages
-            .get::<usize>(link.page_id.into())
-            .ok_or(ExecutionError::PageNotFound(link.page_id))?;
-        let gen_row = page
-            .get_mut_row_ref(link)
-            .map_err(ExecutionError::DataPageError)?
-            .unseal_unchecked();
-        let res = op(gen_row);
+        let general_row = <Row as StorableRow>::WrappedRow::from_inner(row);
+        self.insert_using(
+            key,
+            false,
+            |page, link| unsafe { page.save_row_by_link(&general_row, link) },
+            |page| page.save_row(&general_row),
+            || Err(ExecutionError::NoSpace),
+        )
+        .inspect(|_| metrics::increment(metrics::ROWS_INSERTED, self.table_name, 1))
+    }
+
+    /// Saves row to the empty link or to the active page chosen by `key`. Row is saved by `save_by_link` to the
+    /// empty link and by `save` to the active page, and it's saved by `overflow` if it doesn't fit even in an empty
+    /// page. New pages are added only if `grow` is `true`.
+    fn insert_using<ByLink, Save, Overflow>(
+        &self,
+        key: u64,
+        grow: bool,
+        save_by_link: ByLink,
+        save: Save,
+        overflow: Overflow,
+    ) -> Result<Link, ExecutionError>
+    where
+        ByLink: Fn(
+            &Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>,
+            Link,
+        ) -> Result<Link, DataExecutionError>,
+        Save: Fn(
+            &Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>,
+        ) -> Result<Link, DataExecutionError>,
+        Overflow: FnOnce() -> Result<Link, ExecutionError>,
+    {
+        if let Some(link) = self.empty_links.pop() {
+            self.unmark_freed(link);
+            let pages = self.read_pages().inspect_err(|_| self.push_freed(link))?;
+            let current_page: usize = link.page_id.into();
+            match pages.get(current_page) {
+                // Free list can get out of sync with pages, for example if page of the link was removed by
+                // `truncate_empty_tail_pages`. All links to the removed pages are dropped at once, so none of them is
+                // used after page with same id is added again.
+                None => self.retain_empty_links(pages.len()),
+                // Link points out of the page's used part, so it's dropped too.
+                Some(page) if !page.is_link_valid(link) => {}
+                Some(page) => match save_by_link(page, link) {
+                    Ok(_) => {
+                        self.links_reused.fetch_add(1, Ordering::Relaxed);
+                        metrics::increment(metrics::LINKS_REUSED, self.table_name, 1);
+                        return Ok(link);
+                    }
+                    // Row doesn't fit in empty slot, so it's saved to the current page.
+                    Err(DataExecutionError::InvalidLink) => self.push_freed(link),
+                    Err(e) => {
+                        self.push_freed(link);
+                        return Err(e.into());
+                    }
+                },
+            }
+        }
+
+        let shard = (key % self.current_pages.len() as u64) as usize;
+        let (link, tried_page) = {
+            let pages = self.read_pages()?;
+            let current_page = self.current_pages[shard].load(Ordering::Acquire);
+            let page = &pages[current_page as usize];
+
+            (save(page), current_page)
+        };
+        let res = match link {
+            Ok(link) => {
+                self.row_count.fetch_add(1, Ordering::Relaxed);
+                link
+            }
+            Err(e) => {
+                return match e {
+                    DataExecutionError::PageIsFull { .. } => {
+                        self.retry_insert(&save_by_link, &save, e, shard, tried_page, grow)
+                    }
+                    // Row doesn't fit even in empty page, so it's split between overflow pages.
+                    DataExecutionError::RowTooLarge { .. } => overflow(),
+                    _ => Err(e.into()),
+                };
+            }
+        };
+
         Ok(res)
     }
 
-    pub unsafe fn update<const N: usize>(
+    /// Returns max length of the serialized row that can be saved. Rows longer than `DATA_LENGTH` are saved as
+    /// overflow rows, see [`DataPages::insert_overflow`].
+    pub const fn max_row_length() -> usize {
+        overflow::max_row_length(DATA_LENGTH)
+    }
+
+    /// Saves row which doesn't fit in single page. Row's bytes are split into chunks which are saved to the new pages,
+    /// and header with row's length and chunks' [`Link`]s is saved after the last chunk. Returned [`Link`] points to
+    /// the header and has [`overflow::OVERFLOW_FLAG`] set in its length, so it's never valid for the page itself and
+    /// readers assemble the row from chunks before access. Free space of the last page is used by next inserts.
+    fn insert_overflow(
         &self,
-        row: Row,
-        link: Link,
+        general_row: &<Row as StorableRow>::WrappedRow,
     ) -> Result<Link, ExecutionError>
     where
-        Row: Archive,
         <Row as StorableRow>::WrappedRow: Archive
             + for<'a> Serialize<
                 Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
             >,
     {
-        let pages = self.pages.read().unwrap();
-        let page = pages
-            .get::<usize>(link.page_id.into())
-            .ok_or(ExecutionError::PageNotFound(link.page_id))?;
-        let gen_row = <Row as StorableRow>::WrappedRow::from_inner(row);
-        page.save_row_by_link(&gen_row, link)
-            .map_err(ExecutionError::DataPageError)
+        let bytes = with_serialized::<DATA_LENGTH, _, _, _>(general_row, |bytes| bytes.to_vec())
+            .map_err(|_| ExecutionError::DataPageError(DataExecutionError::SerializeError))?;
+        self.insert_overflow_bytes(&bytes)
     }
 
-    pub fn delete(&self, link: Link) -> Result<(), ExecutionError> {
-        self.empty_links.push(link);
-        Ok(())
+    /// Same as [`DataPages::insert_overflow`], but saves already serialized row.
+    fn insert_overflow_bytes(&self, bytes: &[u8]) -> Result<Link, ExecutionError> {
+        if bytes.len() > Self::max_row_length() {
+            return Err(ExecutionError::DataPageError(
+                DataExecutionError::RowTooLarge {
+                    size: bytes.len() as u32,
+                    capacity: Self::max_row_length() as u32,
+                },
+            ));
+        }
+        let chunks = bytes
+            .chunks(overflow::chunk_length(DATA_LENGTH))
+            .collect::<Vec<_>>();
+        let header_length = overflow::header_length(chunks.len()).next_multiple_of(RAW_ALIGN);
+        let last_chunk_length = chunks
+            .last()
+            .expect("row is longer than page, so it has chunks")
+            .len()
+            .next_multiple_of(RAW_ALIGN);
+        let header_page = usize::from(last_chunk_length + header_length > DATA_LENGTH);
+
+        let (_assignment, mut pages) = self.lock_for_resize();
+        if !self.fits_max_bytes(&pages, chunks.len() + header_page) {
+            return Err(ExecutionError::CapacityExceeded);
+        }
+        let mut links = Vec::with_capacity(chunks.len());
+        for chunk in chunks {
+            let page = self.push_page(&mut pages);
+            links.push(page.save_raw(chunk)?);
+        }
+        let header = overflow::encode_header(bytes.len(), &links);
+        let page = if header_page == 1 {
+            self.push_page(&mut pages)
+        } else {
+            pages.last().expect("chunks' pages are just pushed")
+        };
+        let link = page.save_raw(&header)?;
+        // Pages of the chunks are full, so only the last page can be assigned to the active pages later. Reserved
+        // pages before them are skipped too.
+        let last_page_id = self.last_page_id.load(Ordering::Relaxed);
+        self.assigned_page_id
+            .fetch_max(last_page_id - 1, Ordering::Relaxed);
+        self.row_count.fetch_add(1, Ordering::Relaxed);
+        self.eviction_pending.store(true, Ordering::Release);
+
+        Ok(overflow::overflow_link(link))
     }
 
-    pub fn get_bytes(&self) -> Vec<([u8; DATA_LENGTH], u32)> {
-        let pages = self.pages.read().unwrap();
-        pages
-            .iter()
-            .map(|p| {
-                (
-                    p.get_bytes(),
-                    p.free_offset.load(Ordering::Relaxed),
-                )
-            })
-            .collect()
-    }
-    pub fn get_page_count(&self) -> usize {
-        self.pages.read().unwrap().len()
+    /// Pushes new empty page after the last one and returns it. Caller must hold locks of
+    /// [`DataPages::lock_for_resize`].
+    fn push_page<'a>(
+        &self,
+        pages: &'a mut Vec<Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>>>,
+    ) -> &'a Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>> {
+        let index = self.last_page_id.load(Ordering::Relaxed) + 1;
+        pages.push(Arc::new(Data::new(index.into())));
+        self.last_page_id.store(index, Ordering::Release);
+        pages.last().expect("page is just pushed")
     }
 
-    pub fn get_empty_links(&self) -> Vec<Link> {
-        let mut res = vec![];
-        for l in self.empty_links.pop_iter() {
-            res.push(l)
+    /// Copies bytes of the overflow row stored by [`Link`] into single aligned buffer. Returns buffer and [`Link`]s
+    /// of the row's chunks.
+    fn read_overflow(
+        pages: &[Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>>],
+        link: Link,
+    ) -> Result<(AlignedVec, Vec<Link>), ExecutionError> {
+        let header = overflow::header_link(link);
+        let header = Self::get_page(pages, header)?
+            .get_raw(header)
+            .map_err(ExecutionError::DataPageError)?;
+        let (length, chunks) = overflow::decode_header(header).ok_or(
+            ExecutionError::DataPageError(DataExecutionError::DeserializeError),
+        )?;
+        let mut bytes = AlignedVec::with_capacity(length);
+        for chunk in &chunks {
+            let chunk_bytes = Self::get_page(pages, *chunk)?
+                .get_raw(*chunk)
+                .map_err(ExecutionError::DataPageError)?;
+            // Last chunk is padded, so only row's bytes are taken from it.
+            let left = length - bytes.len();
+            bytes.extend_from_slice(&chunk_bytes[..left.min(chunk_bytes.len())]);
+        }
+        if bytes.len() != length {
+            return Err(ExecutionError::DataPageError(
+                DataExecutionError::DeserializeError,
+            ));
         }
 
-        res
+        Ok((bytes, chunks))
     }
 
-    pub fn with_empty_links(mut self, links: Vec<Link>) -> Self {
-        let stack = Stack::new();
-        for l in links {
-            stack.push(l)
+    /// Calls `op` with archived row stored by [`Link`]. Overflow row is assembled from its chunks first.
+    fn with_archived<Op, Res>(
+        pages: &[Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>>],
+        link: Link,
+        op: Op,
+    ) -> Result<Res, ExecutionError>
+    where
+        Op: FnOnce(&<<Row as StorableRow>::WrappedRow as Archive>::Archived) -> Res,
+    {
+        if overflow::is_overflow(link) {
+            let (bytes, _) = Self::read_overflow(pages, link)?;
+            let archived = unsafe {
+                rkyv::access_unchecked::<<<Row as StorableRow>::WrappedRow as Archive>::Archived>(
+                    &bytes[..],
+                )
+            };
+            Ok(op(archived))
+        } else {
+            let archived = Self::get_page(pages, link)?
+                .get_row_ref(link)
+                .map_err(ExecutionError::DataPageError)?;
+            Ok(op(archived))
         }
-        self.empty_links = stack;
-
-        self
     }
-}
-
-#[derive(Debug, Display, Error, From)]
-pub enum ExecutionError {
-    DataPageError(DataExecutionError),
 
-    PageNotFound(#[error(not(source))] PageId),
+    /// Deserializes row from the archived one. Tombstoned row is returned as [`ExecutionError::Deleted`].
+    fn deserialize(
+        archived: &<<Row as StorableRow>::WrappedRow as Archive>::Archived,
+    ) -> Result<Row, ExecutionError>
+    where
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        if archived.is_deleted() {
+            return Err(ExecutionError::Deleted);
+        }
+        let gen_row =
+            rkyv::deserialize::<<Row as StorableRow>::WrappedRow, rkyv::rancor::Error>(archived)
+                .map_err(|_| ExecutionError::DataPageError(DataExecutionError::DeserializeError))?;
 
-    Locked,
-}
+        Ok(gen_row.get_inner())
+    }
 
-#[cfg(test)]
+    /// Retries insert after [`DataExecutionError::PageIsFull`] error on `tried_page`. Free tail of the full page is
+    /// saved, and row is saved by `save_by_link` to the shortest free tail of the earlier pages that fits it. If there
+    /// is no such tail, new page is added if `tried_page` is still current one of the `shard` active page, and then
+    /// insert is tried again. Under contention new page can be filled by other threads before row is saved, so this is
+    /// repeated up to [`MAX_INSERT_RETRIES`] times with backoff. Row that can't fit even in an empty page is saved by
+    /// [`DataPages::insert_overflow`] before retries.
+    #[cfg_attr(
+        feature = "perf_measurements",
+        performance_measurement(prefix_name = "DataPages")
+    )]
+    fn retry_insert<ByLink, Save>(
+        &self,
+        save_by_link: &ByLink,
+        save: &Save,
+        mut error: DataExecutionError,
+        shard: usize,
+        mut tried_page: u32,
+        grow: bool,
+    ) -> Result<Link, ExecutionError>
+    where
+        ByLink: Fn(
+            &Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>,
+            Link,
+        ) -> Result<Link, DataExecutionError>,
+        Save: Fn(
+            &Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>,
+        ) -> Result<Link, DataExecutionError>,
+    {
+        self.retry_inserts.fetch_add(1, Ordering::Relaxed);
+        metrics::increment(metrics::INSERT_RETRIES, self.table_name, 1);
+        for attempt in 0..MAX_INSERT_RETRIES {
+            let DataExecutionError::PageIsFull { need, left } = error else {
+                return Err(error.into());
+            };
+            self.push_free_tail(tried_page, left);
+            if let Some(link) = self.insert_free_tail(save_by_link, need)? {
+                return Ok(link);
+            }
+            if tried_page == self.current_pages[shard].load(Ordering::Acquire) {
+                self.add_next_page(shard, tried_page, grow)?;
+            } else if attempt % 4 == 3 {
+                thread::yield_now();
+            } else {
+                hint::spin_loop();
+            }
+
+            let res = {
+                let pages = self.read_pages()?;
+                tried_page = self.current_pages[shard].load(Ordering::Acquire);
+                let page = &pages[tried_page as usize];
+
+                save(page)
+            };
+            match res {
+                Ok(link) => {
+                    self.row_count.fetch_add(1, Ordering::Relaxed);
+                    return Ok(link);
+                }
+                Err(e) => error = e,
+            }
+        }
+
+        Err(error.into())
+    }
+
+    /// Saves free tail of the `page_id` page which was reported by [`DataExecutionError::PageIsFull`] error. Space of
+    /// the failed insert is taken anyway, so only the first insert that didn't fit in the page gets non-negative
+    /// `left`, and every tail is saved once.
+    fn push_free_tail(&self, page_id: u32, left: i64) {
+        if left > 0 {
+            self.free_space.push(Link {
+                page_id: page_id.into(),
+                offset: DATA_LENGTH as u32 - left as u32,
+                length: left as u32,
+            });
+        }
+    }
+
+    /// Saves row of `length` bytes by `save_by_link` to the shortest free tail that fits it. Rest of the tail is kept
+    /// for next inserts. Returns `None` if there is no such tail.
+    fn insert_free_tail<ByLink>(
+        &self,
+        save_by_link: &ByLink,
+        length: u32,
+    ) -> Result<Option<Link>, ExecutionError>
+    where
+        ByLink: Fn(
+            &Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>,
+            Link,
+        ) -> Result<Link, DataExecutionError>,
+    {
+        let Some(tail) = self.free_space.take(length) else {
+            return Ok(None);
+        };
+        let link = Link {
+            page_id: tail.page_id,
+            offset: tail.offset,
+            length,
+        };
+        let res = {
+            let pages = self
+                .read_pages()
+                .inspect_err(|_| self.free_space.push(tail))?;
+            // Tails to the pages removed by `truncate_empty_tail_pages` are dropped with them, but tail can be taken
+            // before that, and then it's just dropped here.
+            let Some(page) = pages.get::<usize>(tail.page_id.into()) else {
+                return Ok(None);
+            };
+            save_by_link(page, link)
+        };
+        match res {
+            Ok(link) => {
+                if tail.length > length {
+                    self.free_space.push(Link {
+                        page_id: tail.page_id,
+                        offset: tail.offset + length,
+                        length: tail.length - length,
+                    });
+                }
+                self.row_count.fetch_add(1, Ordering::Relaxed);
+                self.tails_reused.fetch_add(1, Ordering::Relaxed);
+                metrics::increment(metrics::TAILS_REUSED, self.table_name, 1);
+                Ok(Some(link))
+            }
+            // Tail points out of the page's used part, so its page was removed and added again.
+            Err(DataExecutionError::InvalidLink) => Ok(None),
+            Err(e) => {
+                self.free_space.push(tail);
+                Err(e.into())
+            }
+        }
+    }
+
+    /// Returns [`Data`] page of the [`Link`]. [`Link`]'s offset and length are checked against page's used length,
+    /// so inconsistent [`Link`] is never used to read the page.
+    fn get_page(
+        pages: &[Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>>],
+        link: Link,
+    ) -> Result<&Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>>, ExecutionError> {
+        let page = pages
+            .get::<usize>(link.page_id.into())
+            .ok_or(ExecutionError::PageNotFound(link.page_id))?;
+        if !page.is_link_valid(link) {
+            return Err(ExecutionError::MalformedLink(link));
+        }
+
+        Ok(page)
+    }
+
+    /// Returns [`ExecutionError::Deleted`] if row stored by [`Link`] is marked as tombstone.
+    fn check_deleted(
+        page: &Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>,
+        link: Link,
+    ) -> Result<(), ExecutionError>
+    where
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow + Portable,
+    {
+        let gen_row = page
+            .get_row_ref(link)
+            .map_err(ExecutionError::DataPageError)?;
+        if gen_row.is_deleted() {
+            return Err(ExecutionError::Deleted);
+        }
+
+        Ok(())
+    }
+
+    /// Takes read lock of the pages. Poisoned lock is recovered, because pages are changed under their own locks and
+    /// atomics, so panic in one thread doesn't disable the table. Pending eviction is done first, see
+    /// [`DataPages::evict_pending`].
+    fn read_pages(
+        &self,
+    ) -> Result<
+        RwLockReadGuard<'_, Vec<Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>>>>,
+        ExecutionError,
+    > {
+        self.evict_pending()?;
+        Ok(self.pages.read().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// Same as [`DataPages::read_pages`], but takes write lock. Page assignment lock must be held by caller, so write
+    /// lock is never taken before it.
+    fn write_pages(
+        &self,
+        _assignment: &MutexGuard<'_, ()>,
+    ) -> RwLockWriteGuard<'_, Vec<Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>>>> {
+        self.pages.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Takes page assignment lock. It's the first lock in [`DataPages`]'s lock order, see
+    /// [`DataPages::page_assignment`].
+    fn lock_page_assignment(&self) -> MutexGuard<'_, ()> {
+        self.page_assignment
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Takes page assignment lock and then pages write lock. Every method which changes pages vector must take its
+    /// locks through this helper (or [`DataPages::write_pages`]), so all of them take locks in the same order and
+    /// can't deadlock with each other. Poisoned locks are taken anyway, because pages vector is consistent even if
+    /// they are poisoned.
+    #[allow(clippy::type_complexity)]
+    fn lock_for_resize(
+        &self,
+    ) -> (
+        MutexGuard<'_, ()>,
+        RwLockWriteGuard<'_, Vec<Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>>>>,
+    ) {
+        let assignment = self.lock_page_assignment();
+        let pages = self.pages.write().unwrap_or_else(PoisonError::into_inner);
+        (assignment, pages)
+    }
+
+    /// Same as [`DataPages::lock_for_resize`], but returns `None` instead of waiting if any of the locks is taken.
+    #[allow(clippy::type_complexity)]
+    fn try_lock_for_resize(
+        &self,
+    ) -> Option<(
+        MutexGuard<'_, ()>,
+        RwLockWriteGuard<'_, Vec<Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>>>>,
+    )> {
+        let assignment = match self.page_assignment.try_lock() {
+            Ok(assignment) => assignment,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => return None,
+        };
+        let pages = match self.pages.try_write() {
+            Ok(pages) => pages,
+            Err(TryLockError::Poisoned(e)) => e.into_inner(),
+            Err(TryLockError::WouldBlock) => return None,
+        };
+        Some((assignment, pages))
+    }
+
+    /// Moves `shard` active page from full `tried_page` to the next page that is not assigned to any active page yet.
+    /// If there is no such page, new page is added only if `grow` is `true`, and [`ExecutionError::NoSpace`] is
+    /// returned otherwise.
+    fn add_next_page(
+        &self,
+        shard: usize,
+        tried_page: u32,
+        grow: bool,
+    ) -> Result<(), ExecutionError> {
+        let assignment = self.lock_page_assignment();
+        if tried_page != self.current_pages[shard].load(Ordering::Acquire) {
+            return Ok(());
+        }
+
+        // Next page can be already reserved, so write lock is taken only if new page is needed. New page is taken
+        // before write lock, so readers are not blocked while it's allocated.
+        let next = self.assigned_page_id.load(Ordering::Relaxed) + 1;
+        let mut pages = None;
+        if next > self.last_page_id.load(Ordering::Acquire) {
+            if !grow {
+                return Err(ExecutionError::NoSpace);
+            }
+            let page = self.take_spare_page(next);
+            let mut write_pages = self.write_pages(&assignment);
+            if !self.fits_max_bytes(&write_pages, 1) {
+                drop(write_pages);
+                self.return_spare_page(page);
+                return Err(ExecutionError::CapacityExceeded);
+            }
+            write_pages.push(page);
+            self.last_page_id.store(next, Ordering::Release);
+            metrics::set(metrics::PAGES, self.table_name, write_pages.len());
+            pages = Some(write_pages);
+        }
+        self.assigned_page_id.store(next, Ordering::Relaxed);
+        self.current_pages[shard].store(next, Ordering::Release);
+        self.pages_allocated.fetch_add(1, Ordering::Relaxed);
+        metrics::increment(metrics::PAGES_ALLOCATED, self.table_name, 1);
+        let page_added = pages.is_some();
+        drop(pages);
+        drop(assignment);
+        if page_added {
+            // Added page is evicted by the next read of pages, without page assignment lock.
+            self.eviction_pending.store(true, Ordering::Release);
+            self.refill_spare_pages();
+        }
+
+        Ok(())
+    }
+
+    /// Returns empty page with `id` for [`DataPages::add_next_page`]. Spare page is used if there is one, and new
+    /// page is allocated otherwise.
+    fn take_spare_page(&self, id: u32) -> Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>> {
+        let page = self
+            .spare_pages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pop();
+        let Some(mut page) = page else {
+            return Arc::new(Data::new(id.into()));
+        };
+        Arc::get_mut(&mut page)
+            .expect("spare pages are not shared")
+            .set_page_id(id.into());
+
+        page
+    }
+
+    /// Puts page taken by [`DataPages::take_spare_page`] back to the spare pages if it's not added to the pages.
+    /// Page is dropped if spare pages are already full.
+    fn return_spare_page(&self, page: Arc<Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>>) {
+        let mut spare_pages = self
+            .spare_pages
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if spare_pages.len() < self.spare_page_count {
+            spare_pages.push(page);
+        }
+    }
+
+    /// Allocates spare pages up to [`DataPages::spare_page_count`]. Pages are allocated without any lock held, so
+    /// concurrent refills can allocate few extra pages, which are just dropped.
+    fn refill_spare_pages(&self) {
+        if self.max_bytes.is_some() {
+            return;
+        }
+        loop {
+            let len = self
+                .spare_pages
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .len();
+            if len >= self.spare_page_count {
+                return;
+            }
+            let page = Arc::new(Data::new(0.into()));
+            let mut spare_pages = self
+                .spare_pages
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            if spare_pages.len() >= self.spare_page_count {
+                return;
+            }
+            spare_pages.push(page);
+        }
+    }
+
+    /// Appends `n` empty [`Data`] pages under single write lock. Inserts fill reserved pages one by one without taking
+    /// write lock until they are exhausted. Pages that don't fit in [`DataPages::max_bytes`] are not reserved.
+    pub fn reserve_pages(&self, n: u32) {
+        let (_assignment, mut pages) = self.lock_for_resize();
+        let resident = pages.iter().filter(|p| !p.is_evicted()).count();
+        let n = match self.max_bytes {
+            Some(max) => (n as usize).min((max / DATA_LENGTH).saturating_sub(resident)),
+            None => n as usize,
+        };
+        pages.reserve(n);
+        for _ in 0..n {
+            let index = self.last_page_id.load(Ordering::Relaxed) + 1;
+            pages.push(Arc::new(Data::new(index.into())));
+            self.last_page_id.store(index, Ordering::Release);
+        }
+        metrics::set(metrics::PAGES, self.table_name, pages.len());
+        self.eviction_pending.store(true, Ordering::Release);
+    }
+
+    #[cfg_attr(
+        feature = "perf_measurements",
+        performance_measurement(prefix_name = "DataPages")
+    )]
+    pub fn select(&self, link: Link) -> Result<Row, ExecutionError>
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Portable
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        let pages = self.read_pages()?;
+        metrics::increment(metrics::SELECTS, self.table_name, 1);
+        Self::with_archived(&pages, link, Self::deserialize)?
+    }
+
+    /// Selects `Row`s of all [`Link`]s under single pages read lock. Result of every [`Link`] is returned in its slot.
+    pub fn select_many(
+        &self,
+        links: &[Link],
+    ) -> Result<Vec<Result<Row, ExecutionError>>, ExecutionError>
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Portable
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        let pages = self.read_pages()?;
+        metrics::increment(metrics::SELECTS, self.table_name, links.len() as u64);
+        Ok(links
+            .iter()
+            .map(|link| Self::with_archived(&pages, *link, Self::deserialize)?)
+            .collect())
+    }
+
+    #[cfg_attr(
+        feature = "perf_measurements",
+        performance_measurement(prefix_name = "DataPages")
+    )]
+    pub fn with_ref<Op, Res>(&self, link: Link, op: Op) -> Result<Res, ExecutionError>
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow,
+        Op: Fn(&<<Row as StorableRow>::WrappedRow as Archive>::Archived) -> Res,
+    {
+        let pages = self.read_pages()?;
+        Self::with_archived(&pages, link, |gen_row| {
+            if gen_row.is_deleted() {
+                return Err(ExecutionError::Deleted);
+            }
+            Ok(op(gen_row))
+        })?
+    }
+
+    /// Folds archived `Row`s of all [`Link`]s into single value without deserialization, taking pages lock once. Fails
+    /// with [`ExecutionError::LinkFailed`] on the first [`Link`] that can't be read.
+    pub fn fold_refs<Acc, Op>(
+        &self,
+        links: &[Link],
+        init: Acc,
+        op: Op,
+    ) -> Result<Acc, ExecutionError>
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow,
+        Op: Fn(Acc, &<<Row as StorableRow>::WrappedRow as Archive>::Archived) -> Acc,
+    {
+        let pages = self.read_pages()?;
+        let mut acc = init;
+        for (position, link) in links.iter().enumerate() {
+            acc = Self::with_archived(&pages, *link, |gen_row| {
+                if gen_row.is_deleted() {
+                    Err(ExecutionError::Deleted)
+                } else {
+                    Ok(op(acc, gen_row))
+                }
+            })
+            .and_then(|res| res)
+            .map_err(|e| ExecutionError::LinkFailed {
+                position,
+                link: *link,
+                source: Box::new(e),
+            })?;
+        }
+
+        Ok(acc)
+    }
+
+    /// Applies `op` to the mutable archived `Row` stored by [`Link`]. Generated tables have safe
+    /// `with_row_scalars_mut` for the common case of changing not indexed fixed-size columns.
+    ///
+    /// # Safety
+    ///
+    /// `op` must keep the archived row's layout unchanged, otherwise next read of this row is UB. Sound mutations are:
+    ///
+    /// * overwriting archived primitive (integer, float, `bool`, `char`) with any value of the same type;
+    /// * overwriting `ArchivedOption` of such primitive with any value of the same type, including `None` and `Some`;
+    /// * changing row wrapper's flags through its methods (like `set_deleted`).
+    ///
+    /// Everything else is unsound: fields of the variable-length values (like `ArchivedString` or `ArchivedVec`) hold
+    /// relative pointers and lengths, so they must not be overwritten, swapped with values of other rows or changed
+    /// between `None` and `Some`, even if new value looks like it has the same length. Values of the custom types are
+    /// treated as variable-length, because their layout is unknown. Caller must also guarantee that no other thread
+    /// accesses same row while `op` is running (row locks are used for this in generated code).
+    #[cfg_attr(
+        feature = "perf_measurements",
+        performance_measurement(prefix_name = "DataPages")
+    )]
+    pub unsafe fn with_mut_ref<Op, Res>(&self, link: Link, op: Op) -> Result<Res, ExecutionError>
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: Portable,
+        Op: FnOnce(&mut <<Row as StorableRow>::WrappedRow as Archive>::Archived) -> Res,
+    {
+        let pages = self.read_pages()?;
+        if overflow::is_overflow(link) {
+            // Overflow row is changed in assembled copy, which is written back to its chunks. Layout is not changed
+            // by `op`, so every chunk gets the same count of bytes back.
+            let (mut bytes, chunks) = Self::read_overflow(&pages, link)?;
+            let gen_row = rkyv::access_unchecked_mut::<
+                <<Row as StorableRow>::WrappedRow as Archive>::Archived,
+            >(&mut bytes[..])
+            .unseal_unchecked();
+            let res = op(gen_row);
+            let chunk_length = overflow::chunk_length(DATA_LENGTH);
+            for (chunk, chunk_bytes) in chunks.into_iter().zip(bytes.chunks(chunk_length)) {
+                Self::get_page(&pages, chunk)?
+                    .save_raw_by_link(chunk_bytes, chunk)
+                    .map_err(ExecutionError::DataPageError)?;
+            }
+            return Ok(res);
+        }
+        let page = Self::get_page(&pages, link)?;
+        let gen_row = page
+            .get_mut_row_ref(link)
+            .map_err(ExecutionError::DataPageError)?
+            .unseal_unchecked();
+        let res = op(gen_row);
+        page.mark_dirty();
+        Ok(res)
+    }
+
+    /// Overwrites row stored by `link` with provided one. Page capacity is defined once by `DATA_LENGTH` of the
+    /// [`DataPages`], so no per-call buffer size is needed.
+    ///
+    /// # Safety
+    ///
+    /// New row's serialized length must be equal to `link.length`, and no references to old row must be alive.
+    pub unsafe fn update(&self, row: Row, link: Link) -> Result<Link, ExecutionError>
+    where
+        Row: Archive,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+    {
+        let pages = self.read_pages()?;
+        let page = Self::get_page(&pages, link)?;
+        let gen_row = <Row as StorableRow>::WrappedRow::from_inner(row);
+        page.save_row_by_link(&gen_row, link)
+            .map_err(ExecutionError::DataPageError)
+    }
+
+    /// Overwrites row stored by `link` with `new` only if stored row is equal to `expected`. Returns `true` if row was
+    /// overwritten and `false` if stored row was changed by someone else. Row is compared and overwritten under pages
+    /// write lock, so no other operation on pages sees or changes it in between. New row's serialized length must be
+    /// equal to `link.length`, otherwise [`DataExecutionError::InvalidLink`] error is returned.
+    pub fn update_if(&self, link: Link, expected: &Row, new: Row) -> Result<bool, ExecutionError>
+    where
+        Row: Archive + PartialEq,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Portable
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        let assignment = self.lock_page_assignment();
+        let pages = self.write_pages(&assignment);
+        let page = Self::get_page(&pages, link)?;
+        Self::check_deleted(page, link)?;
+        let current = page
+            .get_row(link)
+            .map_err(ExecutionError::DataPageError)?
+            .get_inner();
+        if &current != expected {
+            return Ok(false);
+        }
+
+        let gen_row = <Row as StorableRow>::WrappedRow::from_inner(new);
+        // Pages write lock is held, so nobody else has a reference to this row.
+        unsafe { page.save_row_by_link(&gen_row, link) }.map_err(ExecutionError::DataPageError)?;
+        Ok(true)
+    }
+
+    /// Returns length which `row` will take on the page, so it can be compared with [`Link`]'s length before
+    /// [`DataPages::update`].
+    pub fn stored_length(&self, row: Row) -> Result<u32, ExecutionError>
+    where
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+    {
+        let gen_row = <Row as StorableRow>::WrappedRow::from_inner(row);
+        with_serialized::<DATA_LENGTH, _, _, _>(&gen_row, |bytes| bytes.len() as u32)
+            .map_err(|_| ExecutionError::DataPageError(DataExecutionError::SerializeError))
+    }
+
+    /// Selects `Row` by [`VersionedLink`]. Returns [`ExecutionError::StaleLink`] if slot was freed after
+    /// [`VersionedLink`] was created, even if it's already reused by another row.
+    pub fn select_versioned(&self, link: VersionedLink) -> Result<Row, ExecutionError>
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Portable
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        self.check_generation(link)?;
+        let row = self.select(link.link)?;
+        // Slot could be freed and reused while row was read.
+        self.check_generation(link)?;
+
+        Ok(row)
+    }
+
+    /// Same as [`DataPages::with_ref`], but fails with [`ExecutionError::StaleLink`] for outdated [`VersionedLink`].
+    pub fn with_ref_versioned<Op, Res>(
+        &self,
+        link: VersionedLink,
+        op: Op,
+    ) -> Result<Res, ExecutionError>
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow,
+        Op: Fn(&<<Row as StorableRow>::WrappedRow as Archive>::Archived) -> Res,
+    {
+        self.check_generation(link)?;
+        let res = self.with_ref(link.link, op)?;
+        self.check_generation(link)?;
+
+        Ok(res)
+    }
+
+    /// Returns [`VersionedLink`] with current generation of the slot.
+    pub fn versioned(&self, link: Link) -> VersionedLink {
+        VersionedLink {
+            link,
+            generation: self.generation(link),
+        }
+    }
+
+    fn generation(&self, link: Link) -> u32 {
+        self.generations
+            .get(&Self::slot_start(link))
+            .map(|g| *g.val())
+            .unwrap_or_default()
+    }
+
+    fn bump_generation(&self, link: Link) {
+        self.generations.insert(
+            Self::slot_start(link),
+            self.generation(link).wrapping_add(1),
+        );
+    }
+
+    /// Returns key of the slot in generations: [`Link`] with zero length.
+    fn slot_start(link: Link) -> Link {
+        Link { length: 0, ..link }
+    }
+
+    fn check_generation(&self, link: VersionedLink) -> Result<(), ExecutionError> {
+        if self.generation(link.link) != link.generation {
+            return Err(ExecutionError::StaleLink);
+        }
+
+        Ok(())
+    }
+
+    /// Deletes row stored by [`Link`] and pushes its slot to the empty [`Link`]s. Returns
+    /// [`ExecutionError::AlreadyDeleted`] if slot is already freed and not reused yet, because freeing it again would
+    /// give it to two rows.
+    pub fn delete(&self, link: Link) -> Result<(), ExecutionError> {
+        self.mark_freed(link)?;
+        self.bump_generation(link);
+        self.free_link(link)
+            .inspect_err(|_| self.unmark_freed(link))?;
+        metrics::increment(metrics::ROWS_DELETED, self.table_name, 1);
+
+        Ok(())
+    }
+
+    /// Deletes rows stored by all [`Link`]s like [`DataPages::delete`], but pages lock is taken once for all overflow
+    /// rows and all slots are pushed to the empty [`Link`]s at once. If any slot is already freed, nothing is deleted.
+    pub fn delete_many(&self, links: &[Link]) -> Result<(), ExecutionError> {
+        for (pos, link) in links.iter().enumerate() {
+            if let Err(e) = self.mark_freed(*link) {
+                links[..pos].iter().for_each(|l| self.unmark_freed(*l));
+                return Err(e);
+            }
+        }
+        let mut freed = Vec::with_capacity(links.len());
+        {
+            let pages = self
+                .read_pages()
+                .inspect_err(|_| links.iter().for_each(|l| self.unmark_freed(*l)))?;
+            for link in links {
+                if overflow::is_overflow(*link) {
+                    let (_, chunks) = Self::read_overflow(&pages, *link)
+                        .inspect_err(|_| links.iter().for_each(|l| self.unmark_freed(*l)))?;
+                    freed.extend(chunks);
+                    freed.push(overflow::header_link(*link));
+                } else {
+                    freed.push(*link);
+                }
+            }
+        }
+        for link in links {
+            self.bump_generation(*link);
+            self.expirations.remove(link);
+        }
+        self.empty_links.extend(freed);
+        metrics::increment(metrics::ROWS_DELETED, self.table_name, links.len() as u64);
+
+        Ok(())
+    }
+
+    /// Sets time after which row stored by [`Link`] is expired. Expiration is kept until row's slot is freed, so row
+    /// must be deleted to be removed, see [`DataPages::expired_links`].
+    pub fn set_expiration(&self, link: Link, expires_at: Instant) {
+        self.has_expirations.store(true, Ordering::Release);
+        self.expirations.insert(link, expires_at);
+    }
+
+    /// Returns expiration time of the row stored by [`Link`], or `None` if row was saved without TTL.
+    pub fn expires_at(&self, link: Link) -> Option<Instant> {
+        if !self.has_expirations.load(Ordering::Acquire) {
+            return None;
+        }
+        self.expirations.get(&link).map(|e| *e.val())
+    }
+
+    /// Returns `true` if row stored by [`Link`] is expired at `now`.
+    pub fn is_expired(&self, link: Link, now: Instant) -> bool {
+        self.expires_at(link)
+            .is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// Returns [`Link`]s of all rows which are expired at `now`.
+    pub fn expired_links(&self, now: Instant) -> Vec<Link> {
+        if !self.has_expirations.load(Ordering::Acquire) {
+            return vec![];
+        }
+        self.expirations
+            .iter()
+            .filter(|e| *e.val() <= now)
+            .map(|e| *e.key())
+            .collect()
+    }
+
+    /// Marks slot of the row stored by [`Link`] as freed. Returns [`ExecutionError::AlreadyDeleted`] if it's already
+    /// marked.
+    fn mark_freed(&self, link: Link) -> Result<(), ExecutionError> {
+        if self.freed.insert(overflow::header_link(link), ()).is_some() {
+            return Err(ExecutionError::AlreadyDeleted);
+        }
+
+        Ok(())
+    }
+
+    /// Removes freed mark of the slot, when slot is reused or dropped from the empty [`Link`]s.
+    fn unmark_freed(&self, link: Link) {
+        self.freed.remove(&overflow::header_link(link));
+    }
+
+    /// Pushes [`Link`] which was taken from the empty [`Link`]s, but wasn't reused, back.
+    fn push_freed(&self, link: Link) {
+        self.freed.insert(link, ());
+        self.empty_links.push(link);
+    }
+
+    /// Drops empty [`Link`]s to the pages which don't exist anymore.
+    fn retain_empty_links(&self, page_count: usize) {
+        self.empty_links.retain(|l| {
+            let page_id: usize = l.page_id.into();
+            if page_id >= page_count {
+                self.unmark_freed(*l);
+            }
+            page_id < page_count
+        });
+    }
+
+    /// Pushes [`Link`] to the empty [`Link`]s. Overflow row's header and chunks are pushed instead of its [`Link`],
+    /// because they are reused separately. Row's expiration is removed, so it's not applied to the next row in slot.
+    fn free_link(&self, link: Link) -> Result<(), ExecutionError> {
+        self.expirations.remove(&link);
+        if overflow::is_overflow(link) {
+            let pages = self.read_pages()?;
+            let (_, chunks) = Self::read_overflow(&pages, link)?;
+            for chunk in chunks {
+                self.empty_links.push(chunk);
+            }
+            self.empty_links.push(overflow::header_link(link));
+        } else {
+            self.empty_links.push(link);
+        }
+
+        Ok(())
+    }
+
+    /// Marks row stored by [`Link`] as tombstone, so [`DataPages::select`] and [`DataPages::with_ref`] return
+    /// [`ExecutionError::Deleted`] for it. Slot is not reused until [`DataPages::vacuum`] is called. List of not
+    /// vacuumed tombstones is not persisted.
+    pub fn tombstone(&self, link: Link) -> Result<(), ExecutionError>
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow + Portable,
+    {
+        // SAFETY: only fixed-size deletion flag is changed.
+        unsafe { self.with_mut_ref(link, |archived| archived.set_deleted(true))? };
+        self.bump_generation(link);
+        self.tombstones.push(link);
+        Ok(())
+    }
+
+    /// Moves all tombstoned slots to the empty [`Link`]s, so they can be reused by inserts. Returns count of
+    /// reclaimed slots.
+    pub fn vacuum(&self) -> usize {
+        let mut count = 0;
+        for link in self.tombstones.pop_iter() {
+            // Tombstoned row can be deleted before vacuum, and then its slot is already freed.
+            if self.mark_freed(link).is_err() {
+                continue;
+            }
+            match self.free_link(link) {
+                Ok(()) => count += 1,
+                Err(_) => self.unmark_freed(link),
+            }
+        }
+
+        count
+    }
+
+    /// Returns bytes and used length of all [`Data`] pages. Evicted pages are read from the spill file, but they are
+    /// not loaded back.
+    pub fn get_bytes(&self) -> Result<Vec<([u8; DATA_LENGTH], u32)>, ExecutionError> {
+        let pages = self.pages.read().unwrap_or_else(PoisonError::into_inner);
+        pages
+            .iter()
+            .map(|p| Ok((p.get_bytes()?, p.free_offset.load(Ordering::Relaxed))))
+            .collect()
+    }
+
+    /// Returns bytes and used length of the [`Data`] page with provided index, or `None` if there is no such page. Only
+    /// this page is copied, so pages can be persisted one by one without holding copy of all of them. Evicted page is
+    /// read from the spill file, but it's not loaded back.
+    pub fn get_page_bytes(
+        &self,
+        index: usize,
+    ) -> Result<Option<([u8; DATA_LENGTH], u32)>, ExecutionError> {
+        let pages = self.pages.read().unwrap_or_else(PoisonError::into_inner);
+        let Some(page) = pages.get(index) else {
+            return Ok(None);
+        };
+        Ok(Some((
+            page.get_bytes()?,
+            page.free_offset.load(Ordering::Relaxed),
+        )))
+    }
+
+    /// Returns current modification sequence. Sequence is shared by all [`DataPages`] of the process and is increased
+    /// on every change of any [`Data`] page, so it can be used as logical point for
+    /// [`DataPages::pages_modified_since`]. It must be taken before pages are read, so changes made while they are read
+    /// are returned by next call.
+    pub fn modification_seq(&self) -> u64 {
+        data::modification_seq()
+    }
+
+    /// Returns ids of the [`Data`] pages which were changed after modification sequence `seq` was returned by
+    /// [`DataPages::modification_seq`].
+    pub fn pages_modified_since(&self, seq: u64) -> Vec<u32> {
+        let pages = self.pages.read().unwrap_or_else(PoisonError::into_inner);
+        pages
+            .iter()
+            .filter(|p| p.modified_seq() > seq)
+            .map(|p| {
+                let id: usize = p.id().into();
+                id as u32
+            })
+            .collect()
+    }
+
+    /// Marks [`Data`] pages which were not changed after modification sequence `seq` as persisted. `seq` must be
+    /// taken by [`DataPages::modification_seq`] before pages are copied for full persist, and pages must be marked
+    /// only after the copy is written, so changes made while pages are copied, or pages which failed to be written,
+    /// are not lost by next [`DataPages::get_bytes_for_append`].
+    pub fn mark_persisted(&self, seq: u64) {
+        let pages = self.pages.read().unwrap_or_else(PoisonError::into_inner);
+        for page in pages.iter() {
+            page.clear_dirty(seq);
+        }
+    }
+
+    /// Returns index, bytes and used length of the [`Data`] pages which must be written to file that already
+    /// contains first `persisted` pages: pages changed since they were persisted, last persisted page and all pages
+    /// after it. Returned pages are not marked as persisted, so [`DataPages::mark_persisted`] must be called with
+    /// sequence taken before this call after pages are written. Evicted pages are not loaded back.
+    #[allow(clippy::type_complexity)]
+    pub fn get_bytes_for_append(
+        &self,
+        persisted: usize,
+    ) -> Result<Vec<(usize, [u8; DATA_LENGTH], u32)>, ExecutionError> {
+        let pages = self.pages.read().unwrap_or_else(PoisonError::into_inner);
+        pages
+            .iter()
+            .enumerate()
+            .filter(|(i, p)| p.is_dirty() || i + 1 >= persisted)
+            .map(|(i, p)| Ok((i, p.get_bytes()?, p.free_offset.load(Ordering::Relaxed))))
+            .collect()
+    }
+
+    /// Returns copy of all [`Data`] pages. Pages are written in place, so they are copied instead of shared, and
+    /// writes after snapshot don't change it. Pages write lock is held while they are copied, so writers wait for the
+    /// copy and all pages are copied at the same point. Empty links are not copied, so snapshot is meant only for
+    /// reads. Evicted pages are read from the spill file, but they are not loaded back.
+    pub fn snapshot(&self) -> Result<Self, ExecutionError> {
+        let (_assignment, pages) = self.lock_for_resize();
+        let pages = pages
+            .iter()
+            .map(|p| Ok(Arc::new(p.snapshot()?)))
+            .collect::<Result<Vec<_>, ExecutionError>>()?;
+        let mut snapshot = Self::from_data(pages);
+        snapshot.table_name = self.table_name;
+        Ok(snapshot)
+    }
+
+    /// Returns checksum of the [`Data`] page with provided id, or `None` if there is no such page. Evicted page is not
+    /// loaded back.
+    pub fn page_checksum(&self, page_id: PageId) -> Result<Option<u64>, ExecutionError> {
+        let pages = self.pages.read().unwrap_or_else(PoisonError::into_inner);
+        let Some(page) = pages.get::<usize>(page_id.into()) else {
+            return Ok(None);
+        };
+        Ok(Some(page.checksum()?))
+    }
+
+    /// Replaces [`Data`] page with same id by provided one. If page id is next after the last page, page is appended.
+    /// Pages can be appended only sequentially, so for page id further than that [`ExecutionError::PageNotFound`] is
+    /// returned with id of the first missing page.
+    pub fn replace_page(
+        &self,
+        page: Data<<Row as StorableRow>::WrappedRow, DATA_LENGTH>,
+    ) -> Result<(), ExecutionError> {
+        let (_assignment, mut pages) = self.lock_for_resize();
+        let index: usize = page.id().into();
+        if index < pages.len() {
+            pages[index] = Arc::new(page);
+        } else {
+            if index > pages.len() {
+                return Err(ExecutionError::PageNotFound((pages.len() as u32).into()));
+            }
+            pages.push(Arc::new(page));
+            self.last_page_id.store(index as u32, Ordering::Relaxed);
+            self.assigned_page_id.store(index as u32, Ordering::Relaxed);
+            self.current_pages[0].store(index as u32, Ordering::Release);
+        }
+        // Loaded pages are evicted too, so table bigger than memory can be loaded.
+        self.eviction_pending.store(true, Ordering::Release);
+
+        Ok(())
+    }
+
+    /// Returns latency histograms of measured [`DataPages`] methods. Measurements are global, so they are collected
+    /// from all [`DataPages`] instances.
+    #[cfg(feature = "perf_measurements")]
+    pub fn perf_report(&self) -> performance_measurement::PerfReport {
+        performance_measurement::PerformanceProfiler::report("DataPages::")
+    }
+
+    pub fn get_page_count(&self) -> usize {
+        self.pages
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns fill statistics of every [`Data`] page. Empty links are taken out of the stack and pushed back, so
+    /// inserts are not blocked, but they can miss some empty links during the snapshot. Free tails of the pages are
+    /// counted as free bytes.
+    pub fn page_stats(&self) -> Vec<PageStat> {
+        let empty_links = self.empty_links.snapshot();
+        let tails = self.free_space.snapshot();
+
+        let pages = self.pages.read().unwrap_or_else(PoisonError::into_inner);
+        let mut stats = pages
+            .iter()
+            .map(|p| {
+                let used = p.used_length();
+                PageStat {
+                    page_id: p.id(),
+                    used,
+                    free: DATA_LENGTH as u32 - used,
+                    dead_slots: 0,
+                    dead_bytes: 0,
+                }
+            })
+            .collect::<Vec<_>>();
+        drop(pages);
+
+        for l in empty_links {
+            let index: usize = l.page_id.into();
+            if let Some(stat) = stats.get_mut(index) {
+                stat.dead_slots += 1;
+                stat.dead_bytes += l.length;
+            }
+        }
+        for l in tails {
+            let index: usize = l.page_id.into();
+            if let Some(stat) = stats.get_mut(index) {
+                // Tail can be saved after page's used length was read, so it's not subtracted below zero.
+                stat.used = stat.used.saturating_sub(l.length);
+                stat.free = DATA_LENGTH as u32 - stat.used;
+            }
+        }
+
+        stats
+    }
+
+    /// Removes all rows by resetting pages to single empty page for every active page under single write lock. All
+    /// empty [`Link`]s, free tails, tombstones and expirations are removed too.
+    pub fn clear(&self) {
+        let (_assignment, mut pages) = self.lock_for_resize();
+        *pages = (0..self.current_pages.len() as u32)
+            .map(|i| Arc::new(Data::new(i.into())))
+            .collect();
+        for link in self.empty_links.drain() {
+            self.unmark_freed(link);
+        }
+        self.free_space.drain();
+        for _ in self.tombstones.pop_iter() {}
+        let expired = self
+            .expirations
+            .iter()
+            .map(|e| *e.key())
+            .collect::<Vec<_>>();
+        for link in expired {
+            self.expirations.remove(&link);
+        }
+        self.row_count.store(0, Ordering::Relaxed);
+        let last_page_id = self.current_pages.len() as u32 - 1;
+        self.last_page_id.store(last_page_id, Ordering::Release);
+        self.assigned_page_id.store(last_page_id, Ordering::Relaxed);
+        for (i, current) in self.current_pages.iter().enumerate() {
+            current.store(i as u32, Ordering::Release);
+        }
+        metrics::set(metrics::PAGES, self.table_name, pages.len());
+    }
+
+    /// Removes pages from the end of the pages vector while all their used space is in empty [`Link`]s or free tails.
+    /// Interior pages are never removed, because it would change ids of the following pages. First page is always
+    /// kept. Returns count of removed pages.
+    pub fn truncate_empty_tail_pages(&self) -> u32 {
+        let (_assignment, mut pages) = self.lock_for_resize();
+
+        let mut dead_bytes = vec![0u32; pages.len()];
+        for l in self.empty_links.snapshot() {
+            if let Some(dead) = dead_bytes.get_mut::<usize>(l.page_id.into()) {
+                *dead += l.length;
+            }
+        }
+        for l in self.free_space.snapshot() {
+            if let Some(dead) = dead_bytes.get_mut::<usize>(l.page_id.into()) {
+                *dead += l.length;
+            }
+        }
+
+        let mut removed = 0;
+        while pages.len() > 1 {
+            let last = pages.len() - 1;
+            if pages[last].used_length() != dead_bytes[last] {
+                break;
+            }
+            pages.pop();
+            removed += 1;
+        }
+        if removed == 0 {
+            return 0;
+        }
+
+        let len = pages.len();
+        self.retain_empty_links(len);
+        self.free_space.retain(|l| usize::from(l.page_id) < len);
+        let last_page_id = len as u32 - 1;
+        self.last_page_id.store(last_page_id, Ordering::Release);
+        let _ = self
+            .assigned_page_id
+            .fetch_min(last_page_id, Ordering::Relaxed);
+        for current in &self.current_pages {
+            let _ = current.fetch_min(last_page_id, Ordering::AcqRel);
+        }
+        metrics::set(metrics::PAGES, self.table_name, len);
+
+        removed
+    }
+
+    /// Returns aggregated fill statistics of all [`Data`] pages and counters of the insert events.
+    pub fn stats(&self) -> DataPagesStats {
+        let stats = DataPagesStats {
+            pages_allocated: self.pages_allocated.load(Ordering::Relaxed),
+            links_reused: self.links_reused.load(Ordering::Relaxed),
+            retry_inserts: self.retry_inserts.load(Ordering::Relaxed),
+            tails_reused: self.tails_reused.load(Ordering::Relaxed),
+            empty_links_dropped: self.empty_links_dropped.load(Ordering::Relaxed),
+            ..Default::default()
+        };
+        self.page_stats().into_iter().fold(stats, |mut acc, stat| {
+            acc.page_count += 1;
+            acc.used += stat.used as u64;
+            acc.free += stat.free as u64;
+            acc.dead_slots += stat.dead_slots as u64;
+            acc.dead_bytes += stat.dead_bytes as u64;
+            acc
+        })
+    }
+
+    pub fn get_empty_links(&self) -> Vec<Link> {
+        let links = self.empty_links.drain();
+        links.iter().for_each(|l| self.unmark_freed(*l));
+
+        links
+    }
+
+    /// Returns empty [`Link`]s in reuse order without taking them, unlike [`DataPages::get_empty_links`]. Free list
+    /// can't be read in place, so it's drained and restored. [`Link`]s pushed by concurrent deletes are kept in free
+    /// list even if they are not in the snapshot, and concurrent inserts just don't reuse slots until [`Link`]s are
+    /// restored, so no [`Link`] is lost.
+    pub fn empty_links_snapshot(&self) -> Vec<Link> {
+        self.empty_links.snapshot()
+    }
+
+    /// Returns free tails of the pages, which are left when row didn't fit in the rest of its page, without taking
+    /// them.
+    pub fn free_tails_snapshot(&self) -> Vec<Link> {
+        self.free_space.snapshot()
+    }
+
+    /// Sets empty [`Link`]s of the pages. Links to pages which don't exist are dropped and counted in
+    /// [`DataPagesStats::empty_links_dropped`].
+    pub fn with_empty_links(mut self, links: Vec<Link>) -> Self {
+        let len = self.get_page_count();
+        let empty_links = EmptyLinks::new(self.empty_links.policy());
+        let freed = Map::new();
+        for l in links {
+            let page_id: usize = l.page_id.into();
+            if page_id < len {
+                empty_links.push(l);
+                freed.insert(l, ());
+            } else {
+                *self.empty_links_dropped.get_mut() += 1;
+            }
+        }
+        self.empty_links = empty_links;
+        self.freed = freed;
+
+        self
+    }
+
+    /// Drops empty [`Link`]s which can't be reused safely: [`Link`]s out of their pages' used part, and [`Link`]s
+    /// which overlap slots of the `live` rows or other empty [`Link`]s. Free list read from the file can be out of
+    /// sync with its rows, and reusing such [`Link`] would overwrite live row, so it's checked on load. Overflow
+    /// rows' chunks are treated as live too. Returns count of the dropped [`Link`]s, which are also counted in
+    /// [`DataPagesStats::empty_links_dropped`].
+    pub fn drop_invalid_empty_links<I>(&self, live: I) -> usize
+    where
+        I: IntoIterator<Item = Link>,
+    {
+        let pages = self.pages.read().unwrap_or_else(PoisonError::into_inner);
+        // Used slots by page and offset, with their ends. Slots never overlap each other, so only slot with the
+        // greatest offset before the end of checked `Link` can overlap it.
+        let mut used = BTreeMap::<(usize, u32), u32>::new();
+        let mut use_slot = |link: Link| {
+            used.insert(
+                (link.page_id.into(), link.offset),
+                link.offset.saturating_add(link.length),
+            );
+        };
+        for link in live {
+            if overflow::is_overflow(link) {
+                if let Ok((_, chunks)) = Self::read_overflow(&pages, link) {
+                    chunks.into_iter().for_each(&mut use_slot);
+                }
+                use_slot(overflow::header_link(link));
+            } else {
+                use_slot(link);
+            }
+        }
+
+        let mut dropped = 0;
+        self.empty_links.retain(|l| {
+            let page_id: usize = l.page_id.into();
+            // Valid `Link` ends in its page, so its end can't overflow.
+            let is_valid = pages.get(page_id).is_some_and(|p| p.is_link_valid(*l))
+                && !used
+                    .range((page_id, 0)..(page_id, l.offset + l.length))
+                    .next_back()
+                    .is_some_and(|(_, end)| *end > l.offset);
+            if is_valid {
+                used.insert((page_id, l.offset), l.offset + l.length);
+                true
+            } else {
+                self.freed.remove(l);
+                dropped += 1;
+                false
+            }
+        });
+        self.empty_links_dropped
+            .fetch_add(dropped as u64, Ordering::Relaxed);
+
+        dropped
+    }
+
+    /// Returns count of the empty [`Link`]s which are not reused yet. Overflow row's header and chunks are counted
+    /// separately.
+    pub fn free_list_len(&self) -> usize {
+        self.empty_links.len()
+    }
+
+    /// Drops empty [`Link`]s to the pages which don't exist anymore or out of their pages' used part and releases
+    /// memory kept by free list. Returns count of the dropped [`Link`]s.
+    pub fn shrink_free_list(&self) -> usize {
+        let pages = self.pages.read().unwrap_or_else(PoisonError::into_inner);
+        let mut dropped = 0;
+        self.empty_links.retain(|l| {
+            let page_id: usize = l.page_id.into();
+            if pages.get(page_id).is_some_and(|p| p.is_link_valid(*l)) {
+                true
+            } else {
+                self.freed.remove(l);
+                dropped += 1;
+                false
+            }
+        });
+        drop(pages);
+        self.empty_links.shrink_to_fit();
+
+        dropped
+    }
+
+    pub fn empty_link_policy(&self) -> EmptyLinkPolicy {
+        self.empty_links.policy()
+    }
+
+    /// Sets [`EmptyLinkPolicy`] that defines which empty [`Link`] is reused first. Already stored empty [`Link`]s are
+    /// kept.
+    pub fn set_empty_link_policy(&mut self, policy: EmptyLinkPolicy) {
+        let empty_links = EmptyLinks::new(policy);
+        for l in self.empty_links.drain() {
+            empty_links.push(l)
+        }
+        self.empty_links = empty_links;
+    }
+}
+
+/// Fill statistics of one [`Data`] page.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PageStat {
+    pub page_id: PageId,
+
+    /// Count of bytes that were used by rows, including deleted ones.
+    pub used: u32,
+
+    /// Count of bytes that were never used.
+    pub free: u32,
+
+    /// Count of deleted rows' slots which are not reused yet.
+    pub dead_slots: u32,
+
+    /// Count of bytes in deleted rows' slots which are not reused yet.
+    pub dead_bytes: u32,
+}
+
+/// Aggregated fill statistics of all [`Data`] pages.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DataPagesStats {
+    pub page_count: u64,
+    pub used: u64,
+    pub free: u64,
+    pub dead_slots: u64,
+    pub dead_bytes: u64,
+
+    /// Count of times inserts moved to the next page, because their page was full. Counters are not reset by
+    /// [`DataPages::clear`].
+    pub pages_allocated: u64,
+
+    /// Count of inserts that reused deleted row's slot.
+    pub links_reused: u64,
+
+    /// Count of inserts that didn't fit in their page and were retried.
+    pub retry_inserts: u64,
+
+    /// Count of inserts that didn't fit in their page and were saved to the free tail of the earlier page.
+    pub tails_reused: u64,
+
+    /// Count of empty [`Link`]s read from the file that were dropped on load, because they pointed out of the pages
+    /// or to the slots used by rows.
+    pub empty_links_dropped: u64,
+}
+
+impl DataPagesStats {
+    /// Returns part of the used bytes that are occupied by deleted rows' slots.
+    pub fn fragmentation(&self) -> f64 {
+        if self.used == 0 {
+            0.0
+        } else {
+            self.dead_bytes as f64 / self.used as f64
+        }
+    }
+}
+
+#[derive(Debug, Display, Error, From)]
+pub enum ExecutionError {
+    DataPageError(DataExecutionError),
+
+    PageNotFound(#[error(not(source))] PageId),
+
+    Locked,
+
+    /// New page is needed, but it doesn't fit in [`DataPages::max_bytes`].
+    CapacityExceeded,
+
+    /// Row doesn't fit in the existing pages, and [`DataPages::try_insert`] doesn't add new ones.
+    NoSpace,
+
+    /// [`VersionedLink`] points to the slot that was freed after it was created.
+    #[from(ignore)]
+    StaleLink,
+
+    /// Row is marked as tombstone.
+    #[from(ignore)]
+    Deleted,
+
+    /// Slot of the [`Link`] is already freed by delete and not reused yet.
+    #[from(ignore)]
+    AlreadyDeleted,
+
+    /// [`Link`]'s offset and length are out of page's used bounds.
+    #[display("malformed link {:?}", _0)]
+    MalformedLink(#[error(not(source))] Link),
+
+    /// Operation failed on the [`Link`] with provided position in the list of [`Link`]s.
+    #[display("failed on link {:?} at position {}: {}", link, position, source)]
+    #[from(ignore)]
+    LinkFailed {
+        position: usize,
+        link: Link,
+        source: Box<ExecutionError>,
+    },
+}
+
+#[cfg(test)]
 mod tests {
-    use std::collections::HashSet;
-    use std::sync::atomic::Ordering;
-    use std::sync::{Arc, RwLock};
+    use std::collections::{HashMap, HashSet};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::{mpsc, Arc, RwLock};
     use std::thread;
-    use std::time::Instant;
+    use std::time::{Duration, Instant};
+
+    use crate::in_memory::overflow;
+    use crate::in_memory::pages::{DataPages, ExecutionError, SPARE_PAGES};
+    use crate::in_memory::row::GeneralRow;
+    use crate::in_memory::{Data, DataExecutionError, RowWrapper, SpillConfig, StorableRow};
+    use crate::prelude::Link;
+    use rkyv::{Archive, Deserialize, Serialize};
+
+    #[derive(
+        Archive, Copy, Clone, Deserialize, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+    )]
+
+    struct TestRow {
+        a: u64,
+        b: u64,
+    }
+
+    impl StorableRow for TestRow {
+        type WrappedRow = GeneralRow<TestRow>;
+    }
+
+    #[test]
+    fn insert() {
+        let pages = DataPages::<TestRow>::new();
+
+        let row = TestRow { a: 10, b: 20 };
+        let link = pages.insert(row).unwrap();
+
+        assert_eq!(link.page_id, 0.into());
+        assert_eq!(link.length, 24);
+        assert_eq!(link.offset, 0);
+
+        assert_eq!(pages.row_count.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn insert_raw() {
+        let pages = DataPages::<TestRow>::new();
+        let row = TestRow { a: 10, b: 20 };
+        let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&GeneralRow::from_inner(row)).unwrap();
+
+        let link = unsafe { pages.insert_raw(&bytes) }.unwrap();
+        assert_eq!(link.length, 24);
+        assert_eq!(pages.select(link).unwrap(), row);
+        assert_eq!(
+            pages
+                .with_ref(link, |archived| archived.inner.a.to_native())
+                .unwrap(),
+            10
+        );
+
+        // Empty link of the deleted row is reused by raw row of the same length.
+        pages.delete(link).unwrap();
+        let new_link = unsafe { pages.insert_raw(&bytes) }.unwrap();
+        assert_eq!(new_link, link);
+        assert_eq!(pages.select(new_link).unwrap(), row);
+    }
+
+    #[test]
+    fn select() {
+        let pages = DataPages::<TestRow>::new();
+
+        let row = TestRow { a: 10, b: 20 };
+        let link = pages.insert(row).unwrap();
+        let res = pages.select(link).unwrap();
+
+        assert_eq!(res, row)
+    }
+
+    #[test]
+    fn update() {
+        let pages = DataPages::<TestRow>::new();
+
+        let row = TestRow { a: 10, b: 20 };
+        let link = pages.insert(row).unwrap();
+        let new_row = TestRow { a: 20, b: 30 };
+        let res = unsafe { pages.update(new_row, link) }.unwrap();
+        assert_eq!(res, link);
+
+        let res = pages.select(link).unwrap();
+        assert_eq!(res, new_row)
+    }
+
+    #[test]
+    fn update_if() {
+        let pages = DataPages::<TestRow>::new();
+
+        let row = TestRow { a: 10, b: 20 };
+        let link = pages.insert(row).unwrap();
+        let new_row = TestRow { a: 20, b: 30 };
+
+        let changed = TestRow { a: 10, b: 30 };
+        assert!(!pages.update_if(link, &changed, new_row).unwrap());
+        assert_eq!(pages.select(link).unwrap(), row);
+
+        assert!(pages.update_if(link, &row, new_row).unwrap());
+        assert_eq!(pages.select(link).unwrap(), new_row);
+
+        // Row was already changed, so second update with the same expected row fails.
+        assert!(!pages.update_if(link, &row, changed).unwrap());
+        assert_eq!(pages.select(link).unwrap(), new_row);
+    }
+
+    #[test]
+    fn delete() {
+        let pages = DataPages::<TestRow>::new();
+
+        let row = TestRow { a: 10, b: 20 };
+        let link = pages.insert(row).unwrap();
+        pages.delete(link).unwrap();
+
+        assert_eq!(pages.empty_links.pop(), Some(link));
+        pages.empty_links.push(link);
+
+        let row = TestRow { a: 20, b: 20 };
+        let new_link = pages.insert(row).unwrap();
+        assert_eq!(new_link, link)
+    }
+
+    #[test]
+    fn delete_twice() {
+        let pages = DataPages::<TestRow>::new();
+
+        let link = pages.insert(TestRow { a: 10, b: 20 }).unwrap();
+        pages.delete(link).unwrap();
+        assert!(matches!(
+            pages.delete(link),
+            Err(ExecutionError::AlreadyDeleted)
+        ));
+        assert!(matches!(
+            pages.delete_many(&[link]),
+            Err(ExecutionError::AlreadyDeleted)
+        ));
+        assert_eq!(pages.empty_links.snapshot(), vec![link]);
+
+        let first = pages.insert(TestRow { a: 20, b: 20 }).unwrap();
+        let second = pages.insert(TestRow { a: 30, b: 20 }).unwrap();
+        assert_eq!(first, link);
+        assert_ne!(second, link);
+
+        // Slot is reused, so it can be deleted again.
+        pages.delete(first).unwrap();
+    }
+
+    #[test]
+    fn select_stale_link() {
+        let pages = DataPages::<TestRow>::new();
+
+        let row = TestRow { a: 10, b: 20 };
+        let link = pages.insert(row).unwrap();
+        let versioned = pages.versioned(link);
+        assert_eq!(pages.select_versioned(versioned).unwrap(), row);
+
+        pages.delete(link).unwrap();
+        let new_row = TestRow { a: 20, b: 20 };
+        let new_link = pages.insert(new_row).unwrap();
+        assert_eq!(new_link, link);
+
+        assert!(matches!(
+            pages.select_versioned(versioned),
+            Err(ExecutionError::StaleLink)
+        ));
+        assert_eq!(
+            pages.select_versioned(pages.versioned(new_link)).unwrap(),
+            new_row
+        );
+    }
+
+    #[test]
+    fn generations_by_slot_start() {
+        let pages = DataPages::<TestRow>::new();
+
+        let row = TestRow { a: 10, b: 20 };
+        let link = pages.insert(row).unwrap();
+        let versioned = pages.versioned(link);
+        for _ in 0..10 {
+            let link = pages.insert(row).unwrap();
+            pages.delete(link).unwrap();
+        }
+        pages.delete(link).unwrap();
+        pages.insert(row).unwrap();
+
+        assert!(matches!(
+            pages.select_versioned(versioned),
+            Err(ExecutionError::StaleLink)
+        ));
+        // Slot of the first row and slot reused by the loop's rows.
+        assert_eq!(pages.generations.iter().count(), 2);
+    }
+
+    #[test]
+    fn insert_full() {
+        let pages = DataPages::<TestRow>::new();
+
+        let row = TestRow { a: 10, b: 20 };
+        let _ = pages.insert(row).unwrap();
+        let res = pages.insert(row);
+
+        assert!(res.is_ok())
+    }
+
+    #[test]
+    fn insert_full_multithread() {
+        // Page fits only 20 rows, so threads constantly race on new pages.
+        let pages = Arc::new(DataPages::<TestRow, 480>::new());
+
+        let mut v = Vec::new();
+
+        for j in 0..32 {
+            let pages_shared = pages.clone();
+            let h = thread::spawn(move || {
+                let mut links = Vec::new();
+                for i in 0..100 {
+                    let row = TestRow { a: j, b: i };
+                    links.push((pages_shared.insert(row).unwrap(), row));
+                }
+                links
+            });
+
+            v.push(h)
+        }
+
+        for h in v {
+            for (link, row) in h.join().unwrap() {
+                assert_eq!(pages.select(link).unwrap(), row)
+            }
+        }
+        assert_eq!(pages.row_count.load(Ordering::Relaxed), 3200);
+    }
+
+    #[test]
+    fn insert_select_from_other_thread() {
+        // Links are selected by other thread right after insert, while pages are constantly added.
+        let pages = Arc::new(DataPages::<TestRow, 480>::new());
+        let (tx, rx) = mpsc::channel();
+
+        let mut writers = Vec::new();
+        for j in 0..8 {
+            let pages = pages.clone();
+            let tx = tx.clone();
+            writers.push(thread::spawn(move || {
+                for i in 0..500 {
+                    let row = TestRow { a: j, b: i };
+                    tx.send((pages.insert(row).unwrap(), row)).unwrap();
+                }
+            }));
+        }
+        drop(tx);
+
+        let reader = {
+            let pages = pages.clone();
+            thread::spawn(move || {
+                let mut count = 0;
+                for (link, row) in rx {
+                    assert_eq!(pages.select(link).unwrap(), row);
+                    count += 1;
+                }
+                count
+            })
+        };
+
+        for h in writers {
+            h.join().unwrap();
+        }
+        assert_eq!(reader.join().unwrap(), 4000);
+    }
+
+    #[test]
+    fn insert_active_pages() {
+        // Page fits only 20 rows, so every active page is moved to new page many times.
+        let pages = Arc::new(DataPages::<TestRow, 480>::with_active_pages(4));
+        assert_eq!(pages.active_pages(), 4);
+        assert_eq!(pages.get_page_count(), 4);
+
+        let mut v = Vec::new();
+        for j in 0..8 {
+            let pages = pages.clone();
+            v.push(thread::spawn(move || {
+                (0..100)
+                    .map(|i| {
+                        let row = TestRow { a: j, b: i };
+                        (pages.insert_with_key(row, j).unwrap(), row)
+                    })
+                    .collect::<Vec<_>>()
+            }));
+        }
+
+        let mut page_shards = HashMap::new();
+        for (j, h) in v.into_iter().enumerate() {
+            for (link, row) in h.join().unwrap() {
+                assert_eq!(pages.select(link).unwrap(), row);
+                // Every page is filled by single active page.
+                let page_id: usize = link.page_id.into();
+                assert_eq!(*page_shards.entry(page_id).or_insert(j % 4), j % 4);
+            }
+        }
+        assert_eq!(pages.row_count.load(Ordering::Relaxed), 800);
+
+        pages.clear();
+        assert_eq!(pages.get_page_count(), 4);
+    }
+
+    #[test]
+    fn insert_too_large() {
+        let pages = DataPages::<TestRow, 16>::new();
 
-    use crate::in_memory::pages::DataPages;
-    use crate::in_memory::row::GeneralRow;
-    use crate::in_memory::StorableRow;
-    use rkyv::{Archive, Deserialize, Serialize};
+        let row = TestRow { a: 10, b: 20 };
+        let res = pages.insert(row);
 
-    #[derive(
-        Archive, Copy, Clone, Deserialize, Debug, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
-    )]
+        assert!(matches!(
+            res,
+            Err(ExecutionError::DataPageError(
+                DataExecutionError::RowTooLarge {
+                    size: 24,
+                    capacity: 16
+                }
+            ))
+        ));
+        assert_eq!(pages.get_page_count(), 1);
+    }
 
-    struct TestRow {
+    #[derive(Archive, Clone, Deserialize, Debug, PartialEq, Serialize)]
+    struct VecRow {
         a: u64,
-        b: u64,
+        items: Vec<u64>,
     }
 
-    impl StorableRow for TestRow {
-        type WrappedRow = GeneralRow<TestRow>;
+    impl StorableRow for VecRow {
+        type WrappedRow = GeneralRow<VecRow>;
     }
 
     #[test]
-    fn insert() {
+    fn insert_overflow() {
+        let pages = DataPages::<VecRow, 64>::new();
+
+        let row = VecRow {
+            a: 10,
+            items: (0..16).collect(),
+        };
+        let link = pages.insert(row.clone()).unwrap();
+
+        assert!(overflow::is_overflow(link));
+        assert!(pages.get_page_count() > 1);
+        assert_eq!(pages.select(link).unwrap(), row);
+        assert_eq!(pages.with_ref(link, |r| r.inner.items.len()).unwrap(), 16);
+
+        // Small row is still saved in the page.
+        let small = VecRow {
+            a: 30,
+            items: vec![],
+        };
+        let small_link = pages.insert(small.clone()).unwrap();
+        assert!(!overflow::is_overflow(small_link));
+        assert_eq!(pages.select(small_link).unwrap(), small);
+
+        // Tombstone flag is written back to the overflow row's chunks.
+        pages.tombstone(link).unwrap();
+        assert!(matches!(pages.select(link), Err(ExecutionError::Deleted)));
+        let chunks = overflow::max_row_length(64) / 64;
+        let freed = pages.vacuum();
+        assert_eq!(freed, 1);
+        let empty = pages.empty_links.snapshot();
+        assert!(empty.contains(&overflow::header_link(link)));
+        assert!(empty.len() > 1 && empty.len() <= chunks + 1);
+    }
+
+    #[test]
+    fn delete_many() {
+        let pages = DataPages::<VecRow, 64>::new();
+
+        let overflow_link = pages
+            .insert(VecRow {
+                a: 10,
+                items: (0..16).collect(),
+            })
+            .unwrap();
+        let link = pages
+            .insert(VecRow {
+                a: 20,
+                items: vec![],
+            })
+            .unwrap();
+        pages.delete_many(&[overflow_link, link]).unwrap();
+
+        let empty = pages.empty_links.snapshot();
+        assert!(empty.contains(&link));
+        assert!(empty.contains(&overflow::header_link(overflow_link)));
+        assert!(empty.len() > 2);
+        assert_eq!(pages.generation(link), 1);
+        assert_eq!(pages.generation(overflow_link), 1);
+    }
+
+    #[test]
+    fn add_next_page_uses_spare_pages() {
+        let pages = DataPages::<TestRow, 48>::new();
+        let links = (0..6)
+            .map(|i| pages.insert(TestRow { a: i, b: i }).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(pages.get_page_count(), 3);
+        assert_eq!(pages.spare_pages.lock().unwrap().len(), SPARE_PAGES);
+
+        for (i, link) in links.into_iter().enumerate() {
+            assert_eq!(link.page_id, (i as u32 / 2).into());
+            let row = TestRow {
+                a: i as u64,
+                b: i as u64,
+            };
+            assert_eq!(pages.select(link).unwrap(), row);
+        }
+        for (i, page) in pages.pages.read().unwrap().iter().enumerate() {
+            assert_eq!(page.id(), (i as u32).into());
+        }
+    }
+
+    #[test]
+    fn spare_page_is_kept_if_capacity_exceeded() {
+        let mut pages = DataPages::<TestRow, 48>::new();
+        for i in 0..6 {
+            pages.insert(TestRow { a: i, b: i }).unwrap();
+        }
+        assert_eq!(pages.spare_pages.lock().unwrap().len(), SPARE_PAGES);
+
+        pages.set_max_bytes(Some(48 * 3));
+        assert!(matches!(
+            pages.insert(TestRow { a: 6, b: 6 }),
+            Err(ExecutionError::CapacityExceeded)
+        ));
+        assert_eq!(pages.get_page_count(), 3);
+        assert_eq!(pages.spare_pages.lock().unwrap().len(), SPARE_PAGES);
+    }
+
+    #[test]
+    fn spare_pages_disabled() {
+        let mut pages = DataPages::<TestRow, 48>::new();
+        pages.set_spare_page_count(0);
+        for i in 0..6 {
+            pages.insert(TestRow { a: i, b: i }).unwrap();
+        }
+        assert_eq!(pages.get_page_count(), 3);
+        assert!(pages.spare_pages.lock().unwrap().is_empty());
+
+        // Spare pages are not counted by memory usage, so they are not kept if it's limited.
+        let mut pages = DataPages::<TestRow, 48>::new();
+        pages.set_max_bytes(Some(48 * 4));
+        for i in 0..6 {
+            pages.insert(TestRow { a: i, b: i }).unwrap();
+        }
+        assert!(pages.spare_pages.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn insert_backfills_free_tail() {
+        let pages = DataPages::<VecRow, 128>::new();
+        let big = VecRow {
+            a: 10,
+            items: (0..8).collect(),
+        };
+        let small = VecRow {
+            a: 20,
+            items: vec![],
+        };
+        assert_eq!(pages.stored_length(big.clone()).unwrap(), 88);
+        assert_eq!(pages.stored_length(small.clone()).unwrap(), 24);
+
+        let big_links = (0..2)
+            .map(|_| pages.insert(big.clone()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(big_links[1].page_id, 1.into());
+        let small_links = (0..2)
+            .map(|_| pages.insert(small.clone()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(small_links[0].page_id, 1.into());
+
+        // Second small row doesn't fit in the rest of the page 1, so it's saved to the tail of the page 0 instead of
+        // the new page.
+        assert_eq!(small_links[1].page_id, 0.into());
+        assert_eq!(small_links[1].offset, 88);
+        assert_eq!(pages.get_page_count(), 2);
+        assert_eq!(pages.stats().tails_reused, 1);
+        let stats = pages.page_stats();
+        assert_eq!(stats[0].free, 16);
+        assert_eq!(stats[1].free, 16);
+        for link in big_links {
+            assert_eq!(pages.select(link).unwrap(), big);
+        }
+        for link in small_links {
+            assert_eq!(pages.select(link).unwrap(), small);
+        }
+    }
+
+    #[test]
+    fn truncate_drops_free_tails() {
+        let pages = DataPages::<VecRow, 128>::new();
+        let big = VecRow {
+            a: 10,
+            items: (0..8).collect(),
+        };
+        let links = (0..3)
+            .map(|_| pages.insert(big.clone()).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(pages.free_space.snapshot().len(), 2);
+
+        // Page which has only free tail and deleted rows is removed with its tail.
+        pages.delete(links[2]).unwrap();
+        pages.delete(links[1]).unwrap();
+        assert_eq!(pages.truncate_empty_tail_pages(), 2);
+        assert_eq!(
+            pages.free_space.snapshot(),
+            vec![Link {
+                page_id: 0.into(),
+                offset: 88,
+                length: 40,
+            }]
+        );
+    }
+
+    #[test]
+    fn insert_overflow_too_large() {
+        let pages = DataPages::<VecRow, 64>::new();
+
+        let row = VecRow {
+            a: 10,
+            items: (0..64).collect(),
+        };
+        let res = pages.insert(row);
+
+        assert!(matches!(
+            res,
+            Err(ExecutionError::DataPageError(
+                DataExecutionError::RowTooLarge { capacity: 256, .. }
+            ))
+        ));
+        assert_eq!(pages.get_page_count(), 1);
+    }
+
+    #[test]
+    fn evict_cold_pages() {
+        let path =
+            std::env::temp_dir().join(format!("worktable-spill-{}.bin", uuid::Uuid::new_v4()));
+        let mut pages = DataPages::<TestRow, 48>::new();
+        pages.set_spill(Some(SpillConfig {
+            path: path.clone(),
+            max_resident_pages: 2,
+        }));
+
+        let links = (0..10)
+            .map(|i| pages.insert(TestRow { a: i, b: i }).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(pages.get_page_count(), 5);
+        assert!(pages.resident_pages() <= 2);
+
+        for (i, link) in links.iter().enumerate() {
+            let i = i as u64;
+            assert_eq!(pages.select(*link).unwrap(), TestRow { a: i, b: i });
+        }
+        // Pages loaded back by reads are evicted before next read, so only the last loaded page is over the limit.
+        assert!(pages.resident_pages() <= 3);
+
+        pages.evict_cold_pages().unwrap();
+        assert_eq!(pages.resident_pages(), 2);
+        assert_eq!(pages.memory_usage(), 2 * 48);
+        // Active page is kept in memory.
+        assert!(!pages.pages.read().unwrap()[4].is_evicted());
+        // Evicted pages are copied for persistence without loading.
+        let bytes = pages.get_bytes().unwrap();
+        assert_eq!(bytes.len(), 5);
+        assert_eq!(pages.resident_pages(), 2);
+        assert_eq!(pages.select(links[0]).unwrap(), TestRow { a: 0, b: 0 });
+
+        drop(pages);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn reserve_pages() {
+        let pages = DataPages::<TestRow, 48>::new();
+        pages.reserve_pages(2);
+        assert_eq!(pages.get_page_count(), 3);
+
+        let links = (0..6)
+            .map(|i| pages.insert(TestRow { a: i, b: i }).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(pages.get_page_count(), 3);
+        for (i, link) in links.iter().enumerate() {
+            assert_eq!(link.page_id, ((i / 2) as u32).into());
+        }
+
+        let link = pages.insert(TestRow { a: 6, b: 6 }).unwrap();
+        assert_eq!(pages.get_page_count(), 4);
+        assert_eq!(link.page_id, 3.into());
+    }
+
+    #[test]
+    fn max_bytes() {
+        let mut pages = DataPages::<TestRow, 48>::new();
+        pages.set_max_bytes(Some(100));
+        pages.reserve_pages(5);
+        assert_eq!(pages.get_page_count(), 2);
+        assert_eq!(pages.memory_usage(), 96);
+
+        let links = (0..4)
+            .map(|i| pages.insert(TestRow { a: i, b: i }).unwrap())
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            pages.insert(TestRow { a: 4, b: 4 }),
+            Err(ExecutionError::CapacityExceeded)
+        ));
+        assert_eq!(pages.memory_usage(), 96);
+
+        // Slot of the deleted row is reused without new page.
+        pages.delete(links[1]).unwrap();
+        assert_eq!(pages.insert(TestRow { a: 4, b: 4 }).unwrap(), links[1]);
+    }
+
+    #[test]
+    fn try_insert() {
+        let pages = DataPages::<TestRow, 48>::new();
+        pages.reserve_pages(1);
+
+        // Reserved page is filled, because it already exists.
+        let links = (0..4)
+            .map(|i| pages.try_insert(TestRow { a: i, b: i }).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(pages.get_page_count(), 2);
+        assert!(matches!(
+            pages.try_insert(TestRow { a: 4, b: 4 }),
+            Err(ExecutionError::NoSpace)
+        ));
+        assert_eq!(pages.get_page_count(), 2);
+
+        pages.delete(links[1]).unwrap();
+        assert_eq!(pages.try_insert(TestRow { a: 4, b: 4 }).unwrap(), links[1]);
+        assert!(pages.insert(TestRow { a: 5, b: 5 }).is_ok());
+        assert_eq!(pages.get_page_count(), 3);
+    }
+
+    #[test]
+    fn select_many() {
+        let pages = DataPages::<TestRow>::new();
+        let rows = (0..3).map(|i| TestRow { a: i, b: i }).collect::<Vec<_>>();
+        let mut links = rows
+            .iter()
+            .map(|r| pages.insert(*r).unwrap())
+            .collect::<Vec<_>>();
+        links.push(Link {
+            offset: links[2].offset + 1_000,
+            ..links[2]
+        });
+
+        let res = pages.select_many(&links).unwrap();
+        assert_eq!(res.len(), 4);
+        for (row, selected) in rows.iter().zip(&res) {
+            assert_eq!(selected.as_ref().unwrap(), row);
+        }
+        assert!(matches!(res[3], Err(ExecutionError::MalformedLink(_))));
+    }
+
+    #[test]
+    fn tombstone_and_vacuum() {
         let pages = DataPages::<TestRow>::new();
+        let link = pages.insert(TestRow { a: 10, b: 20 }).unwrap();
+        pages.tombstone(link).unwrap();
+
+        assert!(matches!(pages.select(link), Err(ExecutionError::Deleted)));
+        assert!(matches!(
+            pages.with_ref(link, |_| ()),
+            Err(ExecutionError::Deleted)
+        ));
+
+        let new_link = pages.insert(TestRow { a: 1, b: 2 }).unwrap();
+        assert_ne!(new_link, link);
+
+        assert_eq!(pages.vacuum(), 1);
+        let row = TestRow { a: 3, b: 4 };
+        let reused = pages.insert(row).unwrap();
+        assert_eq!(reused, link);
+        assert_eq!(pages.select(reused).unwrap(), row);
+    }
 
-        let row = TestRow { a: 10, b: 20 };
-        let link = pages.insert(row).unwrap();
+    #[test]
+    fn truncate_empty_tail_pages() {
+        let pages = DataPages::<TestRow, 48>::new();
+        let links = (0..6)
+            .map(|i| pages.insert(TestRow { a: i, b: i }).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(pages.get_page_count(), 3);
+
+        // Second page is empty, but it's interior, so it's kept.
+        pages.delete(links[2]).unwrap();
+        pages.delete(links[3]).unwrap();
+        pages.delete(links[5]).unwrap();
+        assert_eq!(pages.truncate_empty_tail_pages(), 0);
+
+        pages.delete(links[4]).unwrap();
+        assert_eq!(pages.truncate_empty_tail_pages(), 2);
+        assert_eq!(pages.get_page_count(), 1);
+        assert!(pages.get_empty_links().is_empty());
+
+        let link = pages.insert(TestRow { a: 6, b: 6 }).unwrap();
+        assert_eq!(link.page_id, 1.into());
+    }
+
+    #[test]
+    fn truncate_with_select_many_no_deadlock() {
+        // Page fits only 2 rows, so every round adds and truncates pages while other threads select rows and reserve
+        // pages, and all of them take several locks of the pages.
+        let pages = Arc::new(DataPages::<TestRow, 48>::new());
+        let first = pages.insert(TestRow { a: 0, b: 0 }).unwrap();
+        let (tx, rx) = mpsc::channel();
+
+        let truncate_pages = pages.clone();
+        let truncate_tx = tx.clone();
+        thread::spawn(move || {
+            for i in 0..1_000 {
+                let links = (0..6)
+                    .map(|j| truncate_pages.insert(TestRow { a: i, b: j }).unwrap())
+                    .collect::<Vec<_>>();
+                for link in links {
+                    truncate_pages.delete(link).unwrap();
+                }
+                truncate_pages.truncate_empty_tail_pages();
+            }
+            truncate_tx.send(()).unwrap();
+        });
+
+        for _ in 0..2 {
+            let select_pages = pages.clone();
+            let select_tx = tx.clone();
+            thread::spawn(move || {
+                // Link to the page which is never allocated is selected too, like link to the truncated page.
+                let links = [
+                    first,
+                    Link {
+                        page_id: 10_000.into(),
+                        ..first
+                    },
+                ];
+                for i in 0..1_000 {
+                    let rows = select_pages.select_many(&links).unwrap();
+                    assert_eq!(rows[0].as_ref().unwrap(), &TestRow { a: 0, b: 0 });
+                    assert!(rows[1].is_err());
+                    if i % 100 == 0 {
+                        select_pages.reserve_pages(1);
+                    }
+                }
+                select_tx.send(()).unwrap();
+            });
+        }
+
+        for _ in 0..3 {
+            rx.recv_timeout(Duration::from_secs(60))
+                .expect("operations are deadlocked");
+        }
+    }
+
+    #[test]
+    fn shrink_free_list() {
+        let pages = DataPages::<TestRow, 48>::new();
+        let links = (0..6)
+            .map(|i| pages.insert(TestRow { a: i, b: i }).unwrap())
+            .collect::<Vec<_>>();
+        for link in &links[1..] {
+            pages.delete(*link).unwrap();
+        }
+        assert_eq!(pages.free_list_len(), 5);
+
+        // Last two pages are removed with their empty links, only one empty link is left on the first page.
+        assert_eq!(pages.truncate_empty_tail_pages(), 2);
+        assert_eq!(pages.free_list_len(), 1);
+
+        // Free list gets out of sync with pages.
+        pages.empty_links.push(links[4]);
+        assert_eq!(pages.shrink_free_list(), 1);
+        assert_eq!(pages.free_list_len(), 1);
+        assert_eq!(pages.empty_links_snapshot(), vec![links[1]]);
+
+        let _ = pages.insert(TestRow { a: 6, b: 6 }).unwrap();
+        assert_eq!(pages.free_list_len(), 0);
+    }
+
+    #[test]
+    fn insert_with_stale_empty_links() {
+        let pages = DataPages::<TestRow, 48>::new();
+        let links = (0..6)
+            .map(|i| pages.insert(TestRow { a: i, b: i }).unwrap())
+            .collect::<Vec<_>>();
+        for link in &links[2..] {
+            pages.delete(*link).unwrap();
+        }
+        assert_eq!(pages.truncate_empty_tail_pages(), 2);
+
+        // Free list gets out of sync with pages.
+        for link in &links[2..] {
+            pages.empty_links.push(*link);
+        }
+
+        let rows = (6..12).map(|i| TestRow { a: i, b: i }).collect::<Vec<_>>();
+        let new_links = rows
+            .iter()
+            .map(|row| pages.insert(*row).unwrap())
+            .collect::<Vec<_>>();
+        assert!(pages.get_empty_links().is_empty());
+        assert_eq!(pages.get_page_count(), 4);
+        for (row, link) in rows.into_iter().zip(new_links) {
+            assert_eq!(pages.select(link).unwrap(), row);
+        }
+        assert_eq!(pages.select(links[0]).unwrap(), TestRow { a: 0, b: 0 });
+    }
+
+    #[test]
+    fn empty_links_snapshot() {
+        let pages = DataPages::<TestRow, 48>::new();
+        let links = (0..4)
+            .map(|i| pages.insert(TestRow { a: i, b: i }).unwrap())
+            .collect::<Vec<_>>();
+        pages.delete(links[1]).unwrap();
+        pages.delete(links[3]).unwrap();
+
+        let snapshot = pages.empty_links_snapshot();
+        assert_eq!(snapshot, vec![links[3], links[1]]);
+        assert_eq!(pages.empty_links_snapshot(), snapshot);
+
+        let link = pages.insert(TestRow { a: 5, b: 5 }).unwrap();
+        assert_eq!(link, links[3]);
+        assert_eq!(pages.empty_links_snapshot(), vec![links[1]]);
+    }
+
+    #[test]
+    fn pages_modified_since() {
+        let pages = DataPages::<TestRow, 48>::new();
+        let links = (0..4)
+            .map(|i| pages.insert(TestRow { a: i, b: i }).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(pages.get_page_count(), 2);
+
+        let seq = pages.modification_seq();
+        assert!(pages.pages_modified_since(seq).is_empty());
+
+        let _ = pages.insert(TestRow { a: 4, b: 4 }).unwrap();
+        assert_eq!(pages.pages_modified_since(seq), vec![2]);
+
+        unsafe { pages.update(TestRow { a: 10, b: 10 }, links[0]).unwrap() };
+        assert_eq!(pages.pages_modified_since(seq), vec![0, 2]);
+        assert!(pages.modification_seq() > seq);
+        assert!(pages
+            .pages_modified_since(pages.modification_seq())
+            .is_empty());
+    }
+
+    #[test]
+    fn pages_modified_since_concurrent() {
+        let pages = Arc::new(DataPages::<TestRow, 48>::new());
+        let seq = pages.modification_seq();
+
+        let handles = (0..4)
+            .map(|t| {
+                let pages = pages.clone();
+                thread::spawn(move || {
+                    (0..100)
+                        .map(|i| pages.insert(TestRow { a: t, b: i }).unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut page_ids = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .map(|l| {
+                let page_id: usize = l.page_id.into();
+                page_id as u32
+            })
+            .collect::<Vec<_>>();
+        page_ids.sort();
+        page_ids.dedup();
+
+        assert_eq!(pages.pages_modified_since(seq), page_ids);
+    }
+
+    #[test]
+    fn empty_links_snapshot_concurrent_delete() {
+        let pages = Arc::new(DataPages::<TestRow, 48>::new());
+        let links = (0..1000)
+            .map(|i| pages.insert(TestRow { a: i, b: i }).unwrap())
+            .collect::<Vec<_>>();
+
+        let shared = pages.clone();
+        let to_delete = links.clone();
+        let deletes = thread::spawn(move || {
+            for link in to_delete {
+                shared.delete(link).unwrap();
+            }
+        });
+        while !deletes.is_finished() {
+            let _ = pages.empty_links_snapshot();
+        }
+        deletes.join().unwrap();
+
+        let freed = pages.get_empty_links().into_iter().collect::<HashSet<_>>();
+        assert_eq!(freed, links.into_iter().collect::<HashSet<_>>());
+    }
+
+    #[test]
+    fn with_empty_links_drops_stale() {
+        let pages = DataPages::<TestRow, 48>::new();
+        let link = pages.insert(TestRow { a: 0, b: 0 }).unwrap();
+        let stale = Link {
+            page_id: 5.into(),
+            offset: 0,
+            length: link.length,
+        };
+
+        let pages = pages.with_empty_links(vec![link, stale]);
+        assert_eq!(pages.get_empty_links(), vec![link]);
+    }
+
+    #[test]
+    fn failed_save_by_link_keeps_empty_link() {
+        let pages = DataPages::<TestRow, 48>::new();
+        let link = pages.insert(TestRow { a: 0, b: 0 }).unwrap();
+        pages.delete(link).unwrap();
+
+        let row = <TestRow as StorableRow>::WrappedRow::from_inner(TestRow { a: 1, b: 1 });
+        let res = pages.insert_using(
+            0,
+            true,
+            |_, _| Err(DataExecutionError::SerializeError),
+            |page| page.save_row(&row),
+            || Err(ExecutionError::NoSpace),
+        );
+        assert!(matches!(
+            res,
+            Err(ExecutionError::DataPageError(
+                DataExecutionError::SerializeError
+            ))
+        ));
+        assert_eq!(pages.get_empty_links(), vec![link]);
+        assert_eq!(pages.insert(TestRow { a: 2, b: 2 }).unwrap(), link);
+    }
+
+    #[test]
+    fn drop_invalid_empty_links() {
+        let pages = DataPages::<TestRow, 48>::new();
+        let links = (0..3)
+            .map(|i| pages.insert(TestRow { a: i, b: i }).unwrap())
+            .collect::<Vec<_>>();
+        let past_used = Link {
+            offset: links[2].offset + links[2].length,
+            ..links[2]
+        };
+        let overlapping = Link {
+            offset: links[1].offset + 1,
+            ..links[1]
+        };
+
+        let pages =
+            pages.with_empty_links(vec![links[0], links[2], past_used, overlapping, links[0]]);
+        assert_eq!(pages.drop_invalid_empty_links(vec![links[1], links[2]]), 4);
+        assert_eq!(pages.get_empty_links(), vec![links[0]]);
+        assert_eq!(pages.stats().empty_links_dropped, 4);
+    }
 
+    #[test]
+    fn clear() {
+        let pages = DataPages::<TestRow, 48>::new();
+        let links = (0..6)
+            .map(|i| pages.insert(TestRow { a: i, b: i }).unwrap())
+            .collect::<Vec<_>>();
+        pages.delete(links[0]).unwrap();
+        pages.tombstone(links[1]).unwrap();
+
+        pages.clear();
+        assert_eq!(pages.get_page_count(), 1);
+        assert!(pages.get_empty_links().is_empty());
+        assert_eq!(pages.vacuum(), 0);
+
+        let row = TestRow { a: 6, b: 6 };
+        let link = pages.insert(row).unwrap();
         assert_eq!(link.page_id, 0.into());
-        assert_eq!(link.length, 24);
         assert_eq!(link.offset, 0);
+        assert_eq!(pages.select(link).unwrap(), row);
+    }
 
-        assert_eq!(pages.row_count.load(Ordering::Relaxed), 1);
+    #[test]
+    fn get_bytes_for_append() {
+        let pages = DataPages::<TestRow, 48>::new();
+        let links = (0..6)
+            .map(|i| pages.insert(TestRow { a: i, b: i }).unwrap())
+            .collect::<Vec<_>>();
+        pages.mark_persisted(pages.modification_seq());
+
+        let changed = |pages: &DataPages<TestRow, 48>, persisted| {
+            pages
+                .get_bytes_for_append(persisted)
+                .unwrap()
+                .into_iter()
+                .map(|(i, _, _)| i)
+                .collect::<Vec<_>>()
+        };
+        // Last persisted page is always written.
+        assert_eq!(changed(&pages, 3), vec![2]);
+
+        unsafe { pages.update(TestRow { a: 10, b: 10 }, links[0]).unwrap() };
+        pages.insert(TestRow { a: 6, b: 6 }).unwrap();
+        assert_eq!(changed(&pages, 3), vec![0, 2, 3]);
+        // Pages stay dirty until they are marked as persisted.
+        assert_eq!(changed(&pages, 3), vec![0, 2, 3]);
+        pages.mark_persisted(pages.modification_seq());
+        assert_eq!(changed(&pages, 4), vec![3]);
     }
 
     #[test]
-    fn select() {
-        let pages = DataPages::<TestRow>::new();
+    fn mark_persisted_keeps_later_changes() {
+        let pages = DataPages::<TestRow, 48>::new();
+        let links = (0..6)
+            .map(|i| pages.insert(TestRow { a: i, b: i }).unwrap())
+            .collect::<Vec<_>>();
+        let seq = pages.modification_seq();
+        // Page is changed after it's copied, but before copy is written.
+        unsafe { pages.update(TestRow { a: 10, b: 10 }, links[0]).unwrap() };
+        pages.mark_persisted(seq);
+
+        let changed = pages
+            .get_bytes_for_append(3)
+            .unwrap()
+            .into_iter()
+            .map(|(i, _, _)| i)
+            .collect::<Vec<_>>();
+        assert_eq!(changed, vec![0, 2]);
+    }
 
-        let row = TestRow { a: 10, b: 20 };
-        let link = pages.insert(row).unwrap();
-        let res = pages.select(link).unwrap();
+    #[test]
+    fn get_page_bytes() {
+        let pages = DataPages::<TestRow, 48>::new();
+        for i in 0..3 {
+            pages.insert(TestRow { a: i, b: i }).unwrap();
+        }
 
-        assert_eq!(res, row)
+        let all = pages.get_bytes().unwrap();
+        assert_eq!(pages.get_page_bytes(0).unwrap(), Some(all[0]));
+        assert_eq!(pages.get_page_bytes(1).unwrap(), Some(all[1]));
+        assert_eq!(pages.get_page_bytes(2).unwrap(), None);
     }
 
     #[test]
-    fn update() {
+    fn replace_page() {
         let pages = DataPages::<TestRow>::new();
+        let link = pages.insert(TestRow { a: 10, b: 20 }).unwrap();
 
-        let row = TestRow { a: 10, b: 20 };
-        let link = pages.insert(row).unwrap();
-        let res = pages.select(link).unwrap();
+        pages.replace_page(Data::new(0.into())).unwrap();
+        assert!(pages.select(link).is_err());
 
-        assert_eq!(res, row)
+        pages.replace_page(Data::new(1.into())).unwrap();
+        assert_eq!(pages.get_page_count(), 2);
+
+        assert!(matches!(
+            pages.replace_page(Data::new(3.into())),
+            Err(ExecutionError::PageNotFound(id)) if id == 2.into()
+        ));
+        assert_eq!(pages.get_page_count(), 2);
     }
 
     #[test]
-    fn delete() {
+    fn fold_refs() {
+        let pages = DataPages::<TestRow>::new();
+        let mut links = (1..=10)
+            .map(|i| pages.insert(TestRow { a: i, b: i * 2 }).unwrap())
+            .collect::<Vec<_>>();
+
+        let sum = pages
+            .fold_refs(&links, 0u64, |acc, row| acc + row.inner.b.to_native())
+            .unwrap();
+        assert_eq!(sum, 110);
+
+        links.insert(
+            3,
+            Link {
+                offset: links[9].offset + 1_000,
+                ..links[9]
+            },
+        );
+        let res = pages.fold_refs(&links, 0u64, |acc, row| acc + row.inner.b.to_native());
+        assert!(matches!(
+            res,
+            Err(ExecutionError::LinkFailed { position: 3, .. })
+        ));
+    }
+
+    #[test]
+    fn poisoned_lock() {
+        let pages = Arc::new(DataPages::<TestRow>::new());
+        let link = pages.insert(TestRow { a: 1, b: 1 }).unwrap();
+
+        let shared = pages.clone();
+        let _ = thread::spawn(move || {
+            let _guard = shared.pages.write().unwrap();
+            panic!("poison pages lock");
+        })
+        .join();
+
+        assert_eq!(pages.select(link).unwrap(), TestRow { a: 1, b: 1 });
+        let link = pages.insert(TestRow { a: 2, b: 2 }).unwrap();
+        assert_eq!(pages.select(link).unwrap(), TestRow { a: 2, b: 2 });
+        assert_eq!(pages.get_page_count(), 1);
+    }
+
+    #[test]
+    fn select_malformed_link() {
         let pages = DataPages::<TestRow>::new();
 
         let row = TestRow { a: 10, b: 20 };
         let link = pages.insert(row).unwrap();
-        pages.delete(link).unwrap();
 
-        assert_eq!(pages.empty_links.pop(), Some(link));
-        pages.empty_links.push(link);
+        let bogus = Link {
+            offset: link.offset + 1_000,
+            ..link
+        };
+        assert!(matches!(
+            pages.select(bogus),
+            Err(ExecutionError::MalformedLink(_))
+        ));
+        let bogus = Link {
+            length: u32::MAX,
+            ..link
+        };
+        assert!(matches!(
+            pages.select(bogus),
+            Err(ExecutionError::MalformedLink(_))
+        ));
+        let bogus = Link {
+            page_id: 1_000.into(),
+            ..link
+        };
+        assert!(matches!(
+            pages.select(bogus),
+            Err(ExecutionError::PageNotFound(_))
+        ));
+    }
 
-        let row = TestRow { a: 20, b: 20 };
-        let new_link = pages.insert(row).unwrap();
-        assert_eq!(new_link, link)
+    #[test]
+    fn page_stats() {
+        let pages = DataPages::<TestRow, 96>::new();
+
+        let links = (0..6)
+            .map(|i| pages.insert(TestRow { a: i, b: i }).unwrap())
+            .collect::<Vec<_>>();
+        pages.delete(links[0]).unwrap();
+        pages.delete(links[1]).unwrap();
+        pages.delete(links[4]).unwrap();
+
+        let stats = pages.page_stats();
+        assert_eq!(stats.len(), 2);
+        assert_eq!(stats[0].page_id, 0.into());
+        assert_eq!(stats[0].used, 96);
+        assert_eq!(stats[0].free, 0);
+        assert_eq!(stats[0].dead_slots, 2);
+        assert_eq!(stats[0].dead_bytes, 48);
+        assert_eq!(stats[1].used, 48);
+        assert_eq!(stats[1].free, 48);
+        assert_eq!(stats[1].dead_slots, 1);
+
+        let stats = pages.stats();
+        assert_eq!(stats.page_count, 2);
+        assert_eq!(stats.used, 144);
+        assert_eq!(stats.dead_slots, 3);
+        assert_eq!(stats.fragmentation(), 0.5);
+
+        // Empty links are not lost after snapshot.
+        assert_eq!(pages.get_empty_links().len(), 3);
     }
 
     #[test]
-    fn insert_full() {
-        let pages = DataPages::<TestRow>::new();
+    fn event_counters() {
+        let pages = DataPages::<TestRow, 96>::new();
+
+        let links = (0..6)
+            .map(|i| pages.insert(TestRow { a: i, b: i }).unwrap())
+            .collect::<Vec<_>>();
+        pages.delete(links[0]).unwrap();
+        pages.insert(TestRow { a: 6, b: 6 }).unwrap();
+
+        let stats = pages.stats();
+        assert_eq!(stats.pages_allocated, 1);
+        assert_eq!(stats.retry_inserts, 1);
+        assert_eq!(stats.links_reused, 1);
+    }
 
-        let row = TestRow { a: 10, b: 20 };
-        let _ = pages.insert(row).unwrap();
-        let res = pages.insert(row);
+    //#[test]
+    fn bench_reserved() {
+        let pages = Arc::new(DataPages::<TestRow>::new());
+        // 10 threads * 1000 rows * 24 bytes fit in 15 pages.
+        pages.reserve_pages(15);
 
-        assert!(res.is_ok())
+        let mut v = Vec::new();
+
+        let now = Instant::now();
+
+        for j in 0..10 {
+            let pages_shared = pages.clone();
+            let h = thread::spawn(move || {
+                for i in 0..1000 {
+                    let row = TestRow { a: i, b: j * i + 1 };
+
+                    pages_shared.insert(row).unwrap();
+                }
+            });
+
+            v.push(h)
+        }
+
+        for h in v {
+            h.join().unwrap()
+        }
+
+        let elapsed = now.elapsed();
+
+        println!("wt2 reserved {:?}", elapsed)
     }
 
     //#[test]
@@ -439,6 +3421,102 @@ mod tests {
         println!("wt2 {:?}", elapsed)
     }
 
+    //#[test]
+    fn bench_spare_pages() {
+        // Reader waits for pages read lock while writer holds write lock to add page, so its max wait shows how long
+        // write lock is held.
+        for spare_page_count in [0, SPARE_PAGES] {
+            let mut pages = DataPages::<TestRow>::new();
+            pages.set_spare_page_count(spare_page_count);
+            let pages = Arc::new(pages);
+            let done = Arc::new(AtomicBool::new(false));
+
+            let reader = {
+                let pages = pages.clone();
+                let done = done.clone();
+                thread::spawn(move || {
+                    let mut max = Duration::ZERO;
+                    let mut total = Duration::ZERO;
+                    let mut count = 0;
+                    while !done.load(Ordering::Relaxed) {
+                        let now = Instant::now();
+                        pages.get_page_count();
+                        let elapsed = now.elapsed();
+                        max = max.max(elapsed);
+                        total += elapsed;
+                        count += 1;
+                    }
+                    (max, total / count.max(1))
+                })
+            };
+
+            let now = Instant::now();
+            for i in 0..1_000_000 {
+                pages.insert(TestRow { a: i, b: i + 1 }).unwrap();
+            }
+            let elapsed = now.elapsed();
+            done.store(true, Ordering::Relaxed);
+            let (max, avg) = reader.join().unwrap();
+
+            println!(
+                "spare pages {spare_page_count}: insert {elapsed:?}, {} pages, read lock max wait {max:?}, avg wait {avg:?}",
+                pages.get_page_count()
+            );
+        }
+    }
+
+    //#[test]
+    fn bench_select() {
+        let pages = DataPages::<TestRow>::new();
+        let links = (0..1_000_000)
+            .map(|i| pages.insert(TestRow { a: i, b: i + 1 }).unwrap())
+            .collect::<Vec<_>>();
+
+        let now = Instant::now();
+        for link in &links {
+            pages.select(*link).unwrap();
+        }
+        println!("select {:?}", now.elapsed());
+
+        let now = Instant::now();
+        for row in pages.select_many(&links).unwrap() {
+            row.unwrap();
+        }
+        println!("select_many {:?}", now.elapsed());
+    }
+
+    //#[test]
+    fn bench_active_pages() {
+        for active_pages in [1, 8] {
+            let pages = Arc::new(DataPages::<TestRow>::with_active_pages(active_pages));
+
+            let mut v = Vec::new();
+
+            let now = Instant::now();
+
+            for j in 0..8 {
+                let pages_shared = pages.clone();
+                let h = thread::spawn(move || {
+                    for i in 0..100_000 {
+                        let row = TestRow { a: i, b: j * i + 1 };
+
+                        pages_shared.insert_with_key(row, j).unwrap();
+                    }
+                });
+
+                v.push(h)
+            }
+
+            for h in v {
+                h.join().unwrap()
+            }
+
+            let elapsed = now.elapsed();
+
+            println!("wt2 {} active pages {:?}", active_pages, elapsed)
+        }
+    }
+
     #[test]
     fn bench_set() {
         let pages = Arc::new(RwLock::new(HashSet::new()));
@@ -19,6 +19,18 @@ pub trait RowWrapper<Inner> {
 
 pub trait ArchivedRow {
     fn is_locked(&self) -> Option<u16>;
+
+    /// Returns `true` if row is marked as tombstone. Default implementation has no tombstone flag, so row is never
+    /// marked.
+    fn is_deleted(&self) -> bool {
+        false
+    }
+
+    /// Marks row as tombstone or clears the mark. Default implementation does nothing, so rows without tombstone flag
+    /// stay visible until their slot is freed.
+    fn set_deleted(&mut self, deleted: bool) {
+        let _ = deleted;
+    }
 }
 
 /// General `Row` wrapper that is used to append general data for every `Inner`
@@ -54,4 +66,12 @@ where
     fn is_locked(&self) -> Option<u16> {
         None
     }
+
+    fn is_deleted(&self) -> bool {
+        self.deleted
+    }
+
+    fn set_deleted(&mut self, deleted: bool) {
+        self.deleted = deleted
+    }
 }
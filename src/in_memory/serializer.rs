@@ -0,0 +1,115 @@
+use std::cell::RefCell;
+
+use rkyv::rancor::Strategy;
+use rkyv::ser::allocator::ArenaHandle;
+use rkyv::ser::sharing::Share;
+use rkyv::ser::Serializer;
+use rkyv::util::AlignedVec;
+use rkyv::Serialize;
+
+thread_local! {
+    /// Output buffer of the serializer which is reused by all serializations on the thread. `rkyv` already reuses
+    /// thread-local arena for scratch space, so output buffer is the only allocation left per serialization.
+    static BUFFER: RefCell<AlignedVec> = RefCell::new(AlignedVec::new());
+}
+
+/// Serializes `value` into thread-local buffer and calls `f` with serialized bytes, so repeated serializations don't
+/// allocate new buffer every time.
+///
+/// `N` is the size of the buffer. Buffer is preallocated to `N` bytes and buffer which has grown larger than both `N`
+/// and its previous capacity to fit bigger value is dropped after use, so thread doesn't keep memory of the biggest
+/// value it ever serialized. Smaller `N` doesn't drop buffer preallocated by call with bigger `N`. `Data` pages use
+/// their `DATA_LENGTH` as `N`, because bigger rows can't be stored anyway.
+pub fn with_serialized<const N: usize, T, Res, F>(
+    value: &T,
+    f: F,
+) -> Result<Res, rkyv::rancor::Error>
+where
+    T: for<'a> Serialize<
+        Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+    >,
+    F: FnOnce(&[u8]) -> Res,
+{
+    // Buffer is taken out of the cell, so nested call (if `f` serializes something too) just uses new buffer.
+    let mut buffer = BUFFER.with(|b| std::mem::take(&mut *b.borrow_mut()));
+    buffer.clear();
+    if buffer.capacity() < N {
+        buffer.reserve(N);
+    }
+    // `reserve` can round capacity up, so buffer is compared with its capacity after it, not with `N`.
+    let max_capacity = buffer.capacity();
+
+    let buffer = rkyv::api::high::to_bytes_in::<_, rkyv::rancor::Error>(value, buffer)?;
+    let res = f(buffer.as_slice());
+
+    if buffer.capacity() <= max_capacity {
+        BUFFER.with(|b| *b.borrow_mut() = buffer);
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{with_serialized, BUFFER};
+
+    #[test]
+    fn buffer_is_reused() {
+        let bytes = with_serialized::<64, _, _, _>(&10u64, |bytes| bytes.to_vec()).unwrap();
+        assert_eq!(
+            bytes,
+            rkyv::to_bytes::<rkyv::rancor::Error>(&10u64)
+                .unwrap()
+                .as_slice()
+        );
+
+        let ptr = BUFFER.with(|b| b.borrow().as_ptr());
+        with_serialized::<64, _, _, _>(&20u64, |_| ()).unwrap();
+        assert_eq!(BUFFER.with(|b| b.borrow().as_ptr()), ptr);
+    }
+
+    #[test]
+    fn buffer_of_not_power_of_two_size_is_reused() {
+        with_serialized::<1000, _, _, _>(&10u64, |_| ()).unwrap();
+        let ptr = BUFFER.with(|b| b.borrow().as_ptr());
+        with_serialized::<1000, _, _, _>(&vec![1u8; 900], |_| ()).unwrap();
+        assert_eq!(BUFFER.with(|b| b.borrow().as_ptr()), ptr);
+    }
+
+    #[test]
+    fn big_buffer_is_dropped() {
+        with_serialized::<64, _, _, _>(&vec![1u8; 1024], |_| ()).unwrap();
+        assert_eq!(BUFFER.with(|b| b.borrow().capacity()), 0);
+    }
+
+    #[test]
+    fn small_size_keeps_big_buffer() {
+        with_serialized::<1024, _, _, _>(&10u64, |_| ()).unwrap();
+        let capacity = BUFFER.with(|b| b.borrow().capacity());
+        with_serialized::<64, _, _, _>(&10u64, |_| ()).unwrap();
+        assert_eq!(BUFFER.with(|b| b.borrow().capacity()), capacity);
+    }
+
+    //#[test]
+    fn bench_with_serialized() {
+        use std::time::Instant;
+
+        let count = 5_000_000u64;
+
+        let now = Instant::now();
+        for i in 0..count {
+            let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&(i, i + 1)).unwrap();
+            std::hint::black_box(bytes);
+        }
+        println!("to_bytes {:?}", now.elapsed());
+
+        let now = Instant::now();
+        for i in 0..count {
+            with_serialized::<64, _, _, _>(&(i, i + 1), |bytes| {
+                std::hint::black_box(bytes);
+            })
+            .unwrap();
+        }
+        println!("with_serialized {:?}", now.elapsed());
+    }
+}
@@ -0,0 +1,126 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use data_bucket::page::PageId;
+
+use crate::in_memory::data::AlignedBytes;
+
+/// Options of the cold pages eviction. When count of the resident [`Data`] pages is bigger than
+/// `max_resident_pages`, least recently used pages are written to the file at `path` and their memory is freed. Evicted
+/// page is loaded back on the first access.
+///
+/// [`Data`]: crate::in_memory::Data
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SpillConfig {
+    /// Path of the file where evicted pages are stored. File is created on the first eviction and removed when pages
+    /// are dropped.
+    pub path: PathBuf,
+
+    /// Count of the pages which are kept in memory.
+    pub max_resident_pages: usize,
+}
+
+/// File where evicted [`Data`] pages are stored. Every page is written at offset of its id, so evicted again page
+/// replaces its previous bytes and file doesn't need any index.
+///
+/// [`Data`]: crate::in_memory::Data
+#[derive(Debug)]
+pub struct SpillFile {
+    file: Mutex<File>,
+    path: PathBuf,
+
+    /// Shows if any page was loaded back from this file since the last eviction pass, so resident pages can be over
+    /// the limit.
+    loaded: AtomicBool,
+}
+
+impl SpillFile {
+    /// Creates empty spill file at `path`. Existing file is truncated, because its pages belong to other pages
+    /// instance.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path.as_ref())?;
+        Ok(Self {
+            file: Mutex::new(file),
+            path: path.as_ref().to_path_buf(),
+            loaded: AtomicBool::new(false),
+        })
+    }
+
+    /// Returns path of this [`SpillFile`].
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Writes bytes of the page with provided id.
+    pub fn write_page<const N: usize>(
+        &self,
+        id: PageId,
+        bytes: &AlignedBytes<N>,
+    ) -> io::Result<()> {
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        file.seek(SeekFrom::Start(Self::offset::<N>(id)))?;
+        file.write_all(&bytes.0)
+    }
+
+    /// Reads bytes of the page with provided id, which were written by [`SpillFile::write_page`].
+    pub fn read_page<const N: usize>(&self, id: PageId) -> io::Result<Box<AlignedBytes<N>>> {
+        let mut bytes = Box::new(AlignedBytes([0; N]));
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        file.seek(SeekFrom::Start(Self::offset::<N>(id)))?;
+        file.read_exact(&mut bytes.0)?;
+        Ok(bytes)
+    }
+
+    /// Marks that page was loaded back from this file.
+    pub fn mark_loaded(&self) {
+        self.loaded.store(true, Ordering::Release);
+    }
+
+    /// Returns if any page was loaded back since previous call and resets it.
+    pub fn take_loaded(&self) -> bool {
+        self.loaded.swap(false, Ordering::AcqRel)
+    }
+
+    fn offset<const N: usize>(id: PageId) -> u64 {
+        let id: usize = id.into();
+        (id * N) as u64
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        // Evicted pages are not needed after pages are dropped, and there is nothing to do if file can't be removed.
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::in_memory::data::AlignedBytes;
+
+    use super::SpillFile;
+
+    #[test]
+    fn write_read_page() {
+        let path =
+            std::env::temp_dir().join(format!("worktable-spill-{}.bin", uuid::Uuid::new_v4()));
+        let file = SpillFile::create(&path).unwrap();
+
+        file.write_page(2.into(), &AlignedBytes([2; 32])).unwrap();
+        file.write_page(0.into(), &AlignedBytes([1; 32])).unwrap();
+
+        assert_eq!(file.read_page::<32>(0.into()).unwrap().0, [1; 32]);
+        assert_eq!(file.read_page::<32>(2.into()).unwrap().0, [2; 32]);
+
+        drop(file);
+        assert!(!path.exists());
+    }
+}
@@ -8,7 +8,10 @@ use crate::prelude::LockFreeSet;
 mod table_index;
 mod table_secondary_index;
 
-pub use table_index::{IndexSet, KeyValue, LockFreeMap, LockedHashMap, TableIndex};
+pub use table_index::{
+    AdaptiveIndex, IndexSet, KeyValue, LockFreeMap, LockedHashMap, TableIndex,
+    ADAPTIVE_INDEX_THRESHOLD,
+};
 pub use table_secondary_index::TableSecondaryIndex;
 
 pub enum IndexType<'a, T> {
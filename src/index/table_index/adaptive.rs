@@ -0,0 +1,301 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::ops::{Bound, RangeBounds};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::{IndexSet, TableIndex};
+
+/// Count of entries after which [`AdaptiveIndex`] moves them from sorted `Vec` to [`IndexSet`].
+pub const ADAPTIVE_INDEX_THRESHOLD: usize = 64;
+
+/// Index for the small tables. Entries are kept in sorted `Vec` until their count reaches
+/// [`ADAPTIVE_INDEX_THRESHOLD`], so lookup of the tiny table is binary search and insert is shift of few entries
+/// instead of tree's maintenance. Insert that reaches threshold moves all entries to [`IndexSet`] once, and index
+/// uses it since then, even if entries are removed later.
+///
+/// Iterator of the small index holds snapshot of its entries, so it's not changed by concurrent writes and doesn't
+/// block them. Write copies entries only if some iterator still holds them. It's default index of the primary key.
+#[derive(Debug)]
+pub struct AdaptiveIndex<K, V> {
+    small: RwLock<Arc<Vec<(K, V)>>>,
+    tree: IndexSet<K, V>,
+    promoted: AtomicBool,
+}
+
+impl<K, V> AdaptiveIndex<K, V>
+where
+    K: Debug + Clone + Ord + Send + Sync + 'static,
+    V: Debug + Clone + Send + Sync + Default + 'static,
+{
+    pub fn new() -> Self {
+        Self {
+            small: RwLock::new(Arc::new(vec![])),
+            tree: IndexSet::default(),
+            promoted: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns `true` if entries were moved to the [`IndexSet`].
+    pub fn is_promoted(&self) -> bool {
+        self.promoted.load(Ordering::Acquire)
+    }
+
+    /// Copies all entries of the small index to the tree. Caller must hold write lock of the small index, so no entry
+    /// is added to it after copy.
+    fn promote(&self, small: &[(K, V)]) {
+        for (key, value) in small {
+            let _ = TableIndex::insert(&self.tree, key.clone(), value.clone());
+        }
+        self.promoted.store(true, Ordering::Release);
+    }
+
+    /// Returns snapshot of the small index's entries, or `None` if index is promoted.
+    fn small_entries(&self) -> Option<Arc<Vec<(K, V)>>> {
+        if self.is_promoted() {
+            return None;
+        }
+        let small = self.small.read().unwrap_or_else(PoisonError::into_inner);
+        if self.is_promoted() {
+            return None;
+        }
+        Some(small.clone())
+    }
+}
+
+/// Inserts entry to the small index's `Vec` at `pos`. Capacity is reserved for all entries before promotion at once,
+/// so insert never reallocates.
+fn insert_small<K, V>(small: &mut Vec<(K, V)>, pos: usize, key: K, value: V) {
+    if small.capacity() < ADAPTIVE_INDEX_THRESHOLD {
+        small.reserve_exact(ADAPTIVE_INDEX_THRESHOLD - small.len());
+    }
+    small.insert(pos, (key, value));
+}
+
+/// Iterator over the snapshot of the small index's entries.
+struct SmallIter<'a, K, V> {
+    entries: Arc<Vec<(K, V)>>,
+    pos: usize,
+    end: usize,
+    phantom: PhantomData<&'a (K, V)>,
+}
+
+impl<'a, K, V> SmallIter<'a, K, V> {
+    fn new(entries: Option<Arc<Vec<(K, V)>>>) -> Self {
+        let entries = entries.unwrap_or_default();
+        let end = entries.len();
+        Self {
+            entries,
+            pos: 0,
+            end,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for SmallIter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.end {
+            return None;
+        }
+        let (key, value) = &self.entries[self.pos];
+        self.pos += 1;
+        // SAFETY: snapshot is never changed while it's shared, because writers copy it, and it's held by iterator.
+        unsafe { Some((&*(key as *const K), &*(value as *const V))) }
+    }
+}
+
+impl<K, V> Default for AdaptiveIndex<K, V>
+where
+    K: Debug + Clone + Ord + Send + Sync + 'static,
+    V: Debug + Clone + Send + Sync + Default + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> TableIndex<K, V> for AdaptiveIndex<K, V>
+where
+    K: Debug + Clone + Ord + Send + Sync + 'static,
+    V: Debug + Clone + Send + Sync + Default + 'static,
+{
+    fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
+        if !self.is_promoted() {
+            let mut small = self.small.write().unwrap_or_else(PoisonError::into_inner);
+            if !self.is_promoted() {
+                match small.binary_search_by(|(k, _)| k.cmp(&key)) {
+                    Ok(pos) => return Err((key, small[pos].1.clone())),
+                    Err(pos) if small.len() < ADAPTIVE_INDEX_THRESHOLD => {
+                        insert_small(Arc::make_mut(&mut small), pos, key, value);
+                        return Ok(());
+                    }
+                    Err(_) => self.promote(&small),
+                }
+            }
+        }
+        TableIndex::insert(&self.tree, key, value)
+    }
+
+    fn peek(&self, key: &K) -> Option<V> {
+        if !self.is_promoted() {
+            let small = self.small.read().unwrap_or_else(PoisonError::into_inner);
+            if !self.is_promoted() {
+                return small
+                    .binary_search_by(|(k, _)| k.cmp(key))
+                    .ok()
+                    .map(|pos| small[pos].1.clone());
+            }
+        }
+        TableIndex::peek(&self.tree, key)
+    }
+
+    fn remove(&self, key: &K) -> bool {
+        if !self.is_promoted() {
+            let mut small = self.small.write().unwrap_or_else(PoisonError::into_inner);
+            if !self.is_promoted() {
+                return match small.binary_search_by(|(k, _)| k.cmp(key)) {
+                    Ok(pos) => {
+                        Arc::make_mut(&mut small).remove(pos);
+                        true
+                    }
+                    Err(_) => false,
+                };
+            }
+        }
+        TableIndex::remove(&self.tree, key)
+    }
+
+    fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        let small = self.small_entries();
+        // Tree is empty until promotion, and it's skipped if small entries were taken before promotion, so no entry
+        // is returned twice.
+        let tree_len = if small.is_some() { 0 } else { usize::MAX };
+        SmallIter::new(small).chain(TableIndex::iter(&self.tree).take(tree_len))
+    }
+
+    fn range<'a, R: RangeBounds<K>>(&'a self, range: R) -> impl Iterator<Item = (&'a K, &'a V)>
+    where
+        K: 'a,
+        V: 'a,
+    {
+        let small = self.small_entries();
+        let tree_len = if small.is_some() { 0 } else { usize::MAX };
+        let mut small = SmallIter::new(small);
+        let entries = small.entries.as_slice();
+        let start = match range.start_bound() {
+            Bound::Included(key) => entries.partition_point(|(k, _)| k < key),
+            Bound::Excluded(key) => entries.partition_point(|(k, _)| k <= key),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(key) => entries.partition_point(|(k, _)| k <= key),
+            Bound::Excluded(key) => entries.partition_point(|(k, _)| k < key),
+            Bound::Unbounded => entries.len(),
+        };
+        small.pos = start;
+        small.end = end.max(start);
+        small.chain(TableIndex::range(&self.tree, range).take(tree_len))
+    }
+
+    fn replace(&self, key: K, value: V) {
+        if !self.is_promoted() {
+            let mut small = self.small.write().unwrap_or_else(PoisonError::into_inner);
+            if !self.is_promoted() {
+                match small.binary_search_by(|(k, _)| k.cmp(&key)) {
+                    Ok(pos) => {
+                        Arc::make_mut(&mut small)[pos].1 = value;
+                        return;
+                    }
+                    Err(pos) if small.len() < ADAPTIVE_INDEX_THRESHOLD => {
+                        insert_small(Arc::make_mut(&mut small), pos, key, value);
+                        return;
+                    }
+                    Err(_) => self.promote(&small),
+                }
+            }
+        }
+        TableIndex::remove(&self.tree, &key);
+        let _ = TableIndex::insert(&self.tree, key, value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::index::table_index::adaptive::{AdaptiveIndex, ADAPTIVE_INDEX_THRESHOLD};
+    use crate::TableIndex;
+
+    #[test]
+    fn small() {
+        let index = AdaptiveIndex::<u64, u64>::new();
+        for key in [5, 1, 3] {
+            index.insert(key, key * 10).unwrap();
+        }
+        assert_eq!(index.insert(3, 0), Err((3, 30)));
+        assert_eq!(index.peek(&3), Some(30));
+        assert_eq!(index.peek(&2), None);
+
+        assert_eq!(
+            TableIndex::iter(&index).collect::<Vec<_>>(),
+            vec![(&1, &10), (&3, &30), (&5, &50)]
+        );
+        assert_eq!(
+            TableIndex::range(&index, 2..=5).collect::<Vec<_>>(),
+            vec![(&3, &30), (&5, &50)]
+        );
+
+        index.replace(3, 33);
+        assert_eq!(index.peek(&3), Some(33));
+        assert!(index.remove(&1));
+        assert!(!index.remove(&1));
+        assert!(!index.is_promoted());
+    }
+
+    #[test]
+    fn iter_snapshot() {
+        let index = AdaptiveIndex::<u64, u64>::new();
+        for key in [1, 2, 3] {
+            index.insert(key, key).unwrap();
+        }
+        let iter = TableIndex::iter(&index);
+        let range = TableIndex::range(&index, 2..);
+        assert!(index.remove(&2));
+        index.insert(0, 0).unwrap();
+        index.replace(3, 30);
+
+        assert_eq!(iter.collect::<Vec<_>>(), vec![(&1, &1), (&2, &2), (&3, &3)]);
+        assert_eq!(range.collect::<Vec<_>>(), vec![(&2, &2), (&3, &3)]);
+        assert_eq!(
+            TableIndex::iter(&index).collect::<Vec<_>>(),
+            vec![(&0, &0), (&1, &1), (&3, &30)]
+        );
+    }
+
+    #[test]
+    fn promotion() {
+        let index = AdaptiveIndex::<u64, u64>::new();
+        let count = ADAPTIVE_INDEX_THRESHOLD as u64 + 10;
+        for key in (0..count).rev() {
+            index.insert(key, key).unwrap();
+        }
+        assert!(index.is_promoted());
+
+        assert_eq!(
+            TableIndex::iter(&index)
+                .map(|(k, _)| *k)
+                .collect::<Vec<_>>(),
+            (0..count).collect::<Vec<_>>()
+        );
+        assert_eq!(TableIndex::range(&index, 10..20).count(), 10);
+        assert_eq!(index.insert(1, 0), Err((1, 1)));
+        assert!(index.remove(&1));
+        assert_eq!(index.peek(&1), None);
+        assert_eq!(index.peek(&2), Some(2));
+    }
+}
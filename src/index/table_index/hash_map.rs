@@ -2,7 +2,7 @@ use std::collections::HashMap;
 use std::hash::Hash;
 use std::intrinsics::transmute;
 use std::ops::RangeBounds;
-use std::sync::{RwLock, RwLockReadGuard};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard};
 
 use crate::TableIndex;
 
@@ -18,7 +18,11 @@ where
     V: Clone + Send + Sync + 'static,
 {
     fn insert(&self, key: K, value: V) -> Result<(), (K, V)> {
-        if let Some(v) = self.write().unwrap().insert(key.clone(), value) {
+        if let Some(v) = self
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key.clone(), value)
+        {
             Err((key, v))
         } else {
             Ok(())
@@ -26,11 +30,23 @@ where
     }
 
     fn peek(&self, key: &K) -> Option<V> {
-        self.read().unwrap().get(key).cloned()
+        self.read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(key)
+            .cloned()
+    }
+
+    fn replace(&self, key: K, value: V) {
+        self.write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(key, value);
     }
 
     fn remove(&self, key: &K) -> bool {
-        self.write().unwrap().remove(key).is_some()
+        self.write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(key)
+            .is_some()
     }
 
     fn iter<'a>(&'a self) -> impl Iterator<Item = (&'a K, &'a V)>
@@ -39,7 +55,9 @@ where
         V: 'a,
     {
         unsafe {
-            transmute::<_, std::collections::hash_map::Iter<'a, _, _>>(self.read().unwrap().iter())
+            transmute::<_, std::collections::hash_map::Iter<'a, _, _>>(
+                self.read().unwrap_or_else(PoisonError::into_inner).iter(),
+            )
         }
     }
 
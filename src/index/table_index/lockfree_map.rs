@@ -21,6 +21,10 @@ where
         lockfree::map::Map::get(self, key).map(|v| v.val().clone())
     }
 
+    fn replace(&self, key: K, value: V) {
+        lockfree::map::Map::insert(self, key, value);
+    }
+
     fn remove(&self, key: &K) -> bool {
         lockfree::map::Map::remove(self, key).is_some()
     }
@@ -1,11 +1,13 @@
 use std::ops::RangeBounds;
 
+mod adaptive;
 mod bplus_tree;
 mod hash_map;
 mod index_set;
 mod lockfree_map;
 mod tree_index;
 
+pub use adaptive::{AdaptiveIndex, ADAPTIVE_INDEX_THRESHOLD};
 pub use hash_map::LockedHashMap;
 pub use index_set::{IndexSet, KeyValue};
 pub use lockfree::map::Map as LockFreeMap;
@@ -22,4 +24,131 @@ pub trait TableIndex<K, V> {
     where
         K: 'a,
         V: 'a;
+
+    /// Sets `value` for the `key`, replacing existing one. Default implementation removes old entry first, so `key` is
+    /// briefly not presented in index. Indexes that can swap value in place override it.
+    fn replace(&self, key: K, value: V) {
+        self.remove(&key);
+        let _ = self.insert(key, value);
+    }
+
+    /// Removes all entries from index.
+    fn clear(&self)
+    where
+        K: Clone,
+    {
+        let keys = self.iter().map(|(k, _)| k.clone()).collect::<Vec<_>>();
+        for k in keys {
+            self.remove(&k);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::RwLock;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::{AdaptiveIndex, IndexSet, TableIndex};
+
+    /// Runs 90/10 read/write workload on `threads` threads. Every thread inserts its own keys and reads the keys it
+    /// inserted before, so every read must find its key.
+    fn mixed_workload<I>(index: &I, threads: u64, ops: u64) -> Duration
+    where
+        I: TableIndex<u64, u64> + Sync,
+    {
+        let now = Instant::now();
+        thread::scope(|s| {
+            for t in 0..threads {
+                s.spawn(move || {
+                    let first = t * ops;
+                    for i in 0..ops {
+                        if i % 10 == 0 {
+                            index.insert(first + i, i).unwrap();
+                        } else {
+                            let key = first + i / 10 * 10;
+                            assert_eq!(index.peek(&key), Some(i / 10 * 10));
+                        }
+                    }
+                });
+            }
+        });
+
+        now.elapsed()
+    }
+
+    #[test]
+    fn concurrent_mixed_workload() {
+        let index_set = IndexSet::<u64, u64>::new();
+        mixed_workload(&index_set, 4, 1_000);
+        assert_eq!(TableIndex::iter(&index_set).count(), 400);
+
+        let tree_index = scc::TreeIndex::<u64, u64>::new();
+        mixed_workload(&tree_index, 4, 1_000);
+        assert_eq!(TableIndex::iter(&tree_index).count(), 400);
+
+        let locked = RwLock::new(HashMap::<u64, u64>::new());
+        mixed_workload(&locked, 4, 1_000);
+        assert_eq!(TableIndex::peek(&locked, &3_990), Some(990));
+
+        let adaptive = AdaptiveIndex::<u64, u64>::new();
+        mixed_workload(&adaptive, 4, 1_000);
+        assert!(adaptive.is_promoted());
+        assert_eq!(TableIndex::iter(&adaptive).count(), 400);
+    }
+
+    /// Inserts 10 keys and reads them `reads` times, like lookups in the small table.
+    fn small_table_workload<I>(index: &I, reads: u64) -> Duration
+    where
+        I: TableIndex<u64, u64>,
+    {
+        let now = Instant::now();
+        for key in 0..10 {
+            index.insert(key, key).unwrap();
+        }
+        for i in 0..reads {
+            assert_eq!(index.peek(&(i % 10)), Some(i % 10));
+        }
+
+        now.elapsed()
+    }
+
+    /// Compares lookups of the small table. Run by `cargo test --release bench_small_table -- --ignored --nocapture`.
+    #[test]
+    #[ignore = "benchmark"]
+    fn bench_small_table() {
+        let reads = 1_000_000;
+
+        let index_set = IndexSet::<u64, u64>::new();
+        println!("index set {:?}", small_table_workload(&index_set, reads));
+
+        let adaptive = AdaptiveIndex::<u64, u64>::new();
+        println!("adaptive {:?}", small_table_workload(&adaptive, reads));
+    }
+
+    //#[test]
+    fn bench_mixed_workload() {
+        let threads = 8;
+        let ops = 200_000;
+
+        let locked = RwLock::new(HashMap::<u64, u64>::new());
+        println!(
+            "locked hash map {:?}",
+            mixed_workload(&locked, threads, ops)
+        );
+
+        let index_set = IndexSet::<u64, u64>::new();
+        println!("index set {:?}", mixed_workload(&index_set, threads, ops));
+
+        let tree_index = scc::TreeIndex::<u64, u64>::new();
+        println!("tree index {:?}", mixed_workload(&tree_index, threads, ops));
+
+        let lockfree_map = lockfree::map::Map::<u64, u64>::new();
+        println!(
+            "lockfree map {:?}",
+            mixed_workload(&lockfree_map, threads, ops)
+        );
+    }
 }
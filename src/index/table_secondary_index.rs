@@ -1,11 +1,40 @@
 use data_bucket::Link;
 
-use crate::WorkTableError;
+use crate::{BatchError, WorkTableError};
 
 pub trait TableSecondaryIndex<Row> {
     fn save_row(&self, row: Row, link: Link) -> Result<(), WorkTableError>;
 
     fn delete_row(&self, row: Row, link: Link) -> Result<(), WorkTableError>;
+
+    /// Checks that all provided rows can be saved in unique indexes without collisions with existing values or with
+    /// each other. Default implementation reports no collisions, which fits indexes without unique ones.
+    fn validate_rows(&self, rows: &[Row]) -> Vec<BatchError> {
+        let _ = rows;
+        vec![]
+    }
+
+    /// Removes all entries from all indexes. Default implementation does nothing, which fits tables without secondary
+    /// indexes.
+    fn clear(&self) {}
+
+    /// Removes entries of all provided rows. If removal of one row fails, entries of already removed rows are saved
+    /// back and error is returned. Default implementation removes rows one by one.
+    fn delete_rows(&self, rows: &[(Row, Link)]) -> Result<(), WorkTableError>
+    where
+        Row: Clone,
+    {
+        for (pos, (row, link)) in rows.iter().enumerate() {
+            if let Err(e) = self.delete_row(row.clone(), *link) {
+                for (row, link) in &rows[..pos] {
+                    let _ = self.save_row(row.clone(), *link);
+                }
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl<Row> TableSecondaryIndex<Row> for () {
@@ -16,4 +45,10 @@ impl<Row> TableSecondaryIndex<Row> for () {
     fn delete_row(&self, _: Row, _: Link) -> Result<(), WorkTableError> {
         Ok(())
     }
+
+    fn validate_rows(&self, _: &[Row]) -> Vec<BatchError> {
+        vec![]
+    }
+
+    fn clear(&self) {}
 }
@@ -1,10 +1,22 @@
+#[cfg(feature = "async")]
+mod blocking;
+mod checksum;
 pub mod in_memory;
 mod index;
 pub mod lock;
+pub mod metrics;
+mod migration;
 mod primary_key;
+mod recovery;
 mod row;
 mod table;
+mod wal;
 pub use data_bucket as persistence;
+// Used by code generated for tables with `serde` config, so crates that use it don't need their own dependencies.
+#[cfg(feature = "serde")]
+pub use serde;
+#[cfg(feature = "serde")]
+pub use serde_json;
 mod database;
 
 // mod ty;
@@ -19,16 +31,39 @@ pub use table::*;
 pub use worktable_codegen::worktable;
 
 pub mod prelude {
-    pub use crate::database::DatabaseManager;
-    pub use crate::in_memory::{ArchivedRow, Data, DataPages, RowWrapper, StorableRow};
-    pub use crate::lock::LockMap;
-    pub use crate::primary_key::{PrimaryKeyGenerator, PrimaryKeyGeneratorState, TablePrimaryKey};
+    #[cfg(feature = "async")]
+    pub use crate::blocking::run_blocking;
+    pub use crate::checksum::{
+        checksum_path, update_checksums, verify_checksums, write_checksums, Blake3, Checksum,
+        ChecksumAlgorithm, ChecksumError, Crc32, XxHash64,
+    };
+    pub use crate::database::{
+        page_bytes, page_intervals, parse_pages, parse_space_info, persist_pages, table_file_path,
+        validate_disjoint_intervals, validate_space_intervals, CheckpointHandle, DatabaseManager,
+        DurabilityMode, IntervalError, PageParseError, PathError, PersistStats, PersistableTable,
+        SpaceDiff, WritePolicy, RESERVED_FILE_NAMES,
+    };
+    pub use crate::in_memory::{
+        ArchivedRow, Data, DataPages, DataPagesStats, EmptyLinkPolicy, PageStat,
+        PagesExecutionError, RowWrapper, SpillConfig, StorableRow, VersionedLink,
+    };
+    pub use crate::lock::{LockMap, RowLockGuard};
+    pub use crate::migration::{
+        migrate_file, register_migration, MigratedFile, Migration, VersionError,
+    };
+    pub use crate::primary_key::{
+        serialized_key_hash, PrimaryKeyGenerator, PrimaryKeyGeneratorState, TablePrimaryKey,
+        UuidV7Generator,
+    };
+    pub use crate::recovery::LenientParseReport;
     pub use crate::table::select::{
         Order, SelectQueryBuilder, SelectQueryExecutor, SelectResult, SelectResultExecutor,
     };
+    pub use crate::wal::{wal_path, Wal, WalError, WalOp, WalRecord, WalWriter};
     pub use crate::{
-        lock::Lock, IndexSet, KeyValue, LockFreeMap, LockedHashMap, TableIndex, TableRow,
-        TableSecondaryIndex, WorkTable, WorkTableError,
+        lock::Lock, AdaptiveIndex, BatchError, ImportError, IndexSet, IntegrityError, KeyValue,
+        LockFreeMap, LockedHashMap, ReadOnlyTable, TableIndex, TableRow, TableSecondaryIndex,
+        Transaction, TransactionOp, WorkTable, WorkTableError,
     };
     pub use data_bucket::{
         align, map_data_pages_to_general, map_index_pages_to_general, map_tree_index,
@@ -3,6 +3,7 @@ mod set;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use derive_more::From;
@@ -48,3 +49,34 @@ impl Lock {
         self.waker.wake()
     }
 }
+
+/// Row's [`Lock`] taken by generated write, which is registered in [`LockMap`] under its id. Lock is released and
+/// removed from the map when guard is dropped, so it's released on every exit path of the write, including errors.
+#[derive(Debug)]
+pub struct RowLockGuard<'a> {
+    id: u16,
+    lock: Arc<Lock>,
+    lock_map: &'a LockMap,
+}
+
+impl<'a> RowLockGuard<'a> {
+    /// Creates new locked [`Lock`] and registers it in `lock_map`.
+    pub fn new(lock_map: &'a LockMap) -> Self {
+        let id = lock_map.next_id();
+        let lock = Arc::new(Lock::new());
+        lock_map.insert(id.into(), lock.clone());
+        Self { id, lock, lock_map }
+    }
+
+    /// Returns id of the lock, which is written to the locked row.
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+}
+
+impl Drop for RowLockGuard<'_> {
+    fn drop(&mut self) {
+        self.lock.unlock();
+        self.lock_map.remove(&self.id.into());
+    }
+}
@@ -0,0 +1,47 @@
+//! Names of the counters and gauges which are emitted through [`metrics`](https://docs.rs/metrics) facade when
+//! `metrics` feature is enabled. Every metric has `table` label with table's name, so one exporter collects metrics of
+//! all tables. Without the feature nothing is emitted and recording calls are compiled to nothing.
+
+/// Count of rows saved to the data pages, including rows moved by updates.
+pub const ROWS_INSERTED: &str = "worktable_rows_inserted_total";
+
+/// Count of rows deleted from the data pages, including rows moved by updates.
+pub const ROWS_DELETED: &str = "worktable_rows_deleted_total";
+
+/// Count of rows read from the data pages.
+pub const SELECTS: &str = "worktable_selects_total";
+
+/// Count of inserts that didn't fit in their page and were retried.
+pub const INSERT_RETRIES: &str = "worktable_insert_retries_total";
+
+/// Count of times inserts moved to the next page, because their page was full.
+pub const PAGES_ALLOCATED: &str = "worktable_pages_allocated_total";
+
+/// Count of inserts that reused deleted row's slot from the free list.
+pub const LINKS_REUSED: &str = "worktable_links_reused_total";
+
+/// Count of inserts that were saved to the free tail of the earlier page.
+pub const TAILS_REUSED: &str = "worktable_tails_reused_total";
+
+/// Count of bytes written to the table's files by full and streaming persists and checkpoints. `persist_append` is
+/// not counted.
+pub const BYTES_PERSISTED: &str = "worktable_bytes_persisted_total";
+
+/// Current count of the data pages.
+pub const PAGES: &str = "worktable_pages";
+
+#[inline(always)]
+pub(crate) fn increment(name: &'static str, table: &'static str, value: u64) {
+    #[cfg(feature = "metrics")]
+    ::metrics::counter!(name, "table" => table).increment(value);
+    #[cfg(not(feature = "metrics"))]
+    let _ = (name, table, value);
+}
+
+#[inline(always)]
+pub(crate) fn set(name: &'static str, table: &'static str, value: usize) {
+    #[cfg(feature = "metrics")]
+    ::metrics::gauge!(name, "table" => table).set(value as f64);
+    #[cfg(not(feature = "metrics"))]
+    let _ = (name, table, value);
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::{OnceLock, RwLock};
+
+use data_bucket::DATA_VERSION;
+use derive_more::{Display, Error};
+
+/// Migration of the whole file's bytes from some data version to the next one.
+pub type Migration = fn(Vec<u8>) -> eyre::Result<Vec<u8>>;
+
+fn migrations() -> &'static RwLock<HashMap<u32, Migration>> {
+    static MIGRATIONS: OnceLock<RwLock<HashMap<u32, Migration>>> = OnceLock::new();
+    MIGRATIONS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Registers [`Migration`] that converts file with `from` data version to `from + 1` version. Registering migration
+/// for same version again replaces previous one.
+pub fn register_migration(from: u32, migration: Migration) {
+    migrations()
+        .write()
+        .expect("lock should be not poisoned")
+        .insert(from, migration);
+}
+
+/// Migrates file with `found` data version to current [`DATA_VERSION`] by applying registered migrations one by one.
+/// Migrated bytes are written to temporary file, which is returned. Original file is not changed. File with data
+/// version newer than [`DATA_VERSION`] can't be migrated, so [`VersionError::FileFromNewerVersion`] is returned.
+pub fn migrate_file(file: &mut File, found: u32) -> eyre::Result<MigratedFile> {
+    let expected = DATA_VERSION as u32;
+    if found > expected {
+        return Err(VersionError::FileFromNewerVersion {
+            found,
+            supported: expected,
+        }
+        .into());
+    }
+
+    let mut bytes = vec![];
+    file.seek(SeekFrom::Start(0))?;
+    file.read_to_end(&mut bytes)?;
+    for version in found..expected {
+        let migration = migrations()
+            .read()
+            .expect("lock should be not poisoned")
+            .get(&version)
+            .copied()
+            .ok_or(VersionError::UnsupportedVersion { found, expected })?;
+        bytes = migration(bytes)?;
+    }
+
+    let path =
+        std::env::temp_dir().join(format!("worktable-migration-{}.wt", uuid::Uuid::new_v4()));
+    let mut migrated = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&path)?;
+    migrated.write_all(&bytes)?;
+
+    Ok(MigratedFile {
+        file: Some(migrated),
+        path,
+    })
+}
+
+/// Temporary file with migrated data. It's removed on drop.
+#[derive(Debug)]
+pub struct MigratedFile {
+    file: Option<File>,
+    path: PathBuf,
+}
+
+impl MigratedFile {
+    pub fn file(&mut self) -> &mut File {
+        self.file.as_mut().expect("is set until drop")
+    }
+}
+
+impl Drop for MigratedFile {
+    fn drop(&mut self) {
+        // File must be closed before removal.
+        self.file.take();
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[derive(Debug, Display, Error)]
+pub enum VersionError {
+    /// File's data version is not current and there is no migration chain from it.
+    #[display("unsupported data version {}, expected {}", found, expected)]
+    UnsupportedVersion { found: u32, expected: u32 },
+
+    /// File was written by newer version of the crate, so it can't be read by this one. It must not be overwritten,
+    /// because its data will be lost.
+    #[display(
+        "file's data version {} is newer than supported version {}",
+        found,
+        supported
+    )]
+    FileFromNewerVersion { found: u32, supported: u32 },
+
+    /// All migrations were applied, but migrated file's data version is still not current, so some migration didn't
+    /// update it.
+    #[display(
+        "migrated file's data version {} doesn't match expected version {}",
+        found,
+        expected
+    )]
+    MigratedVersionMismatch { found: u32, expected: u32 },
+}
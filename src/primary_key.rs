@@ -1,19 +1,64 @@
+use std::hash::{DefaultHasher, Hasher};
 use std::sync::atomic::{AtomicI64, AtomicU32, AtomicU64, Ordering};
 
+use rkyv::rancor::Strategy;
+use rkyv::ser::allocator::ArenaHandle;
+use rkyv::ser::sharing::Share;
+use rkyv::ser::Serializer;
+use rkyv::util::AlignedVec;
+use rkyv::Serialize;
+use uuid::Uuid;
+
+use crate::in_memory::with_serialized;
+
 pub trait TablePrimaryKey {
     type Generator;
+
+    /// Returns key which is used to choose active page for the row with this primary key, so inserts of different
+    /// keys are spread over active pages. Default implementation returns same key for all rows, so all of them go to
+    /// the same active page.
+    fn active_page_key(&self) -> u64 {
+        0
+    }
+}
+
+/// Hashes serialized `key`. It's used as [`TablePrimaryKey::active_page_key`] for generated primary keys. Serialization
+/// reuses thread-local buffer, so it doesn't allocate on every insert.
+pub fn serialized_key_hash<T>(key: &T) -> u64
+where
+    T: for<'a> Serialize<
+        Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+    >,
+{
+    with_serialized::<64, _, _, _>(key, |bytes| {
+        let mut hasher = DefaultHasher::new();
+        hasher.write(bytes);
+        hasher.finish()
+    })
+    .unwrap_or_default()
 }
 
 pub trait PrimaryKeyGenerator<T> {
     fn next(&self) -> T;
 }
 
+/// State of the [`PrimaryKeyGenerator`] which is persisted with the table, so generator continues from the same
+/// point after load. It can be implemented for custom generators to use them in persisted tables. Generators which
+/// don't need to persist anything, like [`UuidV7Generator`], use `()` as [`PrimaryKeyGeneratorState::State`].
+///
+/// `State::default()` is written for the table which never generated a key, so it must be valid initial state.
 pub trait PrimaryKeyGeneratorState {
     type State;
 
+    /// Returns current state that will be persisted.
     fn get_state(&self) -> Self::State;
 
+    /// Creates generator from persisted state.
     fn from_state(state: Self::State) -> Self;
+
+    /// Resets generator to its initial state. Default implementation does nothing, which fits generators without state,
+    /// so generators with state must override it.
+    fn reset(&self) {}
 }
 
 impl<T> PrimaryKeyGenerator<T> for AtomicU32
@@ -35,6 +80,10 @@ impl PrimaryKeyGeneratorState for AtomicU32 {
     fn from_state(state: Self::State) -> Self {
         AtomicU32::from(state)
     }
+
+    fn reset(&self) {
+        self.store(0, Ordering::Relaxed)
+    }
 }
 
 impl<T> PrimaryKeyGenerator<T> for AtomicU64
@@ -56,6 +105,10 @@ impl PrimaryKeyGeneratorState for AtomicU64 {
     fn from_state(state: Self::State) -> Self {
         AtomicU64::from(state)
     }
+
+    fn reset(&self) {
+        self.store(0, Ordering::Relaxed)
+    }
 }
 
 impl<T> PrimaryKeyGenerator<T> for AtomicI64
@@ -77,6 +130,10 @@ impl PrimaryKeyGeneratorState for AtomicI64 {
     fn from_state(state: Self::State) -> Self {
         AtomicI64::from(state)
     }
+
+    fn reset(&self) {
+        self.store(0, Ordering::Relaxed)
+    }
 }
 
 impl PrimaryKeyGeneratorState for () {
@@ -89,4 +146,32 @@ impl PrimaryKeyGeneratorState for () {
     fn from_state((): Self::State) -> Self {
         ()
     }
+
+    fn reset(&self) {}
+}
+
+/// Generator of UUIDv7 keys. Keys are unique and ordered by creation time across processes, so generator has no state
+/// to persist.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UuidV7Generator;
+
+impl<T> PrimaryKeyGenerator<T> for UuidV7Generator
+where
+    T: From<Uuid>,
+{
+    fn next(&self) -> T {
+        Uuid::now_v7().into()
+    }
+}
+
+impl PrimaryKeyGeneratorState for UuidV7Generator {
+    type State = ();
+
+    fn get_state(&self) -> Self::State {}
+
+    fn from_state((): Self::State) -> Self {
+        Self
+    }
+
+    fn reset(&self) {}
 }
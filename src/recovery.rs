@@ -0,0 +1,12 @@
+/// Report of the lenient file parsing, which stops on the first unreadable page instead of failing.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct LenientParseReport {
+    /// Count of pages that were not read.
+    pub pages_skipped: usize,
+
+    /// Count of rows which were lost with not read pages.
+    pub rows_skipped: usize,
+
+    /// Count of rows which were recovered.
+    pub rows_recovered: usize,
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+
+use data_bucket::Link;
+use derive_more::{Display, Error};
+use rkyv::api::high::HighDeserializer;
+use rkyv::rancor::Strategy;
+use rkyv::ser::allocator::ArenaHandle;
+use rkyv::ser::sharing::Share;
+use rkyv::ser::Serializer;
+use rkyv::util::AlignedVec;
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::in_memory::{overflow, ArchivedRow, RowWrapper, StorableRow};
+use crate::primary_key::TablePrimaryKey;
+use crate::{TableIndex, TableRow, WorkTable};
+
+/// Name of the primary index in [`IntegrityError`]s.
+pub const PRIMARY_INDEX_NAME: &str = "primary";
+
+/// Inconsistency between table's indexes and rows found by generated `verify_integrity`. `index` is name of the
+/// index, or [`PRIMARY_INDEX_NAME`] for primary index.
+#[derive(Clone, Copy, Debug, Display, Error, PartialEq, Eq)]
+pub enum IntegrityError {
+    /// Index entry points to the [`Link`] which has no live row: slot is freed, tombstoned or can't be read.
+    #[display("{} index points to {:?} which has no live row", index, link)]
+    OrphanedLink { index: &'static str, link: Link },
+
+    /// Row stored by [`Link`] doesn't have value of the index entry that points to it.
+    #[display("{} index key doesn't match row stored by {:?}", index, link)]
+    KeyMismatch { index: &'static str, link: Link },
+
+    /// Live row stored by [`Link`] has no entry in the index.
+    #[display("row stored by {:?} is missing in {} index", link, index)]
+    MissingEntry { index: &'static str, link: Link },
+
+    /// Index entry points to the [`Link`] which overlaps slot in the free lists, so the slot can be given to other row
+    /// by the next insert.
+    #[display("{} index points to {:?} which overlaps freed slot", index, link)]
+    FreedLink { index: &'static str, link: Link },
+
+    /// Slot of the primary index entry overlaps slot of other entry, so two rows share bytes of the page.
+    #[display("{} index points to {:?} which overlaps other row", index, link)]
+    OverlappingLink { index: &'static str, link: Link },
+}
+
+/// Returns `true` if bytes of two [`Link`]s overlap. Overflow row's [`Link`] is compared by its header.
+fn overlaps(a: Link, b: Link) -> bool {
+    let (a, b) = (overflow::header_link(a), overflow::header_link(b));
+    a.page_id == b.page_id && a.offset < b.offset + b.length && b.offset < a.offset + a.length
+}
+
+impl<Row, PrimaryKey, IndexType, SecondaryIndexes, PkGen, const DATA_LENGTH: usize>
+    WorkTable<Row, PrimaryKey, IndexType, SecondaryIndexes, PkGen, DATA_LENGTH>
+where
+    Row: TableRow<PrimaryKey>,
+    PrimaryKey: Clone + Ord + TablePrimaryKey,
+    IndexType: TableIndex<PrimaryKey, Link>,
+    Row: StorableRow,
+    <Row as StorableRow>::WrappedRow: RowWrapper<Row>,
+{
+    /// Checks that every primary index entry points to the live row with the same primary key, that no two entries
+    /// share bytes of the page, and that no entry points into the free lists. Returns live rows by their [`Link`]s, so
+    /// secondary indexes are checked against them without reading rows again. Live row is a row reachable from
+    /// primary index, because slots of the deleted rows keep their bytes until they are reused.
+    pub fn verify_primary_index(&self) -> (HashMap<Link, Row>, Vec<IntegrityError>)
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        let freed = self.freed_slots();
+        let mut rows = HashMap::new();
+        let mut errors = vec![];
+
+        for (pk, link) in TableIndex::iter(&self.pk_map) {
+            if freed.iter().any(|l| overlaps(*l, *link)) {
+                errors.push(IntegrityError::FreedLink {
+                    index: PRIMARY_INDEX_NAME,
+                    link: *link,
+                });
+                continue;
+            }
+            if rows.contains_key(link) {
+                errors.push(IntegrityError::OverlappingLink {
+                    index: PRIMARY_INDEX_NAME,
+                    link: *link,
+                });
+                continue;
+            }
+            match self.data.select(*link) {
+                Ok(row) if &row.get_primary_key() == pk => {
+                    rows.insert(*link, row);
+                }
+                Ok(_) => errors.push(IntegrityError::KeyMismatch {
+                    index: PRIMARY_INDEX_NAME,
+                    link: *link,
+                }),
+                Err(_) => errors.push(IntegrityError::OrphanedLink {
+                    index: PRIMARY_INDEX_NAME,
+                    link: *link,
+                }),
+            }
+        }
+
+        // Links are sorted by position, so every overlap is found between neighbours.
+        let mut links = rows.keys().copied().collect::<Vec<_>>();
+        links.sort_by_key(|l| (l.page_id, l.offset));
+        for pair in links.windows(2) {
+            if overlaps(pair[0], pair[1]) {
+                errors.push(IntegrityError::OverlappingLink {
+                    index: PRIMARY_INDEX_NAME,
+                    link: pair[1],
+                });
+            }
+        }
+
+        (rows, errors)
+    }
+
+    /// Replaces [`IntegrityError::OrphanedLink`]s of the secondary indexes which point into the free lists by
+    /// [`IntegrityError::FreedLink`], so links to the freed slots are reported separately from links to missing rows.
+    pub fn mark_freed_links(&self, errors: Vec<IntegrityError>) -> Vec<IntegrityError> {
+        let freed = self.freed_slots();
+        errors
+            .into_iter()
+            .map(|e| match e {
+                IntegrityError::OrphanedLink { index, link }
+                    if freed.iter().any(|l| overlaps(*l, link)) =>
+                {
+                    IntegrityError::FreedLink { index, link }
+                }
+                e => e,
+            })
+            .collect()
+    }
+
+    /// Returns slots of the free lists: empty links of the deleted rows and free tails of the pages.
+    fn freed_slots(&self) -> Vec<Link> {
+        let mut freed = self.data.empty_links_snapshot();
+        freed.extend(self.data.free_tails_snapshot());
+        freed
+    }
+}
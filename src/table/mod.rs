@@ -1,20 +1,32 @@
+mod integrity;
+mod read_only;
 pub mod select;
+mod transaction;
+mod ttl;
 
-use crate::in_memory::{DataPages, RowWrapper, StorableRow};
+pub use integrity::{IntegrityError, PRIMARY_INDEX_NAME};
+pub use read_only::ReadOnlyTable;
+pub use transaction::{Transaction, TransactionOp};
+
+use crate::in_memory::{ArchivedRow, DataPages, RowWrapper, StorableRow};
 use crate::lock::LockMap;
-use crate::primary_key::{PrimaryKeyGenerator, TablePrimaryKey};
+use crate::metrics;
+use crate::primary_key::{PrimaryKeyGenerator, PrimaryKeyGeneratorState, TablePrimaryKey};
+use crate::wal::{WalError, WalOp, WalRecord};
 use crate::{in_memory, TableIndex, TableRow, TableSecondaryIndex};
 use data_bucket::{Link, INNER_PAGE_SIZE};
 use derive_more::{Display, Error, From};
 #[cfg(feature = "perf_measurements")]
 use performance_measurement_codegen::performance_measurement;
-use rkyv::api::high::HighDeserializer;
+use rkyv::api::high::{HighDeserializer, HighValidator};
+use rkyv::bytecheck::CheckBytes;
 use rkyv::rancor::Strategy;
 use rkyv::ser::allocator::ArenaHandle;
 use rkyv::ser::sharing::Share;
 use rkyv::ser::Serializer;
 use rkyv::util::AlignedVec;
 use rkyv::{Archive, Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 use std::marker::PhantomData;
 
 #[derive(Debug)]
@@ -40,6 +52,13 @@ pub struct WorkTable<
 
     pub lock_map: LockMap,
 
+    /// Serializes generated `transaction`s, so only one of them is applied at a time.
+    pub transaction_lock: tokio::sync::Mutex<()>,
+
+    /// Held for read by generated writes of persisted table while they are applied and logged, and for write by
+    /// checkpoint while table's image is captured, so image doesn't contain half of a write.
+    pub capture_lock: std::sync::RwLock<()>,
+
     pub table_name: &'static str,
 
     pub pk_phantom: PhantomData<PrimaryKey>,
@@ -63,6 +82,8 @@ where
             indexes: SecondaryIndexes::default(),
             pk_gen: Default::default(),
             lock_map: LockMap::new(),
+            transaction_lock: tokio::sync::Mutex::default(),
+            capture_lock: std::sync::RwLock::default(),
             table_name: "",
             pk_phantom: PhantomData,
         }
@@ -85,7 +106,20 @@ where
         self.pk_gen.next()
     }
 
-    /// Selects `Row` from table identified with provided primary key. Returns `None` if no value presented.
+    /// Sets name of the table, which is also used as label of the table's [`metrics`](crate::metrics).
+    pub fn set_table_name(&mut self, name: &'static str) {
+        self.table_name = name;
+        self.data.set_table_name(name);
+    }
+
+    /// Emits count of bytes written to the table's file to [`metrics`](crate::metrics). It's called by generated
+    /// persist functions.
+    pub fn record_persisted(&self, bytes: u64) {
+        metrics::increment(metrics::BYTES_PERSISTED, self.table_name, bytes);
+    }
+
+    /// Selects `Row` from table identified with provided primary key. Returns `None` if no value presented or row is
+    /// expired.
     #[cfg_attr(
         feature = "perf_measurements",
         performance_measurement(prefix_name = "WorkTable")
@@ -96,18 +130,208 @@ where
             + for<'a> Serialize<
                 Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
             >,
-        <<Row as StorableRow>::WrappedRow as Archive>::Archived:
-            Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
     {
-        let link = self.pk_map.peek(&pk)?;
+        let link = self.pk_map.peek(&pk).filter(|l| !self.is_expired(*l))?;
         self.data.select(link).ok()
     }
 
+    /// Selects `Row` from table identified with provided primary key. Unlike [`WorkTable::select`], it distinguishes
+    /// missing row from failed read: `Ok(None)` is returned if primary key is not presented or its row is tombstoned
+    /// or expired, and `Err` only if row can't be read from the pages.
+    pub fn get(&self, pk: &PrimaryKey) -> Result<Option<Row>, in_memory::PagesExecutionError>
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        let Some(link) = self.pk_map.peek(pk).filter(|l| !self.is_expired(*l)) else {
+            return Ok(None);
+        };
+        match self.data.select(link) {
+            Ok(row) => Ok(Some(row)),
+            Err(in_memory::PagesExecutionError::Deleted) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Takes read-only snapshot of the table's rows. Primary index is copied before pages, so every copied [`Link`]
+    /// points to the row which is already saved. Pages are copied, not shared, because rows are updated in place.
+    /// Pages are copied at one point (see [`DataPages::snapshot`]), but primary index is not locked with them, so
+    /// rows inserted between two copies are not visible and rows updated between them have new values. Fails if
+    /// evicted page can't be read from the spill file.
+    pub fn snapshot_readonly(
+        &self,
+    ) -> Result<ReadOnlyTable<Row, PrimaryKey, DATA_LENGTH>, WorkTableError> {
+        let pk_map = TableIndex::iter(&self.pk_map)
+            .map(|(pk, link)| (pk.clone(), *link))
+            .collect();
+        let data = self.data.snapshot().map_err(WorkTableError::PagesError)?;
+        Ok(ReadOnlyTable::new(data, pk_map))
+    }
+
+    /// Converts table into [`ReadOnlyTable`]. Unlike [`WorkTable::snapshot_readonly`], pages are moved, not copied,
+    /// and secondary indexes, locks and primary key generator of the table are dropped.
+    pub fn into_readonly(self) -> ReadOnlyTable<Row, PrimaryKey, DATA_LENGTH> {
+        let pk_map = TableIndex::iter(&self.pk_map)
+            .map(|(pk, link)| (pk.clone(), *link))
+            .collect();
+        ReadOnlyTable::new(self.data, pk_map)
+    }
+
+    /// Checks if row with provided primary key exists and is not expired. Only primary index is read, so pages are not
+    /// touched.
+    pub fn exists(&self, pk: &PrimaryKey) -> bool {
+        self.pk_map
+            .peek(pk)
+            .is_some_and(|link| !self.is_expired(link))
+    }
+
+    /// Selects `Row`s of all provided primary keys. All index lookups are done first, and then rows are read under
+    /// single pages lock. `None` is returned in slot of the key that is not presented or expired.
+    pub fn select_many_by_pk(&self, keys: &[PrimaryKey]) -> Vec<Option<Row>>
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        let links = keys
+            .iter()
+            .map(|pk| self.pk_map.peek(pk).filter(|l| !self.is_expired(*l)))
+            .collect::<Vec<_>>();
+        let found = links.iter().flatten().copied().collect::<Vec<_>>();
+        let Ok(rows) = self.data.select_many(&found) else {
+            return keys.iter().map(|_| None).collect();
+        };
+        let mut rows = rows.into_iter();
+
+        links
+            .into_iter()
+            .map(|link| link.and_then(|_| rows.next()?.ok()))
+            .collect()
+    }
+
+    /// Returns all `Row`s in primary key's order. Links are taken from primary index first, and then all rows are
+    /// read under single pages lock. Rows deleted meanwhile are skipped, and any other error of the read is returned
+    /// instead of short result.
+    pub fn get_all(&self) -> Result<Vec<Row>, WorkTableError>
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        let links = self.ordered_links();
+        let rows = self
+            .data
+            .select_many(&links)
+            .map_err(WorkTableError::PagesError)?;
+
+        rows.into_iter()
+            .filter(|row| !matches!(row, Err(in_memory::PagesExecutionError::Deleted)))
+            .map(|row| row.map_err(WorkTableError::PagesError))
+            .collect()
+    }
+
+    /// Returns iterator over all `Row`s in primary key's order, which doesn't depend on rows' placement in pages, so
+    /// it's the same after deleted rows' slots are reused. Every row is read by its own [`Link`] from wherever it's
+    /// stored, so it's slower than sequential read of the pages, but rows are not collected before they are returned
+    /// like by [`WorkTable::get_all`]. Rows that can't be read, for example deleted meanwhile, are skipped.
+    pub fn iter_ordered(&self) -> impl Iterator<Item = Row> + '_
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        self.ordered_links()
+            .into_iter()
+            .filter_map(|link| self.data.select(link).ok())
+    }
+
+    /// Returns [`Link`]s of all not expired rows in primary key's order.
+    fn ordered_links(&self) -> Vec<Link> {
+        let mut entries = TableIndex::iter(&self.pk_map)
+            .filter(|(_, link)| !self.is_expired(**link))
+            .map(|(pk, link)| (pk.clone(), *link))
+            .collect::<Vec<_>>();
+        // Hash indexes are not ordered. For ordered ones entries are already sorted, so it's linear.
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        entries.into_iter().map(|(_, link)| link).collect()
+    }
+
+    /// Returns all not expired `Row`s with their [`Link`]s in primary index's order. [`Link`] can be used to update
+    /// matched row without second index lookup.
+    pub fn iter_with_links(
+        &self,
+    ) -> impl Iterator<Item = Result<(Link, Row), in_memory::PagesExecutionError>> + '_
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        TableIndex::iter(&self.pk_map)
+            .filter(|(_, link)| !self.is_expired(**link))
+            .map(|(_, link)| self.data.select(*link).map(|row| (*link, row)))
+    }
+
     #[cfg_attr(
         feature = "perf_measurements",
         performance_measurement(prefix_name = "WorkTable")
     )]
     pub fn insert(&self, row: Row) -> Result<PrimaryKey, WorkTableError>
+    where
+        Row: Archive
+            + Clone
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        PrimaryKey: Clone,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        self.insert_with(row, true)
+    }
+
+    /// Same as [`WorkTable::insert`], but row is saved only if it fits in the existing pages, see
+    /// [`DataPages::try_insert`]. Otherwise [`in_memory::PagesExecutionError::NoSpace`] is returned and table is not
+    /// changed.
+    pub fn try_insert(&self, row: Row) -> Result<PrimaryKey, WorkTableError>
+    where
+        Row: Archive
+            + Clone
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        PrimaryKey: Clone,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        self.insert_with(row, false)
+    }
+
+    /// Inserts row like [`WorkTable::insert`]. New pages are added only if `grow` is `true`.
+    fn insert_with(&self, row: Row, grow: bool) -> Result<PrimaryKey, WorkTableError>
     where
         Row: Archive
             + Clone
@@ -122,16 +346,438 @@ where
         SecondaryIndexes: TableSecondaryIndex<Row>,
     {
         let pk = row.get_primary_key().clone();
-        let link = self
+        // Existing primary key is checked before row is saved, so its slot is not left unreachable.
+        if self.pk_map.peek(&pk).is_some() {
+            return Err(WorkTableError::DuplicatePrimaryKey);
+        }
+        let key = self.active_page_key(&pk);
+        let link = if grow {
+            self.data.insert_with_key(row.clone(), key)
+        } else {
+            self.data.try_insert_with_key(row.clone(), key)
+        }
+        .map_err(WorkTableError::PagesError)?;
+        if self.pk_map.insert(pk.clone(), link).is_err() {
+            // Same primary key was inserted concurrently after the check, so saved row is freed.
+            self.data.delete(link).map_err(WorkTableError::PagesError)?;
+            return Err(WorkTableError::DuplicatePrimaryKey);
+        }
+        // Secondary indexes are left unchanged if `save_row` fails, so only primary key and row are removed.
+        if let Err(e) = self.indexes.save_row(row, link) {
+            self.pk_map.remove(&pk);
+            self.data.delete(link).map_err(WorkTableError::PagesError)?;
+            return Err(e);
+        }
+
+        Ok(pk)
+    }
+
+    /// Returns key of the active page to which row with `pk` is saved, see [`TablePrimaryKey::active_page_key`], so
+    /// inserts of different keys are spread over active pages. Key is skipped if there is single active page.
+    fn active_page_key(&self, pk: &PrimaryKey) -> u64 {
+        if self.data.active_pages() == 1 {
+            return 0;
+        }
+        pk.active_page_key()
+    }
+
+    /// Moves row stored by `link` to new slot, which is used when updated row doesn't fit in its old slot. New row is
+    /// saved first, then primary and secondary indexes are pointed to the new [`Link`] and old slot is freed, so row
+    /// is found by primary key during whole relocation. If secondary indexes can't be pointed to the new [`Link`],
+    /// indexes are pointed back to the old slot, new slot is freed and error is returned. Returns new [`Link`].
+    ///
+    /// Writers waiting for the row's lock hold the old [`Link`], so they must resolve it again by primary key after
+    /// wait, because old slot can be reused.
+    pub fn relocate(&self, previous: Row, row: Row, link: Link) -> Result<Link, WorkTableError>
+    where
+        Row: Archive
+            + Clone
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        let pk = row.get_primary_key();
+        let new_link = self
             .data
             .insert(row.clone())
             .map_err(WorkTableError::PagesError)?;
-        self.pk_map
-            .insert(pk.clone(), link)
-            .map_err(|_| WorkTableError::AlreadyExists)?;
-        self.indexes.save_row(row, link)?;
+        if let Some(expires_at) = self.data.expires_at(link) {
+            self.data.set_expiration(new_link, expires_at);
+        }
+        self.pk_map.replace(pk.clone(), new_link);
+        self.indexes.delete_row(previous.clone(), link)?;
+        // `save_row` leaves indexes unchanged on error, so only removed entries of the old slot are saved back.
+        if let Err(e) = self.indexes.save_row(row, new_link) {
+            let _ = self.indexes.save_row(previous, link);
+            self.pk_map.replace(pk, link);
+            self.data
+                .delete(new_link)
+                .map_err(WorkTableError::PagesError)?;
+            return Err(e);
+        }
+        self.data.delete(link).map_err(WorkTableError::PagesError)?;
 
-        Ok(pk)
+        Ok(new_link)
+    }
+
+    /// Writes `previous` value back to the row stored by `link`. It's used by generated operations to revert row's
+    /// change which can't be logged to write-ahead log, while row is still locked. Row is moved if `previous` doesn't
+    /// fit in its slot. Returns [`Link`] of the restored row.
+    pub fn restore(&self, previous: Row, link: Link) -> Result<Link, WorkTableError>
+    where
+        Row: Archive
+            + Clone
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        let current = self.data.select(link).map_err(WorkTableError::PagesError)?;
+        let length = self
+            .data
+            .stored_length(previous.clone())
+            .map_err(WorkTableError::PagesError)?;
+        if length != link.length {
+            return self.relocate(current, previous, link);
+        }
+        self.indexes.delete_row(current.clone(), link)?;
+        // Row stays in pages on errors below, so its index entries are saved back to not leave it unindexed.
+        // SAFETY: length is checked above, and row is locked by caller.
+        if let Err(e) = unsafe { self.data.update(previous.clone(), link) } {
+            let _ = self.indexes.save_row(current, link);
+            return Err(WorkTableError::PagesError(e));
+        }
+        if let Err(e) = self.indexes.save_row(previous, link) {
+            // SAFETY: same as above.
+            if unsafe { self.data.update(current.clone(), link) }.is_ok() {
+                let _ = self.indexes.save_row(current, link);
+            }
+            return Err(e);
+        }
+
+        Ok(link)
+    }
+
+    /// Removes row with provided primary key from indexes and pages. Returns removed row, or `None` if there is no
+    /// row with this primary key.
+    pub fn remove(&self, pk: &PrimaryKey) -> Result<Option<Row>, WorkTableError>
+    where
+        Row: Archive
+            + Clone
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        let Some(link) = self.pk_map.peek(pk) else {
+            return Ok(None);
+        };
+        let row = self.data.select(link).map_err(WorkTableError::PagesError)?;
+        // Row can be removed concurrently, so it's removed from other indexes and pages only by the one who removed
+        // its key.
+        if !self.pk_map.remove(pk) {
+            return Ok(None);
+        }
+        self.indexes.delete_row(row.clone(), link)?;
+        self.data.delete(link).map_err(WorkTableError::PagesError)?;
+
+        Ok(Some(row))
+    }
+
+    /// Checks that all rows of the batch can be inserted: every row fits in pages, primary keys are not
+    /// duplicated in batch and are not presented in table, and unique indexes are not violated. Returns all found
+    /// problems.
+    pub fn validate_batch(&self, rows: &[Row]) -> Result<(), Vec<BatchError>>
+    where
+        Row: Clone,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        let mut errors = vec![];
+        let mut pks = BTreeMap::new();
+        for (pos, row) in rows.iter().enumerate() {
+            let wrapped = <Row as StorableRow>::WrappedRow::from_inner(row.clone());
+            match rkyv::to_bytes::<rkyv::rancor::Error>(&wrapped) {
+                Ok(bytes) if bytes.len() > DataPages::<Row, DATA_LENGTH>::max_row_length() => {
+                    errors.push(BatchError::RowTooLarge {
+                        row: pos,
+                        length: bytes.len(),
+                    })
+                }
+                Ok(_) => {}
+                Err(_) => errors.push(BatchError::SerializeError { row: pos }),
+            }
+
+            let pk = row.get_primary_key();
+            if self.pk_map.peek(&pk).is_some() {
+                errors.push(BatchError::PrimaryKeyExists { row: pos });
+            } else if let Some(first) = pks.get(&pk) {
+                errors.push(BatchError::DuplicatePrimaryKey {
+                    row: pos,
+                    first: *first,
+                });
+            } else {
+                pks.insert(pk, pos);
+            }
+        }
+        errors.extend(self.indexes.validate_rows(rows));
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Deletes rows with provided primary keys and returns deleted rows, so caller knows which keys were deleted. Keys
+    /// which are not presented are skipped. Rows are removed from indexes by [`WorkTable::unlink_many_by_pk`] and
+    /// their slots are freed at once. If slots can't be freed, rows are linked back and error is returned.
+    pub fn delete_many_by_pk(&self, keys: &[PrimaryKey]) -> Result<Vec<Row>, WorkTableError>
+    where
+        Row: Archive
+            + Clone
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        let entries = self.unlink_many_by_pk(keys)?;
+        let links = entries.iter().map(|(_, link)| *link).collect::<Vec<_>>();
+        if let Err(e) = self.data.delete_many(&links) {
+            return Err(WorkTableError::PagesError(e).with_rollback(self.relink_many(&entries)));
+        }
+
+        Ok(entries.into_iter().map(|(row, _)| row).collect())
+    }
+
+    /// Removes rows with provided primary keys from primary and secondary indexes, but keeps them in pages, so they
+    /// can be linked back by [`WorkTable::relink_many`] until their slots are freed. Keys which are not presented or
+    /// removed concurrently are skipped. Returns removed rows with their [`Link`]s. If removal fails, indexes are
+    /// left unchanged.
+    pub fn unlink_many_by_pk(&self, keys: &[PrimaryKey]) -> Result<Vec<(Row, Link)>, WorkTableError>
+    where
+        Row: Archive
+            + Clone
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        let mut seen = HashSet::new();
+        let links = keys
+            .iter()
+            .filter_map(|pk| self.pk_map.peek(pk))
+            .filter(|link| seen.insert(*link))
+            .collect::<Vec<_>>();
+        let rows = self
+            .data
+            .select_many(&links)
+            .map_err(WorkTableError::PagesError)?
+            .into_iter()
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(WorkTableError::PagesError)?;
+        // Rows can be removed concurrently, so each row is removed from secondary indexes only by the one who removed
+        // its key.
+        let entries = rows
+            .into_iter()
+            .zip(links)
+            .filter(|(row, _)| self.pk_map.remove(&row.get_primary_key()))
+            .collect::<Vec<_>>();
+        if let Err(e) = self.indexes.delete_rows(&entries) {
+            let rollback = entries.iter().try_for_each(|(row, link)| {
+                self.pk_map
+                    .insert(row.get_primary_key(), *link)
+                    .map_err(|_| WorkTableError::DuplicatePrimaryKey)
+            });
+            return Err(e.with_rollback(rollback));
+        }
+
+        Ok(entries)
+    }
+
+    /// Saves rows removed by [`WorkTable::unlink_many_by_pk`] back to primary and secondary indexes. All rows are
+    /// tried, and the first error is returned.
+    pub fn relink_many(&self, entries: &[(Row, Link)]) -> Result<(), WorkTableError>
+    where
+        Row: Clone,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        let mut res = Ok(());
+        for (row, link) in entries {
+            let relinked = self
+                .pk_map
+                .insert(row.get_primary_key(), *link)
+                .map_err(|_| WorkTableError::DuplicatePrimaryKey)
+                .and_then(|_| self.indexes.save_row(row.clone(), *link));
+            if res.is_ok() {
+                res = relinked;
+            }
+        }
+
+        res
+    }
+
+    /// Removes all rows from table: [`DataPages`] are reset to single empty page, and primary and secondary indexes are
+    /// cleared. Primary key generator is not reset, because it can be custom. Must not be called concurrently with
+    /// other operations.
+    pub fn truncate(&self)
+    where
+        PrimaryKey: Clone,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        self.data.clear();
+        self.pk_map.clear();
+        self.indexes.clear();
+    }
+
+    /// Inserts all rows of the batch if [`WorkTable::validate_batch`] succeeds. If validation fails, nothing is
+    /// inserted.
+    pub fn insert_batch(&self, rows: Vec<Row>) -> Result<Vec<PrimaryKey>, WorkTableError>
+    where
+        Row: Archive
+            + Clone
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        self.validate_batch(&rows)
+            .map_err(WorkTableError::BatchValidationError)?;
+        rows.into_iter().map(|row| self.insert(row)).collect()
+    }
+
+    /// Loads rows into empty table. Rows are saved in [`DataPages`] first, and primary and secondary indexes are
+    /// built in one pass after all rows are saved, which is much faster than inserting rows one by one.
+    ///
+    /// Returns [`WorkTableError::NotEmpty`] if table already has rows. Must not be called concurrently with other
+    /// inserts. If secondary index fails on some row, table is left partially loaded.
+    pub fn bulk_load(&self, rows: Vec<Row>) -> Result<Vec<PrimaryKey>, WorkTableError>
+    where
+        Row: Archive
+            + Clone
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        if self.pk_map.iter().next().is_some() {
+            return Err(WorkTableError::NotEmpty);
+        }
+        let mut pks = BTreeMap::new();
+        for (pos, row) in rows.iter().enumerate() {
+            if let Some(first) = pks.insert(row.get_primary_key(), pos) {
+                return Err(WorkTableError::BatchValidationError(vec![
+                    BatchError::DuplicatePrimaryKey { row: pos, first },
+                ]));
+            }
+        }
+
+        let mut links = Vec::with_capacity(rows.len());
+        for row in &rows {
+            let link = self
+                .data
+                .insert(row.clone())
+                .map_err(WorkTableError::PagesError)?;
+            links.push(link);
+        }
+
+        let mut keys = Vec::with_capacity(rows.len());
+        for (row, link) in rows.into_iter().zip(links) {
+            let pk = row.get_primary_key();
+            self.pk_map
+                .insert(pk.clone(), link)
+                .map_err(|_| WorkTableError::AlreadyExists)?;
+            self.indexes.save_row(row, link)?;
+            keys.push(pk);
+        }
+
+        Ok(keys)
+    }
+
+    /// Applies [`WalRecord`]s on top of the loaded table. Inserted and updated rows replace existing ones and deleted
+    /// rows that are not presented are skipped, so records which are already in table's file can be replayed safely.
+    /// Primary key generator is restored from the last logged state. Returns count of applied records.
+    pub fn replay_wal(&mut self, records: Vec<WalRecord>) -> Result<usize, WorkTableError>
+    where
+        Row: Archive
+            + Clone
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <Row as Archive>::Archived: for<'a> CheckBytes<HighValidator<'a, rkyv::rancor::Error>>
+            + Deserialize<Row, HighDeserializer<rkyv::rancor::Error>>,
+        <Row as StorableRow>::WrappedRow: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+        PrimaryKey: Archive,
+        <PrimaryKey as Archive>::Archived: for<'a> CheckBytes<HighValidator<'a, rkyv::rancor::Error>>
+            + Deserialize<PrimaryKey, HighDeserializer<rkyv::rancor::Error>>,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+        PkGen: PrimaryKeyGeneratorState,
+        <PkGen as PrimaryKeyGeneratorState>::State: Archive,
+        <<PkGen as PrimaryKeyGeneratorState>::State as Archive>::Archived: for<'a> CheckBytes<HighValidator<'a, rkyv::rancor::Error>>
+            + Deserialize<
+                <PkGen as PrimaryKeyGeneratorState>::State,
+                HighDeserializer<rkyv::rancor::Error>,
+            >,
+    {
+        let count = records.len();
+        for record in records {
+            match record.op {
+                WalOp::Insert | WalOp::Update => {
+                    let row = record.deserialize::<Row>()?;
+                    let pk = row.get_primary_key();
+                    if let Some(link) = self.pk_map.peek(&pk) {
+                        let previous =
+                            self.data.select(link).map_err(WorkTableError::PagesError)?;
+                        self.relocate(previous, row, link)?;
+                    } else {
+                        self.insert(row)?;
+                    }
+                }
+                WalOp::Delete => {
+                    let pk = record.deserialize::<PrimaryKey>()?;
+                    self.remove(&pk)?;
+                }
+                WalOp::Truncate => self.truncate(),
+                WalOp::PrimaryKeyState => {
+                    self.pk_gen = PkGen::from_state(record.deserialize()?);
+                }
+            }
+        }
+
+        Ok(count)
     }
 }
 
@@ -139,8 +785,90 @@ where
 pub enum WorkTableError {
     NotFound,
     AlreadyExists,
+    /// Row with same primary key is already presented in table.
+    DuplicatePrimaryKey,
+    NotEmpty,
     SerializeError,
     PagesError(in_memory::PagesExecutionError),
+    WalError(WalError),
+    #[display("batch validation failed: {:?}", _0)]
+    BatchValidationError(#[error(not(source))] Vec<BatchError>),
+    /// Operation failed with `error`, and its changes which were already applied failed to be reverted with
+    /// `rollback`, so table can be left partially changed.
+    #[display("{}, and rollback failed: {}", error, rollback)]
+    RollbackFailed {
+        error: Box<WorkTableError>,
+        rollback: Box<WorkTableError>,
+    },
+}
+
+impl WorkTableError {
+    /// Returns this error if `rollback` of the failed operation succeeded, or [`WorkTableError::RollbackFailed`] with
+    /// both errors otherwise.
+    pub fn with_rollback(self, rollback: Result<(), WorkTableError>) -> Self {
+        match rollback {
+            Ok(()) => self,
+            Err(rollback) => Self::RollbackFailed {
+                error: Box::new(self),
+                rollback: Box::new(rollback),
+            },
+        }
+    }
+}
+
+/// Problem found in a batch of rows by [`WorkTable::validate_batch`]. `row` is position of the row in the batch.
+#[derive(Clone, Debug, Display, Error, PartialEq, Eq)]
+pub enum BatchError {
+    /// Row can't be serialized.
+    #[display("row {} can't be serialized", row)]
+    SerializeError { row: usize },
+
+    /// Serialized row is larger than [`DataPages::max_row_length`].
+    #[display("row {} has length {}, which doesn't fit in pages", row, length)]
+    RowTooLarge { row: usize, length: usize },
+
+    /// Row's primary key is already presented in table.
+    #[display("row {} has primary key which already exists", row)]
+    PrimaryKeyExists { row: usize },
+
+    /// Row's primary key duplicates primary key of another row in batch.
+    #[display("row {} has same primary key as row {}", row, first)]
+    DuplicatePrimaryKey { row: usize, first: usize },
+
+    /// Row's value of unique index is already presented in table or duplicates value of another row in batch.
+    #[display("row {} violates unique index {}", row, index)]
+    UniqueIndexViolation { row: usize, index: &'static str },
+}
+
+impl BatchError {
+    /// Returns position of the row in the batch.
+    pub fn row(&self) -> usize {
+        match self {
+            Self::SerializeError { row }
+            | Self::RowTooLarge { row, .. }
+            | Self::PrimaryKeyExists { row }
+            | Self::DuplicatePrimaryKey { row, .. }
+            | Self::UniqueIndexViolation { row, .. } => *row,
+        }
+    }
+}
+
+/// Error of importing rows into table. Line numbers start from 1.
+#[derive(Debug, Display, Error)]
+pub enum ImportError {
+    /// Error of reading the input.
+    Io(std::io::Error),
+
+    /// Line can't be parsed as a row.
+    #[display("line {}: {}", line, message)]
+    MalformedLine { line: usize, message: String },
+
+    /// Parsed rows failed batch validation. Every [`BatchError`] is paired with its line number.
+    #[display("invalid rows: {:?}", _0)]
+    InvalidRows(#[error(not(source))] Vec<(usize, BatchError)>),
+
+    /// Valid rows failed to insert.
+    Insert(WorkTableError),
 }
 
 #[cfg(test)]
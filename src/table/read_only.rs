@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+
+use data_bucket::{Link, INNER_PAGE_SIZE};
+use rkyv::api::high::HighDeserializer;
+use rkyv::rancor::Strategy;
+use rkyv::ser::allocator::ArenaHandle;
+use rkyv::ser::sharing::Share;
+use rkyv::ser::Serializer;
+use rkyv::util::AlignedVec;
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::in_memory::{ArchivedRow, DataPages, PagesExecutionError, RowWrapper, StorableRow};
+use crate::TableRow;
+
+/// Read-only snapshot of the [`WorkTable`]'s rows, which is taken by [`WorkTable::snapshot_readonly`] or
+/// [`WorkTable::into_readonly`]. Snapshot has its own copy of data pages and primary index, so writes to the table
+/// after snapshot is taken are not visible in it and long reads of the snapshot don't block the table. It has no
+/// write methods, so it can't be modified.
+///
+/// [`WorkTable`]: crate::WorkTable
+/// [`WorkTable::snapshot_readonly`]: crate::WorkTable::snapshot_readonly
+/// [`WorkTable::into_readonly`]: crate::WorkTable::into_readonly
+#[derive(Debug)]
+pub struct ReadOnlyTable<Row, PrimaryKey, const DATA_LENGTH: usize = INNER_PAGE_SIZE>
+where
+    Row: StorableRow,
+{
+    data: DataPages<Row, DATA_LENGTH>,
+
+    pk_map: BTreeMap<PrimaryKey, Link>,
+}
+
+impl<Row, PrimaryKey, const DATA_LENGTH: usize> ReadOnlyTable<Row, PrimaryKey, DATA_LENGTH>
+where
+    Row: TableRow<PrimaryKey> + StorableRow,
+    <Row as StorableRow>::WrappedRow: RowWrapper<Row>,
+    PrimaryKey: Ord,
+{
+    /// Creates read-only table from its pages and primary index. It's used by generated `load_readonly_from_file`,
+    /// which fills it from the file without building write-side state of the table.
+    pub fn new(data: DataPages<Row, DATA_LENGTH>, pk_map: BTreeMap<PrimaryKey, Link>) -> Self {
+        Self { data, pk_map }
+    }
+
+    /// Returns count of the rows in snapshot.
+    pub fn count(&self) -> usize {
+        self.pk_map.len()
+    }
+
+    /// Selects `Row` with provided primary key. Returns `None` if it was not presented when snapshot was taken.
+    pub fn select(&self, pk: PrimaryKey) -> Option<Row>
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        let link = self.pk_map.get(&pk)?;
+        self.data
+            .select(*link)
+            .ok()
+            .filter(|row| row.get_primary_key() == pk)
+    }
+
+    /// Selects `Row` with provided primary key. Same as [`ReadOnlyTable::select`], but failed read of the pages is
+    /// returned as `Err` instead of `None`.
+    pub fn get(&self, pk: &PrimaryKey) -> Result<Option<Row>, PagesExecutionError>
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        let Some(link) = self.pk_map.get(pk) else {
+            return Ok(None);
+        };
+        match self.data.select(*link) {
+            Ok(row) => Ok(Some(row).filter(|row| &row.get_primary_key() == pk)),
+            Err(PagesExecutionError::Deleted) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Selects `Row` stored by `link`. It's used by selects of the secondary indexes, which are built from rows of
+    /// the snapshot, so their links always point to the rows of the snapshot.
+    pub fn select_by_link(&self, link: Link) -> Option<Row>
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        self.data.select(link).ok()
+    }
+
+    /// Returns all `Row`s of the snapshot in primary key's order.
+    pub fn iter(&self) -> impl Iterator<Item = Row> + '_
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        self.iter_with_links().map(|(_, row)| row)
+    }
+
+    /// Returns all `Row`s of the snapshot with their [`Link`]s in primary key's order.
+    pub fn iter_with_links(&self) -> impl Iterator<Item = (Link, Row)> + '_
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        self.pk_map.iter().filter_map(|(pk, link)| {
+            self.data
+                .select(*link)
+                .ok()
+                .filter(|row| &row.get_primary_key() == pk)
+                .map(|row| (*link, row))
+        })
+    }
+
+    /// Calls `op` with every archived `Row` and its primary key in primary key's order, and collects values that
+    /// `op` returned. `op` must check primary key of the deserialized `Row`, because slot of the deleted row can be
+    /// reused by other row while snapshot is taken.
+    pub fn filter_map_refs<Res, Op>(&self, op: Op) -> Vec<Res>
+    where
+        Row: Archive
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow,
+        Op: Fn(
+            &PrimaryKey,
+            &<<Row as StorableRow>::WrappedRow as Archive>::Archived,
+        ) -> Option<Res>,
+    {
+        self.pk_map
+            .iter()
+            .filter_map(|(pk, link)| {
+                self.data
+                    .with_ref(*link, |archived| op(pk, archived))
+                    .ok()?
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,39 @@
+/// Table's operation buffered by [`Transaction`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum TransactionOp<Row, PrimaryKey> {
+    Insert(Row),
+    Update(Row),
+    Delete(PrimaryKey),
+}
+
+/// Buffer of the table's operations, which are applied together by generated `transaction` method. Operations are
+/// applied in the same order as they were buffered.
+#[derive(Debug)]
+pub struct Transaction<Row, PrimaryKey> {
+    ops: Vec<TransactionOp<Row, PrimaryKey>>,
+}
+
+impl<Row, PrimaryKey> Default for Transaction<Row, PrimaryKey> {
+    fn default() -> Self {
+        Self { ops: vec![] }
+    }
+}
+
+impl<Row, PrimaryKey> Transaction<Row, PrimaryKey> {
+    pub fn insert(&mut self, row: Row) {
+        self.ops.push(TransactionOp::Insert(row))
+    }
+
+    pub fn update(&mut self, row: Row) {
+        self.ops.push(TransactionOp::Update(row))
+    }
+
+    pub fn delete(&mut self, pk: PrimaryKey) {
+        self.ops.push(TransactionOp::Delete(pk))
+    }
+
+    /// Returns buffered operations in order of their buffering.
+    pub fn into_ops(self) -> Vec<TransactionOp<Row, PrimaryKey>> {
+        self.ops
+    }
+}
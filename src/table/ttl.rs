@@ -0,0 +1,79 @@
+use std::time::Instant;
+
+use data_bucket::Link;
+use rkyv::api::high::HighDeserializer;
+use rkyv::rancor::Strategy;
+use rkyv::ser::allocator::ArenaHandle;
+use rkyv::ser::sharing::Share;
+use rkyv::ser::Serializer;
+use rkyv::util::AlignedVec;
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::in_memory::{ArchivedRow, RowWrapper, StorableRow};
+use crate::primary_key::TablePrimaryKey;
+use crate::{TableIndex, TableRow, TableSecondaryIndex, WorkTable, WorkTableError};
+
+impl<Row, PrimaryKey, IndexType, SecondaryIndexes, PkGen, const DATA_LENGTH: usize>
+    WorkTable<Row, PrimaryKey, IndexType, SecondaryIndexes, PkGen, DATA_LENGTH>
+where
+    Row: TableRow<PrimaryKey>,
+    PrimaryKey: Clone + Ord + TablePrimaryKey,
+    IndexType: TableIndex<PrimaryKey, Link>,
+    Row: StorableRow,
+    <Row as StorableRow>::WrappedRow: RowWrapper<Row>,
+{
+    /// Sets time after which row with provided primary key expires. Returns `false` if row is not presented.
+    pub fn expire_at(&self, pk: &PrimaryKey, expires_at: Instant) -> bool {
+        let Some(link) = self.pk_map.peek(pk) else {
+            return false;
+        };
+        self.data.set_expiration(link, expires_at);
+
+        true
+    }
+
+    /// Returns `true` if row stored by [`Link`] is expired now. Expired rows are skipped by all reads.
+    pub fn is_expired(&self, link: Link) -> bool {
+        self.data
+            .expires_at(link)
+            .is_some_and(|expires_at| expires_at <= Instant::now())
+    }
+
+    /// Returns [`Link`]s of the rows which are expired at `now`. Rows can be changed or deleted after links are
+    /// collected, so each of them is checked again by [`WorkTable::purge_expired_row`].
+    pub fn expired_links(&self, now: Instant) -> Vec<Link> {
+        self.data.expired_links(now)
+    }
+
+    /// Deletes row stored by `link` if it's still expired at `now`, freeing its slot and removing it from indexes.
+    /// Returns deleted row, or `None` if row is not expired or its slot was reused by other row. It must be called
+    /// while row is locked by caller, so row's expiration can't be refreshed and row can't be changed by other writer
+    /// between check and delete.
+    pub fn purge_expired_row(&self, link: Link, now: Instant) -> Result<Option<Row>, WorkTableError>
+    where
+        Row: Archive
+            + Clone
+            + for<'a> Serialize<
+                Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+            >,
+        <<Row as StorableRow>::WrappedRow as Archive>::Archived: ArchivedRow
+            + Deserialize<<Row as StorableRow>::WrappedRow, HighDeserializer<rkyv::rancor::Error>>,
+        SecondaryIndexes: TableSecondaryIndex<Row>,
+    {
+        if !self.data.is_expired(link, now) {
+            return Ok(None);
+        }
+        let Ok(row) = self.data.select(link) else {
+            return Ok(None);
+        };
+        let pk = row.get_primary_key();
+        if self.pk_map.peek(&pk) != Some(link) {
+            return Ok(None);
+        }
+        self.indexes.delete_row(row.clone(), link)?;
+        self.pk_map.remove(&pk);
+        self.data.delete(link).map_err(WorkTableError::PagesError)?;
+
+        Ok(Some(row))
+    }
+}
@@ -0,0 +1,440 @@
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use derive_more::{Display, Error};
+use rkyv::api::high::{HighDeserializer, HighValidator};
+use rkyv::bytecheck::CheckBytes;
+use rkyv::rancor::Strategy;
+use rkyv::ser::allocator::ArenaHandle;
+use rkyv::ser::sharing::Share;
+use rkyv::ser::Serializer;
+use rkyv::util::AlignedVec;
+use rkyv::{Archive, Deserialize, Serialize};
+
+use crate::database::DurabilityMode;
+
+/// Length of the record's header: operation byte, little-endian `u32` payload length and `u32` payload checksum.
+const RECORD_HEADER_LENGTH: usize = 9;
+
+/// Returns path of the write-ahead log for the table's file, which is `<file>.wal`.
+pub fn wal_path<P: AsRef<Path>>(path: P) -> PathBuf {
+    let mut path = OsString::from(path.as_ref());
+    path.push(".wal");
+    path.into()
+}
+
+/// Table's operation recorded in [`Wal`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(u8)]
+pub enum WalOp {
+    /// Payload is inserted row.
+    Insert = 0,
+    /// Payload is updated row.
+    Update = 1,
+    /// Payload is deleted row's primary key.
+    Delete = 2,
+    /// All rows are removed, payload is single unused byte, because record with empty payload marks end of log.
+    Truncate = 3,
+    /// Payload is primary key generator's state after insert.
+    PrimaryKeyState = 4,
+}
+
+impl WalOp {
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0 => Some(Self::Insert),
+            1 => Some(Self::Update),
+            2 => Some(Self::Delete),
+            3 => Some(Self::Truncate),
+            4 => Some(Self::PrimaryKeyState),
+            _ => None,
+        }
+    }
+}
+
+/// Single operation read from [`Wal`].
+#[derive(Debug)]
+pub struct WalRecord {
+    pub op: WalOp,
+    pub payload: AlignedVec,
+}
+
+impl WalRecord {
+    /// Deserializes record's payload as `T`. Payload is validated first, so corrupted record returns error.
+    pub fn deserialize<T>(&self) -> Result<T, WalError>
+    where
+        T: Archive,
+        <T as Archive>::Archived: for<'a> CheckBytes<HighValidator<'a, rkyv::rancor::Error>>
+            + Deserialize<T, HighDeserializer<rkyv::rancor::Error>>,
+    {
+        rkyv::from_bytes::<T, rkyv::rancor::Error>(&self.payload[..])
+            .map_err(|_| WalError::DeserializeError)
+    }
+}
+
+/// Write-ahead log of the table's operations. Every operation is appended before it's acknowledged, so operations done
+/// after last `persist` can be replayed on load. `persist` removes operations that are already in table's file.
+///
+/// Record is operation byte, little-endian `u32` payload length, `u32` CRC32 of payload and `rkyv` payload. Record
+/// that was not fully written before crash is detected by length or checksum and is dropped with the rest of log.
+/// Payload is never empty, so zero-filled tail of the file is not read as a record.
+#[derive(Debug)]
+pub struct Wal {
+    path: PathBuf,
+    file: Mutex<File>,
+    durability: DurabilityMode,
+}
+
+impl Wal {
+    /// Opens log for appending, creating it if it doesn't exist. Log's directory is synced, so created log is not
+    /// lost on crash.
+    pub fn open<P: AsRef<Path>>(path: P, durability: DurabilityMode) -> Result<Self, WalError> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(WalError::Io)?;
+        sync_parent_dir(path.as_ref())?;
+
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            file: Mutex::new(file),
+            durability,
+        })
+    }
+
+    /// Returns [`DurabilityMode`] of the log's appends.
+    pub fn durability(&self) -> DurabilityMode {
+        self.durability
+    }
+
+    /// Appends operation with serialized `value`. With [`DurabilityMode::Fsync`] record is synced to disk before return.
+    pub fn append<T>(&self, op: WalOp, value: &T) -> Result<(), WalError>
+    where
+        T: for<'a> Serialize<
+            Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+        >,
+    {
+        self.writer().append(op, value)
+    }
+
+    /// Appends operation with value returned by `f`. `f` is called under log's lock, so values are appended in the
+    /// same order as they are taken, which is needed for values like generator's state.
+    pub fn append_with<T, F>(&self, op: WalOp, f: F) -> Result<(), WalError>
+    where
+        T: for<'a> Serialize<
+            Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+        >,
+        F: FnOnce() -> T,
+    {
+        self.writer().append(op, &f())
+    }
+
+    /// Locks log for appending. Other appends wait until returned [`WalWriter`] is dropped, so operation applied
+    /// while it's held is logged in the same order as it's applied.
+    pub fn writer(&self) -> WalWriter<'_> {
+        WalWriter {
+            wal: self,
+            file: self.file.lock().unwrap_or_else(PoisonError::into_inner),
+            records: vec![],
+        }
+    }
+
+    fn encode<T>(op: WalOp, value: &T) -> Result<Vec<u8>, WalError>
+    where
+        T: for<'a> Serialize<
+            Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+        >,
+    {
+        let payload =
+            rkyv::to_bytes::<rkyv::rancor::Error>(value).map_err(|_| WalError::SerializeError)?;
+        if payload.is_empty() {
+            return Err(WalError::SerializeError);
+        }
+        let mut record = Vec::with_capacity(RECORD_HEADER_LENGTH + payload.len());
+        record.push(op as u8);
+        record.extend_from_slice(&(payload.len() as u32).to_le_bytes());
+        record.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+        record.extend_from_slice(&payload);
+
+        Ok(record)
+    }
+
+    /// Writes `record` to the end of the log. If write or sync fails, log is cut back to its previous length, so
+    /// partly written record doesn't hide records appended after it.
+    fn write(&self, file: &mut File, record: &[u8]) -> Result<(), WalError> {
+        let end = file.metadata().map_err(WalError::Io)?.len();
+        let written = file.write_all(record).and_then(|_| {
+            if self.durability == DurabilityMode::Fsync {
+                file.sync_data()
+            } else {
+                Ok(())
+            }
+        });
+        if let Err(e) = written {
+            let _ = file.set_len(end);
+            return Err(WalError::Io(e));
+        }
+
+        Ok(())
+    }
+
+    /// Returns current end of the log. It's taken before table is persisted and passed to [`Wal::checkpoint`] after.
+    pub fn mark(&self) -> Result<u64, WalError> {
+        let file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        Ok(file.metadata().map_err(WalError::Io)?.len())
+    }
+
+    /// Removes records before `mark`, which are already persisted in table's file. Records appended after `mark` are
+    /// kept. Log is replaced atomically by renaming new file over it.
+    pub fn checkpoint(&self, mark: u64) -> Result<(), WalError> {
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut tail = vec![];
+        let mut reader = File::open(&self.path).map_err(WalError::Io)?;
+        reader.seek(SeekFrom::Start(mark)).map_err(WalError::Io)?;
+        reader.read_to_end(&mut tail).map_err(WalError::Io)?;
+
+        let mut tmp_path = self.path.clone().into_os_string();
+        tmp_path.push(".tmp");
+        let mut tmp = File::create(&tmp_path).map_err(WalError::Io)?;
+        tmp.write_all(&tail).map_err(WalError::Io)?;
+        if self.durability == DurabilityMode::Fsync {
+            tmp.sync_all().map_err(WalError::Io)?;
+        }
+        // New log is opened before rename, so after rename appends never go to the replaced file.
+        let reopened = OpenOptions::new()
+            .append(true)
+            .open(&tmp_path)
+            .map_err(WalError::Io)?;
+        fs::rename(&tmp_path, &self.path).map_err(WalError::Io)?;
+        *file = reopened;
+        if self.durability == DurabilityMode::Fsync {
+            sync_parent_dir(&self.path)?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads all records of the log. Returns empty list if log doesn't exist. Reading stops on the first record that
+    /// is incomplete, empty or has wrong checksum, because it can only be the last record written before crash or
+    /// zero-filled tail of the file. Payload is never empty, so zero length marks zero-filled tail. Operation is
+    /// checked only for complete record with right checksum.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Vec<WalRecord>, WalError> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => return Err(WalError::Io(e)),
+        };
+
+        let mut records = vec![];
+        let mut rest = bytes.as_slice();
+        while rest.len() >= RECORD_HEADER_LENGTH {
+            let length = u32::from_le_bytes(rest[1..5].try_into().expect("4 bytes")) as usize;
+            let checksum = u32::from_le_bytes(rest[5..9].try_into().expect("4 bytes"));
+            if length == 0 {
+                break;
+            }
+            let Some(payload) = rest[RECORD_HEADER_LENGTH..].get(..length) else {
+                break;
+            };
+            if crc32fast::hash(payload) != checksum {
+                break;
+            }
+            let op = WalOp::from_id(rest[0]).ok_or(WalError::UnknownOperation(rest[0]))?;
+
+            let mut aligned = AlignedVec::with_capacity(length);
+            aligned.extend_from_slice(payload);
+            records.push(WalRecord {
+                op,
+                payload: aligned,
+            });
+            rest = &rest[RECORD_HEADER_LENGTH + length..];
+        }
+
+        Ok(records)
+    }
+}
+
+/// Locked [`Wal`], which is returned by [`Wal::writer`].
+#[derive(Debug)]
+pub struct WalWriter<'a> {
+    wal: &'a Wal,
+    file: MutexGuard<'a, File>,
+    records: Vec<u8>,
+}
+
+impl WalWriter<'_> {
+    /// Appends operation with serialized `value`, like [`Wal::append`].
+    pub fn append<T>(&mut self, op: WalOp, value: &T) -> Result<(), WalError>
+    where
+        T: for<'a> Serialize<
+            Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+        >,
+    {
+        self.push(op, value)?;
+        self.flush()
+    }
+
+    /// Adds operation with serialized `value` to the records which are appended by next [`WalWriter::flush`].
+    pub fn push<T>(&mut self, op: WalOp, value: &T) -> Result<(), WalError>
+    where
+        T: for<'a> Serialize<
+            Strategy<Serializer<AlignedVec, ArenaHandle<'a>, Share>, rkyv::rancor::Error>,
+        >,
+    {
+        let record = Wal::encode(op, value)?;
+        self.records.extend_from_slice(&record);
+        Ok(())
+    }
+
+    /// Appends records added by [`WalWriter::push`] by single write, so they are logged together or not logged. If
+    /// write fails, records are dropped.
+    pub fn flush(&mut self) -> Result<(), WalError> {
+        if self.records.is_empty() {
+            return Ok(());
+        }
+        let res = self.wal.write(&mut self.file, &self.records);
+        self.records.clear();
+        res
+    }
+}
+
+/// Syncs directory of the `path`, so file created or renamed in it is not lost on crash. Directories can't be opened
+/// as files on Windows, so it's no-op there.
+fn sync_parent_dir(path: &Path) -> Result<(), WalError> {
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+        File::open(dir)
+            .and_then(|dir| dir.sync_all())
+            .map_err(WalError::Io)?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+
+    Ok(())
+}
+
+#[derive(Debug, Display, Error)]
+pub enum WalError {
+    Io(std::io::Error),
+
+    #[display("operation can't be serialized")]
+    SerializeError,
+
+    #[display("record can't be deserialized")]
+    DeserializeError,
+
+    /// Record has unknown operation identifier.
+    #[display("unknown operation {}", _0)]
+    UnknownOperation(#[error(not(source))] u8),
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{self, OpenOptions};
+    use std::io::Write;
+
+    use super::{Wal, WalOp};
+    use crate::database::DurabilityMode;
+
+    #[test]
+    fn append_read_checkpoint() {
+        let dir = std::env::temp_dir().join(format!("worktable-wal-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.wt.wal");
+
+        let wal = Wal::open(&path, DurabilityMode::NoSync).unwrap();
+        wal.append(WalOp::Insert, &1u64).unwrap();
+        wal.append(WalOp::Update, &2u64).unwrap();
+        let mark = wal.mark().unwrap();
+        wal.append(WalOp::Delete, &3u64).unwrap();
+
+        let records = Wal::read(&path).unwrap();
+        assert_eq!(
+            records.iter().map(|r| r.op).collect::<Vec<_>>(),
+            vec![WalOp::Insert, WalOp::Update, WalOp::Delete]
+        );
+        assert_eq!(records[1].deserialize::<u64>().unwrap(), 2);
+
+        wal.checkpoint(mark).unwrap();
+        wal.append(WalOp::Truncate, &0u8).unwrap();
+        let records = Wal::read(&path).unwrap();
+        assert_eq!(
+            records.iter().map(|r| r.op).collect::<Vec<_>>(),
+            vec![WalOp::Delete, WalOp::Truncate]
+        );
+        assert_eq!(records[0].deserialize::<u64>().unwrap(), 3);
+
+        // Torn last record is dropped.
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[WalOp::Insert as u8, 8, 0]).unwrap();
+        assert_eq!(Wal::read(&path).unwrap().len(), 2);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn zero_filled_tail() {
+        let dir = std::env::temp_dir().join(format!("worktable-wal-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.wt.wal");
+
+        let wal = Wal::open(&path, DurabilityMode::NoSync).unwrap();
+        wal.append(WalOp::Insert, &1u64).unwrap();
+        assert!(wal.append(WalOp::Truncate, &()).is_err());
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[0; 64]).unwrap();
+        wal.append(WalOp::Update, &2u64).unwrap();
+
+        let records = Wal::read(&path).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].deserialize::<u64>().unwrap(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn torn_tail_with_unknown_operation() {
+        let dir = std::env::temp_dir().join(format!("worktable-wal-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.wt.wal");
+
+        let wal = Wal::open(&path, DurabilityMode::NoSync).unwrap();
+        wal.append(WalOp::Insert, &1u64).unwrap();
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(&[0xff, 8, 0, 0, 0, 1, 2, 3, 4, 5]).unwrap();
+
+        let records = Wal::read(&path).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].deserialize::<u64>().unwrap(), 1);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn record_with_zero_checksum() {
+        let dir = std::env::temp_dir().join(format!("worktable-wal-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.wt.wal");
+
+        let payload = [157, 10, 217, 109];
+        assert_eq!(crc32fast::hash(&payload), 0);
+        let mut record = vec![WalOp::Update as u8, 4, 0, 0, 0, 0, 0, 0, 0];
+        record.extend_from_slice(&payload);
+        fs::write(&path, &record).unwrap();
+        let wal = Wal::open(&path, DurabilityMode::NoSync).unwrap();
+        wal.append(WalOp::Delete, &3u64).unwrap();
+
+        let records = Wal::read(&path).unwrap();
+        assert_eq!(
+            records.iter().map(|r| r.op).collect::<Vec<_>>(),
+            vec![WalOp::Update, WalOp::Delete]
+        );
+        assert_eq!(records[0].payload.as_slice(), &payload);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,5 +1,7 @@
 use std::sync::Arc;
 
+use rkyv::{Archive, Deserialize, Serialize};
+use uuid::Uuid;
 use worktable::prelude::*;
 use worktable::worktable;
 
@@ -27,13 +29,138 @@ worktable! (
     },
 );
 
+worktable! (
+    name: TestPersistAutoincrement,
+    persist: true,
+    columns: {
+        id: u64 primary_key autoincrement,
+        another: u64,
+    },
+);
+
+worktable! (
+    name: TestPersistUuid,
+    persist: true,
+    columns: {
+        id: Uuid primary_key custom,
+        another: u64,
+    },
+);
+
+worktable! (
+    name: TestPersistHash,
+    persist: true,
+    columns: {
+        id: u64 primary_key autoincrement,
+        value: u64,
+        another: u64,
+    },
+    indexes: {
+        value_idx: value LockedHashMap,
+        another_idx: another,
+    },
+);
+
+worktable! (
+    name: TestPersistOptional,
+    persist: true,
+    columns: {
+        id: u64 primary_key autoincrement,
+        another: u64 optional,
+    },
+    indexes: {
+        another_idx: another,
+    },
+);
+
+#[derive(
+    Archive,
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    Deserialize,
+    Eq,
+    Hash,
+    Ord,
+    PartialEq,
+    PartialOrd,
+    Serialize,
+)]
+#[rkyv(compare(PartialEq), derive(Debug))]
+pub enum TaskStatus {
+    #[default]
+    Pending,
+    Active,
+    Done,
+}
+
+impl SizeMeasurable for TaskStatus {
+    fn aligned_size(&self) -> usize {
+        // Unit-only enum is archived as its `u8` discriminant.
+        (*self as u8).aligned_size()
+    }
+}
+
+worktable! (
+    name: TestPersistEnum,
+    persist: true,
+    columns: {
+        id: u64 primary_key autoincrement,
+        status: TaskStatus,
+    },
+    indexes: {
+        status_idx: status,
+    },
+);
+
+/// Same table with persisted and not persisted secondary index. Both of them use the same file, so file written by one
+/// declaration is loaded by another.
+pub mod index_persisted {
+    use worktable::prelude::*;
+    use worktable::worktable;
+
+    worktable! (
+        name: TestIndexPersist,
+        persist: true,
+        columns: {
+            id: u64 primary_key autoincrement,
+            another: u64,
+        },
+        indexes: {
+            another_idx: another,
+        },
+    );
+}
+
+pub mod index_not_persisted {
+    use worktable::prelude::*;
+    use worktable::worktable;
+
+    worktable! (
+        name: TestIndexPersist,
+        persist: true,
+        columns: {
+            id: u64 primary_key autoincrement,
+            another: u64,
+        },
+        indexes: {
+            another_idx: another persist: false,
+        },
+    );
+}
+
+impl TablePrimaryKey for TestPersistUuidPrimaryKey {
+    type Generator = UuidV7Generator;
+}
+
 pub const TEST_ROW_COUNT: usize = 100;
 
 pub fn get_empty_test_wt() -> TestPersistWorkTable {
-    let manager = Arc::new(DatabaseManager {
-        config_path: "tests/data".to_string(),
-        database_files_dir: "test/data".to_string(),
-    });
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data".to_string(),
+        "test/data".to_string(),
+    ));
 
     let a = TestWithoutSecondaryIndexesIndexPersisted::default();
 
@@ -55,10 +182,10 @@ pub fn get_test_wt() -> TestPersistWorkTable {
 }
 
 pub fn get_test_wt_without_secondary_indexes() -> TestWithoutSecondaryIndexesWorkTable {
-    let manager = Arc::new(DatabaseManager {
-        config_path: "tests/data".to_string(),
-        database_files_dir: "test/data".to_string(),
-    });
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data".to_string(),
+        "test/data".to_string(),
+    ));
 
     let table = TestWithoutSecondaryIndexesWorkTable::new(manager);
 
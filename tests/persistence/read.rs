@@ -1,12 +1,13 @@
-use std::fs::File;
+use std::fs::{File, OpenOptions};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use worktable::prelude::*;
 
 // TODO: Fix naming.
 use crate::persistence::{
-    get_empty_test_wt, get_test_wt, TestPersistRow, TestPersistWorkTable, TEST_PERSIST_INNER_SIZE,
-    TEST_PERSIST_PAGE_SIZE, TEST_ROW_COUNT,
+    get_empty_test_wt, get_test_wt, TestPersistRow, TestPersistSpace, TestPersistWorkTable,
+    TEST_PERSIST_INNER_SIZE, TEST_PERSIST_PAGE_SIZE, TEST_ROW_COUNT,
 };
 
 #[test]
@@ -38,6 +39,29 @@ fn test_info_parse() {
     assert_eq!(info.inner.empty_links_list, vec![]);
 }
 
+#[test]
+fn test_parse_pages() {
+    let file = File::open("tests/data/expected/test_persist.wt").unwrap();
+    let pages = parse_pages(file).collect::<Result<Vec<_>, _>>().unwrap();
+
+    assert_eq!(
+        pages.iter().map(|p| p.header.page_type).collect::<Vec<_>>(),
+        vec![
+            PageType::SpaceInfo,
+            PageType::Index,
+            PageType::Index,
+            PageType::Data
+        ]
+    );
+    assert_eq!(pages[3].header.page_id, 3.into());
+    assert_eq!(pages[3].inner.len(), 4752);
+
+    let file = File::open("tests/data/expected/test_persist.wt").unwrap();
+    let info = parse_space_info::<_, ()>(file).unwrap();
+    assert_eq!(info.inner.name, "TestPersist");
+    assert_eq!(info.inner.data_intervals, vec![Interval(3, 3)]);
+}
+
 #[test]
 fn test_index_parse() {
     let mut file = File::open("tests/data/expected/test_persist.wt").unwrap();
@@ -89,10 +113,10 @@ fn test_data_parse() {
 
 #[test]
 fn test_space_parse() {
-    let manager = Arc::new(DatabaseManager {
-        config_path: "tests/data".to_string(),
-        database_files_dir: "tests/data/expected".to_string(),
-    });
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data".to_string(),
+        "tests/data/expected".to_string(),
+    ));
     let table = TestPersistWorkTable::load_from_file(manager).unwrap();
     let expected = get_test_wt();
 
@@ -102,12 +126,39 @@ fn test_space_parse() {
     );
 }
 
+#[test]
+fn test_space_parse_readonly() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data".to_string(),
+        "tests/data/expected".to_string(),
+    ));
+    let table = TestPersistWorkTable::load_readonly_from_file(manager).unwrap();
+    let expected = get_test_wt();
+    let mut rows = expected.select_all().execute().unwrap();
+    rows.sort_by_key(|row| row.id);
+
+    assert_eq!(table.count(), rows.len());
+    assert_eq!(table.iter().collect::<Vec<_>>(), rows);
+    for row in rows {
+        let mut by_another = table.select_by_another(row.another);
+        by_another.sort_by_key(|row| row.id);
+        let mut expected_rows = expected
+            .select_by_another(row.another)
+            .unwrap()
+            .execute()
+            .unwrap();
+        expected_rows.sort_by_key(|row| row.id);
+        assert_eq!(by_another, expected_rows);
+        assert_eq!(table.get(&row.id.into()).unwrap(), Some(row));
+    }
+}
+
 #[test]
 fn test_space_parse_no_file() {
-    let manager = Arc::new(DatabaseManager {
-        config_path: "tests/data".to_string(),
-        database_files_dir: "tests/data/non-existent".to_string(),
-    });
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data".to_string(),
+        "tests/data/non-existent".to_string(),
+    ));
     let table = TestPersistWorkTable::load_from_file(manager).unwrap();
     let expected = get_empty_test_wt();
     assert_eq!(
@@ -118,10 +169,10 @@ fn test_space_parse_no_file() {
 
 #[test]
 fn test_space_insert_after_read() {
-    let manager = Arc::new(DatabaseManager {
-        config_path: "tests/data".to_string(),
-        database_files_dir: "tests/data/expected".to_string(),
-    });
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data".to_string(),
+        "tests/data/expected".to_string(),
+    ));
     let table = TestPersistWorkTable::load_from_file(manager).unwrap();
 
     let row = TestPersistRow {
@@ -140,10 +191,10 @@ fn test_space_insert_after_read() {
 
 #[tokio::test]
 async fn test_space_delete_after_read() {
-    let manager = Arc::new(DatabaseManager {
-        config_path: "tests/data".to_string(),
-        database_files_dir: "tests/data/expected".to_string(),
-    });
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data".to_string(),
+        "tests/data/expected".to_string(),
+    ));
     let table = TestPersistWorkTable::load_from_file(manager).unwrap();
 
     table
@@ -161,3 +212,278 @@ async fn test_space_delete_after_read() {
         expected.select_all().execute().unwrap()
     );
 }
+
+#[test]
+fn test_space_refresh_from_file() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/refresh".to_string(),
+        "tests/data/expected".to_string(),
+    ));
+    let table = TestPersistWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(
+        table
+            .refresh_from_file("tests/data/expected/test_persist.wt")
+            .unwrap(),
+        0
+    );
+
+    let external = TestPersistWorkTable::load_from_file(manager).unwrap();
+    let row = TestPersistRow {
+        another: TEST_ROW_COUNT as u64,
+        id: TEST_ROW_COUNT as u128,
+    };
+    external.insert(row.clone()).unwrap();
+    external.persist().unwrap();
+
+    let refreshed = table
+        .refresh_from_file("tests/data/refresh/test_persist.wt")
+        .unwrap();
+    assert_eq!(refreshed, 1);
+    assert_eq!(table.select(row.id.into()), Some(row.clone()));
+    assert_eq!(
+        table.select_by_another(row.another).unwrap().execute(),
+        vec![row]
+    );
+
+    let expected = get_test_wt();
+    assert_eq!(table.select(1.into()), expected.select(1.into()));
+}
+
+#[test]
+fn test_space_parse_migrated() {
+    static MIGRATED: AtomicBool = AtomicBool::new(false);
+
+    let mut file = File::open("tests/data/expected/test_persist.wt").unwrap();
+    let mut space = TestPersistSpace::parse_file(&mut file).unwrap();
+    space.info.header.data_version = DATA_VERSION - 1;
+    space.path = "tests/data/migration/older".to_string();
+    space.persist().unwrap();
+    write_checksums::<Crc32, _>(
+        "tests/data/migration/older/test_persist.wt",
+        DurabilityMode::default(),
+    )
+    .unwrap();
+
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/migration/older".to_string(),
+        "tests/data/migration/older".to_string(),
+    ));
+
+    register_migration((DATA_VERSION - 1) as u32, Ok);
+    let err = TestPersistWorkTable::load_from_file(manager.clone()).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<VersionError>(),
+        Some(VersionError::MigratedVersionMismatch { found, expected })
+            if *found == DATA_VERSION as u32 - 1 && *expected == DATA_VERSION as u32
+    ));
+
+    register_migration((DATA_VERSION - 1) as u32, |mut bytes| {
+        MIGRATED.store(true, Ordering::Relaxed);
+        // Data version is the first field of the space info page's header.
+        bytes[..4].copy_from_slice(&(DATA_VERSION as u32).to_le_bytes());
+        Ok(bytes)
+    });
+    let table = TestPersistWorkTable::load_from_file(manager).unwrap();
+    let expected = get_test_wt();
+
+    assert!(MIGRATED.load(Ordering::Relaxed));
+    assert_eq!(
+        table.select_all().execute().unwrap(),
+        expected.select_all().execute().unwrap()
+    );
+}
+
+#[test]
+fn test_space_parse_unsupported_version() {
+    let mut file = File::open("tests/data/expected/test_persist.wt").unwrap();
+    let mut space = TestPersistSpace::parse_file(&mut file).unwrap();
+    space.info.header.data_version = DATA_VERSION + 1;
+    space.path = "tests/data/migration/newer".to_string();
+    space.persist().unwrap();
+
+    let mut file = File::open("tests/data/migration/newer/test_persist.wt").unwrap();
+    let err = TestPersistSpace::parse_file(&mut file).unwrap_err();
+
+    assert!(matches!(
+        err.downcast_ref::<VersionError>(),
+        Some(VersionError::FileFromNewerVersion { found, supported })
+            if *found == DATA_VERSION as u32 + 1 && *supported == DATA_VERSION as u32
+    ));
+}
+
+#[test]
+fn test_load_from_newer_version() {
+    let mut file = File::open("tests/data/expected/test_persist.wt").unwrap();
+    let mut space = TestPersistSpace::parse_file(&mut file).unwrap();
+    space.info.header.data_version = DATA_VERSION + 1;
+    space.path = "tests/data/migration/newer_load".to_string();
+    space.persist().unwrap();
+    write_checksums::<Crc32, _>(
+        "tests/data/migration/newer_load/test_persist.wt",
+        DurabilityMode::default(),
+    )
+    .unwrap();
+
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/migration/newer_load".to_string(),
+        "tests/data/migration/newer_load".to_string(),
+    ));
+    let err = TestPersistWorkTable::load_from_file(manager.clone()).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<VersionError>(),
+        Some(VersionError::FileFromNewerVersion { .. })
+    ));
+
+    let table = TestPersistWorkTable::new(manager);
+    let err = table.persist_append().unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<VersionError>(),
+        Some(VersionError::FileFromNewerVersion { .. })
+    ));
+    let err = table.persist().unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<VersionError>(),
+        Some(VersionError::FileFromNewerVersion { .. })
+    ));
+}
+
+#[test]
+fn test_space_debug() {
+    let mut file = File::open("tests/data/expected/test_persist.wt").unwrap();
+    let space = TestPersistSpace::parse_file(&mut file).unwrap();
+    let debug = format!("{space:?}");
+
+    assert!(debug.starts_with("TestPersistSpace {"));
+    assert!(debug.contains(&format!("data_pages: {}", space.data.len())));
+    assert!(debug.contains(&format!(
+        "data_intervals: {:?}",
+        space.info.inner.data_intervals
+    )));
+    assert!(debug.contains(&format!(
+        "pk_gen_state: {:?}",
+        space.info.inner.pk_gen_state
+    )));
+    assert!(!debug.contains("DataPage"));
+}
+
+#[test]
+fn test_space_parse_lenient() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/lenient".to_string(),
+        "tests/data/lenient".to_string(),
+    ));
+    let table = TestPersistWorkTable::new(manager.clone());
+    for i in 0..1000 {
+        let row = TestPersistRow {
+            another: i as u64,
+            id: i,
+        };
+        table.insert(row).unwrap();
+    }
+    table.persist().unwrap();
+
+    // Cut last data page in the middle.
+    let path = "tests/data/lenient/test_persist.wt";
+    let file = OpenOptions::new().write(true).open(path).unwrap();
+    let len = file.metadata().unwrap().len();
+    file.set_len(len - 100).unwrap();
+
+    let mut file = File::open(path).unwrap();
+    let (recovered, report) = TestPersistWorkTable::parse_file_lenient(&mut file, manager).unwrap();
+
+    assert_eq!(report.pages_skipped, 1);
+    assert!(report.rows_recovered > 0);
+    assert_eq!(report.rows_recovered + report.rows_skipped, 1000);
+    let rows = recovered.select_all().execute().unwrap();
+    assert_eq!(rows.len(), report.rows_recovered);
+    for row in rows {
+        assert_eq!(table.select(row.id.into()), Some(row.clone()));
+        assert_eq!(
+            recovered.select_by_another(row.another).unwrap().execute(),
+            vec![row]
+        );
+    }
+}
+
+#[test]
+fn test_space_parse_lenient_truncated_data() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/lenient_truncated".to_string(),
+        "tests/data/lenient_truncated".to_string(),
+    ));
+    let table = TestPersistWorkTable::new(manager.clone());
+    for i in 0..1000 {
+        let row = TestPersistRow {
+            another: i as u64,
+            id: i,
+        };
+        table.insert(row).unwrap();
+    }
+    table.persist().unwrap();
+
+    // Cuts all data pages, so their interval points after the end of the file.
+    let path = "tests/data/lenient_truncated/test_persist.wt";
+    let space = TestPersistSpace::parse_file(&mut File::open(path).unwrap()).unwrap();
+    let first_data_page = space.info.inner.data_intervals[0].0;
+    let file = OpenOptions::new().write(true).open(path).unwrap();
+    file.set_len((first_data_page * TEST_PERSIST_PAGE_SIZE) as u64)
+        .unwrap();
+
+    let mut file = File::open(path).unwrap();
+    let (recovered, report) = TestPersistWorkTable::parse_file_lenient(&mut file, manager).unwrap();
+
+    assert_eq!(report.rows_recovered, 0);
+    assert_eq!(report.rows_skipped, 1000);
+    assert!(report.pages_skipped > 0);
+    assert!(recovered.select_all().execute().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_load_drops_invalid_empty_links() {
+    let _ = std::fs::remove_dir_all("tests/data/invalid_empty_links");
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/invalid_empty_links".to_string(),
+        "tests/data/invalid_empty_links".to_string(),
+    ));
+    let table = TestPersistWorkTable::new(manager.clone());
+    for i in 0..10 {
+        let row = TestPersistRow {
+            another: i as u64,
+            id: i,
+        };
+        table.insert(row).unwrap();
+    }
+    table.delete(3.into()).await.unwrap();
+    let empty_links = table.0.data.empty_links_snapshot();
+    let live = TableIndex::peek(&table.0.pk_map, &5.into()).unwrap();
+
+    // Free list of the file points to the live row and to the page which doesn't exist.
+    let mut space = table.into_space().unwrap();
+    space.info.inner.empty_links_list.push(Link {
+        page_id: 100.into(),
+        ..live
+    });
+    space.info.inner.empty_links_list.push(live);
+    space.persist().unwrap();
+    write_checksums::<Crc32, _>(
+        "tests/data/invalid_empty_links/test_persist.wt",
+        DurabilityMode::default(),
+    )
+    .unwrap();
+
+    let loaded = TestPersistWorkTable::load_from_file(manager).unwrap();
+    assert_eq!(loaded.0.data.empty_links_snapshot(), empty_links);
+    assert_eq!(loaded.0.data.stats().empty_links_dropped, 2);
+
+    loaded
+        .insert(TestPersistRow {
+            another: 10,
+            id: 10,
+        })
+        .unwrap();
+    assert_eq!(
+        loaded.select(5.into()),
+        Some(TestPersistRow { another: 5, id: 5 })
+    );
+    assert_eq!(loaded.verify_integrity(), Ok(()));
+}
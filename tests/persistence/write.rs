@@ -1,6 +1,17 @@
+use std::fs;
+use std::sync::Arc;
+
+use worktable::prelude::*;
+
 use crate::{check_if_files_are_same, remove_file_if_exists};
 
-use crate::persistence::{get_test_wt, get_test_wt_without_secondary_indexes};
+use crate::persistence::{
+    get_test_wt, get_test_wt_without_secondary_indexes, TestPersistAutoincrementRow,
+    TestPersistAutoincrementWorkTable, TestPersistEnumRow, TestPersistEnumWorkTable,
+    TestPersistHashRow, TestPersistHashWorkTable, TestPersistOptionalRow,
+    TestPersistOptionalWorkTable, TestPersistRow, TestPersistSpace, TestPersistUuidRow,
+    TestPersistUuidWorkTable, TestPersistWorkTable, TEST_ROW_COUNT,
+};
 
 #[test]
 fn test_persist() {
@@ -27,3 +38,753 @@ fn test_persist_without_secondary_indexes() {
         "tests/data/expected/test_without_secondary_indexes.wt".to_string()
     ))
 }
+
+#[test]
+fn test_persist_with_stats() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/stats".to_string(),
+        "tests/data/stats".to_string(),
+    ));
+    let mut table = get_test_wt();
+    table.1 = manager.clone();
+    let stats = table.persist_with_stats().unwrap();
+
+    let file_len = fs::metadata("tests/data/stats/test_persist.wt")
+        .unwrap()
+        .len();
+    assert_eq!(stats.bytes_written, file_len);
+    assert_eq!(stats.data_pages, table.0.data.get_page_count());
+    // Primary index and secondary index have at least one page each.
+    assert!(stats.index_pages >= 2);
+    assert_eq!(
+        (1 + stats.index_pages + stats.data_pages) as u64,
+        file_len.div_ceil(PAGE_SIZE as u64)
+    );
+}
+
+#[tokio::test]
+async fn test_checkpoint() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/checkpoint".to_string(),
+        "tests/data/checkpoint".to_string(),
+    ));
+    let mut table = get_test_wt();
+    table.1 = manager.clone();
+    let table = Arc::new(table);
+    let expected = table.select_all().execute().unwrap();
+
+    let handle = table.checkpoint().await.unwrap();
+    // Row inserted after capture is not in checkpoint.
+    table
+        .insert(TestPersistRow {
+            another: TEST_ROW_COUNT as u64,
+            id: TEST_ROW_COUNT as u128,
+        })
+        .unwrap();
+    let stats = handle.wait().unwrap();
+
+    assert_eq!(
+        stats.bytes_written,
+        fs::metadata("tests/data/checkpoint/test_persist.wt")
+            .unwrap()
+            .len()
+    );
+    let loaded = TestPersistWorkTable::load_from_file(manager).unwrap();
+    assert_eq!(loaded.select_all().execute().unwrap(), expected);
+}
+
+#[test]
+fn test_persist_with_checksum() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/checksum/xxhash".to_string(),
+        "tests/data/checksum/xxhash".to_string(),
+    ));
+    let mut table = get_test_wt();
+    table.1 = manager.clone();
+    table.persist_with_checksum::<XxHash64>().unwrap();
+
+    let loaded = TestPersistWorkTable::load_from_file(manager).unwrap();
+    assert_eq!(
+        loaded.select_all().execute().unwrap(),
+        table.select_all().execute().unwrap()
+    );
+}
+
+#[test]
+fn test_load_verifies_recorded_checksum() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/checksum/blake3".to_string(),
+        "tests/data/checksum/blake3".to_string(),
+    ));
+    let mut table = get_test_wt();
+    table.1 = manager.clone();
+    table.persist_with_checksum::<Blake3>().unwrap();
+
+    let path = "tests/data/checksum/blake3/test_persist.wt";
+    assert_eq!(verify_checksums(path).unwrap(), ChecksumAlgorithm::Blake3);
+
+    let mut bytes = fs::read(path).unwrap();
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+    fs::write(path, bytes).unwrap();
+    assert!(TestPersistWorkTable::load_from_file(manager).is_err());
+}
+
+#[test]
+fn test_load_detects_corrupt_space_info() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/checksum/space_info".to_string(),
+        "tests/data/checksum/space_info".to_string(),
+    ));
+    let mut table = get_test_wt();
+    table.1 = manager.clone();
+    table.persist().unwrap();
+
+    // Corrupts space info page's data, which contains intervals.
+    let path = "tests/data/checksum/space_info/test_persist.wt";
+    let mut bytes = fs::read(path).unwrap();
+    bytes[GENERAL_HEADER_SIZE + 1] ^= 1;
+    fs::write(path, bytes).unwrap();
+
+    assert!(matches!(
+        verify_checksums(path),
+        Err(ChecksumError::SpaceInfoCorrupt)
+    ));
+    let err = TestPersistWorkTable::load_from_file(manager).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ChecksumError>(),
+        Some(ChecksumError::SpaceInfoCorrupt)
+    ));
+}
+
+#[test]
+fn test_load_fails_without_checksums() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/checksum/missing".to_string(),
+        "tests/data/checksum/missing".to_string(),
+    ));
+    let mut table = get_test_wt();
+    table.1 = manager.clone();
+    table.persist().unwrap();
+
+    let path = "tests/data/checksum/missing/test_persist.wt";
+    fs::remove_file(checksum_path(path)).unwrap();
+    let err = TestPersistWorkTable::load_from_file(manager.clone()).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ChecksumError>(),
+        Some(ChecksumError::Missing)
+    ));
+
+    write_checksums::<Crc32, _>(path, DurabilityMode::default()).unwrap();
+    let loaded = TestPersistWorkTable::load_from_file(manager).unwrap();
+    assert_eq!(loaded.get_all().unwrap(), table.get_all().unwrap());
+}
+
+#[tokio::test]
+async fn test_persist_append() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/append".to_string(),
+        "tests/data/append".to_string(),
+    ));
+    let mut table = get_test_wt();
+    table.1 = manager.clone();
+    table.persist().unwrap();
+
+    for i in 100..2000 {
+        let row = TestPersistRow {
+            another: i as u64,
+            id: i,
+        };
+        table.insert(row).unwrap();
+    }
+    table.persist_append().unwrap();
+
+    // Row on already persisted page is changed, and it's rewritten in place.
+    table
+        .update(TestPersistRow { another: 0, id: 1 })
+        .await
+        .unwrap();
+    for i in 2000..2100 {
+        let row = TestPersistRow {
+            another: i as u64,
+            id: i,
+        };
+        table.insert(row).unwrap();
+    }
+    table.persist_append().unwrap();
+
+    assert!(verify_checksums("tests/data/append/test_persist.wt").is_ok());
+    let loaded = TestPersistWorkTable::load_from_file(manager).unwrap();
+    assert_eq!(loaded.get_all().unwrap().len(), 2099);
+    assert_eq!(
+        loaded.select(1.into()),
+        Some(TestPersistRow { another: 0, id: 1 })
+    );
+    for i in 2..2100 {
+        assert_eq!(
+            loaded.select(i.into()),
+            Some(TestPersistRow {
+                another: i as u64,
+                id: i
+            })
+        );
+    }
+    assert_eq!(
+        loaded.select_by_another(1999).unwrap().execute(),
+        vec![TestPersistRow {
+            another: 1999,
+            id: 1999
+        }]
+    );
+}
+
+#[test]
+fn test_persist_deterministic() {
+    for dir in [
+        "tests/data/deterministic/first",
+        "tests/data/deterministic/second",
+    ] {
+        let mut table = get_test_wt();
+        table.1 = Arc::new(DatabaseManager::new(dir.to_string(), dir.to_string()));
+        table.persist().unwrap();
+    }
+
+    assert!(check_if_files_are_same(
+        "tests/data/deterministic/first/test_persist.wt".to_string(),
+        "tests/data/deterministic/second/test_persist.wt".to_string()
+    ))
+}
+
+#[test]
+fn test_persist_deterministic_non_unique_indexes() {
+    let dirs = [
+        "tests/data/deterministic/non_unique_first",
+        "tests/data/deterministic/non_unique_second",
+    ];
+    for dir in dirs {
+        let manager = Arc::new(DatabaseManager::new(dir.to_string(), dir.to_string()));
+        let table = TestPersistHashWorkTable::new(manager);
+        for i in 0..100 {
+            let row = TestPersistHashRow {
+                id: table.get_next_pk().into(),
+                value: i % 7,
+                another: i % 3,
+            };
+            table.insert(row).unwrap();
+        }
+        table.persist().unwrap();
+    }
+
+    let [first, second] = dirs.map(|dir| fs::read(format!("{dir}/test_persist_hash.wt")).unwrap());
+    assert_eq!(first.len(), second.len());
+    // Intervals of the secondary indexes are kept in `HashMap` of `SpaceInfoData`, which is archived in its hash
+    // order, so only index and data pages after space info page are compared.
+    assert_eq!(first[PAGE_SIZE..], second[PAGE_SIZE..]);
+}
+
+#[test]
+fn test_spaces_diff() {
+    let write = |dir: &str, rows: Vec<TestPersistRow>| {
+        let manager = Arc::new(DatabaseManager::new(dir.to_string(), dir.to_string()));
+        let table = TestPersistWorkTable::new(manager);
+        for row in rows {
+            table.insert(row).unwrap();
+        }
+        table.persist().unwrap();
+        fs::File::open(format!("{dir}/test_persist.wt")).unwrap()
+    };
+    let rows = (1..100)
+        .map(|i| TestPersistRow {
+            another: i as u64,
+            id: i,
+        })
+        .collect::<Vec<_>>();
+
+    let mut first = write("tests/data/diff/first", rows.clone());
+    // Rows are inserted in reverse order, so they are stored by other links.
+    let mut second = write(
+        "tests/data/diff/second",
+        rows.iter().rev().cloned().collect(),
+    );
+    assert!(TestPersistSpace::spaces_equal(&mut first, &mut second).unwrap());
+
+    let mut changed = rows.clone();
+    changed[49].another = 500;
+    changed.pop();
+    let mut third = write("tests/data/diff/third", changed);
+    let mut first = fs::File::open("tests/data/diff/first/test_persist.wt").unwrap();
+    let diff = TestPersistSpace::spaces_diff(&mut first, &mut third).unwrap();
+    assert_eq!(diff.only_in_first, vec![rows[98].clone()]);
+    assert!(diff.only_in_second.is_empty());
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].1, rows[49]);
+    assert_eq!(diff.changed[0].2.another, 500);
+    assert!(diff.first_integrity.is_empty() && diff.second_integrity.is_empty());
+}
+
+#[test]
+fn test_persist_without_sync() {
+    let mut table = get_test_wt();
+    table.1 = Arc::new(DatabaseManager::new(
+        "tests/data/durability".to_string(),
+        "tests/data/durability".to_string(),
+    ));
+    table
+        .persist_with_durability(DurabilityMode::NoSync)
+        .unwrap();
+
+    assert!(check_if_files_are_same(
+        "tests/data/durability/test_persist.wt".to_string(),
+        "tests/data/expected/test_persist.wt".to_string()
+    ))
+}
+
+#[test]
+fn test_persist_streaming() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/streaming".to_string(),
+        "tests/data/streaming".to_string(),
+    ));
+    let mut table = get_test_wt();
+    table.1 = manager.clone();
+    for i in 100..2000 {
+        let row = TestPersistRow {
+            another: i as u64,
+            id: i,
+        };
+        table.insert(row).unwrap();
+    }
+    table.persist_streaming().unwrap();
+
+    assert!(verify_checksums("tests/data/streaming/test_persist.wt").is_ok());
+    let loaded = TestPersistWorkTable::load_from_file(manager).unwrap();
+    assert_eq!(loaded.get_all().unwrap(), table.get_all().unwrap());
+    assert_eq!(
+        loaded.select_by_another(1999).unwrap().execute(),
+        vec![TestPersistRow {
+            another: 1999,
+            id: 1999
+        }]
+    );
+}
+
+#[test]
+fn test_pk_gen_state_restored_on_load() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/pk_gen".to_string(),
+        "tests/data/pk_gen".to_string(),
+    ));
+    let table = TestPersistAutoincrementWorkTable::new(manager.clone());
+    let mut pks = vec![];
+    for i in 0..10 {
+        let row = TestPersistAutoincrementRow {
+            id: table.get_next_pk().into(),
+            another: i,
+        };
+        pks.push(table.insert(row).unwrap());
+    }
+    table.persist().unwrap();
+
+    let loaded = TestPersistAutoincrementWorkTable::load_from_file(manager).unwrap();
+    let row = TestPersistAutoincrementRow {
+        id: loaded.get_next_pk().into(),
+        another: 10,
+    };
+    let pk = loaded.insert(row).unwrap();
+
+    assert!(pks.iter().all(|existing| pk > *existing));
+    assert_eq!(loaded.select_all().execute().unwrap().len(), 11);
+}
+
+#[tokio::test]
+async fn test_persist_keeps_empty_links() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/empty_links".to_string(),
+        "tests/data/empty_links".to_string(),
+    ));
+    let table = TestPersistAutoincrementWorkTable::new(manager.clone());
+    let mut pks = vec![];
+    for i in 0..3 {
+        let row = TestPersistAutoincrementRow {
+            id: table.get_next_pk().into(),
+            another: i,
+        };
+        pks.push(table.insert(row).unwrap());
+    }
+    let link = TableIndex::peek(&table.0.pk_map, &pks[1]).unwrap();
+    table.delete(pks[1].clone()).await.unwrap();
+
+    table.persist().unwrap();
+    assert_eq!(table.0.data.empty_links_snapshot(), vec![link]);
+
+    let loaded = TestPersistAutoincrementWorkTable::load_from_file(manager).unwrap();
+    assert_eq!(loaded.0.data.empty_links_snapshot(), vec![link]);
+}
+
+#[test]
+fn test_persist_multi_page_primary_index() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/multi_page_index".to_string(),
+        "tests/data/multi_page_index".to_string(),
+    ));
+    let table = TestPersistWorkTable::new(manager.clone());
+    let count = 20_000;
+    for i in 0..count {
+        let row = TestPersistRow {
+            another: i as u64,
+            id: i,
+        };
+        table.insert(row).unwrap();
+    }
+
+    let space = table.into_space().unwrap();
+    let page_ids = space
+        .primary_index
+        .iter()
+        .map(|p| p.header.page_id.into())
+        .collect::<Vec<usize>>();
+    assert!(page_ids.len() > 1);
+    assert_eq!(
+        space
+            .primary_index
+            .iter()
+            .map(|p| p.inner.index_values.len())
+            .sum::<usize>(),
+        count as usize
+    );
+    assert!(page_ids.windows(2).all(|w| w[0] + 1 == w[1]));
+    let intervals = &space.info.inner.primary_key_intervals;
+    assert_eq!(intervals.len(), 1);
+    assert_eq!(intervals[0].0, page_ids[0]);
+    assert_eq!(intervals[0].1, *page_ids.last().unwrap());
+    let intervals = intervals
+        .iter()
+        .chain(&space.info.inner.secondary_index_intervals["another_idx"])
+        .chain(&space.info.inner.data_intervals)
+        .collect::<Vec<_>>();
+    assert!(intervals.windows(2).all(|w| w[0].1 < w[1].0));
+    table.persist().unwrap();
+
+    let loaded = TestPersistWorkTable::load_from_file(manager).unwrap();
+    assert_eq!(loaded.get_all().unwrap().len(), count as usize);
+    for i in 0..count {
+        assert_eq!(
+            loaded.select(i.into()),
+            Some(TestPersistRow {
+                another: i as u64,
+                id: i,
+            })
+        );
+    }
+    let space = loaded.into_space().unwrap();
+    assert_eq!(space.primary_index.len(), page_ids.len());
+}
+
+#[test]
+fn test_persist_empty_secondary_index() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/empty_index".to_string(),
+        "tests/data/empty_index".to_string(),
+    ));
+    let table = TestPersistWorkTable::new(manager.clone());
+
+    // Empty index still owns its page, so intervals must not overlap.
+    let space = table.into_space().unwrap();
+    let intervals = space
+        .info
+        .inner
+        .primary_key_intervals
+        .iter()
+        .chain(&space.info.inner.secondary_index_intervals["another_idx"])
+        .chain(&space.info.inner.data_intervals)
+        .collect::<Vec<_>>();
+    assert!(intervals.iter().all(|i| i.0 <= i.1));
+    assert!(intervals.windows(2).all(|w| w[0].1 < w[1].0));
+    table.persist().unwrap();
+
+    let loaded = TestPersistWorkTable::load_from_file(manager).unwrap();
+    let row = TestPersistRow { id: 1, another: 1 };
+    loaded.insert(row.clone()).unwrap();
+    assert_eq!(loaded.select_by_another(1).unwrap().execute(), vec![row]);
+
+    loaded.persist().unwrap();
+    let space = loaded.into_space().unwrap();
+    assert_eq!(space.primary_index.len(), 1);
+    assert_eq!(space.indexes.another_idx.len(), 1);
+}
+
+#[test]
+fn test_persist_all() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/persist_all".to_string(),
+        "tests/data/persist_all".to_string(),
+    ));
+    let table = TestPersistWorkTable::new(manager.clone()).register();
+    table.insert(TestPersistRow { id: 1, another: 1 }).unwrap();
+    let dropped = TestPersistAutoincrementWorkTable::new(manager.clone()).register();
+    drop(dropped);
+
+    let results = manager.persist_all().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].0, "TestPersist");
+    assert!(results[0].1.is_ok());
+
+    // Tables are registered in manager, so other manager with same paths doesn't persist them.
+    let other = DatabaseManager::new(
+        "tests/data/persist_all".to_string(),
+        "tests/data/persist_all".to_string(),
+    );
+    assert!(other.persist_all().unwrap().is_empty());
+
+    let loaded = TestPersistWorkTable::load_from_file(manager).unwrap();
+    assert_eq!(loaded.select(1.into()), table.select(1.into()));
+}
+
+#[test]
+fn test_persist_uuid_pk() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/uuid".to_string(),
+        "tests/data/uuid".to_string(),
+    ));
+    // Generator has no state, so empty table must be persisted and loaded too.
+    TestPersistUuidWorkTable::new(manager.clone())
+        .persist()
+        .unwrap();
+    let table = TestPersistUuidWorkTable::load_from_file(manager.clone()).unwrap();
+
+    let mut rows = vec![];
+    for i in 0..10 {
+        let row = TestPersistUuidRow {
+            id: table.get_next_pk().into(),
+            another: i,
+        };
+        table.insert(row.clone()).unwrap();
+        rows.push(row);
+    }
+    table.persist().unwrap();
+
+    let loaded = TestPersistUuidWorkTable::load_from_file(manager).unwrap();
+    for row in rows {
+        assert_eq!(row.id.get_version_num(), 7);
+        assert_eq!(loaded.select(row.id.into()), Some(row));
+    }
+    let pk = loaded.get_next_pk();
+    assert!(!loaded.exists(&pk));
+}
+
+#[test]
+fn test_builder_with_wal() {
+    let _ = fs::remove_dir_all("tests/data/builder");
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/builder".to_string(),
+        "tests/data/builder".to_string(),
+    ));
+
+    let table = TestPersistAutoincrementWorkTable::builder(manager.clone())
+        .wal(DurabilityMode::NoSync)
+        .empty_link_policy(EmptyLinkPolicy::LowestFirst)
+        .build()
+        .unwrap();
+    for i in 0..10 {
+        let row = TestPersistAutoincrementRow {
+            id: table.get_next_pk().into(),
+            another: i,
+        };
+        table.insert(row).unwrap();
+    }
+    // Table is dropped without persist, so rows are only in the log.
+    drop(table);
+
+    let table = TestPersistAutoincrementWorkTable::builder(manager)
+        .wal(DurabilityMode::Fsync)
+        .build()
+        .unwrap();
+    assert_eq!(table.select(9.into()).unwrap().another, 9);
+    assert_eq!(table.get_next_pk(), 10.into());
+}
+
+#[test]
+fn test_builder_write_policy() {
+    let _ = fs::remove_dir_all("tests/data/write_policy");
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/write_policy".to_string(),
+        "tests/data/write_policy".to_string(),
+    ));
+    let wal =
+        wal_path(table_file_path("tests/data/write_policy", "test_persist_autoincrement").unwrap());
+
+    let table = TestPersistAutoincrementWorkTable::builder(manager.clone())
+        .write_policy(WritePolicy::MemoryOnly)
+        .build()
+        .unwrap();
+    assert_eq!(table.write_policy(), WritePolicy::MemoryOnly);
+    table
+        .insert(TestPersistAutoincrementRow { id: 0, another: 0 })
+        .unwrap();
+    assert!(Wal::read(&wal).unwrap().is_empty());
+    drop(table);
+
+    let table = TestPersistAutoincrementWorkTable::builder(manager.clone())
+        .write_policy(WritePolicy::WalDurable)
+        .build()
+        .unwrap();
+    assert_eq!(table.write_policy(), WritePolicy::WalDurable);
+    // Row inserted with `MemoryOnly` policy was not persisted.
+    assert_eq!(table.select(0.into()), None);
+    table
+        .insert(TestPersistAutoincrementRow { id: 1, another: 1 })
+        .unwrap();
+    drop(table);
+
+    let table = TestPersistAutoincrementWorkTable::builder(manager)
+        .write_policy(WritePolicy::Synced)
+        .build()
+        .unwrap();
+    assert_eq!(table.write_policy(), WritePolicy::Synced);
+    assert_eq!(table.select(1.into()).unwrap().another, 1);
+}
+
+#[tokio::test]
+async fn test_wal_replay() {
+    let _ = fs::remove_dir_all("tests/data/wal");
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/wal".to_string(),
+        "tests/data/wal".to_string(),
+    ));
+    let wal = wal_path(table_file_path("tests/data/wal", "test_persist_autoincrement").unwrap());
+
+    let table =
+        TestPersistAutoincrementWorkTable::load_from_file_with_wal(manager.clone()).unwrap();
+    for i in 0..10 {
+        let row = TestPersistAutoincrementRow {
+            id: table.get_next_pk().into(),
+            another: i,
+        };
+        table.insert(row).unwrap();
+    }
+    table
+        .update(TestPersistAutoincrementRow { id: 3, another: 33 })
+        .await
+        .unwrap();
+    table.delete(5.into()).await.unwrap();
+    // Table is dropped without persist, so rows are only in the log.
+    drop(table);
+
+    let table =
+        TestPersistAutoincrementWorkTable::load_from_file_with_wal(manager.clone()).unwrap();
+    assert_eq!(table.select(3.into()).unwrap().another, 33);
+    assert_eq!(table.select(5.into()), None);
+    assert_eq!(table.select(9.into()).unwrap().another, 9);
+    assert_eq!(table.get_next_pk(), 10.into());
+
+    table.persist().unwrap();
+    assert!(Wal::read(&wal).unwrap().is_empty());
+    table
+        .insert(TestPersistAutoincrementRow {
+            id: 11,
+            another: 11,
+        })
+        .unwrap();
+    drop(table);
+
+    let table = TestPersistAutoincrementWorkTable::load_from_file_with_wal(manager).unwrap();
+    assert_eq!(table.select(3.into()).unwrap().another, 33);
+    assert_eq!(table.select(5.into()), None);
+    assert_eq!(table.select(11.into()).unwrap().another, 11);
+}
+
+#[test]
+fn test_persist_optional_index() {
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/optional".to_string(),
+        "tests/data/optional".to_string(),
+    ));
+    let table = TestPersistOptionalWorkTable::new(manager.clone());
+    let rows = (0..10)
+        .map(|i| {
+            let row = TestPersistOptionalRow {
+                id: table.get_next_pk().into(),
+                another: (i % 2 == 0).then_some(i % 4),
+            };
+            table.insert(row.clone()).unwrap();
+            row
+        })
+        .collect::<Vec<_>>();
+    table.persist().unwrap();
+
+    let loaded = TestPersistOptionalWorkTable::load_from_file(manager).unwrap();
+    for row in rows.iter() {
+        assert_eq!(loaded.select(row.id.into()), Some(row.clone()));
+    }
+    let mut selected = loaded.select_by_another(2).unwrap().execute();
+    selected.sort_by_key(|r| r.id);
+    assert_eq!(selected, vec![rows[2].clone(), rows[6].clone()]);
+    assert_eq!(loaded.select_by_another(0).unwrap().execute().len(), 3);
+}
+
+#[test]
+fn test_not_persisted_index() {
+    use crate::persistence::{index_not_persisted, index_persisted};
+
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/index_persist".to_string(),
+        "tests/data/index_persist".to_string(),
+    ));
+    let table = index_not_persisted::TestIndexPersistWorkTable::new(manager.clone());
+    let mut ids = vec![];
+    for i in 0..100 {
+        let row = index_not_persisted::TestIndexPersistRow {
+            id: table.get_next_pk().into(),
+            another: i % 10,
+        };
+        if row.another == 3 {
+            ids.push(row.id);
+        }
+        table.insert(row).unwrap();
+    }
+    table.persist().unwrap();
+
+    // File has no index pages, so index is rebuilt from the rows by both declarations.
+    let loaded =
+        index_not_persisted::TestIndexPersistWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(loaded.select_by_another(3).unwrap().execute().len(), 10);
+    let loaded =
+        index_persisted::TestIndexPersistWorkTable::load_from_file(manager.clone()).unwrap();
+    assert_eq!(loaded.select_by_another(3).unwrap().execute().len(), 10);
+
+    // File has index pages, but they are not used by declaration which doesn't persist index.
+    loaded.persist().unwrap();
+    let loaded = index_not_persisted::TestIndexPersistWorkTable::load_from_file(manager).unwrap();
+    let mut selected = loaded.select_by_another(3).unwrap().execute();
+    selected.sort_by_key(|r| r.id);
+    assert_eq!(selected.iter().map(|r| r.id).collect::<Vec<_>>(), ids);
+}
+
+#[test]
+fn test_persist_enum_index() {
+    use crate::persistence::TaskStatus;
+
+    let manager = Arc::new(DatabaseManager::new(
+        "tests/data/enum".to_string(),
+        "tests/data/enum".to_string(),
+    ));
+    let table = TestPersistEnumWorkTable::new(manager.clone());
+    let statuses = [TaskStatus::Pending, TaskStatus::Active, TaskStatus::Done];
+    for i in 0..30 {
+        let row = TestPersistEnumRow {
+            id: table.get_next_pk().into(),
+            status: statuses[i % 3],
+        };
+        table.insert(row).unwrap();
+    }
+    table.persist().unwrap();
+
+    let loaded = TestPersistEnumWorkTable::load_from_file(manager).unwrap();
+    let active = loaded
+        .select_by_status(TaskStatus::Active)
+        .unwrap()
+        .execute();
+    assert_eq!(active.len(), 10);
+    assert!(active.iter().all(|r| r.status == TaskStatus::Active));
+    assert_eq!(loaded.verify_integrity(), Ok(()));
+}
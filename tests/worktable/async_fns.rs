@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key autoincrement,
+        test: i64,
+        exchange: String
+    },
+    indexes: {
+        test_idx: test unique,
+    }
+);
+
+#[tokio::test]
+async fn async_fns() {
+    let table = Arc::new(TestWorkTable::default());
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert_async(row.clone()).await.unwrap();
+    assert_eq!(table.select_async(pk.clone()).await, Some(row.clone()));
+
+    let updated = TestRow {
+        exchange: "updated".to_string(),
+        ..row
+    };
+    table.update_async(updated.clone()).await.unwrap();
+    assert_eq!(table.get_async(pk.clone()).await.unwrap(), Some(updated));
+
+    let upserted = TestRow {
+        id: table.get_next_pk().into(),
+        test: 2,
+        exchange: "test".to_string(),
+    };
+    table.upsert_async(upserted.clone()).await.unwrap();
+    assert_eq!(table.get_all_async().await.unwrap().len(), 2);
+
+    table.delete_async(pk.clone()).await.unwrap();
+    assert_eq!(table.select_async(pk).await, None);
+    assert_eq!(table.get_all_async().await.unwrap(), vec![upserted]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn concurrent_inserts() {
+    let table = Arc::new(TestWorkTable::default());
+    let tasks = (0..8)
+        .map(|i| {
+            let table = table.clone();
+            tokio::spawn(async move {
+                let row = TestRow {
+                    id: table.get_next_pk().into(),
+                    test: i,
+                    exchange: "test".to_string(),
+                };
+                table.insert_async(row).await
+            })
+        })
+        .collect::<Vec<_>>();
+    for task in tasks {
+        task.await.unwrap().unwrap();
+    }
+
+    assert_eq!(table.get_all_async().await.unwrap().len(), 8);
+}
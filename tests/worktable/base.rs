@@ -64,6 +64,444 @@ fn iter_with() {
     table.iter_with(|_| Ok(())).unwrap()
 }
 
+#[test]
+fn builder() {
+    let table = TestWorkTable::builder()
+        .empty_link_policy(EmptyLinkPolicy::Fifo)
+        .active_pages(4)
+        .reserved_pages(2)
+        .max_bytes(1 << 30)
+        .build();
+    assert_eq!(table.0.data.empty_link_policy(), EmptyLinkPolicy::Fifo);
+    assert_eq!(table.0.data.active_pages(), 4);
+    assert_eq!(table.0.data.max_bytes(), Some(1 << 30));
+
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: 1,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert(row.clone()).unwrap();
+    assert_eq!(table.select(pk), Some(row));
+}
+
+#[tokio::test]
+async fn select_col_by_pk() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: 2,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert(row.clone()).unwrap();
+
+    assert_eq!(table.select_col_another_by_pk(pk.clone()), Some(2));
+    assert_eq!(table.select_col_test_by_pk(pk.clone()), Some(1));
+    assert_eq!(
+        table.select_col_exchange_by_pk(pk.clone()),
+        Some("test".to_string())
+    );
+
+    table.delete(pk.clone()).await.unwrap();
+    assert_eq!(table.select_col_another_by_pk(pk), None);
+}
+
+#[tokio::test]
+async fn with_row_ref() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: 2,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert(row.clone()).unwrap();
+
+    let exchange = table
+        .with_row_ref(pk.clone(), |archived| {
+            archived.exchange.as_str().to_string()
+        })
+        .unwrap();
+    assert_eq!(exchange, Some("test".to_string()));
+
+    let previous = unsafe {
+        table.with_row_mut_ref(pk.clone(), |archived| {
+            let previous = archived.another.to_native();
+            archived.another = (previous + 3).into();
+            previous
+        })
+    }
+    .unwrap();
+    assert_eq!(previous, Some(2));
+    assert_eq!(table.select(pk.clone()).unwrap().another, 5);
+
+    table.delete(pk.clone()).await.unwrap();
+    assert_eq!(table.with_row_ref(pk, |_| ()).unwrap(), None);
+}
+
+#[tokio::test]
+async fn iter_ordered() {
+    let table = TestWorkTable::default();
+    let mut pks = vec![];
+    for i in 0..10 {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test: i,
+            another: i as u64,
+            exchange: format!("test{i}"),
+        };
+        pks.push(table.insert(row).unwrap());
+    }
+
+    // Slots of the deleted rows are reused by the new ones, so rows are not in primary key's order in pages.
+    table.delete(pks[1].clone()).await.unwrap();
+    table.delete(pks[2].clone()).await.unwrap();
+    for i in 10..12 {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test: i,
+            another: i as u64,
+            exchange: format!("test{i}"),
+        };
+        pks.push(table.insert(row).unwrap());
+    }
+    pks.drain(1..3);
+
+    let ids = table.iter_ordered().map(|r| r.id).collect::<Vec<_>>();
+    assert_eq!(ids, pks.into_iter().map(Into::into).collect::<Vec<u64>>());
+}
+
+#[tokio::test]
+async fn verify_integrity() {
+    let table = TestWorkTable::default();
+    let mut links = vec![];
+    for i in 0..3 {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test: i,
+            another: i as u64,
+            exchange: format!("test{i}"),
+        };
+        let pk = table.insert(row).unwrap();
+        links.push(TableIndex::peek(&table.0.pk_map, &pk).unwrap());
+    }
+    let pk = table.insert(TestRow {
+        id: table.get_next_pk().into(),
+        test: 3,
+        another: 3,
+        exchange: "test3".to_string(),
+    });
+    table.delete(pk.unwrap()).await.unwrap();
+    assert_eq!(table.verify_integrity(), Ok(()));
+
+    TableIndex::remove(&table.0.indexes.test_idx, &0);
+    TableIndex::peek(&table.0.indexes.exchnage_idx, &"test1".to_string())
+        .unwrap()
+        .insert(links[0])
+        .unwrap();
+    table.0.data.delete(links[2]).unwrap();
+
+    let mut errors = table.verify_integrity().unwrap_err();
+    errors.sort_by_key(|e| e.to_string());
+    let mut expected = vec![
+        IntegrityError::FreedLink {
+            index: "primary",
+            link: links[2],
+        },
+        IntegrityError::FreedLink {
+            index: "test_idx",
+            link: links[2],
+        },
+        IntegrityError::FreedLink {
+            index: "exchnage_idx",
+            link: links[2],
+        },
+        IntegrityError::MissingEntry {
+            index: "test_idx",
+            link: links[0],
+        },
+        IntegrityError::KeyMismatch {
+            index: "exchnage_idx",
+            link: links[0],
+        },
+    ];
+    expected.sort_by_key(|e| e.to_string());
+    assert_eq!(errors, expected);
+}
+
+#[tokio::test]
+async fn scan_where() {
+    let table = TestWorkTable::default();
+    let mut pks = vec![];
+    for i in 0..10 {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test: i,
+            another: i as u64 % 3,
+            exchange: format!("test{i}"),
+        };
+        pks.push(table.insert(row).unwrap());
+    }
+    table.delete(pks[3].clone()).await.unwrap();
+
+    let mut rows = table
+        .scan_where(|row| row.another.to_native() == 0)
+        .unwrap();
+    rows.sort_by_key(|row| row.test);
+    assert_eq!(
+        rows.iter().map(|row| row.test).collect::<Vec<_>>(),
+        vec![0, 6, 9]
+    );
+    assert!(table
+        .scan_where(|row| row.exchange.as_str() == "test3")
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn snapshot_readonly() {
+    let table = TestWorkTable::default();
+    let mut pks = vec![];
+    for i in 0..10 {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test: i,
+            another: i as u64 % 3,
+            exchange: format!("test{i}"),
+        };
+        pks.push(table.insert(row).unwrap());
+    }
+    let snapshot = table.snapshot_readonly().unwrap();
+
+    table.delete(pks[0].clone()).await.unwrap();
+    let mut updated = table.select(pks[1].clone()).unwrap();
+    updated.another = 100;
+    table.update(updated).await.unwrap();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 10,
+        another: 0,
+        exchange: "test10".to_string(),
+    };
+    let pk = table.insert(row).unwrap();
+
+    assert_eq!(snapshot.count(), 10);
+    assert_eq!(snapshot.select(pks[0].clone()).unwrap().test, 0);
+    assert_eq!(snapshot.select(pks[1].clone()).unwrap().another, 1);
+    assert!(snapshot.select(pk).is_none());
+    assert_eq!(
+        snapshot.iter().map(|row| row.test).collect::<Vec<_>>(),
+        (0..10).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        snapshot
+            .scan_where(|row| row.another.to_native() == 0)
+            .iter()
+            .map(|row| row.test)
+            .collect::<Vec<_>>(),
+        vec![0, 3, 6, 9]
+    );
+    assert_eq!(snapshot.select_by_test(1).unwrap().another, 1);
+    assert!(snapshot.select_by_test(10).is_none());
+    assert_eq!(
+        snapshot
+            .select_by_exchange("test0".to_string())
+            .iter()
+            .map(|row| row.test)
+            .collect::<Vec<_>>(),
+        vec![0]
+    );
+}
+
+#[test]
+fn iter_with_links() {
+    let table = TestWorkTable::default();
+    for i in 0..5 {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test: i,
+            another: i as u64,
+            exchange: format!("test{i}"),
+        };
+        table.insert(row).unwrap();
+    }
+
+    let rows = table
+        .iter_with_links()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        rows.iter().map(|(_, row)| row.test).collect::<Vec<_>>(),
+        vec![0, 1, 2, 3, 4]
+    );
+    for (link, row) in rows {
+        assert_eq!(table.0.data.select(link).unwrap(), row);
+    }
+}
+
+#[tokio::test]
+async fn index_entries() {
+    let table = TestWorkTable::default();
+    assert_eq!(table.index_entries().count(), 0);
+
+    let mut pks = vec![];
+    for i in 0..5 {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test: i,
+            another: i as u64,
+            exchange: format!("test{i}"),
+        };
+        pks.push(table.insert(row).unwrap());
+    }
+    table.delete(pks.remove(2)).await.unwrap();
+
+    let entries = table.index_entries().collect::<Vec<_>>();
+    assert_eq!(
+        entries.iter().map(|(pk, _)| pk.clone()).collect::<Vec<_>>(),
+        pks
+    );
+    for (pk, link) in entries {
+        assert_eq!(
+            table.0.data.select(link).unwrap(),
+            table.select(pk).unwrap()
+        );
+    }
+}
+
+#[tokio::test]
+async fn get_all() {
+    let table = TestWorkTable::default();
+    assert!(table.get_all().unwrap().is_empty());
+
+    let mut pks = vec![];
+    for i in 0..5 {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test: i,
+            another: i as u64,
+            exchange: format!("test{i}"),
+        };
+        pks.push(table.insert(row).unwrap());
+    }
+    table.delete(pks[2].clone()).await.unwrap();
+
+    assert_eq!(
+        table
+            .get_all()
+            .unwrap()
+            .iter()
+            .map(|row| row.test)
+            .collect::<Vec<_>>(),
+        vec![0, 1, 3, 4]
+    );
+}
+
+fn transaction_table() -> (TestWorkTable, Vec<TestPrimaryKey>) {
+    let table = TestWorkTable::default();
+    let mut pks = vec![];
+    for i in 0..3 {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test: i,
+            another: i as u64,
+            exchange: format!("test{i}"),
+        };
+        pks.push(table.insert(row).unwrap());
+    }
+
+    (table, pks)
+}
+
+#[tokio::test]
+async fn transaction() {
+    let (table, pks) = transaction_table();
+    let inserted = TestRow {
+        id: table.get_next_pk().into(),
+        test: 3,
+        another: 3,
+        exchange: "test3".to_string(),
+    };
+    let mut updated = table.select(pks[0].clone()).unwrap();
+    updated.another = 100;
+
+    let res = table
+        .transaction(|tx| {
+            tx.insert(inserted.clone());
+            tx.update(updated.clone());
+            tx.delete(pks[1].clone());
+            Ok(1)
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(res, 1);
+    assert_eq!(table.select(inserted.id.into()), Some(inserted));
+    assert_eq!(table.select(pks[0].clone()), Some(updated));
+    assert!(table.select(pks[1].clone()).is_none());
+}
+
+#[tokio::test]
+async fn transaction_closure_error() {
+    let (table, pks) = transaction_table();
+    let before = table.get_all().unwrap();
+
+    let res = table
+        .transaction(|tx| {
+            tx.delete(pks[0].clone());
+            Err::<(), _>(WorkTableError::NotFound)
+        })
+        .await;
+
+    assert!(res.is_err());
+    assert_eq!(table.get_all().unwrap(), before);
+}
+
+#[tokio::test]
+async fn transaction_rollback() {
+    let (table, pks) = transaction_table();
+    let before = table.get_all().unwrap();
+    let mut updated = table.select(pks[0].clone()).unwrap();
+    updated.another = 100;
+    let inserted = TestRow {
+        id: table.get_next_pk().into(),
+        test: 3,
+        another: 3,
+        exchange: "test3".to_string(),
+    };
+    // Violates unique index on `test`.
+    let duplicate = TestRow {
+        id: table.get_next_pk().into(),
+        test: 2,
+        another: 4,
+        exchange: "test4".to_string(),
+    };
+
+    let res = table
+        .transaction(|tx| {
+            tx.update(updated.clone());
+            tx.delete(pks[1].clone());
+            tx.insert(inserted.clone());
+            tx.insert(duplicate.clone());
+            Ok(())
+        })
+        .await;
+
+    assert!(res.is_err());
+    assert_eq!(table.get_all().unwrap(), before);
+    assert!(table.select(inserted.id.into()).is_none());
+    assert!(table.select(duplicate.id.into()).is_none());
+    assert_eq!(
+        table
+            .select_by_exchange("test1".to_string())
+            .unwrap()
+            .execute(),
+        vec![before[1].clone()]
+    );
+}
+
 #[tokio::test]
 async fn iter_with_async() {
     let table = TestWorkTable::default();
@@ -167,6 +605,76 @@ fn insert() {
     assert!(table.select(2.into()).is_none())
 }
 
+#[tokio::test]
+async fn exists() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: 1,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert(row).unwrap();
+
+    assert!(table.exists(&pk));
+    assert!(!table.exists(&10.into()));
+
+    table.delete(pk.clone()).await.unwrap();
+    assert!(!table.exists(&pk));
+}
+
+#[tokio::test]
+async fn get() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: 1,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert(row.clone()).unwrap();
+
+    assert_eq!(table.get(&pk).unwrap(), Some(row));
+    assert_eq!(table.get(&10.into()).unwrap(), None);
+
+    table.delete(pk.clone()).await.unwrap();
+    assert_eq!(table.get(&pk).unwrap(), None);
+
+    // Primary index points to the page which doesn't exist, so row can't be read.
+    let link = Link {
+        page_id: 10.into(),
+        offset: 0,
+        length: 10,
+    };
+    TableIndex::insert(&table.0.pk_map, 20.into(), link).unwrap();
+    assert!(matches!(
+        table.get(&20.into()),
+        Err(PagesExecutionError::PageNotFound(_))
+    ));
+    assert_eq!(table.select(20.into()), None);
+}
+
+#[test]
+fn insert_active_pages() {
+    let table = TestWorkTable::default().with_active_pages(4);
+    let rows = (0..100)
+        .map(|i| TestRow {
+            id: table.get_next_pk().into(),
+            test: i,
+            another: i as u64,
+            exchange: format!("test{i}"),
+        })
+        .collect::<Vec<_>>();
+    for row in rows.iter() {
+        table.insert(row.clone()).unwrap();
+    }
+
+    assert!(table.0.data.get_page_count() >= 4);
+    for row in rows {
+        assert_eq!(table.select(row.id.into()), Some(row));
+    }
+}
+
 #[tokio::test]
 async fn update() {
     let table = TestWorkTable::default();
@@ -190,6 +698,284 @@ async fn update() {
     assert!(table.select(2.into()).is_none())
 }
 
+#[tokio::test]
+async fn update_returning() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: 1,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert(row.clone()).unwrap();
+    let updated = TestRow {
+        id: pk.clone().into(),
+        test: 2,
+        another: 3,
+        exchange: "test".to_string(),
+    };
+
+    let previous = table.update_returning(updated.clone()).await.unwrap();
+
+    assert_eq!(previous, Some(row));
+    assert_eq!(table.select(pk).unwrap(), updated);
+}
+
+#[tokio::test]
+async fn update_returning_missing() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: 10,
+        test: 1,
+        another: 1,
+        exchange: "test".to_string(),
+    };
+
+    let previous = table.update_returning(row).await.unwrap();
+
+    assert!(previous.is_none());
+    assert!(table.select(10.into()).is_none())
+}
+
+#[tokio::test]
+async fn update_relocates_grown_row() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: 1,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert(row.clone()).unwrap();
+    let link = TableIndex::peek(&table.0.pk_map, &pk).unwrap();
+    let updated = TestRow {
+        id: pk.clone().into(),
+        test: 2,
+        another: 1,
+        exchange: "much longer exchange name".to_string(),
+    };
+
+    let previous = table.update_returning(updated.clone()).await.unwrap();
+
+    assert_eq!(previous, Some(row));
+    assert_ne!(TableIndex::peek(&table.0.pk_map, &pk).unwrap(), link);
+    assert_eq!(table.select(pk).unwrap(), updated);
+    assert_eq!(table.select_by_test(2), Some(updated.clone()));
+    assert!(table.select_by_test(1).is_none());
+    assert_eq!(
+        table
+            .select_by_exchange(updated.exchange.clone())
+            .unwrap()
+            .execute(),
+        vec![updated]
+    );
+    assert!(table
+        .select_by_exchange("test".to_string())
+        .unwrap()
+        .execute()
+        .is_empty());
+    assert_eq!(table.select_all().execute().unwrap().len(), 1);
+}
+
+#[test]
+fn try_insert_doesnt_add_pages() {
+    let table = TestWorkTable::default();
+    let page_count = table.0.data.get_page_count();
+    let row = |table: &TestWorkTable, test| TestRow {
+        id: table.get_next_pk().into(),
+        test,
+        another: 1,
+        exchange: "test".to_string(),
+    };
+
+    let mut inserted = 0;
+    let err = loop {
+        match table.try_insert(row(&table, inserted)) {
+            Ok(_) => inserted += 1,
+            Err(e) => break e,
+        }
+    };
+    assert!(matches!(
+        err,
+        WorkTableError::PagesError(PagesExecutionError::NoSpace)
+    ));
+    assert!(inserted > 0);
+    assert_eq!(table.0.data.get_page_count(), page_count);
+    assert_eq!(
+        table.select_all().execute().unwrap().len(),
+        inserted as usize
+    );
+    assert!(table.select_by_test(inserted).is_none());
+
+    table.insert(row(&table, inserted)).unwrap();
+    assert_eq!(table.0.data.get_page_count(), page_count + 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn with_row_locked_blocks_writers() {
+    let table = Arc::new(TestWorkTable::default());
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: 1,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert(row.clone()).unwrap();
+    let finished = Arc::new(std::sync::atomic::AtomicBool::new(false));
+
+    let (locked_tx, locked_rx) = tokio::sync::oneshot::channel();
+    let locked = {
+        let table = table.clone();
+        let finished = finished.clone();
+        let pk = pk.clone();
+        tokio::spawn(async move {
+            table
+                .with_row_locked(pk, |row| {
+                    locked_tx.send(()).unwrap();
+                    std::thread::sleep(Duration::from_millis(100));
+                    finished.store(true, std::sync::atomic::Ordering::Release);
+                    row.another
+                })
+                .await
+                .unwrap()
+        })
+    };
+    locked_rx.await.unwrap();
+    table
+        .update(TestRow {
+            another: 2,
+            ..row.clone()
+        })
+        .await
+        .unwrap();
+
+    assert!(finished.load(std::sync::atomic::Ordering::Acquire));
+    assert_eq!(locked.await.unwrap(), Some(1));
+    assert_eq!(table.select(pk).unwrap().another, 2);
+    assert_eq!(
+        table.with_row_locked(10.into(), |_| ()).await.unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn with_row_locked_releases_lock_on_panic() {
+    let table = Arc::new(TestWorkTable::default());
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: 1,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert(row.clone()).unwrap();
+
+    let locked = {
+        let table = table.clone();
+        tokio::spawn(async move { table.with_row_locked(pk, |_| panic!("op failed")).await })
+    };
+    assert!(locked.await.is_err());
+
+    tokio::time::timeout(
+        Duration::from_secs(1),
+        table.update(TestRow {
+            another: 2,
+            ..row.clone()
+        }),
+    )
+    .await
+    .expect("row's lock is released")
+    .unwrap();
+    assert_eq!(table.select(row.id.into()).unwrap().another, 2);
+}
+
+#[tokio::test]
+async fn with_row_locked_mut() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: 1,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert(row.clone()).unwrap();
+    table
+        .insert(TestRow {
+            id: table.get_next_pk().into(),
+            test: 10,
+            another: 10,
+            exchange: "other".to_string(),
+        })
+        .unwrap();
+
+    let previous = table
+        .with_row_locked_mut(pk.clone(), |row| {
+            // Primary key's change is ignored.
+            row.id = 100;
+            row.test = 2;
+            row.exchange = "tset".to_string();
+            std::mem::replace(&mut row.another, 3)
+        })
+        .await
+        .unwrap();
+    assert_eq!(previous, Some(1));
+    let updated = TestRow {
+        id: row.id,
+        test: 2,
+        another: 3,
+        exchange: "tset".to_string(),
+    };
+    assert_eq!(table.select(pk.clone()), Some(updated.clone()));
+    assert!(table.select(100.into()).is_none());
+    assert_eq!(table.select_by_test(2), Some(updated.clone()));
+    assert!(table.select_by_test(1).is_none());
+    assert_eq!(
+        table
+            .select_by_exchange("tset".to_string())
+            .unwrap()
+            .execute(),
+        vec![updated.clone()]
+    );
+
+    // Longer row is moved.
+    table
+        .with_row_locked_mut(pk.clone(), |row| {
+            row.exchange = "much longer exchange name".to_string()
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        table.select(pk.clone()).unwrap().exchange,
+        "much longer exchange name"
+    );
+
+    // Unique index violation keeps the row.
+    let res = table
+        .with_row_locked_mut(pk.clone(), |row| row.test = 10)
+        .await;
+    assert!(matches!(res, Err(WorkTableError::AlreadyExists)));
+    assert_eq!(table.select(pk.clone()).unwrap().test, 2);
+    assert_eq!(table.verify_integrity(), Ok(()));
+
+    // Failed move releases the lock and resets it in the row.
+    let res = table
+        .with_row_locked_mut(pk.clone(), |row| row.exchange = "a".repeat(1 << 20))
+        .await;
+    assert!(res.is_err());
+    let link = TableIndex::peek(&table.0.pk_map, &pk).unwrap();
+    assert_eq!(
+        table.0.data.with_ref(link, |archived| archived.is_locked()),
+        Ok(None)
+    );
+    tokio::time::timeout(
+        Duration::from_secs(1),
+        table.with_row_locked_mut(pk.clone(), |row| row.another = 4),
+    )
+    .await
+    .expect("row's lock is released")
+    .unwrap();
+    assert_eq!(table.select(pk).unwrap().another, 4);
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn update_parallel() {
     let table = Arc::new(TestWorkTable::default());
@@ -221,6 +1007,36 @@ async fn update_parallel() {
     h.await.unwrap();
 }
 
+#[test]
+fn truncate() {
+    let table = TestWorkTable::default();
+    for i in 0..10 {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test: i,
+            another: i as u64,
+            exchange: "test".to_string(),
+        };
+        table.insert(row).unwrap();
+    }
+
+    table.truncate().unwrap();
+
+    assert!(table.select_all().execute().unwrap().is_empty());
+    assert!(table.select_by_test(1).is_none());
+    assert!(table.select_by_exchange("test".to_string()).is_err());
+
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: 1,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert(row.clone()).unwrap();
+    assert_eq!(pk, 0.into());
+    assert_eq!(table.select(pk).unwrap(), row);
+}
+
 #[tokio::test]
 async fn delete() {
     let table = TestWorkTable::default();
@@ -252,6 +1068,92 @@ async fn delete() {
     assert_eq!(link, new_link)
 }
 
+#[tokio::test]
+async fn delete_many_by_pk() {
+    let table = TestWorkTable::default();
+    let mut pks = vec![];
+    for i in 0..4 {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test: i,
+            another: 1,
+            exchange: "test".to_string(),
+        };
+        pks.push(table.insert(row).unwrap());
+    }
+    let links = [&pks[0], &pks[2]].map(|pk| TableIndex::peek(&table.0.pk_map, pk).unwrap());
+
+    let missing = table.get_next_pk();
+    let deleted = table
+        .delete_many_by_pk(&[pks[0].clone(), missing, pks[2].clone(), pks[0].clone()])
+        .await
+        .unwrap();
+    assert_eq!(deleted, 2);
+
+    assert!(table.select(pks[0].clone()).is_none());
+    assert!(table.select(pks[2].clone()).is_none());
+    assert!(table.select_by_test(0).is_none());
+    assert!(table.select_by_test(1).is_some());
+    assert_eq!(
+        table
+            .select_by_exchange("test".to_string())
+            .unwrap()
+            .execute()
+            .len(),
+        2
+    );
+    let empty_links = table.0.data.empty_links_snapshot();
+    assert_eq!(empty_links.len(), 2);
+    assert!(links.iter().all(|link| empty_links.contains(link)));
+    assert_eq!(table.verify_integrity(), Ok(()));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn delete_many_by_pk_waits_for_locked_row() {
+    let table = Arc::new(TestWorkTable::default());
+    let mut pks = vec![];
+    for i in 0..2 {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test: i,
+            another: 1,
+            exchange: "test".to_string(),
+        };
+        pks.push(table.insert(row).unwrap());
+    }
+
+    let (locked_tx, locked_rx) = tokio::sync::oneshot::channel();
+    let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+    let locked = {
+        let table = table.clone();
+        let pk = pks[0].clone();
+        tokio::spawn(async move {
+            table
+                .with_row_locked(pk, move |_| {
+                    locked_tx.send(()).unwrap();
+                    release_rx.recv().unwrap();
+                })
+                .await
+        })
+    };
+    locked_rx.await.unwrap();
+
+    let deleting = {
+        let table = table.clone();
+        let pks = pks.clone();
+        tokio::spawn(async move { table.delete_many_by_pk(&pks).await })
+    };
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(!deleting.is_finished());
+    assert!(table.select(pks[0].clone()).is_some());
+
+    release_tx.send(()).unwrap();
+    locked.await.unwrap().unwrap();
+    assert_eq!(deleting.await.unwrap().unwrap(), 2);
+    assert!(table.select(pks[0].clone()).is_none());
+    assert!(table.select(pks[1].clone()).is_none());
+}
+
 #[tokio::test]
 async fn delete_by_another() {
     let table = TestWorkTable::default();
@@ -349,6 +1251,36 @@ async fn delete_and_insert_less() {
     assert_ne!(link, new_link)
 }
 
+#[tokio::test]
+async fn delete_and_insert_lowest_first() {
+    let table = TestWorkTable::default().with_empty_link_policy(EmptyLinkPolicy::LowestFirst);
+    let mut pks = vec![];
+    for i in 0..3 {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test: i,
+            another: i as u64,
+            exchange: "test".to_string(),
+        };
+        pks.push(table.insert(row).unwrap());
+    }
+    let first_link = TableIndex::peek(&table.0.pk_map, &pks[0]).unwrap();
+    // With default policy second row's slot would be reused, because it was deleted last.
+    table.delete(pks[0].clone()).await.unwrap();
+    table.delete(pks[1].clone()).await.unwrap();
+
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 3,
+        another: 3,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert(row).unwrap();
+    let new_link = TableIndex::peek(&table.0.pk_map, &pk).unwrap();
+
+    assert_eq!(new_link, first_link)
+}
+
 #[tokio::test]
 async fn delete_and_replace() {
     let table = TestWorkTable::default();
@@ -416,8 +1348,17 @@ fn insert_same() {
         exchange: "test".to_string(),
     };
     let _ = table.insert(row.clone()).unwrap();
-    let res = table.insert(row.clone());
-    assert!(res.is_err())
+    let used = table.0.data.stats().used;
+
+    let duplicate = TestRow {
+        test: 2,
+        ..row.clone()
+    };
+    let res = table.insert(duplicate);
+    assert!(matches!(res, Err(WorkTableError::DuplicatePrimaryKey)));
+    // Duplicate is not saved to pages.
+    assert_eq!(table.0.data.stats().used, used);
+    assert_eq!(table.select(row.id.into()), Some(row));
 }
 
 #[test]
@@ -488,6 +1429,128 @@ fn select_multiple_by_exchange() {
     assert!(table.select_by_exchange("test1".to_string()).is_err())
 }
 
+#[test]
+fn select_by_test_range() {
+    let table = TestWorkTable::default();
+    for test in [5, 1, 4, 2, 3] {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test,
+            another: test as u64,
+            exchange: format!("test{}", test),
+        };
+        table.insert(row).unwrap();
+    }
+
+    let values = |rows: Vec<TestRow>| rows.into_iter().map(|r| r.test).collect::<Vec<_>>();
+    assert_eq!(
+        values(table.select_by_test_range(2..).unwrap().execute()),
+        vec![2, 3, 4, 5]
+    );
+    assert_eq!(
+        values(table.select_by_test_range(..3).unwrap().execute()),
+        vec![1, 2]
+    );
+    assert_eq!(
+        values(table.select_by_test_range(2..=4).unwrap().execute()),
+        vec![2, 3, 4]
+    );
+    assert!(table
+        .select_by_test_range(6..10)
+        .unwrap()
+        .execute()
+        .is_empty());
+}
+
+#[test]
+fn select_by_exchange_range() {
+    let table = TestWorkTable::default();
+    for (test, exchange) in [(1, "b"), (2, "a"), (3, "c"), (4, "b"), (5, "d")] {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test,
+            another: test as u64,
+            exchange: exchange.to_string(),
+        };
+        table.insert(row).unwrap();
+    }
+
+    let exchanges = |rows: Vec<TestRow>| rows.into_iter().map(|r| r.exchange).collect::<Vec<_>>();
+    assert_eq!(
+        exchanges(
+            table
+                .select_by_exchange_range("b".to_string()..)
+                .unwrap()
+                .execute()
+        ),
+        vec!["b", "b", "c", "d"]
+    );
+    let rows = table
+        .select_by_exchange_range("a".to_string()..="b".to_string())
+        .unwrap()
+        .execute();
+    assert_eq!(exchanges(rows.clone()), vec!["a", "b", "b"]);
+    let mut tests = rows.iter().map(|r| r.test).collect::<Vec<_>>();
+    tests.sort();
+    assert_eq!(tests, vec![1, 2, 4]);
+}
+
+#[tokio::test]
+async fn select_by_iter() {
+    let table = TestWorkTable::default();
+    for (test, exchange) in [(1, "b"), (2, "a"), (3, "c"), (4, "b"), (5, "b")] {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test,
+            another: test as u64,
+            exchange: exchange.to_string(),
+        };
+        table.insert(row).unwrap();
+    }
+
+    let mut tests = table
+        .select_by_exchange_iter("b".to_string())
+        .map(|r| r.unwrap().test)
+        .collect::<Vec<_>>();
+    tests.sort();
+    assert_eq!(tests, vec![1, 4, 5]);
+    assert_eq!(
+        table
+            .select_by_exchange_iter("b".to_string())
+            .take(2)
+            .count(),
+        2
+    );
+    assert_eq!(table.select_by_exchange_iter("e".to_string()).count(), 0);
+
+    assert_eq!(
+        table
+            .select_by_test_range_iter(2..)
+            .map(|r| r.unwrap().test)
+            .take(2)
+            .collect::<Vec<_>>(),
+        vec![2, 3]
+    );
+    assert_eq!(
+        table
+            .select_by_exchange_range_iter("a".to_string()..="b".to_string())
+            .map(|r| r.unwrap().exchange)
+            .collect::<Vec<_>>(),
+        vec!["a", "b", "b", "b"]
+    );
+
+    // Row changed after links are read from index is skipped.
+    let iter = table.select_by_exchange_iter("b".to_string());
+    let range_iter = table.select_by_exchange_range_iter("b".to_string()..="b".to_string());
+    let mut row = table.select_by_test(1).unwrap();
+    row.exchange = "x".to_string();
+    table.update(row).await.unwrap();
+    let mut tests = iter.map(|r| r.unwrap().test).collect::<Vec<_>>();
+    tests.sort();
+    assert_eq!(tests, vec![4, 5]);
+    assert_eq!(range_iter.count(), 2);
+}
+
 #[test]
 fn select_by_test() {
     let table = TestWorkTable::default();
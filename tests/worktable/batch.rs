@@ -0,0 +1,183 @@
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key,
+        test: i64,
+        exchange: String
+    },
+    indexes: {
+        test_idx: test unique,
+        exchange_idx: exchange,
+    }
+);
+
+#[test]
+fn validate_batch_pk_duplicate() {
+    let table = TestWorkTable::default();
+    let rows = vec![
+        TestRow {
+            id: 1,
+            test: 1,
+            exchange: "test".to_string(),
+        },
+        TestRow {
+            id: 2,
+            test: 2,
+            exchange: "test".to_string(),
+        },
+        TestRow {
+            id: 1,
+            test: 3,
+            exchange: "test".to_string(),
+        },
+    ];
+
+    assert_eq!(
+        table.validate_batch(&rows),
+        Err(vec![BatchError::DuplicatePrimaryKey { row: 2, first: 0 }])
+    );
+    assert!(table.insert_batch(rows).is_err());
+    assert!(table.select(1.into()).is_none());
+    assert!(table.select(2.into()).is_none());
+}
+
+#[test]
+fn validate_batch_unique_index() {
+    let table = TestWorkTable::default();
+    table
+        .insert(TestRow {
+            id: 1,
+            test: 1,
+            exchange: "test".to_string(),
+        })
+        .unwrap();
+    let rows = vec![
+        TestRow {
+            id: 1,
+            test: 2,
+            exchange: "test".to_string(),
+        },
+        TestRow {
+            id: 2,
+            test: 1,
+            exchange: "test".to_string(),
+        },
+        TestRow {
+            id: 3,
+            test: 2,
+            exchange: "test".to_string(),
+        },
+    ];
+
+    let errors = table.validate_batch(&rows).unwrap_err();
+    assert_eq!(errors.len(), 3);
+    assert!(errors.contains(&BatchError::PrimaryKeyExists { row: 0 }));
+    assert!(errors.contains(&BatchError::UniqueIndexViolation {
+        row: 1,
+        index: "test_idx"
+    }));
+    assert!(errors.contains(&BatchError::UniqueIndexViolation {
+        row: 2,
+        index: "test_idx"
+    }));
+}
+
+#[test]
+fn insert_batch() {
+    let table = TestWorkTable::default();
+    let rows = vec![
+        TestRow {
+            id: 1,
+            test: 1,
+            exchange: "test".to_string(),
+        },
+        TestRow {
+            id: 2,
+            test: 2,
+            exchange: "test".to_string(),
+        },
+    ];
+
+    table.insert_batch(rows.clone()).unwrap();
+    assert_eq!(table.select(1.into()), Some(rows[0].clone()));
+    assert_eq!(table.select(2.into()), Some(rows[1].clone()));
+    assert_eq!(
+        table
+            .select_by_exchange("test".to_string())
+            .unwrap()
+            .execute()
+            .len(),
+        2
+    );
+}
+
+#[test]
+fn bulk_load() {
+    let table = TestWorkTable::default();
+    let rows = (0..1000)
+        .map(|i| TestRow {
+            id: i,
+            test: i as i64,
+            exchange: format!("test_{}", i % 10),
+        })
+        .collect::<Vec<_>>();
+
+    table.bulk_load(rows.clone()).unwrap();
+
+    for row in &rows {
+        assert_eq!(table.select(row.id.into()), Some(row.clone()));
+        assert_eq!(table.select_by_test(row.test), Some(row.clone()));
+    }
+    assert_eq!(
+        table
+            .select_by_exchange("test_1".to_string())
+            .unwrap()
+            .execute()
+            .len(),
+        100
+    );
+}
+
+#[test]
+fn bulk_load_not_empty() {
+    let table = TestWorkTable::default();
+    table
+        .insert(TestRow {
+            id: 1,
+            test: 1,
+            exchange: "test".to_string(),
+        })
+        .unwrap();
+
+    let res = table.bulk_load(vec![TestRow {
+        id: 2,
+        test: 2,
+        exchange: "test".to_string(),
+    }]);
+
+    assert!(matches!(res, Err(WorkTableError::NotEmpty)));
+    assert!(table.select(2.into()).is_none());
+}
+
+#[test]
+fn select_many_by_pk() {
+    let table = TestWorkTable::default();
+    let rows = (0..3)
+        .map(|i| TestRow {
+            id: i,
+            test: i as i64,
+            exchange: "test".to_string(),
+        })
+        .collect::<Vec<_>>();
+    table.insert_batch(rows.clone()).unwrap();
+
+    let selected = table.select_many_by_pk(&[2.into(), 10.into(), 0.into()]);
+
+    assert_eq!(
+        selected,
+        vec![Some(rows[2].clone()), None, Some(rows[0].clone())]
+    );
+}
@@ -0,0 +1,190 @@
+use worktable::prelude::*;
+use worktable::worktable;
+
+fn lowercase_name(row: &TestRow) -> String {
+    row.name.to_lowercase()
+}
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key autoincrement,
+        name: String,
+        timestamp: u64,
+    },
+    indexes: {
+        day_idx: computed(u64, |row| row.timestamp / 86_400),
+        name_idx: computed(String, lowercase_name) unique,
+    },
+    queries: {
+        in_place: {
+            timestamp,
+        },
+    }
+);
+
+#[tokio::test]
+async fn select_by_computed_index() {
+    let table = TestWorkTable::default();
+    for (name, timestamp) in [("First", 10), ("Second", 20), ("Third", 86_410)] {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            name: name.to_string(),
+            timestamp,
+        };
+        table.insert(row).unwrap();
+    }
+
+    let first_day = table.select_by_day_idx(0).unwrap().execute();
+    assert_eq!(first_day.len(), 2);
+    let second_day = table.select_by_day_idx(1).unwrap().execute();
+    assert_eq!(second_day.len(), 1);
+    assert_eq!(second_day[0].name, "Third");
+    assert_eq!(
+        table
+            .select_by_name_idx("second".to_string())
+            .unwrap()
+            .timestamp,
+        20
+    );
+    assert_eq!(table.verify_integrity(), Ok(()));
+}
+
+#[tokio::test]
+async fn computed_unique_index_violation() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        name: "Name".to_string(),
+        timestamp: 0,
+    };
+    table.insert(row).unwrap();
+
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        name: "NAME".to_string(),
+        timestamp: 0,
+    };
+    assert!(table.insert(row).is_err());
+}
+
+#[tokio::test]
+async fn computed_index_update() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        name: "Name".to_string(),
+        timestamp: 10,
+    };
+    let pk = table.insert(row.clone()).unwrap();
+
+    // Same length, so row is updated in place.
+    table
+        .update(TestRow {
+            timestamp: 86_410,
+            name: "Mame".to_string(),
+            ..row.clone()
+        })
+        .await
+        .unwrap();
+    assert!(table.select_by_day_idx(0).unwrap().execute().is_empty());
+    assert_eq!(table.select_by_day_idx(1).unwrap().execute().len(), 1);
+    assert!(table.select_by_name_idx("name".to_string()).is_none());
+    assert!(table.select_by_name_idx("mame".to_string()).is_some());
+
+    // Longer name, so row is moved.
+    table
+        .update(TestRow {
+            timestamp: 86_410,
+            name: "Longer name".to_string(),
+            ..row.clone()
+        })
+        .await
+        .unwrap();
+    assert!(table.select_by_name_idx("mame".to_string()).is_none());
+    assert!(table
+        .select_by_name_idx("longer name".to_string())
+        .is_some());
+
+    let link = TableIndex::peek(&table.0.pk_map, &pk).unwrap();
+    table
+        .update_field_timestamp(link, 2 * 86_400)
+        .await
+        .unwrap();
+    assert!(table.select_by_day_idx(1).unwrap().execute().is_empty());
+    assert_eq!(table.select_by_day_idx(2).unwrap().execute().len(), 1);
+    assert_eq!(table.verify_integrity(), Ok(()));
+
+    table.delete(pk).await.unwrap();
+    assert!(table.select_by_day_idx(2).unwrap().execute().is_empty());
+    assert!(table
+        .select_by_name_idx("longer name".to_string())
+        .is_none());
+}
+
+#[tokio::test]
+async fn computed_unique_index_update_violation() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        name: "Name".to_string(),
+        timestamp: 10,
+    };
+    table.insert(row).unwrap();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        name: "Else".to_string(),
+        timestamp: 20,
+    };
+    let pk = table.insert(row.clone()).unwrap();
+
+    // Same length, so row is updated in place. `day_idx` is moved before `name_idx` fails, and is moved back.
+    let res = table
+        .update(TestRow {
+            name: "NAME".to_string(),
+            timestamp: 86_410,
+            ..row.clone()
+        })
+        .await;
+    assert!(matches!(res, Err(WorkTableError::AlreadyExists)));
+    assert_eq!(table.select(pk.clone()), Some(row.clone()));
+    assert_eq!(table.select_by_day_idx(0).unwrap().execute().len(), 2);
+    assert!(table.select_by_day_idx(1).unwrap().execute().is_empty());
+    assert_eq!(table.select_by_name_idx("else".to_string()), Some(row));
+    assert_eq!(table.verify_integrity(), Ok(()));
+
+    // Row is not locked after failed update.
+    let link = TableIndex::peek(&table.0.pk_map, &pk).unwrap();
+    table.update_field_timestamp(link, 86_410).await.unwrap();
+    assert_eq!(table.select_by_day_idx(1).unwrap().execute().len(), 1);
+}
+
+#[tokio::test]
+async fn computed_unique_index_relocate_violation() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        name: "Longer name".to_string(),
+        timestamp: 10,
+    };
+    table.insert(row).unwrap();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        name: "Else".to_string(),
+        timestamp: 20,
+    };
+    let pk = table.insert(row.clone()).unwrap();
+
+    // Longer name, so row is moved, and new slot is freed when `name_idx` fails.
+    let res = table
+        .update(TestRow {
+            name: "LONGER NAME".to_string(),
+            ..row.clone()
+        })
+        .await;
+    assert!(matches!(res, Err(WorkTableError::AlreadyExists)));
+    assert_eq!(table.select(pk.clone()), Some(row.clone()));
+    assert_eq!(table.select_by_name_idx("else".to_string()), Some(row));
+    assert_eq!(table.select_by_day_idx(0).unwrap().execute().len(), 2);
+    assert_eq!(table.verify_integrity(), Ok(()));
+}
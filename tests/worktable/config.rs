@@ -15,3 +15,68 @@ worktable! (
 fn test_page_size() {
     assert_eq!(TEST_PAGE_SIZE, 32_000)
 }
+
+worktable! (
+    name: SmallPage,
+    columns: {
+        id: u64 primary_key autoincrement,
+        name: String,
+    },
+    config: {
+        page_size: 1_024,
+    }
+);
+
+#[test]
+fn test_small_page_size() {
+    let table = SmallPageWorkTable::default();
+    let mut pks = vec![];
+    for i in 0..100 {
+        let row = SmallPageRow {
+            id: table.get_next_pk().into(),
+            name: format!("row{i}"),
+        };
+        pks.push(table.insert(row).unwrap());
+    }
+
+    assert_eq!(SMALL_PAGE_INNER_SIZE, 1_024 - GENERAL_HEADER_SIZE);
+    assert!(table.0.data.get_page_count() > 1);
+    assert_eq!(
+        table.0.data.memory_usage(),
+        table.0.data.get_page_count() * SMALL_PAGE_INNER_SIZE
+    );
+    assert_eq!(table.select(pks[50].clone()).unwrap().name, "row50");
+}
+
+#[test]
+fn test_spill_cold_pages() {
+    let path = std::env::temp_dir().join(format!("worktable-spill-{}.bin", uuid::Uuid::new_v4()));
+    let table = SmallPageWorkTable::builder()
+        .spill(SpillConfig {
+            path: path.clone(),
+            max_resident_pages: 2,
+        })
+        .build();
+    let mut pks = vec![];
+    for i in 0..500 {
+        let row = SmallPageRow {
+            id: table.get_next_pk().into(),
+            name: format!("row{i}"),
+        };
+        pks.push(table.insert(row).unwrap());
+    }
+
+    assert!(table.0.data.get_page_count() > 2);
+    assert!(table.0.data.resident_pages() <= 2);
+    assert!(path.exists());
+
+    // Evicted pages are loaded back on access.
+    for (i, pk) in pks.iter().enumerate() {
+        assert_eq!(table.select(pk.clone()).unwrap().name, format!("row{i}"));
+    }
+    table.0.data.evict_cold_pages().unwrap();
+    assert!(table.0.data.resident_pages() <= 2);
+
+    drop(table);
+    assert!(!path.exists());
+}
@@ -0,0 +1,214 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key autoincrement,
+        test: u64,
+        another: i64 optional,
+        exchange: String,
+    },
+    queries: {
+        in_place: {
+            test,
+            another,
+        },
+    }
+);
+
+#[tokio::test]
+async fn update_field() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: None,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert(row.clone()).unwrap();
+    let link = TableIndex::peek(&table.0.pk_map, &pk).unwrap();
+
+    table.update_field_test(link, 2).await.unwrap();
+    table.update_field_another(link, Some(3)).await.unwrap();
+
+    let selected_row = table.select(pk).unwrap();
+    assert_eq!(
+        selected_row,
+        TestRow {
+            id: row.id,
+            test: 2,
+            another: Some(3),
+            exchange: "test".to_string(),
+        }
+    );
+    assert_eq!(TableIndex::peek(&table.0.pk_map, &row.id.into()), Some(link));
+}
+
+#[tokio::test]
+async fn with_row_scalars_mut() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: None,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert(row.clone()).unwrap();
+
+    let previous = table
+        .with_row_scalars_mut(pk.clone(), |scalars| {
+            let previous = scalars.test.to_native();
+            *scalars.test = (previous + 1).into();
+            *scalars.another = rkyv::option::ArchivedOption::Some(5.into());
+            previous
+        })
+        .await
+        .unwrap();
+    assert_eq!(previous, Some(1));
+    assert_eq!(
+        table.select(pk).unwrap(),
+        TestRow {
+            id: row.id,
+            test: 2,
+            another: Some(5),
+            exchange: "test".to_string(),
+        }
+    );
+
+    let missing = table
+        .with_row_scalars_mut(u64::MAX.into(), |_| ())
+        .await
+        .unwrap();
+    assert_eq!(missing, None);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn with_row_scalars_mut_blocks_update() {
+    let table = Arc::new(TestWorkTable::default());
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: None,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert(row.clone()).unwrap();
+
+    let (locked_tx, locked_rx) = tokio::sync::oneshot::channel();
+    let mut locked_tx = Some(locked_tx);
+    let scalars = {
+        let table = table.clone();
+        let pk = pk.clone();
+        tokio::spawn(async move {
+            table
+                .with_row_scalars_mut(pk, |scalars| {
+                    if let Some(tx) = locked_tx.take() {
+                        tx.send(()).unwrap();
+                    }
+                    std::thread::sleep(Duration::from_millis(100));
+                    *scalars.test = 2u64.into();
+                })
+                .await
+                .unwrap()
+        })
+    };
+    locked_rx.await.unwrap();
+    table
+        .update(TestRow {
+            test: 3,
+            exchange: "tset".to_string(),
+            ..row.clone()
+        })
+        .await
+        .unwrap();
+    scalars.await.unwrap();
+
+    // Update waits until `with_row_scalars_mut` releases the row, so its values are written last.
+    assert_eq!(
+        table.select(pk).unwrap(),
+        TestRow {
+            id: row.id,
+            test: 3,
+            another: None,
+            exchange: "tset".to_string(),
+        }
+    );
+}
+
+mod computed {
+    use worktable::prelude::*;
+    use worktable::worktable;
+
+    worktable! (
+        name: Test,
+        columns: {
+            id: u64 primary_key autoincrement,
+            value: u64,
+        },
+        indexes: {
+            value_idx: computed(u64, |row| row.value) unique,
+        },
+        queries: {
+            in_place: {
+                value,
+            },
+        }
+    );
+
+    /// Inserts rows with values 1 and 2, and returns primary key of the second one.
+    fn table() -> (TestWorkTable, TestPrimaryKey) {
+        let table = TestWorkTable::default();
+        for value in [1, 2] {
+            table
+                .insert(TestRow {
+                    id: table.get_next_pk().into(),
+                    value,
+                })
+                .unwrap();
+        }
+        let pk = table.select_by_value_idx(2).unwrap().id.into();
+
+        (table, pk)
+    }
+
+    fn lock_of(table: &TestWorkTable, pk: &TestPrimaryKey) -> Option<u16> {
+        let link = TableIndex::peek(&table.0.pk_map, pk).unwrap();
+        table
+            .0
+            .data
+            .with_ref(link, |archived| archived.is_locked())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn failed_update_field_resets_lock() {
+        let (table, pk) = table();
+        let link = TableIndex::peek(&table.0.pk_map, &pk).unwrap();
+
+        let res = table.update_field_value(link, 1).await;
+        assert!(matches!(res, Err(WorkTableError::AlreadyExists)));
+        assert_eq!(lock_of(&table, &pk), None);
+
+        table.update_field_value(link, 3).await.unwrap();
+        assert_eq!(table.select(pk).unwrap().value, 3);
+    }
+
+    #[tokio::test]
+    async fn failed_with_row_scalars_mut_resets_lock() {
+        let (table, pk) = table();
+        let link = TableIndex::peek(&table.0.pk_map, &pk).unwrap();
+
+        let res = table
+            .with_row_scalars_mut(pk.clone(), |scalars| *scalars.value = 1u64.into())
+            .await;
+        assert!(matches!(res, Err(WorkTableError::AlreadyExists)));
+        assert_eq!(lock_of(&table, &pk), None);
+        assert_eq!(table.select(pk.clone()).unwrap().value, 2);
+
+        table.update_field_value(link, 3).await.unwrap();
+        assert_eq!(table.select(pk).unwrap().value, 3);
+    }
+}
@@ -1,4 +1,5 @@
 use worktable::prelude::*;
+use worktable::ADAPTIVE_INDEX_THRESHOLD;
 use worktable_codegen::worktable;
 
 worktable! (
@@ -21,3 +22,66 @@ worktable! (
         }
     }
 );
+
+worktable! (
+    name: Small,
+    columns: {
+        id: u64 primary_key autoincrement AdaptiveIndex,
+        value: u64,
+    },
+    indexes: {
+        value_idx: value unique AdaptiveIndex,
+    }
+);
+
+#[tokio::test]
+async fn adaptive_index() {
+    let table = SmallWorkTable::default();
+    for value in 0..ADAPTIVE_INDEX_THRESHOLD as u64 * 2 {
+        table
+            .insert(SmallRow {
+                id: table.get_next_pk().into(),
+                value,
+            })
+            .unwrap();
+        assert_eq!(table.select_by_value(value).unwrap().value, value);
+    }
+    assert!(table.0.pk_map.is_promoted());
+    assert!(table.0.indexes.value_idx.is_promoted());
+
+    let values = |rows: Vec<SmallRow>| rows.into_iter().map(|r| r.value).collect::<Vec<_>>();
+    assert_eq!(
+        values(table.select_by_value_range(10..13).unwrap().execute()),
+        vec![10, 11, 12]
+    );
+
+    let pk = table.select_by_value(5).unwrap().id;
+    table.delete(pk.into()).await.unwrap();
+    assert!(table.select_by_value(5).is_none());
+    assert_eq!(
+        table.select_all().execute().unwrap().len(),
+        ADAPTIVE_INDEX_THRESHOLD * 2 - 1
+    );
+    assert_eq!(table.verify_integrity(), Ok(()));
+}
+
+worktable! (
+    name: Plain,
+    columns: {
+        id: u64 primary_key autoincrement,
+        value: u64,
+    }
+);
+
+#[tokio::test]
+async fn adaptive_primary_key_by_default() {
+    let table = PlainWorkTable::default();
+    let pk = table
+        .insert(PlainRow {
+            id: table.get_next_pk().into(),
+            value: 1,
+        })
+        .unwrap();
+    assert!(!table.0.pk_map.is_promoted());
+    assert_eq!(table.select(pk).unwrap().value, 1);
+}
@@ -0,0 +1,114 @@
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key autoincrement,
+        test: i64,
+        another: u64 optional,
+        exchange: String
+    },
+    indexes: {
+        test_idx: test unique,
+    },
+    config: {
+        serde: true,
+    }
+);
+
+#[test]
+fn export_jsonl() {
+    let table = TestWorkTable::default();
+    let first = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: Some(1),
+        exchange: "test".to_string(),
+    };
+    let second = TestRow {
+        id: table.get_next_pk().into(),
+        test: 2,
+        another: None,
+        exchange: "other".to_string(),
+    };
+    table.insert(first.clone()).unwrap();
+    table.insert(second.clone()).unwrap();
+
+    let mut out = vec![];
+    table.export_jsonl(&mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let lines = out.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 2);
+    let value: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(value["id"], first.id);
+    assert_eq!(value["test"], 1);
+    assert_eq!(value["another"], 1);
+    assert_eq!(value["exchange"], "test");
+    assert_eq!(serde_json::from_str::<TestRow>(lines[1]).unwrap(), second);
+}
+
+#[test]
+fn import_jsonl() {
+    let input = r#"{"id":1,"test":1,"another":1,"exchange":"test"}
+
+{"id":2,"test":2,"another":null,"exchange":"other"}
+"#;
+
+    let table = TestWorkTable::default();
+    let imported = table.import_jsonl(&mut input.as_bytes()).unwrap();
+
+    assert_eq!(imported, 2);
+    assert_eq!(
+        table.select(2.into()),
+        Some(TestRow {
+            id: 2,
+            test: 2,
+            another: None,
+            exchange: "other".to_string(),
+        })
+    );
+    assert!(table.select_by_test(1).is_some());
+}
+
+#[test]
+fn import_jsonl_malformed_line() {
+    let input = r#"{"id":1,"test":1,"another":1,"exchange":"test"}
+{"id":2,"test":2,"exchange":
+"#;
+
+    let table = TestWorkTable::default();
+    let res = table.import_jsonl(&mut input.as_bytes());
+
+    assert!(matches!(
+        res,
+        Err(ImportError::MalformedLine { line: 2, .. })
+    ));
+    assert!(table.select(1.into()).is_none());
+}
+
+#[test]
+fn import_jsonl_invalid_rows() {
+    let input = r#"{"id":1,"test":1,"another":1,"exchange":"test"}
+{"id":2,"test":1,"another":1,"exchange":"test"}
+"#;
+
+    let table = TestWorkTable::default();
+    let res = table.import_jsonl(&mut input.as_bytes());
+
+    let Err(ImportError::InvalidRows(errors)) = res else {
+        panic!("rows should be invalid")
+    };
+    assert_eq!(
+        errors,
+        vec![(
+            2,
+            BatchError::UniqueIndexViolation {
+                row: 1,
+                index: "test_idx"
+            }
+        )]
+    );
+    assert!(table.select(1.into()).is_none());
+}
@@ -0,0 +1,51 @@
+use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key autoincrement,
+        test: i64,
+    }
+);
+
+#[test]
+fn pager_metrics() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    ::metrics::with_local_recorder(&recorder, || {
+        let table = TestWorkTable::default();
+        let pk = table
+            .insert(TestRow {
+                id: table.get_next_pk().into(),
+                test: 1,
+            })
+            .unwrap();
+        assert!(table.select(pk).is_some());
+    });
+
+    let counters = snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .filter_map(|(key, _, _, value)| match value {
+            DebugValue::Counter(value) => Some((
+                key.key().name().to_string(),
+                key.key()
+                    .labels()
+                    .map(|l| (l.key().to_string(), l.value().to_string()))
+                    .collect::<Vec<_>>(),
+                value,
+            )),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let labels = vec![("table".to_string(), "Test".to_string())];
+    assert!(counters.contains(&(
+        worktable::metrics::ROWS_INSERTED.to_string(),
+        labels.clone(),
+        1
+    )));
+    assert!(counters.contains(&(worktable::metrics::SELECTS.to_string(), labels, 1)));
+}
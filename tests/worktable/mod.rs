@@ -1,9 +1,19 @@
 mod array;
+#[cfg(feature = "async")]
+mod async_fns;
 mod base;
+mod batch;
+mod computed_index;
 mod config;
 mod custom_pk;
+mod in_place;
 mod index_type;
+#[cfg(feature = "serde")]
+mod jsonl;
+#[cfg(feature = "metrics")]
+mod metrics;
 mod option;
+mod ttl;
 mod tuple_primary_key;
 mod uuid;
 mod with_enum;
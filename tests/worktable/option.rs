@@ -78,3 +78,87 @@ async fn update_by_exchange() {
     let selected_row = table.select(pk).unwrap();
     assert_eq!(selected_row.test, Some(1));
 }
+
+worktable! (
+    name: TestIndexed,
+    columns: {
+        id: u64 primary_key autoincrement,
+        unique: u64 optional,
+        non_unique: i32 optional,
+    },
+    indexes: {
+        unique_idx: unique unique,
+        non_unique_idx: non_unique,
+    },
+    queries: {
+        delete: {
+            ByNonUnique() by non_unique,
+        }
+    }
+);
+
+fn indexed_rows(table: &TestIndexedWorkTable) -> Vec<TestIndexedRow> {
+    (0..6)
+        .map(|i| {
+            let row = TestIndexedRow {
+                id: table.get_next_pk().into(),
+                unique: (i % 2 == 0).then_some(i as u64),
+                non_unique: (i % 3 != 0).then_some(1),
+            };
+            table.insert(row.clone()).unwrap();
+            row
+        })
+        .collect()
+}
+
+#[test]
+fn select_by_optional_index() {
+    let table = TestIndexedWorkTable::default();
+    let rows = indexed_rows(&table);
+
+    assert_eq!(table.select_by_unique(2), Some(rows[2].clone()));
+    assert_eq!(table.select_by_unique(1), None);
+
+    let mut selected = table.select_by_non_unique(1).unwrap().execute();
+    selected.sort_by_key(|r| r.id);
+    assert_eq!(
+        selected,
+        vec![
+            rows[1].clone(),
+            rows[2].clone(),
+            rows[4].clone(),
+            rows[5].clone()
+        ]
+    );
+
+    // Rows with `None` value are not lost when ordered by optional indexed column.
+    let ordered = table
+        .select_all()
+        .order_by(Order::Asc, "unique")
+        .execute()
+        .unwrap();
+    assert_eq!(ordered.len(), 6);
+}
+
+#[tokio::test]
+async fn delete_with_optional_index() {
+    let table = TestIndexedWorkTable::default();
+    let rows = indexed_rows(&table);
+
+    // Unique index doesn't conflict on `None` values.
+    let row = TestIndexedRow {
+        id: table.get_next_pk().into(),
+        unique: None,
+        non_unique: None,
+    };
+    table.insert(row.clone()).unwrap();
+
+    table.delete(row.id.into()).await.unwrap();
+    table.delete(rows[0].id.into()).await.unwrap();
+    assert_eq!(table.select_by_unique(0), None);
+    assert_eq!(table.select_by_unique(4), Some(rows[4].clone()));
+
+    table.delete_by_non_unique(1).await.unwrap();
+    assert!(table.select_by_non_unique(1).unwrap().execute().is_empty());
+    assert_eq!(table.select(rows[3].id.into()), Some(rows[3].clone()));
+}
@@ -0,0 +1,247 @@
+use std::time::{Duration, Instant};
+
+use worktable::prelude::*;
+use worktable::worktable;
+
+worktable! (
+    name: Test,
+    columns: {
+        id: u64 primary_key autoincrement,
+        test: i64,
+        another: u64,
+        exchange: String
+    },
+    indexes: {
+        test_idx: test unique,
+    }
+);
+
+#[test]
+fn expired_row_is_absent() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: 1,
+        exchange: "test".to_string(),
+    };
+    let pk = table
+        .insert_with_ttl(row.clone(), Duration::from_millis(50))
+        .unwrap();
+    let kept = TestRow {
+        id: table.get_next_pk().into(),
+        test: 2,
+        another: 2,
+        exchange: "test".to_string(),
+    };
+    let kept_pk = table.insert(kept.clone()).unwrap();
+    assert_eq!(table.select(pk.clone()), Some(row));
+
+    std::thread::sleep(Duration::from_millis(60));
+    assert_eq!(table.select(pk.clone()), None);
+    assert_eq!(table.get(&pk).unwrap(), None);
+    assert!(!table.exists(&pk));
+    assert_eq!(
+        table.select_many_by_pk(&[pk, kept_pk]),
+        vec![None, Some(kept.clone())]
+    );
+    assert!(table.select_by_test(1).is_none());
+    assert_eq!(table.select_by_test(2), Some(kept.clone()));
+    assert_eq!(table.select_all().execute().unwrap(), vec![kept.clone()]);
+    assert_eq!(table.get_all().unwrap(), vec![kept]);
+}
+
+#[test]
+fn purge_expired() {
+    let table = TestWorkTable::default();
+    let mut pks = vec![];
+    for i in 0..4 {
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test: i,
+            another: i as u64,
+            exchange: "test".to_string(),
+        };
+        let ttl = Duration::from_secs(if i % 2 == 0 { 1 } else { 3600 });
+        pks.push(table.insert_with_ttl(row, ttl).unwrap());
+    }
+    let links = [&pks[0], &pks[2]].map(|pk| TableIndex::peek(&table.0.pk_map, pk).unwrap());
+
+    assert_eq!(table.purge_expired(Instant::now()).unwrap(), 0);
+    let later = Instant::now() + Duration::from_secs(2);
+    assert_eq!(table.purge_expired(later).unwrap(), 2);
+    assert_eq!(table.purge_expired(later).unwrap(), 0);
+
+    assert!(TableIndex::peek(&table.0.pk_map, &pks[0]).is_none());
+    assert!(table.select_by_test(0).is_none());
+    assert!(table.select_by_test(1).is_some());
+    let empty_links = table.0.data.empty_links_snapshot();
+    assert!(links.iter().all(|link| empty_links.contains(link)));
+
+    // Slot of the purged row is reused without its expiration.
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 10,
+        another: 10,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert(row.clone()).unwrap();
+    let link = TableIndex::peek(&table.0.pk_map, &pk).unwrap();
+    assert!(links.contains(&link));
+    assert_eq!(table.0.data.expires_at(link), None);
+    assert_eq!(table.select(pk), Some(row));
+}
+
+#[tokio::test]
+async fn delete_expired() {
+    let table = TestWorkTable::default();
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: 1,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert_with_ttl(row, Duration::ZERO).unwrap();
+    assert!(table.select(pk.clone()).is_none());
+
+    table.delete(pk).await.unwrap();
+    assert_eq!(table.purge_expired(Instant::now()).unwrap(), 0);
+}
+
+/// Inserts expired row, which is the first in primary key's order, and not expired one. Returns their primary keys and
+/// not expired row.
+fn expired_and_kept(table: &TestWorkTable) -> (TestPrimaryKey, TestPrimaryKey, TestRow) {
+    let row = TestRow {
+        id: table.get_next_pk().into(),
+        test: 1,
+        another: 1,
+        exchange: "test".to_string(),
+    };
+    let pk = table.insert_with_ttl(row, Duration::ZERO).unwrap();
+    let kept = TestRow {
+        id: table.get_next_pk().into(),
+        test: 2,
+        another: 2,
+        exchange: "test".to_string(),
+    };
+    let kept_pk = table.insert(kept.clone()).unwrap();
+
+    (pk, kept_pk, kept)
+}
+
+#[test]
+fn expired_row_is_absent_in_scan_where() {
+    let table = TestWorkTable::default();
+    let (_, _, kept) = expired_and_kept(&table);
+
+    assert_eq!(table.scan_where(|_| true).unwrap(), vec![kept]);
+}
+
+#[tokio::test]
+async fn expired_row_is_absent_in_iter_with() {
+    let table = TestWorkTable::default();
+    let (_, _, kept) = expired_and_kept(&table);
+
+    let rows = std::sync::Mutex::new(vec![]);
+    table
+        .iter_with(|row| {
+            rows.lock().unwrap().push(row);
+            Ok(())
+        })
+        .unwrap();
+    assert_eq!(
+        rows.lock().unwrap().drain(..).collect::<Vec<_>>(),
+        vec![kept.clone()]
+    );
+
+    table
+        .iter_with_async(|row| {
+            rows.lock().unwrap().push(row);
+            async { Ok(()) }
+        })
+        .await
+        .unwrap();
+    assert_eq!(rows.into_inner().unwrap(), vec![kept]);
+}
+
+#[test]
+fn expired_row_is_absent_in_iter_with_links() {
+    let table = TestWorkTable::default();
+    let (_, _, kept) = expired_and_kept(&table);
+
+    let rows = table
+        .iter_with_links()
+        .map(|res| res.unwrap().1)
+        .collect::<Vec<_>>();
+    assert_eq!(rows, vec![kept]);
+}
+
+#[test]
+fn expired_row_is_absent_in_index_entries() {
+    let table = TestWorkTable::default();
+    let (_, kept_pk, _) = expired_and_kept(&table);
+
+    let keys = table.index_entries().map(|(pk, _)| pk).collect::<Vec<_>>();
+    assert_eq!(keys, vec![kept_pk]);
+}
+
+#[tokio::test]
+async fn expired_row_is_absent_in_with_row_fns() {
+    let table = TestWorkTable::default();
+    let (pk, _, _) = expired_and_kept(&table);
+
+    assert_eq!(
+        unsafe { table.with_row_mut_ref(pk.clone(), |_| ()) }.unwrap(),
+        None
+    );
+    assert_eq!(
+        table.with_row_locked(pk.clone(), |_| ()).await.unwrap(),
+        None
+    );
+    assert_eq!(
+        table.with_row_locked_mut(pk.clone(), |_| ()).await.unwrap(),
+        None
+    );
+    assert_eq!(table.with_row_scalars_mut(pk, |_| ()).await.unwrap(), None);
+}
+
+#[cfg(feature = "serde")]
+mod jsonl {
+    use std::time::Duration;
+
+    use worktable::prelude::*;
+    use worktable::worktable;
+
+    worktable! (
+        name: Test,
+        columns: {
+            id: u64 primary_key autoincrement,
+            test: i64,
+        },
+        config: {
+            serde: true,
+        }
+    );
+
+    #[test]
+    fn expired_row_is_absent_in_export_jsonl() {
+        let table = TestWorkTable::default();
+        let row = TestRow {
+            id: table.get_next_pk().into(),
+            test: 1,
+        };
+        table.insert_with_ttl(row, Duration::ZERO).unwrap();
+        let kept = TestRow {
+            id: table.get_next_pk().into(),
+            test: 2,
+        };
+        table.insert(kept.clone()).unwrap();
+
+        let mut out = vec![];
+        table.export_jsonl(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 1);
+        assert_eq!(serde_json::from_str::<TestRow>(lines[0]).unwrap(), kept);
+    }
+}
@@ -55,3 +55,175 @@ async fn update() {
     assert_eq!(selected_row, updated);
     assert!(table.select(2.into()).is_none())
 }
+
+#[derive(
+    Archive, Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize,
+)]
+#[rkyv(compare(PartialEq), derive(Debug))]
+pub enum Status {
+    Pending,
+    Active,
+    Done,
+}
+
+worktable! (
+    name: Task,
+    columns: {
+        id: u64 primary_key autoincrement,
+        status: Status,
+        name: String,
+    },
+    indexes: {
+        status_idx: status,
+    },
+    queries: {
+        update: {
+            StatusById(status) by id,
+            NameByStatus(name) by status,
+        },
+        delete: {
+            ByStatus() by status,
+        }
+    }
+);
+
+fn insert_tasks(table: &TaskWorkTable) -> Vec<TaskPrimaryKey> {
+    let mut pks = vec![];
+    for (i, status) in [Status::Pending, Status::Active, Status::Done]
+        .into_iter()
+        .cycle()
+        .take(9)
+        .enumerate()
+    {
+        let row = TaskRow {
+            id: table.get_next_pk().into(),
+            status,
+            name: format!("task {i}"),
+        };
+        pks.push(table.insert(row).unwrap());
+    }
+    pks
+}
+
+#[test]
+fn select_by_enum_index() {
+    let table = TaskWorkTable::default();
+    insert_tasks(&table);
+
+    let active = table.select_by_status(Status::Active).unwrap().execute();
+    assert_eq!(active.len(), 3);
+    assert!(active.iter().all(|r| r.status == Status::Active));
+
+    // Index is ordered by enum's `Ord`, so range follows variants' order.
+    let not_done = table
+        .select_by_status_range(..Status::Done)
+        .unwrap()
+        .execute();
+    assert_eq!(not_done.len(), 6);
+    assert_eq!(not_done[0].status, Status::Pending);
+    assert_eq!(not_done[5].status, Status::Active);
+}
+
+#[tokio::test]
+async fn update_enum_index() {
+    let table = TaskWorkTable::default();
+    let pks = insert_tasks(&table);
+
+    table
+        .update_status_by_id(
+            StatusByIdQuery {
+                status: Status::Done,
+            },
+            pks[0].clone(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        table
+            .select_by_status(Status::Pending)
+            .unwrap()
+            .execute()
+            .len(),
+        2
+    );
+    assert_eq!(
+        table
+            .select_by_status(Status::Done)
+            .unwrap()
+            .execute()
+            .len(),
+        4
+    );
+
+    table
+        .update_name_by_status(
+            NameByStatusQuery {
+                name: "closed".to_string(),
+            },
+            Status::Done,
+        )
+        .await
+        .unwrap();
+    let done = table.select_by_status(Status::Done).unwrap().execute();
+    assert!(done.iter().all(|r| r.name == "closed"));
+
+    table.delete_by_status(Status::Done).await.unwrap();
+    assert!(table
+        .select_by_status(Status::Done)
+        .unwrap()
+        .execute()
+        .is_empty());
+    assert_eq!(table.select_all().execute().unwrap().len(), 5);
+    assert_eq!(table.verify_integrity(), Ok(()));
+}
+
+#[derive(Archive, Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[rkyv(compare(PartialEq), derive(Debug))]
+pub enum Event {
+    Created,
+    Moved { to: u32 },
+}
+
+impl Event {
+    fn kind(&self) -> u8 {
+        match self {
+            Event::Created => 0,
+            Event::Moved { .. } => 1,
+        }
+    }
+}
+
+// Data-carrying enum is indexed by its projection to the key.
+worktable! (
+    name: Log,
+    columns: {
+        id: u64 primary_key autoincrement,
+        event: Event,
+    },
+    indexes: {
+        kind_idx: computed(u8, |row| row.event.kind()),
+    }
+);
+
+#[test]
+fn select_by_enum_projection() {
+    let table = LogWorkTable::default();
+    for event in [
+        Event::Created,
+        Event::Moved { to: 1 },
+        Event::Moved { to: 2 },
+    ] {
+        let row = LogRow {
+            id: table.get_next_pk().into(),
+            event,
+        };
+        table.insert(row).unwrap();
+    }
+
+    let mut moved = table.select_by_kind_idx(1).unwrap().execute();
+    moved.sort_by_key(|r| r.id);
+    assert_eq!(
+        moved.into_iter().map(|r| r.event).collect::<Vec<_>>(),
+        vec![Event::Moved { to: 1 }, Event::Moved { to: 2 }]
+    );
+}